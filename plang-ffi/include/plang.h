@@ -0,0 +1,193 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ *
+ * Copyright (c) DUSK NETWORK. All rights reserved. */
+
+#ifndef PLANG_H
+#define PLANG_H
+
+/* Generated by cbindgen from plang-ffi/src/lib.rs - do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ The outcome of a call, in the same categories as the exit codes of
+ `plangc`.
+ */
+typedef enum PlangStatus {
+  /*
+   Success.
+   */
+  PLANG_STATUS_OK = 0,
+  /*
+   A null pointer, or an otherwise invalid argument, was passed.
+   */
+  PLANG_STATUS_INVALID_ARGUMENT = 1,
+  /*
+   The circuit could not be parsed.
+   */
+  PLANG_STATUS_PARSE = 2,
+  /*
+   The circuit is not valid.
+   */
+  PLANG_STATUS_INVALID = 3,
+  /*
+   Compiling the circuit or proving failed.
+   */
+  PLANG_STATUS_PROVE = 4,
+  /*
+   The proof did not verify.
+   */
+  PLANG_STATUS_VERIFY = 5,
+  /*
+   Invalid values were given for the circuit.
+   */
+  PLANG_STATUS_VALUES = 6,
+  /*
+   Keys or parameters don't match the circuit.
+   */
+  PLANG_STATUS_MISMATCH = 7,
+  /*
+   An artifact could not be deserialized.
+   */
+  PLANG_STATUS_ARTIFACT = 8,
+  /*
+   Plang panicked, which is a bug in it.
+   */
+  PLANG_STATUS_INTERNAL = 9,
+} PlangStatus;
+
+/*
+ A parsed circuit, with the values set on it.
+ */
+typedef struct PlangCircuit PlangCircuit;
+
+/*
+ A buffer of bytes owned by plang.
+ */
+typedef struct PlangBuffer {
+  uint8_t *data;
+  size_t len;
+} PlangBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Returns the message of the last error on the calling thread, or null if
+ there was none. It remains valid until the next call on the thread.
+ */
+const char *plang_last_error(void);
+
+/*
+ Parses and checks a circuit from its UTF-8 text, writing a handle to it to
+ `out`.
+
+ # Safety
+
+ `text` must point to `len` readable bytes, and `out` must be valid for
+ writes. The handle must be freed with [`plang_circuit_free`].
+ */
+enum PlangStatus plang_circuit_parse(const uint8_t *text, size_t len, struct PlangCircuit **out);
+
+/*
+ Frees a circuit. Passing null does nothing.
+
+ # Safety
+
+ `circuit` must be null or a handle returned by [`plang_circuit_parse`] that
+ hasn't been freed.
+ */
+void plang_circuit_free(struct PlangCircuit *circuit);
+
+/*
+ Writes the 32 byte hash of the circuit to `out`.
+
+ # Safety
+
+ `circuit` must be a valid handle, and `out` must be valid for writes of 32
+ bytes.
+ */
+enum PlangStatus plang_circuit_hash(const struct PlangCircuit *circuit, uint8_t *out);
+
+/*
+ Sets the value of a witness or public input, given its UTF-8 name and its
+ value as a 32 byte little endian scalar.
+
+ # Safety
+
+ `circuit` must be a valid handle, `name` must point to `name_len` readable
+ bytes and `value` to 32.
+ */
+enum PlangStatus plang_circuit_set_val(struct PlangCircuit *circuit,
+                                       const uint8_t *name,
+                                       size_t name_len,
+                                       const uint8_t *value);
+
+/*
+ Compiles the circuit with the given public parameters, writing the key
+ archive to `out`. The archive records the transcript label declared in the
+ circuit, or otherwise "dusk_plang".
+
+ # Safety
+
+ `circuit` must be a valid handle, `pp` must point to `pp_len` readable
+ bytes, and `out` must be valid for writes.
+ */
+enum PlangStatus plang_compile(struct PlangCircuit *circuit,
+                               const uint8_t *pp,
+                               size_t pp_len,
+                               struct PlangBuffer *out);
+
+/*
+ Proves the circuit with the values set on it, writing the proof to `out`.
+
+ # Safety
+
+ `circuit` must be a valid handle, `pp` and `keys` must point to `pp_len`
+ and `keys_len` readable bytes, and `out` must be valid for writes.
+ */
+enum PlangStatus plang_prove(struct PlangCircuit *circuit,
+                             const uint8_t *pp,
+                             size_t pp_len,
+                             const uint8_t *keys,
+                             size_t keys_len,
+                             struct PlangBuffer *out);
+
+/*
+ Verifies a proof of the circuit, with the values of the public inputs set
+ on it. Returns [`PlangStatus::Verify`] if the proof is invalid.
+
+ # Safety
+
+ `circuit` must be a valid handle, and `pp`, `keys` and `proof` must point
+ to `pp_len`, `keys_len` and `proof_len` readable bytes.
+ */
+enum PlangStatus plang_verify(const struct PlangCircuit *circuit,
+                              const uint8_t *pp,
+                              size_t pp_len,
+                              const uint8_t *keys,
+                              size_t keys_len,
+                              const uint8_t *proof,
+                              size_t proof_len);
+
+/*
+ Frees a buffer returned by plang. Passing an empty buffer does nothing.
+
+ # Safety
+
+ The buffer must have been returned by plang, and not been freed before.
+ */
+void plang_buffer_free(struct PlangBuffer buffer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PLANG_H */
@@ -0,0 +1,436 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! C bindings for plang, so software not written in Rust can embed it.
+//!
+//! A circuit is parsed into an opaque handle, which values are set on, and
+//! which is then compiled, proven or verified. Artifacts - public parameters,
+//! key archives and proofs - are passed as the same bytes `plangc` reads and
+//! writes, and values as 32 byte little endian scalars.
+//!
+//! Functions return a [`PlangStatus`], telling what went wrong in the same
+//! categories as the exit codes of `plangc`. The message of the last error on
+//! the calling thread is available through [`plang_last_error`]. Buffers
+//! returned by plang must be freed with [`plang_buffer_free`].
+//!
+//! Panics never unwind into the caller. A function that panics returns
+//! [`PlangStatus::Internal`] instead, with the message of the panic as the
+//! last error.
+//!
+//! The header `include/plang.h` is generated from this crate with `cbindgen`,
+//! and has to be regenerated when the functions change:
+//!
+//! ```sh
+//! cbindgen --config plang-ffi/cbindgen.toml --output plang-ffi/include/plang.h plang-ffi
+//! ```
+
+use plang::dusk_plonk::error::Error as PlonkError;
+use plang::dusk_plonk::prelude::*;
+use plang::transcript::DEFAULT_LABEL;
+use plang::{params, ParseError, PlangError, PlangKeys, PlangProof, ProveError, ProveOptions};
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use dusk_bytes::Serializable;
+
+/// A parsed circuit, with the values set on it.
+pub struct PlangCircuit(plang::PlangCircuit);
+
+/// A buffer of bytes owned by plang.
+#[repr(C)]
+pub struct PlangBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+/// The outcome of a call, in the same categories as the exit codes of
+/// `plangc`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlangStatus {
+    /// Success.
+    Ok = 0,
+    /// A null pointer, or an otherwise invalid argument, was passed.
+    InvalidArgument = 1,
+    /// The circuit could not be parsed.
+    Parse = 2,
+    /// The circuit is not valid.
+    Invalid = 3,
+    /// Compiling the circuit or proving failed.
+    Prove = 4,
+    /// The proof did not verify.
+    Verify = 5,
+    /// Invalid values were given for the circuit.
+    Values = 6,
+    /// Keys or parameters don't match the circuit.
+    Mismatch = 7,
+    /// An artifact could not be deserialized.
+    Artifact = 8,
+    /// Plang panicked, which is a bug in it.
+    Internal = 9,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::default();
+}
+
+/// Returns the message of the last error on the calling thread, or null if
+/// there was none. It remains valid until the next call on the thread.
+#[no_mangle]
+pub extern "C" fn plang_last_error() -> *const c_char {
+    let last_error = || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(ptr::null(), |message| message.as_ptr())
+        })
+    };
+    panic::catch_unwind(last_error).unwrap_or(ptr::null())
+}
+
+/// Parses and checks a circuit from its UTF-8 text, writing a handle to it to
+/// `out`.
+///
+/// # Safety
+///
+/// `text` must point to `len` readable bytes, and `out` must be valid for
+/// writes. The handle must be freed with [`plang_circuit_free`].
+#[no_mangle]
+pub unsafe extern "C" fn plang_circuit_parse(
+    text: *const u8,
+    len: usize,
+    out: *mut *mut PlangCircuit,
+) -> PlangStatus {
+    guard(|| {
+        if out.is_null() {
+            return PlangStatus::InvalidArgument;
+        }
+        let text = match bytes(text, len) {
+            Some(text) => text,
+            None => return PlangStatus::InvalidArgument,
+        };
+
+        run(|| {
+            let text = String::from_utf8(text.to_vec())?;
+            let circuit = plang::PlangCircuit::parse(text)?;
+
+            *out = Box::into_raw(Box::new(PlangCircuit(circuit)));
+            Ok(())
+        })
+    })
+}
+
+/// Frees a circuit. Passing null does nothing.
+///
+/// # Safety
+///
+/// `circuit` must be null or a handle returned by [`plang_circuit_parse`] that
+/// hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn plang_circuit_free(circuit: *mut PlangCircuit) {
+    guard(|| {
+        if !circuit.is_null() {
+            drop(Box::from_raw(circuit));
+        }
+        PlangStatus::Ok
+    });
+}
+
+/// Writes the 32 byte hash of the circuit to `out`.
+///
+/// # Safety
+///
+/// `circuit` must be a valid handle, and `out` must be valid for writes of 32
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn plang_circuit_hash(
+    circuit: *const PlangCircuit,
+    out: *mut u8,
+) -> PlangStatus {
+    guard(|| {
+        if circuit.is_null() || out.is_null() {
+            return PlangStatus::InvalidArgument;
+        }
+
+        let hash = (*circuit).0.hash();
+        ptr::copy_nonoverlapping(hash.as_ptr(), out, hash.len());
+        PlangStatus::Ok
+    })
+}
+
+/// Sets the value of a witness or public input, given its UTF-8 name and its
+/// value as a 32 byte little endian scalar.
+///
+/// # Safety
+///
+/// `circuit` must be a valid handle, `name` must point to `name_len` readable
+/// bytes and `value` to 32.
+#[no_mangle]
+pub unsafe extern "C" fn plang_circuit_set_val(
+    circuit: *mut PlangCircuit,
+    name: *const u8,
+    name_len: usize,
+    value: *const u8,
+) -> PlangStatus {
+    guard(|| {
+        if circuit.is_null() {
+            return PlangStatus::InvalidArgument;
+        }
+        let (name, value) = match (bytes(name, name_len), bytes(value, BlsScalar::SIZE)) {
+            (Some(name), Some(value)) => (name, value),
+            _ => return PlangStatus::InvalidArgument,
+        };
+
+        let name = match std::str::from_utf8(name) {
+            Ok(name) => name.to_owned(),
+            Err(_) => return PlangStatus::InvalidArgument,
+        };
+        let value = match scalar(value) {
+            Some(value) => value,
+            None => return PlangStatus::InvalidArgument,
+        };
+
+        run(|| (*circuit).0.set_vals(vec![(name, value)]))
+    })
+}
+
+/// Compiles the circuit with the given public parameters, writing the key
+/// archive to `out`. The archive records the transcript label declared in the
+/// circuit, or otherwise "dusk_plang".
+///
+/// # Safety
+///
+/// `circuit` must be a valid handle, `pp` must point to `pp_len` readable
+/// bytes, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn plang_compile(
+    circuit: *mut PlangCircuit,
+    pp: *const u8,
+    pp_len: usize,
+    out: *mut PlangBuffer,
+) -> PlangStatus {
+    guard(|| {
+        let pp = match bytes(pp, pp_len) {
+            Some(pp) if !circuit.is_null() && !out.is_null() => pp,
+            _ => return PlangStatus::InvalidArgument,
+        };
+        let circuit = &mut (*circuit).0;
+
+        run(|| {
+            let pp = params::read_for_circuit(pp, circuit)?;
+            let (pk, vd) = circuit.compile(&pp)?;
+
+            let label = circuit.transcript().unwrap_or(DEFAULT_LABEL).to_vec();
+            let keys = PlangKeys::new(circuit, &pp, Some(pk), vd, label);
+
+            *out = PlangBuffer::new(keys.to_bytes());
+            Ok(())
+        })
+    })
+}
+
+/// Proves the circuit with the values set on it, writing the proof to `out`.
+///
+/// # Safety
+///
+/// `circuit` must be a valid handle, `pp` and `keys` must point to `pp_len`
+/// and `keys_len` readable bytes, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn plang_prove(
+    circuit: *mut PlangCircuit,
+    pp: *const u8,
+    pp_len: usize,
+    keys: *const u8,
+    keys_len: usize,
+    out: *mut PlangBuffer,
+) -> PlangStatus {
+    guard(|| {
+        let (pp, keys) = match (bytes(pp, pp_len), bytes(keys, keys_len)) {
+            (Some(pp), Some(keys)) if !circuit.is_null() && !out.is_null() => (pp, keys),
+            _ => return PlangStatus::InvalidArgument,
+        };
+        let circuit = &mut (*circuit).0;
+
+        run(|| {
+            let pp = params::read_for_circuit(pp, circuit)?;
+            let keys = PlangKeys::read_for_circuit(keys, circuit, &pp)?;
+            let pk = keys
+                .prover_key
+                .ok_or(PlangError::Prove(ProveError::NoProverKey))?;
+
+            let options = ProveOptions::new(keys.label.clone());
+            let proof = circuit.prove_with(&pp, &pk, &options)?;
+
+            *out = PlangBuffer::new(PlangProof::new(proof, keys.label).to_bytes());
+            Ok(())
+        })
+    })
+}
+
+/// Verifies a proof of the circuit, with the values of the public inputs set
+/// on it. Returns [`PlangStatus::Verify`] if the proof is invalid.
+///
+/// # Safety
+///
+/// `circuit` must be a valid handle, and `pp`, `keys` and `proof` must point
+/// to `pp_len`, `keys_len` and `proof_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn plang_verify(
+    circuit: *const PlangCircuit,
+    pp: *const u8,
+    pp_len: usize,
+    keys: *const u8,
+    keys_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+) -> PlangStatus {
+    guard(|| {
+        let (pp, keys, proof) = match (
+            bytes(pp, pp_len),
+            bytes(keys, keys_len),
+            bytes(proof, proof_len),
+        ) {
+            (Some(pp), Some(keys), Some(proof)) if !circuit.is_null() => (pp, keys, proof),
+            _ => return PlangStatus::InvalidArgument,
+        };
+        let circuit = &(*circuit).0;
+
+        run(|| {
+            let pp = params::read_for_circuit(pp, circuit)?;
+            let keys = PlangKeys::read_for_circuit(keys, circuit, &pp)?;
+            let proof = PlangProof::from_slice(proof)?;
+
+            let vals: Vec<(String, BlsScalar)> = circuit
+                .public_input_order()
+                .into_iter()
+                .map(|name| (name.to_owned(), circuit.value(name).unwrap_or_default()))
+                .collect();
+            let pinputs = circuit.public_inputs_from(vals)?;
+
+            let options = ProveOptions::new(keys.label);
+            plang::PlangCircuit::verify_with(
+                &pp,
+                &keys.verifier_data,
+                &proof.proof,
+                &pinputs,
+                &options,
+            )
+        })
+    })
+}
+
+/// Frees a buffer returned by plang. Passing an empty buffer does nothing.
+///
+/// # Safety
+///
+/// The buffer must have been returned by plang, and not been freed before.
+#[no_mangle]
+pub unsafe extern "C" fn plang_buffer_free(buffer: PlangBuffer) {
+    guard(|| {
+        if !buffer.data.is_null() {
+            drop(Vec::from_raw_parts(buffer.data, buffer.len, buffer.len));
+        }
+        PlangStatus::Ok
+    });
+}
+
+impl PlangBuffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        let mut bytes = bytes.into_boxed_slice();
+        let buffer = Self {
+            data: bytes.as_mut_ptr(),
+            len: bytes.len(),
+        };
+        std::mem::forget(bytes);
+        buffer
+    }
+}
+
+/// Runs the body of a call, catching any panic, since unwinding into the
+/// caller is undefined behavior. A panic is recorded as the last error of the
+/// thread, and returned as [`PlangStatus::Internal`].
+fn guard<F: FnOnce() -> PlangStatus>(f: F) -> PlangStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(status) => status,
+        Err(payload) => {
+            let message = format!("internal error: {}", panic_message(&*payload));
+            LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+            PlangStatus::Internal
+        }
+    }
+}
+
+/// The message a panic was raised with, if it was raised with one.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload
+            .downcast_ref::<String>()
+            .map_or("panicked", String::as_str),
+    }
+}
+
+/// Runs a call, recording its error, if any, as the last error of the thread.
+fn run<F: FnOnce() -> Result<(), PlangError>>(f: F) -> PlangStatus {
+    let (status, message) = match f() {
+        Ok(()) => (PlangStatus::Ok, None),
+        Err(err) => {
+            let message = match err.code() {
+                Some(code) => format!("error[{}]: {}", code, err),
+                None => format!("error: {}", err),
+            };
+            // Messages can't have interior nuls, short of a variable name
+            // containing one, which the grammar doesn't allow.
+            (status(&err), CString::new(message).ok())
+        }
+    };
+
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+fn status(err: &PlangError) -> PlangStatus {
+    match err {
+        PlangError::Parse(ParseError::InvalidR1cs(_)) => PlangStatus::Artifact,
+        PlangError::Parse(_) => PlangStatus::Parse,
+        PlangError::Semantic(_) => PlangStatus::Invalid,
+        PlangError::Prove(ProveError::Plonk(PlonkError::ProofVerificationError)) => {
+            PlangStatus::Verify
+        }
+        PlangError::Prove(ProveError::Plonk(_) | ProveError::TooManyGates(_)) => PlangStatus::Prove,
+        PlangError::Assignment(_) => PlangStatus::Values,
+        PlangError::Prove(
+            ProveError::ParamsTooSmall { .. }
+            | ProveError::KeysMismatch
+            | ProveError::CircuitMismatch
+            | ProveError::TranscriptMismatch
+            | ProveError::ParamsMismatch
+            | ProveError::NoProverKey,
+        ) => PlangStatus::Mismatch,
+        _ => PlangStatus::Artifact,
+    }
+}
+
+/// Reads a buffer passed in, which may only be null if it's empty.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (data.is_null(), len) {
+        (true, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(data, len)),
+    }
+}
+
+fn scalar(bytes: &[u8]) -> Option<BlsScalar> {
+    let mut buf = [0u8; BlsScalar::SIZE];
+    buf.copy_from_slice(bytes);
+    BlsScalar::from_bytes(&buf).ok()
+}
@@ -0,0 +1,55 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::ffi::CStr;
+use std::ptr;
+
+use plang_ffi::*;
+
+#[test]
+fn parses_and_sets_values() {
+    let text = "a + b = c\n";
+    let mut circuit = ptr::null_mut();
+
+    unsafe {
+        let status = plang_circuit_parse(text.as_ptr(), text.len(), &mut circuit);
+        assert_eq!(status, PlangStatus::Ok);
+
+        let mut hash = [0u8; 32];
+        assert_eq!(
+            plang_circuit_hash(circuit, hash.as_mut_ptr()),
+            PlangStatus::Ok
+        );
+        assert_eq!(hash, plang::PlangCircuit::parse(text).unwrap().hash());
+
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        let status = plang_circuit_set_val(circuit, "a".as_ptr(), 1, one.as_ptr());
+        assert_eq!(status, PlangStatus::Ok);
+
+        let status = plang_circuit_set_val(circuit, "x".as_ptr(), 1, one.as_ptr());
+        assert_eq!(status, PlangStatus::Values);
+        let message = CStr::from_ptr(plang_last_error()).to_str().unwrap();
+        assert_eq!(message, "error[P0009]: variable `x` is not in the circuit");
+
+        plang_circuit_free(circuit);
+    }
+}
+
+#[test]
+fn reports_errors() {
+    let text = "a * a = c\n";
+    let mut circuit = ptr::null_mut();
+
+    unsafe {
+        let status = plang_circuit_parse(text.as_ptr(), text.len(), &mut circuit);
+        assert_eq!(status, PlangStatus::Invalid);
+        assert!(circuit.is_null());
+
+        let status = plang_circuit_parse(ptr::null(), 1, &mut circuit);
+        assert_eq!(status, PlangStatus::InvalidArgument);
+    }
+}
@@ -0,0 +1,83 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+/**
+ * Values of the witnesses and public inputs of a circuit, keyed by name.
+ * Values are reduced modulo the order of the scalar field, so negative values
+ * and bigints of any size are accepted.
+ */
+export type Assignment = Record<string, number | bigint>
+
+/** An error in a line of a circuit. */
+export interface Diagnostic {
+  /** The line the error is in, counting from 0. */
+  line: number
+  /** The code of the error, such as `P0004`, if it has one. */
+  code?: string
+  message: string
+}
+
+/** Statistics of a circuit. */
+export interface Stats {
+  /** The hash of the circuit, hex encoded. */
+  hash: string
+  /** The number of gates the circuit is padded to. */
+  paddedGates: number
+  /** The degree of the public parameters the circuit requires. */
+  degree: number
+  /** The public inputs, in the order verification expects them in. */
+  publicInputs: Array<string>
+  /** The transcript label declared in the circuit. */
+  transcript?: Buffer
+}
+
+/** A value of a public input. */
+export interface PublicInput {
+  name: string
+  value: bigint
+}
+
+/** Checks a circuit, returning its errors in the order of the lines they're in. */
+export function check(text: string): Array<Diagnostic>
+
+/**
+ * Generates public parameters large enough for the circuit, derived from the
+ * given seed of up to 32 bytes. They're insecure, and flagged as such in their
+ * header, so they must only be used for testing.
+ */
+export function setupSeeded(circuit: Circuit, seed: Buffer): Buffer
+
+/** A circuit, with the values set on it. */
+export class Circuit {
+  /** Parses and checks a circuit from its text. */
+  static parse(text: string): Circuit
+  /** Sets the values of witnesses and public inputs. */
+  setVals(assignment: Assignment): void
+  /** Returns the value set for a variable, or null if it isn't in the circuit. */
+  value(name: string): bigint | null
+  /** Returns the hash of the circuit. */
+  hash(): Buffer
+  /** Returns statistics of the circuit. */
+  stats(): Stats
+  /**
+   * Solves for the values of the public inputs, in the order verification
+   * expects them in, from the values set on the circuit.
+   */
+  solve(): Array<PublicInput>
+  /**
+   * Compiles the circuit with the given public parameters, returning a key
+   * archive. The archive records the transcript label declared in the
+   * circuit, or otherwise "dusk_plang".
+   */
+  compile(pp: Buffer): Buffer
+  /** Proves the circuit with the values set on it, returning the proof. */
+  prove(pp: Buffer, keys: Buffer): Buffer
+  /**
+   * Verifies a proof of the circuit, with the values of the public inputs set
+   * on it. Throws if the proof is invalid.
+   */
+  verify(pp: Buffer, keys: Buffer, proof: Buffer): void
+}
@@ -0,0 +1,258 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Node.js bindings for plang, with the same surface as the Python module.
+//!
+//! Values are given as an assignment, an object mapping the names of
+//! variables to numbers or bigints, reduced modulo the order of the scalar
+//! field. Artifacts - public parameters, key archives and proofs - are the
+//! same bytes `plangc` reads and writes. The TypeScript definitions are in
+//! `index.d.ts`.
+
+use plang::artifact::{self, ArtifactKind};
+use plang::document::Document;
+use plang::dusk_plonk::prelude::{BlsScalar, Circuit as _};
+use plang::scalar::Scalar;
+use plang::transcript::DEFAULT_LABEL;
+use plang::{params, PlangError, PlangKeys, PlangProof, ProveError, ProveOptions};
+
+use std::collections::HashMap;
+
+use dusk_bytes::Serializable;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// An error in a line of a circuit.
+#[napi(object)]
+pub struct Diagnostic {
+    /// The line the error is in, counting from 0.
+    pub line: u32,
+    /// The code of the error, if it has one.
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// Statistics of a circuit.
+#[napi(object)]
+pub struct Stats {
+    /// The hash of the circuit, hex encoded.
+    pub hash: String,
+    /// The number of gates the circuit is padded to.
+    pub padded_gates: u32,
+    /// The degree of the public parameters the circuit requires.
+    pub degree: u32,
+    /// The public inputs, in the order verification expects them in.
+    pub public_inputs: Vec<String>,
+    /// The transcript label declared in the circuit.
+    pub transcript: Option<Buffer>,
+}
+
+/// A value of a public input.
+#[napi(object)]
+pub struct PublicInput {
+    pub name: String,
+    pub value: BigInt,
+}
+
+/// Checks a circuit, returning its errors in the order of the lines they're
+/// in.
+#[napi]
+pub fn check(text: String) -> Vec<Diagnostic> {
+    Document::new(text)
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| Diagnostic {
+            line: diagnostic.line as u32,
+            code: diagnostic.error.code().map(str::to_owned),
+            message: diagnostic.error.to_string(),
+        })
+        .collect()
+}
+
+/// Generates public parameters large enough for the circuit, derived from the
+/// given seed of up to 32 bytes. They're insecure, and flagged as such in their
+/// header, so they must only be used for testing.
+#[napi]
+pub fn setup_seeded(circuit: &Circuit, seed: Buffer) -> Result<Buffer> {
+    let mut padded = [0u8; 32];
+    let len = seed.len().min(32);
+    padded[..len].copy_from_slice(&seed[..len]);
+
+    let degree = params::degree_for_gates(circuit.0.padded_gates()).map_err(to_napi)?;
+    let pp = params::setup_seeded(degree, padded).map_err(to_napi)?;
+
+    Ok(artifact::wrap(
+        ArtifactKind::Params,
+        artifact::FLAG_INSECURE,
+        &pp.to_var_bytes(),
+    )
+    .into())
+}
+
+/// A circuit, with the values set on it.
+#[napi]
+pub struct Circuit(plang::PlangCircuit);
+
+#[napi]
+impl Circuit {
+    /// Parses and checks a circuit from its text.
+    #[napi(factory)]
+    pub fn parse(text: String) -> Result<Self> {
+        Ok(Self(plang::PlangCircuit::parse(text).map_err(to_napi)?))
+    }
+
+    /// Sets the values of witnesses and public inputs.
+    #[napi(ts_args_type = "assignment: Assignment")]
+    pub fn set_vals(&mut self, assignment: HashMap<String, Either<i64, BigInt>>) -> Result<()> {
+        self.0
+            .set_vals(from_assignment(assignment))
+            .map_err(to_napi)
+    }
+
+    /// Returns the value set for a variable, or null if it isn't in the
+    /// circuit.
+    #[napi]
+    pub fn value(&self, name: String) -> Option<BigInt> {
+        self.0.value(&name).map(|val| to_bigint(&val))
+    }
+
+    /// Returns the hash of the circuit.
+    #[napi]
+    pub fn hash(&self) -> Buffer {
+        self.0.hash().to_vec().into()
+    }
+
+    /// Returns statistics of the circuit.
+    #[napi]
+    pub fn stats(&self) -> Result<Stats> {
+        let gates = self.0.padded_gates();
+
+        Ok(Stats {
+            hash: hex::encode(self.0.hash()),
+            padded_gates: gates as u32,
+            degree: params::degree_for_gates(gates).map_err(to_napi)? as u32,
+            public_inputs: self
+                .0
+                .public_input_order()
+                .into_iter()
+                .map(str::to_owned)
+                .collect(),
+            transcript: self.0.transcript().map(|t| t.to_vec().into()),
+        })
+    }
+
+    /// Solves for the values of the public inputs, in the order verification
+    /// expects them in, from the values set on the circuit.
+    #[napi]
+    pub fn solve(&self) -> Vec<PublicInput> {
+        self.0
+            .public_input_order()
+            .into_iter()
+            .map(|name| PublicInput {
+                name: name.to_owned(),
+                value: to_bigint(&self.0.value(name).unwrap_or_default()),
+            })
+            .collect()
+    }
+
+    /// Compiles the circuit with the given public parameters, returning a key
+    /// archive. The archive records the transcript label declared in the
+    /// circuit, or otherwise "dusk_plang".
+    #[napi]
+    pub fn compile(&mut self, pp: Buffer) -> Result<Buffer> {
+        let pp = params::read_for_circuit(&pp, &self.0).map_err(to_napi)?;
+        let (pk, vd) = self.0.compile(&pp).map_err(to_napi)?;
+
+        let label = self.0.transcript().unwrap_or(DEFAULT_LABEL).to_vec();
+        let keys = PlangKeys::new(&self.0, &pp, Some(pk), vd, label);
+        Ok(keys.to_bytes().into())
+    }
+
+    /// Proves the circuit with the values set on it, returning the proof.
+    #[napi]
+    pub fn prove(&mut self, pp: Buffer, keys: Buffer) -> Result<Buffer> {
+        let pp = params::read_for_circuit(&pp, &self.0).map_err(to_napi)?;
+        let keys = PlangKeys::read_for_circuit(&keys, &self.0, &pp).map_err(to_napi)?;
+        let pk = keys
+            .prover_key
+            .ok_or(PlangError::Prove(ProveError::NoProverKey))
+            .map_err(to_napi)?;
+
+        let options = ProveOptions::new(keys.label.clone());
+        let proof = self.0.prove_with(&pp, &pk, &options).map_err(to_napi)?;
+
+        Ok(PlangProof::new(proof, keys.label).to_bytes().into())
+    }
+
+    /// Verifies a proof of the circuit, with the values of the public inputs
+    /// set on it. Throws if the proof is invalid.
+    #[napi]
+    pub fn verify(&self, pp: Buffer, keys: Buffer, proof: Buffer) -> Result<()> {
+        let pp = params::read_for_circuit(&pp, &self.0).map_err(to_napi)?;
+        let keys = PlangKeys::read_for_circuit(&keys, &self.0, &pp).map_err(to_napi)?;
+        let proof = PlangProof::from_slice(&proof).map_err(to_napi)?;
+
+        let vals: Vec<(String, BlsScalar)> = self
+            .0
+            .public_input_order()
+            .into_iter()
+            .map(|name| (name.to_owned(), self.0.value(name).unwrap_or_default()))
+            .collect();
+        let pinputs = self.0.public_inputs_from(vals).map_err(to_napi)?;
+
+        let options = ProveOptions::new(keys.label);
+        plang::PlangCircuit::verify_with(&pp, &keys.verifier_data, &proof.proof, &pinputs, &options)
+            .map_err(to_napi)
+    }
+}
+
+/// Converts the values of an assignment into scalars, reducing bigints modulo
+/// the order of the field.
+fn from_assignment(
+    assignment: HashMap<String, Either<i64, BigInt>>,
+) -> impl Iterator<Item = (String, BlsScalar)> {
+    assignment.into_iter().map(|(name, val)| {
+        let (negative, magnitude) = match val {
+            Either::A(val) => (val.is_negative(), Scalar::from(val.unsigned_abs())),
+            Either::B(val) => {
+                // Words are least significant first, as bytes are reduced.
+                let bytes: Vec<u8> = val.words.iter().flat_map(|w| w.to_le_bytes()).collect();
+                (val.sign_bit, Scalar::from_bytes_wide(&bytes))
+            }
+        };
+
+        let val = match negative {
+            true => -magnitude,
+            false => magnitude,
+        };
+        (name, BlsScalar::from(val))
+    })
+}
+
+fn to_bigint(scalar: &BlsScalar) -> BigInt {
+    let words = scalar
+        .to_bytes()
+        .chunks(8)
+        .map(|chunk| {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(chunk);
+            u64::from_le_bytes(word)
+        })
+        .collect();
+
+    BigInt {
+        sign_bit: false,
+        words,
+    }
+}
+
+fn to_napi<E: Into<PlangError>>(err: E) -> Error {
+    let err = err.into();
+    match err.code() {
+        Some(code) => Error::from_reason(format!("error[{}]: {}", code, err)),
+        None => Error::from_reason(err.to_string()),
+    }
+}
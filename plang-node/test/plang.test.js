@@ -0,0 +1,47 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+const test = require('node:test')
+const assert = require('node:assert')
+
+const { Circuit, check } = require('..')
+
+const ORDER = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001n
+
+test('values are reduced', () => {
+  const circuit = Circuit.parse('a * b = c\n')
+  circuit.setVals({ a: 3, b: 2n ** 300n, c: -1 })
+
+  assert.strictEqual(circuit.value('a'), 3n)
+  assert.strictEqual(circuit.value('b'), 2n ** 300n % ORDER)
+  assert.strictEqual(circuit.value('x'), null)
+  assert.deepStrictEqual(circuit.solve(), [{ name: 'c', value: ORDER - 1n }])
+
+  circuit.setVals({ a: -(2n ** 256n) })
+  assert.strictEqual(circuit.value('a'), ORDER - ((2n ** 256n) % ORDER))
+})
+
+test('stats', () => {
+  const stats = Circuit.parse('transcript "t"\na * b = c\n').stats()
+
+  assert.strictEqual(stats.paddedGates, 4)
+  assert.deepStrictEqual(stats.publicInputs, ['c'])
+  assert.deepStrictEqual(stats.transcript, Buffer.from('t'))
+})
+
+test('diagnostics', () => {
+  const diagnostics = check('a + b = c\na * a = d\na + = e\n')
+
+  assert.deepStrictEqual(
+    diagnostics.map(({ line, code }) => [line, code]),
+    [
+      [1, 'P0004'],
+      [2, 'P0001'],
+    ]
+  )
+  assert.match(diagnostics[1].message, /--> 3:5/)
+  assert.throws(() => Circuit.parse('a * a = d\n'), /P0004/)
+})
@@ -0,0 +1,213 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Python bindings for plang, so circuits can be scripted from notebooks.
+//!
+//! ```python
+//! import plang
+//!
+//! circuit = plang.Circuit.parse("a * b = c\n")
+//! circuit.set_vals({"a": 3, "b": 5, "c": 15})
+//!
+//! pp = plang.setup_seeded(circuit, b"seed")
+//! keys = circuit.compile(pp)
+//! proof = circuit.prove(pp, keys)
+//! circuit.verify(pp, keys, proof)
+//! ```
+//!
+//! Values are Python ints of any size, reduced modulo the order of the scalar
+//! field. Artifacts - public parameters, key archives and proofs - are the same
+//! bytes `plangc` reads and writes. Errors are raised as `plang.PlangError`.
+
+use plang::artifact::{self, ArtifactKind};
+use plang::dusk_plonk::prelude::*;
+use plang::scalar::Scalar;
+use plang::transcript::DEFAULT_LABEL;
+use plang::{params, PlangKeys, PlangProof, ProveError, ProveOptions};
+
+use std::collections::HashMap;
+
+use dusk_bytes::Serializable;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyLong};
+
+create_exception!(plang, PlangError, PyException);
+
+/// A circuit, with the values set on it.
+#[pyclass(name = "Circuit")]
+struct PyCircuit(plang::PlangCircuit);
+
+#[pymethods]
+impl PyCircuit {
+    /// Parses and checks a circuit from its text.
+    #[staticmethod]
+    fn parse(text: &str) -> PyResult<Self> {
+        Ok(Self(plang::PlangCircuit::parse(text).map_err(to_py)?))
+    }
+
+    /// Sets the values of witnesses and public inputs from a dict mapping
+    /// their names to ints.
+    fn set_vals(&mut self, vals: HashMap<String, &PyAny>) -> PyResult<()> {
+        let vals = vals
+            .into_iter()
+            .map(|(name, val)| Ok((name, to_scalar(val)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        self.0.set_vals(vals).map_err(to_py)
+    }
+
+    /// Returns the value set for a variable, or None if it isn't in the
+    /// circuit.
+    fn value(&self, py: Python<'_>, name: &str) -> PyResult<Option<PyObject>> {
+        self.0
+            .value(name)
+            .map(|val| from_scalar(py, &val))
+            .transpose()
+    }
+
+    /// Returns the hash of the circuit.
+    fn hash<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.0.hash())
+    }
+
+    /// Returns a dict with the hash of the circuit, the number of gates it's
+    /// padded to, the degree of the public parameters it requires, its public
+    /// inputs in the order verification expects them in, and its transcript
+    /// label.
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let gates = self.0.padded_gates();
+
+        let stats = PyDict::new(py);
+        stats.set_item("hash", hex::encode(self.0.hash()))?;
+        stats.set_item("padded_gates", gates)?;
+        stats.set_item("degree", params::degree_for_gates(gates).map_err(to_py)?)?;
+        stats.set_item("public_inputs", self.0.public_input_order())?;
+        stats.set_item(
+            "transcript",
+            self.0.transcript().map(|t| PyBytes::new(py, t)),
+        )?;
+        Ok(stats)
+    }
+
+    /// Compiles the circuit with the given public parameters, returning a key
+    /// archive. The archive records the transcript label declared in the
+    /// circuit, or otherwise "dusk_plang".
+    fn compile<'py>(&mut self, py: Python<'py>, pp: &[u8]) -> PyResult<&'py PyBytes> {
+        let pp = params::read_for_circuit(pp, &self.0).map_err(to_py)?;
+        let (pk, vd) = self.0.compile(&pp).map_err(to_py)?;
+
+        let label = self.0.transcript().unwrap_or(DEFAULT_LABEL).to_vec();
+        let keys = PlangKeys::new(&self.0, &pp, Some(pk), vd, label);
+        Ok(PyBytes::new(py, &keys.to_bytes()))
+    }
+
+    /// Proves the circuit with the values set on it, returning the proof.
+    fn prove<'py>(&mut self, py: Python<'py>, pp: &[u8], keys: &[u8]) -> PyResult<&'py PyBytes> {
+        let pp = params::read_for_circuit(pp, &self.0).map_err(to_py)?;
+        let keys = PlangKeys::read_for_circuit(keys, &self.0, &pp).map_err(to_py)?;
+        let pk = keys
+            .prover_key
+            .ok_or(plang::PlangError::Prove(ProveError::NoProverKey))
+            .map_err(to_py)?;
+
+        let options = ProveOptions::new(keys.label.clone());
+        let proof = self.0.prove_with(&pp, &pk, &options).map_err(to_py)?;
+
+        Ok(PyBytes::new(
+            py,
+            &PlangProof::new(proof, keys.label).to_bytes(),
+        ))
+    }
+
+    /// Verifies a proof of the circuit, with the values of the public inputs
+    /// set on it. Raises if the proof is invalid.
+    fn verify(&self, pp: &[u8], keys: &[u8], proof: &[u8]) -> PyResult<()> {
+        let pp = params::read_for_circuit(pp, &self.0).map_err(to_py)?;
+        let keys = PlangKeys::read_for_circuit(keys, &self.0, &pp).map_err(to_py)?;
+        let proof = PlangProof::from_slice(proof).map_err(to_py)?;
+
+        let vals: Vec<(String, BlsScalar)> = self
+            .0
+            .public_input_order()
+            .into_iter()
+            .map(|name| (name.to_owned(), self.0.value(name).unwrap_or_default()))
+            .collect();
+        let pinputs = self.0.public_inputs_from(vals).map_err(to_py)?;
+
+        let options = ProveOptions::new(keys.label);
+        plang::PlangCircuit::verify_with(&pp, &keys.verifier_data, &proof.proof, &pinputs, &options)
+            .map_err(to_py)
+    }
+}
+
+/// Generates public parameters large enough for the circuit, derived from the
+/// given seed of up to 32 bytes. They're insecure, and flagged as such in their
+/// header, so they must only be used for testing.
+#[pyfunction]
+fn setup_seeded<'py>(py: Python<'py>, circuit: &PyCircuit, seed: &[u8]) -> PyResult<&'py PyBytes> {
+    let mut padded = [0u8; 32];
+    let len = seed.len().min(32);
+    padded[..len].copy_from_slice(&seed[..len]);
+
+    let degree = params::degree_for_gates(circuit.0.padded_gates()).map_err(to_py)?;
+    let pp = params::setup_seeded(degree, padded).map_err(to_py)?;
+
+    Ok(PyBytes::new(
+        py,
+        &artifact::wrap(
+            ArtifactKind::Params,
+            artifact::FLAG_INSECURE,
+            &pp.to_var_bytes(),
+        ),
+    ))
+}
+
+#[pymodule]
+#[pyo3(name = "plang")]
+fn module(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyCircuit>()?;
+    m.add_function(wrap_pyfunction!(setup_seeded, m)?)?;
+    m.add("PlangError", py.get_type::<PlangError>())?;
+    Ok(())
+}
+
+/// Converts a Python int into a scalar, reducing it modulo the order of the
+/// field.
+fn to_scalar(val: &PyAny) -> PyResult<BlsScalar> {
+    let val: &PyLong = val.downcast()?;
+
+    // The magnitude is reduced from its bytes, and negated back if the int is
+    // negative. It always fits in one byte more than its whole bytes.
+    let magnitude = val.call_method0("__abs__")?;
+    let len = magnitude.call_method0("bit_length")?.extract::<usize>()? / 8 + 1;
+    let bytes: &PyBytes = magnitude
+        .call_method1("to_bytes", (len, "little"))?
+        .downcast()?;
+
+    let scalar = Scalar::from_bytes_wide(bytes.as_bytes());
+    Ok(BlsScalar::from(match val.lt(0)? {
+        true => -scalar,
+        false => scalar,
+    }))
+}
+
+/// Converts a scalar into a Python int.
+fn from_scalar(py: Python<'_>, scalar: &BlsScalar) -> PyResult<PyObject> {
+    let bytes = PyBytes::new(py, &scalar.to_bytes());
+    let int = py
+        .get_type::<PyLong>()
+        .call_method1("from_bytes", (bytes, "little"))?;
+    Ok(int.into())
+}
+
+fn to_py<E: Into<plang::PlangError>>(err: E) -> PyErr {
+    let err = err.into();
+    match err.code() {
+        Some(code) => PlangError::new_err(format!("error[{}]: {}", code, err)),
+        None => PlangError::new_err(err.to_string()),
+    }
+}
@@ -0,0 +1,38 @@
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at http://mozilla.org/MPL/2.0/.
+#
+# Copyright (c) DUSK NETWORK. All rights reserved.
+
+import pytest
+
+import plang
+
+ORDER = 0x73EDA753299D7D483339D80809A1D80553BDA402FFFE5BFEFFFFFFFF00000001
+
+
+def test_values_are_reduced():
+    circuit = plang.Circuit.parse("a * b = c\n")
+    circuit.set_vals({"a": 2**300, "b": -1, "c": -(2**256)})
+
+    assert circuit.value("a") == 2**300 % ORDER
+    assert circuit.value("b") == ORDER - 1
+    assert circuit.value("c") == -(2**256) % ORDER
+    assert circuit.value("x") is None
+
+
+def test_stats():
+    stats = plang.Circuit.parse('transcript "t"\na * b = c\n').stats()
+
+    assert stats["padded_gates"] == 4
+    assert stats["public_inputs"] == ["c"]
+    assert stats["transcript"] == b"t"
+
+
+def test_errors():
+    with pytest.raises(plang.PlangError, match="P0004"):
+        plang.Circuit.parse("a * a = c\n")
+
+    circuit = plang.Circuit.parse("a + b = c\n")
+    with pytest.raises(plang.PlangError, match="P0009"):
+        circuit.set_vals({"x": 1})
@@ -0,0 +1,254 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! WebAssembly bindings for plang, so circuits can be checked, solved,
+//! compiled, proven and verified entirely client-side.
+//!
+//! Circuits are passed as text. Values are passed as an object mapping the
+//! names of variables to integers, or to numbers written as strings in decimal
+//! or, with a `0x` prefix, in hex, and artifacts - public parameters, key
+//! archives and proofs - as the same bytes `plangc` reads and writes, so they
+//! can be moved between the two. Errors are thrown as `Error`s with the
+//! message `plangc` would print.
+
+use plang::artifact::{self, ArtifactKind};
+use plang::document::Document;
+use plang::dusk_plonk::prelude::*;
+use plang::scalar::{Scalar, ScalarValue};
+use plang::tokens::{self, TokenKind};
+use plang::transcript::DEFAULT_LABEL;
+use plang::{params, PlangCircuit, PlangError, PlangKeys, PlangProof, ProveError, ProveOptions};
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+type Result<T> = std::result::Result<T, JsValue>;
+
+/// Checks a circuit, returning its errors as an array of
+/// `{ line, code, message }` objects. Lines count from 0.
+#[wasm_bindgen]
+pub fn check(text: &str) -> JsValue {
+    let document = Document::new(text);
+
+    let diagnostics: Vec<Value> = document
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.to_json())
+        .collect();
+
+    to_js(&Value::Array(diagnostics))
+}
+
+/// Splits a circuit into tokens for highlighting, returning them as an array
+/// of `{ kind, start, end }` objects, with `start` and `end` in bytes.
+#[wasm_bindgen]
+pub fn tokenize(text: &str) -> JsValue {
+    let tokens: Vec<Value> = tokens::tokenize(text)
+        .into_iter()
+        .map(|token| {
+            let kind = match token.kind {
+                TokenKind::Keyword => "keyword",
+                TokenKind::Variable => "variable",
+                TokenKind::Coefficient => "coefficient",
+                TokenKind::Operator => "operator",
+                TokenKind::String => "string",
+                TokenKind::Comment => "comment",
+                TokenKind::Unknown => "unknown",
+            };
+            json!({
+                "kind": kind,
+                "start": token.span.start,
+                "end": token.span.end,
+            })
+        })
+        .collect();
+
+    to_js(&Value::Array(tokens))
+}
+
+/// Returns statistics of a circuit - its hash, the number of gates it's padded
+/// to, the degree of the public parameters it requires and its public inputs,
+/// in the order verification expects them in.
+#[wasm_bindgen]
+pub fn stats(text: &str) -> Result<JsValue> {
+    let circuit = PlangCircuit::parse(text).map_err(to_error)?;
+    let gates = circuit.padded_gates();
+
+    Ok(to_js(&json!({
+        "hash": hex::encode(circuit.hash()),
+        "paddedGates": gates,
+        "degree": params::degree_for_gates(gates).map_err(to_error)?,
+        "publicInputs": circuit.public_input_order(),
+        "transcript": circuit.transcript().map(String::from_utf8_lossy),
+    })))
+}
+
+/// Arranges the values of the public inputs of a circuit in the order
+/// verification expects them in, returning them as an array of
+/// `{ name, value }` objects. Values of witnesses are ignored, so it can be
+/// given the same values as [`prove`].
+#[wasm_bindgen(js_name = publicInputs)]
+pub fn public_inputs(text: &str, vals: JsValue) -> Result<JsValue> {
+    let circuit = PlangCircuit::parse(text).map_err(to_error)?;
+    let order = circuit.public_input_order();
+
+    let vals: HashMap<String, Value> = from_js_object(&vals)?
+        .into_iter()
+        .filter(|(name, _)| order.contains(&name.as_str()))
+        .collect();
+    circuit
+        .public_inputs_from(
+            vals.iter()
+                .map(|(name, val)| Ok((name.clone(), scalar_value(name, val)?)))
+                .collect::<Result<Vec<_>>>()?,
+        )
+        .map_err(to_error)?;
+
+    let pinputs: Vec<Value> = order
+        .into_iter()
+        .map(|name| json!({ "name": name, "value": vals[name] }))
+        .collect();
+    Ok(to_js(&Value::Array(pinputs)))
+}
+
+/// Solves a circuit for the values of its witnesses, given the values of the
+/// variables it doesn't derive itself, checking they satisfy it. Returns them
+/// as `{ witnesses, publicInputs }`, with the witnesses an object mapping their
+/// names to their values - including those introduced while lowering - and the
+/// public inputs an array of `{ name, value }` objects, in the order
+/// verification expects them in. Values are decimal strings, since they don't
+/// fit in a number.
+#[wasm_bindgen]
+pub fn solve(text: &str, vals: JsValue) -> Result<JsValue> {
+    let mut circuit = PlangCircuit::parse(text).map_err(to_error)?;
+    circuit.set_vals(from_js_vals(&vals)?).map_err(to_error)?;
+    circuit.check_satisfied().map_err(to_error)?;
+
+    let decimal = |value: BlsScalar| Scalar::from(value).to_string();
+    let witnesses: serde_json::Map<String, Value> = circuit
+        .assignment()
+        .witnesses
+        .into_iter()
+        .map(|(name, value)| (name, decimal(value).into()))
+        .collect();
+    let pinputs: Vec<Value> = circuit
+        .public_inputs_named()
+        .map(|(name, value)| json!({ "name": name, "value": decimal(value) }))
+        .collect();
+
+    Ok(to_js(&json!({
+        "witnesses": witnesses,
+        "publicInputs": pinputs,
+    })))
+}
+
+/// Generates public parameters large enough for the circuit, derived from the
+/// given seed. They're insecure, and flagged as such in their header, so they
+/// must only be used for testing.
+#[wasm_bindgen(js_name = setupSeeded)]
+pub fn setup_seeded(text: &str, seed: &[u8]) -> Result<Vec<u8>> {
+    let circuit = PlangCircuit::parse(text).map_err(to_error)?;
+
+    let mut padded = [0u8; 32];
+    let len = seed.len().min(32);
+    padded[..len].copy_from_slice(&seed[..len]);
+
+    let degree = params::degree_for_gates(circuit.padded_gates()).map_err(to_error)?;
+    let pp = params::setup_seeded(degree, padded).map_err(to_error)?;
+
+    Ok(artifact::wrap(
+        ArtifactKind::Params,
+        artifact::FLAG_INSECURE,
+        &pp.to_var_bytes(),
+    ))
+}
+
+/// Compiles a circuit into a key archive, with the transcript label declared
+/// in the circuit.
+#[wasm_bindgen]
+pub fn compile(text: &str, pp: &[u8]) -> Result<Vec<u8>> {
+    let mut circuit = PlangCircuit::parse(text).map_err(to_error)?;
+    let pp = params::read_for_circuit(pp, &circuit).map_err(to_error)?;
+
+    let (pk, vd) = circuit.compile(&pp).map_err(to_error)?;
+    let label = circuit.transcript().unwrap_or(DEFAULT_LABEL).to_vec();
+
+    Ok(PlangKeys::new(&circuit, &pp, Some(pk), vd, label).to_bytes())
+}
+
+/// Proves a circuit with the given values, returning the proof.
+#[wasm_bindgen]
+pub fn prove(text: &str, pp: &[u8], keys: &[u8], vals: JsValue) -> Result<Vec<u8>> {
+    let mut circuit = PlangCircuit::parse(text).map_err(to_error)?;
+    circuit.set_vals(from_js_vals(&vals)?).map_err(to_error)?;
+
+    let pp = params::read_for_circuit(pp, &circuit).map_err(to_error)?;
+    let keys = PlangKeys::read_for_circuit(keys, &circuit, &pp).map_err(to_error)?;
+    let pk = keys
+        .prover_key
+        .ok_or(PlangError::Prove(ProveError::NoProverKey))
+        .map_err(to_error)?;
+
+    let options = ProveOptions::new(keys.label.clone());
+    let proof = circuit.prove_with(&pp, &pk, &options).map_err(to_error)?;
+
+    Ok(PlangProof::new(proof, keys.label).to_bytes())
+}
+
+/// Verifies a proof of a circuit with the given values of its public inputs,
+/// throwing if it's invalid.
+#[wasm_bindgen]
+pub fn verify(text: &str, pp: &[u8], keys: &[u8], proof: &[u8], vals: JsValue) -> Result<()> {
+    let circuit = PlangCircuit::parse(text).map_err(to_error)?;
+
+    let pp = params::read_for_circuit(pp, &circuit).map_err(to_error)?;
+    let keys = PlangKeys::read_for_circuit(keys, &circuit, &pp).map_err(to_error)?;
+    let proof = PlangProof::from_slice(proof).map_err(to_error)?;
+
+    let pinputs = circuit
+        .public_inputs_from(from_js_vals(&vals)?)
+        .map_err(to_error)?;
+
+    let options = ProveOptions::new(keys.label);
+    PlangCircuit::verify_with(&pp, &keys.verifier_data, &proof.proof, &pinputs, &options)
+        .map_err(to_error)
+}
+
+/// Reads an object mapping the names of variables to values.
+fn from_js_object(vals: &JsValue) -> Result<serde_json::Map<String, Value>> {
+    let json = js_sys::JSON::stringify(vals)?
+        .as_string()
+        .unwrap_or_default();
+    serde_json::from_str(&json)
+        .map_err(|_| JsValue::from(js_sys::Error::new("values must be an object")))
+}
+
+/// Reads an object mapping the names of variables to integers, or to numbers
+/// written as strings in decimal or, with a `0x` prefix, in hex.
+fn from_js_vals(vals: &JsValue) -> Result<Vec<(String, ScalarValue)>> {
+    from_js_object(vals)?
+        .into_iter()
+        .map(|(name, val)| {
+            let val = scalar_value(&name, &val)?;
+            Ok((name, val))
+        })
+        .collect()
+}
+
+fn scalar_value(name: &str, val: &Value) -> Result<ScalarValue> {
+    ScalarValue::from_json(val)
+        .ok_or_else(|| js_sys::Error::new(&format!("value of `{}` is not a number", name)).into())
+}
+
+fn to_js(value: &Value) -> JsValue {
+    js_sys::JSON::parse(&value.to_string()).expect("serialized JSON is valid")
+}
+
+fn to_error<E: Into<PlangError>>(err: E) -> JsValue {
+    js_sys::Error::new(&err.into().to_string()).into()
+}
@@ -0,0 +1,71 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Run with `wasm-pack test --node plang-wasm`.
+
+#![cfg(target_arch = "wasm32")]
+
+use serde_json::{json, Value};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+const CIRCUIT: &str = "a * b + c = x\n";
+
+fn js(value: Value) -> JsValue {
+    js_sys::JSON::parse(&value.to_string()).unwrap()
+}
+
+fn json(value: &JsValue) -> Value {
+    let text = js_sys::JSON::stringify(value).unwrap().as_string().unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+fn message(err: JsValue) -> String {
+    js_sys::Error::from(err).message().into()
+}
+
+#[wasm_bindgen_test]
+fn checks_circuits() {
+    assert_eq!(json(&plang_wasm::check(CIRCUIT)), json!([]));
+
+    let diagnostics = json(&plang_wasm::check("a + b = c\na * a = d\n"));
+    assert_eq!(diagnostics[0]["line"], 1);
+    assert_eq!(diagnostics[0]["code"], "P0004");
+}
+
+#[wasm_bindgen_test]
+fn solves_for_witnesses() {
+    let circuit = "bits(x, 4) -> b[]\nb[0] + b[3] = y\n";
+
+    let solved = plang_wasm::solve(circuit, js(json!({ "x": 9, "y": 2 }))).unwrap();
+    let solved = json(&solved);
+    assert_eq!(solved["witnesses"]["b[0]"], "1");
+    assert_eq!(solved["witnesses"]["b[1]"], "0");
+    assert_eq!(solved["witnesses"]["b[3]"], "1");
+    assert_eq!(
+        solved["publicInputs"],
+        json!([{ "name": "y", "value": "2" }])
+    );
+
+    // Values not satisfying the circuit aren't solved for
+    let err = plang_wasm::solve(circuit, js(json!({ "x": 9, "y": 1 }))).unwrap_err();
+    assert!(message(err).contains("is not satisfied"));
+}
+
+#[wasm_bindgen_test]
+fn proves_and_verifies() {
+    let pp = plang_wasm::setup_seeded(CIRCUIT, b"plang-wasm").unwrap();
+    let keys = plang_wasm::compile(CIRCUIT, &pp).unwrap();
+
+    let vals = json!({ "a": 2, "b": "3", "c": "0x4", "x": 10 });
+    let proof = plang_wasm::prove(CIRCUIT, &pp, &keys, js(vals)).unwrap();
+
+    let pinputs = json!({ "x": 10 });
+    plang_wasm::verify(CIRCUIT, &pp, &keys, &proof, js(pinputs)).unwrap();
+
+    let pinputs = json!({ "x": 11 });
+    assert!(plang_wasm::verify(CIRCUIT, &pp, &keys, &proof, js(pinputs)).is_err());
+}
@@ -0,0 +1,101 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use plang::document::Document;
+use plang::dusk_plonk::prelude::*;
+use plang::grammar::PlangGrammar;
+use plang::{generate, params, PlangCircuit};
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// The number of equations in the small, medium and large circuits.
+const SIZES: [(&str, usize); 3] = [("small", 64), ("medium", 4096), ("large", 65536)];
+
+fn circuit_text(equations: usize) -> String {
+    let mut rng = ChaCha20Rng::seed_from_u64(equations as u64);
+    generate::circuit(&mut rng, equations)
+}
+
+/// Each stage of the front end on its own - building the syntax tree, then
+/// lowering and checking the equations in it - and both at once. Checking each
+/// line on its own, as editors do, is measured as well.
+fn front_end(c: &mut Criterion) {
+    let mut group = c.benchmark_group("front_end");
+
+    for &(name, equations) in &SIZES {
+        let text = circuit_text(equations);
+        group.throughput(Throughput::Elements(equations as u64));
+
+        group.bench_with_input(BenchmarkId::new("grammar", name), &text, |b, text| {
+            b.iter(|| PlangGrammar::new(text.as_str()).unwrap())
+        });
+
+        let grammar = PlangGrammar::new(text.as_str()).unwrap();
+        group.bench_with_input(BenchmarkId::new("lower", name), &grammar, |b, grammar| {
+            b.iter_batched(
+                || grammar.clone(),
+                |grammar| PlangCircuit::from_grammar(grammar).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+
+        group.bench_with_input(BenchmarkId::new("parse", name), &text, |b, text| {
+            b.iter(|| PlangCircuit::parse(text.as_str()).unwrap())
+        });
+
+        group.bench_with_input(BenchmarkId::new("document", name), &text, |b, text| {
+            b.iter(|| Document::new(text))
+        });
+    }
+
+    group.finish();
+}
+
+/// Laying the equations out on gates, as done on every compilation and proof.
+fn gadget(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gadget");
+
+    for &(name, equations) in &SIZES {
+        let text = circuit_text(equations);
+        group.throughput(Throughput::Elements(equations as u64));
+
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || PlangCircuit::parse(text.as_str()).unwrap(),
+                |mut circuit| {
+                    let mut composer = TurboComposer::new();
+                    circuit.gadget(&mut composer).unwrap();
+                    composer
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+/// Compiling circuits into their keys. The large circuit is left out, since
+/// compiling it once takes longer than all the other benchmarks together.
+fn compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compile");
+    group.sample_size(10);
+
+    for &(name, equations) in &SIZES[..2] {
+        let mut circuit = PlangCircuit::parse(circuit_text(equations)).unwrap();
+
+        let degree = params::degree_for_gates(circuit.padded_gates()).unwrap();
+        let pp = params::setup_seeded(degree, [0; 32]).unwrap();
+
+        group.bench_function(name, |b| b.iter(|| circuit.compile(&pp).unwrap()));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, front_end, gadget, compile);
+criterion_main!(benches);
@@ -0,0 +1,166 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Headers for the artifacts produced by plang.
+//!
+//! Every artifact - public parameters, keys and proofs - is prefixed by a
+//! small header identifying what it is, the version of its format, and a
+//! checksum of its contents. This makes it possible to give a helpful error
+//! when, for instance, verifier data is passed where a prover key is expected,
+//! instead of failing somewhere deep in deserialization.
+//!
+//! The header is laid out as follows:
+//!
+//! | bytes  | content                                   |
+//! |--------|-------------------------------------------|
+//! | 0..5   | magic bytes `PLANG`                       |
+//! | 5      | artifact kind                             |
+//! | 6      | format version                            |
+//! | 7      | flags                                     |
+//! | 8..16  | payload length as a little endian `u64`   |
+//! | 16..48 | BLAKE2s checksum of the payload           |
+
+use crate::error::{Error as PlangError, ProveError, Result};
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt;
+
+use blake2::{Blake2s, Digest};
+
+/// Magic bytes at the start of every artifact.
+pub const MAGIC: &[u8; 5] = b"PLANG";
+/// Current version of the artifact format.
+pub const VERSION: u8 = 1;
+/// Size of the artifact header.
+pub const HEADER_SIZE: usize = 48;
+
+/// Flag marking public parameters as insecure, for instance because they were
+/// derived from a known seed.
+pub const FLAG_INSECURE: u8 = 1;
+
+/// The kinds of artifact plang produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    Params,
+    ProverKey,
+    VerifierData,
+    Proof,
+    Keys,
+    Verifier,
+    Envelope,
+}
+
+impl ArtifactKind {
+    fn from_byte(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0 => Self::Params,
+            1 => Self::ProverKey,
+            2 => Self::VerifierData,
+            3 => Self::Proof,
+            4 => Self::Keys,
+            5 => Self::Verifier,
+            6 => Self::Envelope,
+            _ => return None,
+        })
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Params => 0,
+            Self::ProverKey => 1,
+            Self::VerifierData => 2,
+            Self::Proof => 3,
+            Self::Keys => 4,
+            Self::Verifier => 5,
+            Self::Envelope => 6,
+        }
+    }
+}
+
+impl fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Params => "public parameters",
+            Self::ProverKey => "prover key",
+            Self::VerifierData => "verifier data",
+            Self::Proof => "proof",
+            Self::Keys => "key archive",
+            Self::Verifier => "verifier archive",
+            Self::Envelope => "proof envelope",
+        })
+    }
+}
+
+/// Describes the circuit an artifact was compiled from, for protocols that
+/// refer to their circuits by name and version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// The name of the circuit.
+    pub name: Option<String>,
+    /// The version of the circuit.
+    pub version: Option<String>,
+}
+
+/// Prefixes the payload with a header for the given kind of artifact.
+pub fn wrap(kind: ArtifactKind, flags: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+
+    bytes.extend(MAGIC);
+    bytes.push(kind.to_byte());
+    bytes.push(VERSION);
+    bytes.push(flags);
+    bytes.extend(&(payload.len() as u64).to_le_bytes());
+    bytes.extend(Blake2s::digest(payload));
+    bytes.extend(payload);
+
+    bytes
+}
+
+/// Checks the header of an artifact of the given kind, returning its payload
+/// and flags.
+///
+/// Artifacts written before headers were introduced don't start with the
+/// magic bytes, and are returned as they are with no flags set.
+pub fn unwrap(kind: ArtifactKind, bytes: &[u8]) -> Result<(&[u8], u8)> {
+    if !bytes.starts_with(MAGIC) {
+        return Ok((bytes, 0));
+    }
+    if bytes.len() < HEADER_SIZE {
+        return Err(PlangError::Prove(ProveError::TruncatedArtifact));
+    }
+
+    let found = ArtifactKind::from_byte(bytes[5])
+        .ok_or(PlangError::Prove(ProveError::UnknownArtifact(bytes[5])))?;
+    if found != kind {
+        return Err(PlangError::Prove(ProveError::WrongArtifact {
+            expected: kind,
+            found,
+        }));
+    }
+
+    let version = bytes[6];
+    if version != VERSION {
+        return Err(PlangError::Prove(ProveError::UnsupportedArtifactVersion(
+            version,
+        )));
+    }
+
+    let flags = bytes[7];
+
+    let len = u64::from_le_bytes(bytes[8..16].try_into().expect("slice is 8 bytes long"));
+    let payload = &bytes[HEADER_SIZE..];
+    if payload.len() as u64 != len {
+        return Err(PlangError::Prove(ProveError::TruncatedArtifact));
+    }
+
+    if Blake2s::digest(payload)[..] != bytes[16..HEADER_SIZE] {
+        return Err(PlangError::Prove(ProveError::ArtifactChecksum));
+    }
+
+    Ok((payload, flags))
+}
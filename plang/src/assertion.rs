@@ -0,0 +1,96 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Assertions on the values of a circuit, such as `assert d != 0;`.
+//!
+//! Assertions aren't constraints: they add no gates, and a proof says nothing
+//! about them. They're checked on the values set on a circuit, once the
+//! variables introduced while lowering are computed, to catch values that
+//! would make the circuit vacuous - such as a zero denominator, which the
+//! quotient of a gadget silently takes to be 0.
+
+use crate::error::{AssignmentError, Error as PlangError, Result};
+use crate::grammar::{NodeRef, Rule};
+use crate::scalar::Scalar;
+
+/// An assertion that a variable is, or isn't, equal to another, or to a
+/// value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(feature = "prover"), allow(dead_code))]
+pub(crate) struct Assertion {
+    var: String,
+    equal: bool,
+    operand: Operand,
+    /// The assertion as written in the source.
+    pub(crate) text: String,
+    /// The line the assertion is on in the source, counting from 0.
+    pub(crate) line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(feature = "prover"), allow(dead_code))]
+enum Operand {
+    Var(String),
+    Value(Scalar),
+}
+
+/// Lowers an `assert` on the given line into the assertion it makes.
+pub(crate) fn lower_assertion(node: NodeRef<'_>, line: usize) -> Assertion {
+    let mut vars = vec![];
+    let mut equal = true;
+    let mut negated = false;
+    let mut value = None;
+
+    for child in node.children() {
+        match child.rule() {
+            Rule::var => vars.push(child.as_str().to_owned()),
+            Rule::assert_op => equal = child.as_str() == "==",
+            Rule::sign => negated = child.as_str() == "-",
+            Rule::value => {
+                value = Scalar::from_decimal(child.as_str()).map(|(value, _)| value);
+            }
+            _ => {}
+        }
+    }
+
+    let mut vars = vars.into_iter();
+    let var = vars.next().expect("the grammar defines the assertion");
+    let operand = match (vars.next(), value) {
+        (Some(other), _) => Operand::Var(other),
+        (None, Some(value)) if negated => Operand::Value(-value),
+        (None, value) => Operand::Value(value.expect("the grammar defines the assertion")),
+    };
+
+    Assertion {
+        var,
+        equal,
+        operand,
+        text: node.as_str().trim_end_matches(';').trim_end().to_owned(),
+        line,
+    }
+}
+
+impl Assertion {
+    /// Returns whether the assertion holds for the values of the variables,
+    /// as given by `value`. It returns an error if a variable is not in the
+    /// circuit.
+    #[cfg_attr(not(feature = "prover"), allow(dead_code))]
+    pub(crate) fn holds<F: Fn(&str) -> Option<Scalar>>(&self, value: F) -> Result<bool> {
+        let get = |name: &str| {
+            value(name).ok_or_else(|| {
+                PlangError::Assignment(AssignmentError::NoSuchValue(name.to_owned()))
+            })
+        };
+
+        let left = get(&self.var)?;
+        let right = match &self.operand {
+            Operand::Var(name) => get(name)?,
+            Operand::Value(value) => *value,
+        };
+
+        Ok((left == right) == self.equal)
+    }
+}
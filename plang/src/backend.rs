@@ -0,0 +1,175 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Constraint systems the gates of a circuit are emitted into.
+//!
+//! A circuit compiles its equations down to gates of the form
+//!
+//! `q_m·a·b + q_l·a + q_r·b + q_o·o + q_4·d + PI + q_c = 0`
+//!
+//! which any PLONK-ish constraint system can express. [`PlangCircuit::emit`]
+//! appends them to a [`Backend`], of which dusk-plonk's [`TurboComposer`] is
+//! one, so the same circuit can be laid out in other systems as well.
+
+use crate::error::Result;
+use crate::scalar::Scalar;
+use crate::PlangCircuit;
+
+use dusk_plonk::prelude::*;
+
+/// Names of the wires of a [`GateView`].
+pub const WIRES: [&str; 5] = ["a", "b", "o", "d", "p"];
+/// Names of the selectors of a [`GateView`].
+pub const SELECTORS: [&str; 7] = ["q_m", "q_l", "q_r", "q_o", "q_4", "q_p", "q_c"];
+
+/// An equation laid out as a standard PLONK gate of the form:
+///
+/// `q_m·a·b + q_l·a + q_r·b + q_o·o + q_4·d + q_p·p + q_c = 0`
+///
+/// The variables of a product go in `a` and `b`, the other variables in the
+/// first free wire, and the public input in `p`. This is the gate the
+/// equation stands for, as exports write it out, by the names of its
+/// variables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GateView<'a> {
+    /// The variable on each wire, in the order of [`WIRES`].
+    pub wires: [Option<&'a str>; 5],
+    /// The selectors, in the order of [`SELECTORS`].
+    pub selectors: [Scalar; 7],
+}
+
+impl GateView<'_> {
+    /// The value of the left side of the gate, given the value of each
+    /// variable. The gate holds if it's 0. Empty wires hold 0.
+    pub fn evaluate<F: Fn(&str) -> Scalar>(&self, value: F) -> Scalar {
+        let value = |wire: usize| self.wires[wire].map_or(Scalar::zero(), &value);
+        let selectors = &self.selectors;
+
+        // q_m·a·b + q_l·a + q_r·b + q_o·o + q_4·d + q_p·p + q_c
+        (0..self.wires.len()).fold(
+            selectors[0] * value(0) * value(1) + selectors[6],
+            |sum, wire| sum + selectors[wire + 1] * value(wire),
+        )
+    }
+}
+
+/// A constraint system gates can be appended to.
+pub trait Backend {
+    /// A handle to a witness appended to the backend.
+    type Witness: Copy;
+
+    /// Appends a witness with the given value.
+    fn append_witness(&mut self, value: BlsScalar) -> Self::Witness;
+
+    /// Appends a gate over witnesses appended before.
+    fn append_gate(&mut self, gate: Gate<Self::Witness>);
+}
+
+/// A gate of the form `q_m·a·b + q_l·a + q_r·b + q_o·o + q_4·d + PI + q_c = 0`.
+///
+/// A wire left empty holds zero, as does a missing public input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gate<W> {
+    pub q_m: BlsScalar,
+    pub q_l: BlsScalar,
+    pub q_r: BlsScalar,
+    pub q_o: BlsScalar,
+    pub q_4: BlsScalar,
+    pub a: Option<W>,
+    pub b: Option<W>,
+    pub o: Option<W>,
+    pub d: Option<W>,
+    /// The value of the public input, already signed as it appears in the
+    /// gate.
+    pub public: Option<BlsScalar>,
+    /// The constant term.
+    pub q_c: BlsScalar,
+}
+
+impl Backend for TurboComposer {
+    type Witness = Witness;
+
+    fn append_witness(&mut self, value: BlsScalar) -> Witness {
+        TurboComposer::append_witness(self, value)
+    }
+
+    fn append_gate(&mut self, gate: Gate<Witness>) {
+        let mut constraint = Constraint::new()
+            .mult(gate.q_m)
+            .left(gate.q_l)
+            .right(gate.q_r)
+            .output(gate.q_o)
+            .fourth(gate.q_4)
+            .constant(gate.q_c);
+
+        if let Some(public) = gate.public {
+            constraint = constraint.public(public);
+        }
+        if let Some(a) = gate.a {
+            constraint = constraint.a(a);
+        }
+        if let Some(b) = gate.b {
+            constraint = constraint.b(b);
+        }
+        if let Some(o) = gate.o {
+            constraint = constraint.o(o);
+        }
+        if let Some(d) = gate.d {
+            constraint = constraint.d(d);
+        }
+
+        TurboComposer::append_gate(self, constraint);
+    }
+}
+
+impl PlangCircuit {
+    /// Lays out each equation of the circuit as a gate, in the order they're
+    /// declared in.
+    pub fn gates(&self) -> Vec<GateView<'_>> {
+        self.exprs().iter().map(|expr| expr.gate()).collect()
+    }
+
+    /// Appends the witnesses and gates of the circuit to a backend, with the
+    /// values currently set.
+    ///
+    /// It returns an error, before appending anything, if an equation of the
+    /// circuit can't be laid out on a gate.
+    pub fn emit<B: Backend>(&self, backend: &mut B) -> Result<()> {
+        let program = self.program();
+        program.check()?;
+
+        let witnesses: Vec<B::Witness> = program
+            .witnesses
+            .iter()
+            .map(|var| backend.append_witness(self.var_value(*var).into()))
+            .collect();
+
+        for gate in &program.gates {
+            let [q_m, q_l, q_r, q_o, q_4] = gate.selectors;
+            let [a, b, o, d] = gate.wires;
+
+            let public = gate
+                .public
+                .map(|(var, coeff)| BlsScalar::from(coeff) * BlsScalar::from(self.var_value(var)));
+
+            backend.append_gate(Gate {
+                q_m: q_m.into(),
+                q_l: q_l.into(),
+                q_r: q_r.into(),
+                q_o: q_o.into(),
+                q_4: q_4.into(),
+                a: a.map(|a| witnesses[a]),
+                b: b.map(|b| witnesses[b]),
+                o: o.map(|o| witnesses[o]),
+                d: d.map(|d| witnesses[d]),
+                public,
+                q_c: gate.constant.into(),
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! An on-disk cache of compiled keys.
+//!
+//! Compiling a circuit is by far the most expensive step short of proving, and
+//! it's redone on every build even when neither the circuit nor the setup
+//! changed. A [`KeyCache`] stores the keys compiled from a circuit as a key
+//! archive, named after the hash of the circuit and the digest of the public
+//! parameters, so they're read back instead of recompiled as long as both
+//! stay the same.
+
+use crate::error::Result;
+use crate::{params, PlangCircuit, PlangKeys};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use dusk_plonk::prelude::*;
+use tracing::{debug, info_span};
+
+/// A directory of compiled keys, keyed by circuit hash and parameters digest.
+#[derive(Debug, Clone)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    /// Uses the given directory as a cache. It's created when the first keys
+    /// are stored in it.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory the cache is in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Compiles the circuit with the given parameters, or reads the keys back
+    /// from the cache if it was compiled with them before.
+    ///
+    /// Keys compiled by a different version of plang are compiled anew, as
+    /// are entries that can't be read, which are then overwritten.
+    pub fn compile(
+        &self,
+        circuit: &mut PlangCircuit,
+        pp: &PublicParameters,
+    ) -> Result<(ProverKey, VerifierData)> {
+        let _span = info_span!("cached_compile").entered();
+
+        let path = self.path(circuit.hash(), params::digest(pp));
+        if let Some(keys) = read_entry(&path)? {
+            debug!(path = %path.display(), "cache hit");
+            return Ok(keys);
+        }
+
+        debug!(path = %path.display(), "cache miss");
+        let (pk, vd) = circuit.compile(pp)?;
+
+        let keys = PlangKeys::new(circuit, pp, Some(pk), vd, vec![]);
+        self.write_entry(&path, &keys)?;
+
+        let PlangKeys {
+            prover_key,
+            verifier_data,
+            ..
+        } = keys;
+        Ok((
+            prover_key.expect("the prover key was just compiled"),
+            verifier_data,
+        ))
+    }
+
+    /// The path of the entry for the given circuit hash and parameters
+    /// digest.
+    pub fn path(&self, circuit_hash: [u8; 32], params_digest: [u8; 32]) -> PathBuf {
+        let name = format!(
+            "{}-{}.plangkeys",
+            hex::encode(circuit_hash),
+            hex::encode(params_digest)
+        );
+        self.dir.join(name)
+    }
+
+    /// Writes an entry to a temporary file first, and then moves it in place,
+    /// so concurrent builds never read one half written.
+    fn write_entry(&self, path: &Path, keys: &PlangKeys) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, keys.to_bytes())?;
+        fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+}
+
+/// Reads the keys in an entry, if there is a usable one.
+fn read_entry(path: &Path) -> Result<Option<(ProverKey, VerifierData)>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let keys = match PlangKeys::from_slice(&bytes) {
+        Ok(keys) => keys,
+        Err(err) => {
+            debug!(%err, "unreadable cache entry");
+            return Ok(None);
+        }
+    };
+
+    if keys.compiler_version != env!("CARGO_PKG_VERSION") {
+        return Ok(None);
+    }
+
+    let verifier_data = keys.verifier_data;
+    Ok(keys.prover_key.map(|pk| (pk, verifier_data)))
+}
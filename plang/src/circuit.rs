@@ -4,471 +4,2431 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use crate::error::{Error as PlangError, Result};
-use crate::grammar::{PlangGrammar, Rule};
-
-use std::collections::{hash_map::Entry, HashMap};
+use crate::artifact::Metadata;
+use crate::assertion::{self, Assertion};
+#[cfg(feature = "prover")]
+use crate::backend::GateView;
+#[cfg(feature = "prover")]
+use crate::error::AssignmentError;
+use crate::error::{Error as PlangError, Result, SemanticError};
+use crate::grammar::{Edition, NodeRef, PlangGrammar, Rule};
+use crate::include::{self, Importer, SearchPath};
+use crate::jubjub;
+#[cfg(feature = "prover")]
+use crate::program::GateProgram;
+#[cfg(feature = "prover")]
+use crate::scalar::IntoScalar;
+use crate::scalar::Scalar;
+use crate::scope::{self, lower_directive, Consts, Env, Scope};
+use crate::symbol::{Symbol, Symbols};
+use crate::vector::{self, TestVector};
+#[cfg(feature = "prover")]
+use crate::verifier;
+use crate::warning::{self, CheckConfig, LineWarning, Suppression, Warning};
+
+#[cfg(feature = "prover")]
+use std::collections::BTreeMap;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::Path;
 use std::str::FromStr;
 
+use blake2::{Blake2s, Digest};
+#[cfg(feature = "prover")]
 use dusk_plonk::prelude::*;
+#[cfg(feature = "prover")]
+use tracing::error;
+use tracing::{debug, debug_span, info_span};
+use zeroize::Zeroize;
 
 /// A plonk circuit parsed from plang.
-#[derive(Debug)]
+///
+/// Circuits are `Send` and `Sync`, so a parsed circuit can be shared between
+/// threads, and cloned for each proof rather than parsed again. Circuits are
+/// equal if their equations, and the values set on them, are.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PlangCircuit {
     exprs: Vec<PlangExpr>,
-    vars: HashMap<String, WitnessOrPublic>,
+    /// The index of each variable in `values`.
+    vars: HashMap<Symbol, usize>,
+    values: Vec<WitnessOrPublic>,
+    #[cfg(feature = "prover")]
+    program: GateProgram,
+    transcript: Option<Vec<u8>>,
+    tests: Vec<TestVector>,
+    assertions: Vec<Assertion>,
+    id: Option<[u8; 32]>,
+    metadata: Metadata,
+}
+
+/// The values set on a circuit, by name, as taken by
+/// [`PlangCircuit::assignment`].
+#[cfg(feature = "prover")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Assignment {
+    /// The values of the witnesses, including those introduced while
+    /// lowering.
+    pub witnesses: BTreeMap<String, BlsScalar>,
+    /// The values of the public inputs.
+    pub public_inputs: BTreeMap<String, BlsScalar>,
+}
+
+/// What's wrong with an assignment of values to a circuit, as found by
+/// [`PlangCircuit::validate_assignment`] and
+/// [`PlangCircuit::validate_public_assignment`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssignmentReport {
+    /// The names given a value that aren't variables of the circuit, sorted.
+    pub unknown: Vec<String>,
+    /// The variables not given a value, in the order they first appear in.
+    pub unassigned: Vec<String>,
+    /// The witnesses given a value where only public inputs are expected,
+    /// sorted.
+    pub not_public: Vec<String>,
+}
+
+impl AssignmentReport {
+    /// Returns whether nothing is wrong with the assignment.
+    pub fn is_valid(&self) -> bool {
+        self.unknown.is_empty() && self.unassigned.is_empty() && self.not_public.is_empty()
+    }
 }
 
 /// Something that is either a witness or a public input.
-#[derive(Debug)]
+///
+/// Values are only read when proving.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(feature = "prover"), allow(dead_code))]
 enum WitnessOrPublic {
-    Witness(BlsScalar),
-    PublicInput(BlsScalar),
+    Witness(Scalar),
+    PublicInput(Scalar),
 }
 
 impl Default for WitnessOrPublic {
     fn default() -> Self {
-        Self::Witness(BlsScalar::zero())
+        Self::Witness(Scalar::zero())
+    }
+}
+
+impl Zeroize for WitnessOrPublic {
+    fn zeroize(&mut self) {
+        match self {
+            Self::Witness(val) | Self::PublicInput(val) => val.zeroize(),
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+impl WitnessOrPublic {
+    fn value(&self) -> Scalar {
+        match self {
+            Self::Witness(val) | Self::PublicInput(val) => *val,
+        }
+    }
+}
+
+/// How circuits are parsed, beyond their text.
+///
+/// By default, imports are only looked up relative to the file importing
+/// them, and circuits are parsed by the first edition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Where the circuits imported are looked up.
+    pub search: SearchPath,
+    /// The edition circuits are parsed by, unless they declare their own.
+    pub edition: Edition,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            search: SearchPath::default(),
+            edition: Edition::E2021,
+        }
     }
 }
 
 impl PlangCircuit {
-    /// Parses a circuit from text.
-    pub fn parse<S: AsRef<str>>(text: S) -> Result<Self> {
-        let grammar = PlangGrammar::new(text.as_ref())?;
+    /// Parses a circuit from text. Text given as a `String` is parsed without
+    /// being copied.
+    pub fn parse<S: Into<String>>(text: S) -> Result<Self> {
+        let _span = info_span!("parse").entered();
+
+        let grammar = debug_span!("grammar").in_scope(|| PlangGrammar::new(text))?;
         Self::from_grammar(grammar)
     }
 
+    /// Parses a circuit from text with the given options, looking its imports
+    /// up in their search path.
+    pub fn parse_with<S: Into<String>>(text: S, options: &ParseOptions) -> Result<Self> {
+        let _span = info_span!("parse").entered();
+
+        let grammar = debug_span!("grammar")
+            .in_scope(|| PlangGrammar::with_edition(text, options.edition))?;
+        Self::from_grammar_in(grammar, Importer::new(options, None))
+    }
+
+    /// Reads and parses a circuit from a file. Errors reading or parsing it
+    /// are returned with the path of the file.
+    ///
+    /// Its imports are looked up relative to the file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_file_with(path, &ParseOptions::default())
+    }
+
+    /// Reads and parses a circuit from a file with the given options, looking
+    /// its imports up relative to the file, and then in their search path.
+    pub fn from_file_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Self> {
+        let path = path.as_ref();
+
+        let read = || -> Result<Self> {
+            let text = String::from_utf8(fs::read(path)?)?;
+            let _span = info_span!("parse").entered();
+
+            let grammar = debug_span!("grammar")
+                .in_scope(|| PlangGrammar::with_edition(text, options.edition))?;
+            Self::from_grammar_in(grammar, Importer::new(options, Some(path)))
+        };
+        read().map_err(|err| PlangError::InFile {
+            path: path.to_owned(),
+            error: Box::new(err),
+        })
+    }
+
+    /// The transcript label declared in the circuit source with a
+    /// `transcript "<label>"` directive, if any.
+    pub fn transcript(&self) -> Option<&[u8]> {
+        self.transcript.as_deref()
+    }
+
+    /// The test vectors declared in the circuit source with `test` blocks, in
+    /// the order they're declared in.
+    pub fn tests(&self) -> &[TestVector] {
+        &self.tests
+    }
+
+    /// Gives the circuit an explicit ID, recorded in the artifacts compiled
+    /// from it instead of its [hash](Self::hash). This is for protocols that
+    /// mandate a specific identifier for a circuit.
+    pub fn with_id(mut self, id: [u8; 32]) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Attaches metadata to the circuit, recorded in the artifacts compiled
+    /// from it.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// The ID of the circuit - the one it was given with
+    /// [`with_id`](Self::with_id), or otherwise its [hash](Self::hash).
+    pub fn id(&self) -> [u8; 32] {
+        self.id.unwrap_or_else(|| self.hash())
+    }
+
+    /// The metadata attached to the circuit.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the warnings about the circuit, in the order of the lines
+    /// they're about. Warnings allowed for an equation with an
+    /// `#[allow(...)]` comment are left out.
+    pub fn warnings(&self) -> Vec<LineWarning> {
+        self.exprs
+            .iter()
+            .flat_map(|expr| {
+                expr.reported_warnings().map(move |warning| LineWarning {
+                    line: expr.line,
+                    warning: warning.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the warnings allowed for each equation with an
+    /// `#[allow(...)]` comment, in the order of the lines they're on, so
+    /// they can be audited.
+    pub fn suppressions(&self) -> Vec<Suppression> {
+        self.exprs
+            .iter()
+            .flat_map(|expr| {
+                expr.allowed.iter().map(move |name| Suppression {
+                    line: expr.line,
+                    name: name.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the warnings about the values of the public inputs currently
+    /// set, each about the first line the public input is on.
+    ///
+    /// A value that fits in 64 bits with the highest one set is most likely a
+    /// negative number converted to a `u64`, rather than the negation in the
+    /// field that was meant.
+    #[cfg(feature = "prover")]
+    pub fn value_warnings(&self) -> Vec<LineWarning> {
+        let mut seen = HashSet::new();
+
+        self.exprs
+            .iter()
+            .filter_map(|expr| {
+                let public = expr.public.as_ref()?;
+                if !seen.insert(&public.var) || expr.allows("wrapped_negative_value") {
+                    return None;
+                }
+
+                let value = self.values[self.vars[&public.var]].value().to_u64()?;
+                (value >> 63 == 1).then(|| LineWarning {
+                    line: expr.line,
+                    warning: Warning::WrappedNegativeValue {
+                        name: public.var.to_string(),
+                        value,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Checks the warnings about the circuit against the given configuration,
+    /// returning the ones to report. It returns an error if any is denied.
+    pub fn check_warnings(&self, config: &CheckConfig) -> Result<Vec<LineWarning>> {
+        config.check(self.warnings())
+    }
+
     /// Sets the witness and public input values. Any value not set will remain
-    /// the default - 0. It returns an error if a value is not in the circuit.
-    pub fn set_vals<B: Into<BlsScalar>, I: IntoIterator<Item = (String, B)>>(
+    /// the default - 0. It returns an error if a value is not in the circuit,
+    /// or if an assertion in the circuit doesn't hold once they're set.
+    #[cfg(feature = "prover")]
+    pub fn set_vals<B: IntoScalar, I: IntoIterator<Item = (String, B)>>(
         &mut self,
         vals: I,
     ) -> Result<()> {
         for (name, val) in vals {
-            match self.vars.entry(name.clone()) {
-                Entry::Vacant(_) => return Err(PlangError::NoSuchValue(name)),
-                Entry::Occupied(mut entry) => match entry.get() {
-                    WitnessOrPublic::PublicInput(_) => {
-                        entry.insert(WitnessOrPublic::PublicInput(val.into()));
-                    }
-                    WitnessOrPublic::Witness(_) => {
-                        entry.insert(WitnessOrPublic::Witness(val.into()));
-                    }
-                },
+            let var = match self.vars.get(name.as_str()) {
+                Some(index) => &mut self.values[*index],
+                None => return Err(PlangError::Assignment(AssignmentError::NoSuchValue(name))),
+            };
+            let val = Scalar::from(val.into_scalar()?);
+            *var = match var {
+                WitnessOrPublic::PublicInput(_) => WitnessOrPublic::PublicInput(val),
+                WitnessOrPublic::Witness(_) => WitnessOrPublic::Witness(val),
+            };
+        }
+
+        self.derive();
+        self.check_assertions()
+    }
+
+    /// Computes the values of the variables introduced while lowering, from
+    /// the values of the others, in the order of the equations introducing
+    /// them.
+    #[cfg(feature = "prover")]
+    fn derive(&mut self) {
+        for hint in self.exprs.iter().flat_map(|expr| &expr.hints) {
+            let value = |var: &str| self.values[self.vars[var]].value();
+
+            let value = match hint {
+                Hint::Sum {
+                    terms, constant, ..
+                } => terms
+                    .iter()
+                    .fold(*constant, |sum, (coeff, term)| sum + *coeff * value(term)),
+                Hint::Product { left, right, .. } => value(left) * value(right),
+                Hint::Quotient {
+                    numerator,
+                    denominator,
+                    ..
+                } => value(numerator) * value(denominator).invert().unwrap_or_default(),
+                Hint::Bits { of, low, width, .. } => {
+                    let bytes = value(of).to_bytes();
+                    (*low..low + width).rev().fold(Scalar::zero(), |bits, bit| {
+                        let bit = u64::from(bytes[bit / 8] >> (bit % 8) & 1);
+                        bits * Scalar::from(2) + Scalar::from(bit)
+                    })
+                }
+            };
+            self.values[self.vars[hint.var()]] = WitnessOrPublic::Witness(value);
+        }
+    }
+
+    /// Checks the values currently set satisfy every equation of the circuit,
+    /// without proving it. It returns an error with the first assertion that
+    /// doesn't hold, or otherwise the first equation that isn't satisfied.
+    #[cfg(feature = "prover")]
+    pub fn check_satisfied(&self) -> Result<()> {
+        self.check_assertions()?;
+
+        for expr in &self.exprs {
+            let sum = expr
+                .gate()
+                .evaluate(|var| self.values[self.vars[var]].value());
+            if sum != Scalar::zero() {
+                return Err(PlangError::Assignment(AssignmentError::Unsatisfied {
+                    line: expr.line,
+                    equation: expr.to_string(),
+                }));
             }
         }
 
         Ok(())
     }
 
+    /// Checks the assertions of the circuit hold for the values currently set,
+    /// in the order they're declared in.
+    #[cfg(feature = "prover")]
+    fn check_assertions(&self) -> Result<()> {
+        let value = |name: &str| self.var(name).map(WitnessOrPublic::value);
+
+        for assertion in &self.assertions {
+            if !assertion.holds(value)? {
+                return Err(PlangError::Assignment(AssignmentError::AssertionFailed {
+                    line: assertion.line,
+                    assertion: assertion.text.clone(),
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks an assignment of values to the witnesses and public inputs,
+    /// without setting them, reporting every name that isn't in the circuit
+    /// and every variable without a value.
+    pub fn validate_assignment<V>(&self, assignment: &HashMap<String, V>) -> AssignmentReport {
+        self.validate(assignment, false)
+    }
+
+    /// Checks an assignment of values to the public inputs, as given for
+    /// verifying a proof, reporting every name that isn't in the circuit,
+    /// every witness, and every public input without a value.
+    pub fn validate_public_assignment<V>(
+        &self,
+        assignment: &HashMap<String, V>,
+    ) -> AssignmentReport {
+        self.validate(assignment, true)
+    }
+
+    fn validate<V>(&self, assignment: &HashMap<String, V>, public_only: bool) -> AssignmentReport {
+        let mut report = AssignmentReport::default();
+
+        for name in assignment.keys() {
+            match self.var(name) {
+                None => report.unknown.push(name.clone()),
+                Some(WitnessOrPublic::Witness(_)) if public_only => {
+                    report.not_public.push(name.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        report.unknown.sort();
+        report.not_public.sort();
+
+        // Variables introduced while lowering are never given values.
+        let mut seen: HashSet<&str> = self
+            .exprs
+            .iter()
+            .flat_map(|expr| &expr.hints)
+            .map(Hint::var)
+            .collect();
+        for var in self.exprs.iter().flat_map(PlangExpr::vars) {
+            let expected =
+                !public_only || matches!(self.var(var), Some(WitnessOrPublic::PublicInput(_)));
+            if expected && seen.insert(var) && !assignment.contains_key(var) {
+                report.unassigned.push(var.to_owned());
+            }
+        }
+
+        report
+    }
+
+    /// Wipes the values set on the circuit from memory, setting every value
+    /// back to the default - 0 - while keeping the circuit as it is otherwise.
+    ///
+    /// This is done when the circuit is dropped as well. Long running provers
+    /// can call it as soon as a proof is done, so secret witnesses don't stay
+    /// around for as long as the circuit does. Copies of the values made by
+    /// the proving backend are beyond its reach.
+    pub fn clear_secrets(&mut self) {
+        for var in &mut self.values {
+            var.zeroize();
+        }
+    }
+
+    /// Sets every value back to the default - 0 - wiping them as
+    /// [`clear_secrets`](Self::clear_secrets) does, so the circuit can be
+    /// reused to prove with other values without any of the previous ones
+    /// left behind.
+    pub fn reset_values(&mut self) {
+        self.clear_secrets();
+    }
+
+    /// Takes a copy of the values currently set on the circuit, split into
+    /// witnesses and public inputs. Setting them back with
+    /// [`set_vals`](Self::set_vals) restores the circuit as it is.
+    ///
+    /// The copy is not wiped when the secrets of the circuit are cleared.
+    #[cfg(feature = "prover")]
+    pub fn assignment(&self) -> Assignment {
+        let mut assignment = Assignment::default();
+        for (name, index) in &self.vars {
+            let (values, value) = match &self.values[*index] {
+                WitnessOrPublic::Witness(value) => (&mut assignment.witnesses, value),
+                WitnessOrPublic::PublicInput(value) => (&mut assignment.public_inputs, value),
+            };
+            values.insert(name.to_string(), BlsScalar::from(*value));
+        }
+        assignment
+    }
+
+    /// Returns the names of the public inputs in the order their values are
+    /// expected when verifying a proof - the order of the equations they
+    /// appear in. A public input appearing in more than one equation is listed
+    /// once for each.
+    pub fn public_input_order(&self) -> Vec<&str> {
+        self.signed_public_inputs().map(|(name, _)| name).collect()
+    }
+
+    /// The gates the equations of the circuit are compiled into.
+    #[cfg(feature = "prover")]
+    pub(crate) fn program(&self) -> &GateProgram {
+        &self.program
+    }
+
+    /// The value of the variable at the given index.
+    #[cfg(feature = "prover")]
+    pub(crate) fn var_value(&self, var: usize) -> Scalar {
+        self.values[var].value()
+    }
+
+    /// The equations of the circuit, in the order they're declared in.
+    pub(crate) fn exprs(&self) -> &[PlangExpr] {
+        &self.exprs
+    }
+
+    /// Returns whether the variable with the given name is a public input.
+    #[cfg(feature = "prover")]
+    pub(crate) fn is_public(&self, name: &str) -> bool {
+        matches!(self.var(name), Some(WitnessOrPublic::PublicInput(_)))
+    }
+
+    /// Returns the value set for the variable with the given name, if it's in
+    /// the circuit.
+    #[cfg(feature = "prover")]
+    pub fn value(&self, name: &str) -> Option<BlsScalar> {
+        self.var(name).map(|var| var.value().into())
+    }
+
+    fn var(&self, name: &str) -> Option<&WitnessOrPublic> {
+        self.vars.get(name).map(|index| &self.values[*index])
+    }
+
+    /// Returns the names of the public inputs in the order their values are
+    /// expected when verifying a proof, each with whether it has a minus sign
+    /// in front of it.
+    pub(crate) fn signed_public_inputs(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.exprs
+            .iter()
+            .filter_map(|expr| expr.public.as_ref())
+            .map(|public| (public.var.as_str(), public.negated))
+    }
+
+    /// Returns the names of the public inputs in the order their values are
+    /// expected when verifying a proof, as
+    /// [`public_input_order`](Self::public_input_order) does, each with the
+    /// value set for it.
+    #[cfg(feature = "prover")]
+    pub fn public_inputs_named(&self) -> impl Iterator<Item = (&str, BlsScalar)> {
+        self.signed_public_inputs()
+            .map(move |(name, _)| (name, self.value(name).unwrap_or_default()))
+    }
+
+    /// Arranges the given public input values, keyed by name, in the order
+    /// expected when verifying a proof. It returns an error if a value is not a
+    /// public input of the circuit, or if a public input is missing a value.
+    #[cfg(feature = "prover")]
+    pub fn public_inputs_from<B: IntoScalar, I: IntoIterator<Item = (String, B)>>(
+        &self,
+        vals: I,
+    ) -> Result<Vec<PublicInputValue>> {
+        let mut named = HashMap::new();
+        for (name, val) in vals {
+            match self.var(&name) {
+                Some(WitnessOrPublic::PublicInput(_)) => {
+                    named.insert(name, val.into_scalar()?);
+                }
+                Some(WitnessOrPublic::Witness(_)) => {
+                    return Err(PlangError::Assignment(AssignmentError::NotPublicInput(
+                        name,
+                    )))
+                }
+                None => return Err(PlangError::Assignment(AssignmentError::NoSuchValue(name))),
+            }
+        }
+
+        verifier::arrange(self.signed_public_inputs(), |name| named.get(name).copied())
+    }
+
+    /// Returns the hash of the circuit.
+    ///
+    /// The hash is computed over a canonical encoding of the circuit's
+    /// equations, meaning it only changes when the constraints do - and not
+    /// with formatting or comments in the source.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Blake2s::new();
+        hasher.update(CIRCUIT_HASH_DOMAIN);
+
+        for expr in &self.exprs {
+            if let Some(tri) = &expr.tri {
+                hasher.update(b"m");
+                hash_coeff(&mut hasher, tri.coeff);
+                hash_var(&mut hasher, &tri.lvar);
+                hash_var(&mut hasher, &tri.rvar);
+            }
+
+            for bi in &expr.bis {
+                hasher.update(b"l");
+                hash_coeff(&mut hasher, bi.coeff);
+                hash_var(&mut hasher, &bi.var);
+            }
+
+            if let Some(public) = &expr.public {
+                hasher.update(b"p");
+                // The sign it's written with, on the right side.
+                hash_coeff(&mut hasher, -public.coeff());
+                hash_var(&mut hasher, &public.var);
+            }
+
+            // Left out when zero, so the hashes of equations without one
+            // don't change.
+            if expr.constant != Scalar::zero() {
+                hasher.update(b"c");
+                hash_coeff(&mut hasher, expr.constant);
+            }
+
+            hasher.update(b";");
+        }
+
+        hasher.finalize().into()
+    }
+
+    /// Renders the circuit as a graph in the DOT language.
+    ///
+    /// Each variable and each gate is a node, with edges connecting a gate to
+    /// the variables it constrains, labelled with the kind of term they appear
+    /// in. If `highlight_public` is set, public inputs are drawn filled.
+    pub fn to_dot(&self, highlight_public: bool) -> String {
+        let mut dot = String::from("graph circuit {\n");
+
+        let mut seen = HashSet::new();
+        for expr in &self.exprs {
+            for var in expr.vars() {
+                if seen.insert(var) {
+                    let attrs = match self.var(var) {
+                        Some(WitnessOrPublic::PublicInput(_)) if highlight_public => {
+                            " [style=filled, fillcolor=lightblue]"
+                        }
+                        _ => "",
+                    };
+                    let _ = writeln!(dot, "    \"{}\"{};", var, attrs);
+                }
+            }
+        }
+
+        for (i, expr) in self.exprs.iter().enumerate() {
+            let _ = writeln!(dot, "    g{} [shape=box, label=\"{}\"];", i, expr);
+
+            if let Some(tri) = &expr.tri {
+                let _ = writeln!(dot, "    g{} -- \"{}\" [label=mul];", i, tri.lvar);
+                let _ = writeln!(dot, "    g{} -- \"{}\" [label=mul];", i, tri.rvar);
+            }
+            for bi in &expr.bis {
+                let _ = writeln!(dot, "    g{} -- \"{}\" [label=lin];", i, bi.var);
+            }
+            if let Some(public) = &expr.public {
+                let _ = writeln!(dot, "    g{} -- \"{}\" [label=pub];", i, public.var);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
     /// Parses a circuit from a grammar.
     ///
     /// It goes through each equation, arranging them all into a vector of
     /// `PlangExpr`s, while inserting all variables into a map with with an
-    /// initial default value.
-    fn from_grammar(grammar: PlangGrammar<'_>) -> Result<Self> {
-        let mut exprs = vec![];
-
-        for pair in grammar.pairs() {
-            let rule = pair.as_rule();
-            if rule == Rule::expr {
-                let mut minus = false;
-                let mut public = None;
-
-                let mut tris = vec![];
-                let mut bis = vec![];
-
-                for expr_inner in pair.into_inner() {
-                    let expr_rule = expr_inner.as_rule();
-                    match expr_rule {
-                        Rule::sign => {
-                            if expr_inner.as_span().as_str() == "-" {
-                                minus = true;
-                            } else {
-                                minus = false;
-                            }
-                        }
-                        Rule::tri_term => {
-                            let mut coeff = 1;
-                            let mut vars = vec![];
-
-                            for term_inner in expr_inner.into_inner() {
-                                let term_rule = term_inner.as_rule();
-                                match term_rule {
-                                    Rule::coeff => {
-                                        coeff = u64::from_str(term_inner.as_span().as_str())?
-                                    }
-                                    Rule::var => {
-                                        vars.push(term_inner.as_span().as_str().to_owned())
-                                    }
-                                    _ => unreachable!(),
-                                }
-                            }
-
-                            tris.push(TriTerm {
-                                minus,
-                                coeff: coeff.into(),
-                                rvar: vars.pop().unwrap(),
-                                lvar: vars.pop().unwrap(),
-                            })
-                        }
-                        Rule::bi_term => {
-                            let mut coeff = 1;
-                            let mut var = String::default();
-
-                            for term_inner in expr_inner.into_inner() {
-                                let term_rule = term_inner.as_rule();
-                                match term_rule {
-                                    Rule::coeff => {
-                                        coeff = u64::from_str(term_inner.as_span().as_str())?
-                                    }
-                                    Rule::var => var = term_inner.as_span().as_str().to_owned(),
-                                    _ => unreachable!(),
-                                }
-                            }
-
-                            bis.push(BiTerm {
-                                minus,
-                                coeff: coeff.into(),
-                                var,
-                            })
-                        }
-                        Rule::var => {
-                            let var = expr_inner.as_span().as_str().to_owned();
-                            public = Some(Public { minus, var });
-                        }
-                        _ => {}
-                    }
-                }
+    /// initial default value. [`parse`](Self::parse) is the same as parsing
+    /// the grammar first, and then the circuit from it.
+    ///
+    /// It imports nothing, since its text isn't in a file and it has no search
+    /// path, so that parsing text never reads files. Circuits importing others
+    /// are parsed with [`from_file`](Self::from_file) or
+    /// [`parse_with`](Self::parse_with).
+    pub fn from_grammar(grammar: PlangGrammar) -> Result<Self> {
+        Self::from_grammar_in(grammar, Importer::new(&ParseOptions::default(), None))
+    }
 
-                // TODO this could be enforced in the grammar - possibly simplifying this
-                //  function as well
-                if tris.len() > 1 {
-                    return Err(PlangError::TooManyTriTerms);
+    /// Parses a circuit from a grammar, importing the circuits it imports
+    /// with the importer.
+    pub(crate) fn from_grammar_in(grammar: PlangGrammar, importer: Importer<'_>) -> Result<Self> {
+        let lower_span = debug_span!("lower").entered();
+
+        let lines: Vec<&str> = grammar.text().lines().collect();
+        let nodes: Vec<NodeRef<'_>> = grammar.nodes().collect();
+        let consts = Consts::collect(nodes.iter().copied());
+
+        // Equations are lowered independently of each other, so they can be
+        // lowered in parallel. Errors are then returned in the order of the
+        // lines they're on, as they would be lowering them one by one.
+        let lowered: Vec<Result<Vec<PlangExpr>>> = par_map(&nodes, |node| {
+            let symbols = Symbols::default();
+            let env = Env::new(&consts, &symbols, node.line());
+            let mut exprs = lower_statement(*node, &env)?;
+            annotate_all(&mut exprs, &lines, node.line());
+            for expr in &mut exprs {
+                expr.line = node.line();
+            }
+            Ok(exprs)
+        });
+
+        let mut exprs = Vec::with_capacity(lowered.len());
+        let mut transcript = None;
+        let mut tests = vec![];
+        let mut assertions = vec![];
+        let mut scope = Scope::default();
+        let mut namespaces = HashSet::new();
+
+        // Lines in blocks that aren't taken are skipped, errors and all.
+        for (node, lowered) in nodes.iter().zip(lowered) {
+            if let Some(directive) = lower_directive(*node) {
+                scope.apply(&directive, node.line())?;
+            } else if !scope.is_active() {
+                continue;
+            } else if node.rule() == Rule::transcript {
+                if transcript.is_some() {
+                    return Err(PlangError::Semantic(SemanticError::RepeatedTranscript));
                 }
-
-                exprs.push(PlangExpr {
-                    tri: tris.pop(),
-                    bis,
-                    public,
-                })
+                transcript = Some(lower_transcript(*node));
+            } else if node.rule() == Rule::test {
+                tests.push(vector::lower_test(*node, node.line()));
+            } else if node.rule() == Rule::assertion {
+                assertions.push(assertion::lower_assertion(*node, node.line()));
+            } else if node.rule() == Rule::import {
+                let (name, namespace) = include::lower_import(*node);
+                if !namespaces.insert(namespace) {
+                    return Err(PlangError::Semantic(SemanticError::RepeatedImport(
+                        namespace.to_owned(),
+                    )));
+                }
+                exprs.extend(importer.import(name, namespace, node.line())?);
+            } else {
+                exprs.extend(lowered?);
             }
         }
+        scope.finish()?;
+
+        // The circuit can only refer to the variables of the circuits it
+        // imports.
+        let undefined = exprs
+            .iter()
+            .flat_map(PlangExpr::vars)
+            .find(|var| matches!(include::namespace_of(var), Some(ns) if !namespaces.contains(ns)));
+        if let Some(var) = undefined {
+            return Err(PlangError::Semantic(SemanticError::UndefinedNamespace(
+                var.to_owned(),
+            )));
+        }
 
+        lower_span.exit();
+        debug!(exprs = exprs.len(), "lowered equations");
+
+        Ok(Self::from_exprs(exprs, transcript)?
+            .with_tests(tests)
+            .with_assertions(assertions))
+    }
+
+    /// Builds a circuit from its equations, checking they're valid.
+    pub(crate) fn from_exprs(exprs: Vec<PlangExpr>, transcript: Option<Vec<u8>>) -> Result<Self> {
         // some checks on the expression to make sure its ok.
-        check_different_tri_vars(&exprs)?;
-        check_less_than_5_vars(&exprs)?;
-        check_no_repeat_vars_in_bis(&exprs)?;
-        check_public_different_from_other_vars(&exprs)?;
+        {
+            let _span = debug_span!("check_exprs").entered();
+            par_try_for_each(&exprs, check_expr)?;
+        }
 
-        let vars = vars_from_exprs(&exprs);
-        Ok(Self { exprs, vars })
+        Ok(Self::from_checked_exprs(exprs, transcript))
     }
-}
 
-// Creates a map of names to witnesses or public inputs.
-fn vars_from_exprs(exprs: &[PlangExpr]) -> HashMap<String, WitnessOrPublic> {
-    let mut vars = HashMap::new();
+    /// Returns a copy of the circuit with every value unset, without parsing
+    /// or checking its equations again.
+    #[cfg(feature = "prover")]
+    pub(crate) fn unassigned(&self) -> Self {
+        let mut circuit = Self::from_checked_exprs(self.exprs.clone(), self.transcript.clone());
+        circuit.tests = self.tests.clone();
+        circuit.assertions = self.assertions.clone();
+        circuit.id = self.id;
+        circuit.metadata = self.metadata.clone();
+        circuit
+    }
 
-    for expr in exprs {
-        // if there is a PI in the expression (right equation side), also
-        // insert it in the map.
-        if let Some(public) = &expr.public {
-            vars.insert(
-                public.var.clone(),
-                WitnessOrPublic::PublicInput(BlsScalar::zero()),
-            );
+    /// Gives the circuit the test vectors declared in its source.
+    pub(crate) fn with_tests(mut self, tests: Vec<TestVector>) -> Self {
+        self.tests = tests;
+        self
+    }
+
+    /// Gives the circuit the assertions declared in its source.
+    pub(crate) fn with_assertions(mut self, assertions: Vec<Assertion>) -> Self {
+        self.assertions = assertions;
+        self
+    }
+
+    /// Builds a circuit from equations that were already checked, one by one,
+    /// with [`check_expr`].
+    pub(crate) fn from_checked_exprs(
+        mut exprs: Vec<PlangExpr>,
+        transcript: Option<Vec<u8>>,
+    ) -> Self {
+        let (vars, values) = debug_span!("vars").in_scope(|| vars_from_exprs(&mut exprs));
+
+        // The gates are only needed to build the gadget.
+        #[cfg(feature = "prover")]
+        let program = {
+            let public: Vec<bool> = values
+                .iter()
+                .map(|var| matches!(var, WitnessOrPublic::PublicInput(_)))
+                .collect();
+            debug_span!("program").in_scope(|| GateProgram::compile(&exprs, &vars, &public))
+        };
+
+        Self {
+            exprs,
+            vars,
+            values,
+            #[cfg(feature = "prover")]
+            program,
+            transcript,
+            tests: vec![],
+            assertions: vec![],
+            id: None,
+            metadata: Metadata::default(),
         }
+    }
+}
 
-        // A term of the form `q_m · a · b` contains two witnesses.
-        if let Some(tri) = &expr.tri {
-            vars.insert(
-                tri.lvar.clone(),
-                WitnessOrPublic::Witness(BlsScalar::zero()),
-            );
-            vars.insert(
-                tri.rvar.clone(),
-                WitnessOrPublic::Witness(BlsScalar::zero()),
-            );
+/// Lists the public inputs and the witnesses of the circuit, in the order they
+/// first appear in, followed by its equations in normal form, one per line:
+///
+/// ```text
+/// # public inputs: c, d
+/// # witnesses: a, b
+/// a + b = c
+/// a * b = d
+/// ```
+impl fmt::Display for PlangCircuit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<(&usize, &Symbol)> = self
+            .vars
+            .iter()
+            .map(|(name, index)| (index, name))
+            .collect();
+        names.sort_unstable();
+
+        for (role, public) in [("public inputs", true), ("witnesses", false)] {
+            let vars: Vec<&str> = names
+                .iter()
+                .filter(|(index, _)| {
+                    matches!(self.values[**index], WitnessOrPublic::PublicInput(_)) == public
+                })
+                .map(|(_, name)| name.as_str())
+                .collect();
+            if !vars.is_empty() {
+                writeln!(f, "# {}: {}", role, vars.join(", "))?;
+            }
         }
 
-        // A term of the form `q_x · y` contains one witness.
-        for bi in &expr.bis {
-            vars.insert(bi.var.clone(), Default::default());
+        for expr in &self.exprs {
+            writeln!(f, "{}", expr)?;
         }
+        Ok(())
+    }
+}
+
+impl FromStr for PlangCircuit {
+    type Err = PlangError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Self::parse(text)
+    }
+}
+
+impl TryFrom<&str> for PlangCircuit {
+    type Error = PlangError;
+
+    fn try_from(text: &str) -> Result<Self> {
+        Self::parse(text)
+    }
+}
+
+impl Drop for PlangCircuit {
+    fn drop(&mut self) {
+        self.clear_secrets();
     }
+}
+
+/// Lowers a `transcript` directive into its label.
+pub(crate) fn lower_transcript(node: NodeRef<'_>) -> Vec<u8> {
+    let label = node
+        .children()
+        .flat_map(|string| string.children())
+        .find(|inner| inner.rule() == Rule::label)
+        .map_or("", |label| label.as_str());
+    label.as_bytes().to_vec()
+}
 
-    vars
+/// Lowers a statement into the equations it stands for - an equation into
+/// itself, and a sum into the equations it's expanded into. Directives stand
+/// for no equations.
+///
+/// Arithmetic on constants is evaluated with the constants of the environment.
+pub(crate) fn lower_statement(node: NodeRef<'_>, env: &Env<'_>) -> Result<Vec<PlangExpr>> {
+    match node.rule() {
+        Rule::expr => Ok(vec![lower_expr(node, env)?]),
+        Rule::sum => lower_sum(node, env),
+        Rule::bits => lower_bits(node, env),
+        Rule::bytes => lower_bytes(node, env),
+        Rule::trunc => lower_trunc(node, env),
+        Rule::compare => lower_compare(node, env),
+        Rule::commit => lower_commit(node, env),
+        _ => Ok(vec![]),
+    }
 }
 
-// Check that `a != b` for all expressions the form `q_m · a · b`.
-fn check_different_tri_vars(exprs: &[PlangExpr]) -> Result<()> {
-    for expr in exprs {
-        if let Some(tri) = &expr.tri {
-            if tri.lvar == tri.rvar {
-                return Err(PlangError::SameTriVars);
+/// The largest number of terms a sum can have.
+const MAX_SUM_TERMS: u64 = 1 << 20;
+
+/// Lowers a sum, `sum(i in lo..hi, c*b[i]) = x`, into equations adding up its
+/// terms `c*b[lo]` to `c*b[hi - 1]`, where the coefficient `c` may be a power
+/// of the index, such as `2^i`.
+///
+/// Each equation adds a term to the partial sum of the terms before it, held
+/// by a variable of its own, and the last is equated to the right side. The
+/// partial sum of the terms up to `k` is named after the sum with the range
+/// `lo..k`, which can't be the name of a variable written in the source, and
+/// is computed when the values of the circuit are set.
+fn lower_sum(node: NodeRef<'_>, env: &Env<'_>) -> Result<Vec<PlangExpr>> {
+    let mut children = node.children();
+    let index = children.next().expect("the grammar defines the sum");
+    let range = children.next().expect("the grammar defines the sum");
+    let summand = children.next().expect("the grammar defines the sum");
+
+    let index = index.as_str();
+    let (lo, hi) = lower_range(range, env)?;
+
+    let mut warnings = vec![];
+    let mut base = None;
+    let mut coeff = Scalar::one();
+    let mut arithmetic = None;
+    let mut array = "";
+
+    for summand_inner in summand.children() {
+        match summand_inner.rule() {
+            Rule::coeff => coeff = lower_coeff(summand_inner.as_str(), &mut warnings),
+            // Evaluated for each term, with the index standing for its value.
+            Rule::const_expr => arithmetic = Some(summand_inner),
+            Rule::power => {
+                let mut power = summand_inner.children();
+                let literal = power.next().expect("the grammar defines the power");
+                let exponent = power.next().expect("the grammar defines the power");
+
+                check_index(index, exponent.as_str())?;
+                base = Some(lower_coeff(literal.as_str(), &mut warnings));
+            }
+            Rule::element => {
+                let mut element = summand_inner.children();
+                let name = element.next().expect("the grammar defines the element");
+                let subscript = element.next().expect("the grammar defines the element");
+
+                check_index(index, subscript.as_str())?;
+                array = name.as_str();
             }
+            _ => unreachable!(),
         }
     }
 
-    Ok(())
+    let terms = (lo..hi)
+        .map(|k| {
+            let coeff = match (base, arithmetic) {
+                (Some(base), _) => base.pow(k),
+                (_, Some(expr)) => scope::eval(expr, &env.with_index(index, k))?,
+                _ => coeff,
+            };
+            Ok((coeff, env.intern(&format!("{}[{}]", array, k))))
+        })
+        .collect::<Result<Vec<(Scalar, Symbol)>>>()?;
+
+    // The partial sums are named after the sum, as written without spaces.
+    let written: String = summand.as_str().split_whitespace().collect();
+    let partial = |k: u64| env.intern(&format!("sum({} in {}..{}, {})", index, lo, k, written));
+
+    let mut minus = false;
+    let mut public = None;
+    for side_inner in node.children().skip(3) {
+        match side_inner.rule() {
+            Rule::sign => minus = side_inner.as_str() == "-",
+            Rule::var => {
+                public = Some(Public {
+                    negated: minus,
+                    var: env.intern(side_inner.as_str()),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let mut exprs = accumulate(&terms, Total::Public(public), |k| partial(lo + k as u64));
+    exprs[0].warnings = warnings;
+    Ok(exprs)
 }
 
-// Check that each expression has less than 5 vars.
-fn check_less_than_5_vars(exprs: &[PlangExpr]) -> Result<()> {
-    for expr in exprs {
-        let mut vars = HashMap::with_capacity(5);
+/// What the terms of a sum add up to - the public input on the right side,
+/// if any, or a witness.
+enum Total {
+    Public(Option<Public>),
+    Witness(Symbol),
+}
 
-        if let Some(public) = &expr.public {
-            vars.insert(&public.var, ());
+/// Expands a sum of terms into equations, each adding a term to the partial
+/// sum of the terms before it, held by a variable named after the number of
+/// terms it sums. The last is equated to the total.
+fn accumulate<F>(terms: &[(Scalar, Symbol)], total: Total, partial: F) -> Vec<PlangExpr>
+where
+    F: Fn(usize) -> Symbol,
+{
+    let bi = |(coeff, var): &(Scalar, Symbol)| BiTerm {
+        coeff: *coeff,
+        var: var.clone(),
+    };
+    let minus = |var: Symbol| BiTerm {
+        coeff: -Scalar::one(),
+        var,
+    };
+
+    let (last, init) = terms.split_last().expect("there are terms to sum");
+    let mut exprs = Vec::with_capacity(terms.len());
+
+    let mut bis = match init.split_first() {
+        None => vec![bi(last)],
+        Some((first, middle)) => {
+            // The first term, then the partial sum of the terms so far.
+            let mut sum = first.clone();
+
+            for (k, term) in (2..).zip(middle) {
+                let var = partial(k);
+                let hint = Hint::Sum {
+                    var: var.clone(),
+                    terms: vec![sum.clone(), term.clone()],
+                    constant: Scalar::zero(),
+                };
+
+                exprs.push(PlangExpr {
+                    bis: vec![bi(&sum), bi(term), minus(var.clone())],
+                    hints: vec![hint],
+                    ..PlangExpr::default()
+                });
+                sum = (Scalar::one(), var);
+            }
+
+            vec![bi(&sum), bi(last)]
         }
+    };
 
-        if let Some(tri) = &expr.tri {
-            vars.insert(&tri.lvar, ());
-            vars.insert(&tri.rvar, ());
+    let public = match total {
+        Total::Public(public) => public,
+        Total::Witness(var) => {
+            bis.push(minus(var));
+            None
         }
+    };
+    exprs.push(PlangExpr {
+        bis,
+        public,
+        ..PlangExpr::default()
+    });
+
+    exprs
+}
+
+/// The largest number of bits a variable can be decomposed into - as many as
+/// it takes to write any element of the scalar field.
+const MAX_BITS: u64 = 255;
+
+/// The largest number of bytes a variable can be decomposed into.
+const MAX_BYTES: u64 = 32;
+
+/// The largest number of bits every number of which is an element of the
+/// scalar field. Decompositions into more bits are constrained to be
+/// canonical, or `x` would have a second one, as `x + r`.
+const CANONICAL_BITS: u64 = 254;
+
+/// Lowers a decomposition of a variable into bits, `bits(x, n) -> b[]`, into
+/// equations constraining each of `b[0]` to `b[n - 1]` to be 0 or 1, and
+/// their sum, each weighted by its power of 2, to be `x`. All 255 bits are
+/// constrained to be canonical as well.
+fn lower_bits(node: NodeRef<'_>, env: &Env<'_>) -> Result<Vec<PlangExpr>> {
+    let mut children = node.children();
+    let var = children.next().expect("the grammar defines the bits");
+    let width = children.next().expect("the grammar defines the bits");
+    let array = children.next().expect("the grammar defines the bits");
+
+    let (var, array) = (env.intern(var.as_str()), array.as_str());
+    let n = lower_width(width, env, MAX_BITS)?;
+
+    let mut exprs = decompose(&var, n, array, "i", env);
+    if n > CANONICAL_BITS {
+        let bits: Vec<Symbol> = (0..n)
+            .map(|i| env.intern(&format!("{}[{}]", array, i)))
+            .collect();
+        exprs.extend(canonical(&bits, array, env));
+    }
+
+    Ok(exprs)
+}
+
+/// Lowers a decomposition of a variable into bytes, `bytes(x, n) -> l[]`,
+/// into equations constraining each of the limbs `l[0]` to `l[n - 1]` to be
+/// 8 bits, and their sum, each weighted by its power of 256, to be `x`.
+///
+/// Each limb is itself decomposed into bits, `l[i][0]` to `l[i][7]`. All 32
+/// bytes are constrained to be canonical as well.
+fn lower_bytes(node: NodeRef<'_>, env: &Env<'_>) -> Result<Vec<PlangExpr>> {
+    let mut children = node.children();
+    let var = children.next().expect("the grammar defines the bytes");
+    let width = children.next().expect("the grammar defines the bytes");
+    let array = children.next().expect("the grammar defines the bytes");
+
+    let (var, array) = (env.intern(var.as_str()), array.as_str());
+    let n = lower_width(width, env, MAX_BYTES)?;
+
+    let limb = |i: u64| env.intern(&format!("{}[{}]", array, i));
+    let mut exprs = Vec::with_capacity(24 * n as usize);
+
+    for i in 0..n {
+        let mut bits = decompose(&limb(i), 8, &limb(i), "j", env);
+        bits[0].hints.insert(
+            0,
+            Hint::Bits {
+                var: limb(i),
+                of: var.clone(),
+                low: 8 * i as usize,
+                width: 8,
+            },
+        );
+        exprs.extend(bits);
+    }
+
+    let base = Scalar::from(256);
+    let terms: Vec<(Scalar, Symbol)> = (0..n).map(|i| (base.pow(i), limb(i))).collect();
+    let partial = |k: usize| env.intern(&format!("sum(i in 0..{}, 256^i*{}[i])", k, array));
+    exprs.extend(accumulate(&terms, Total::Witness(var), partial));
+
+    if 8 * n > CANONICAL_BITS {
+        let bits: Vec<Symbol> = (0..8 * n)
+            .map(|i| env.intern(&format!("{}[{}]", limb(i / 8), i % 8)))
+            .collect();
+        exprs.extend(canonical(&bits, array, env));
+    }
+
+    Ok(exprs)
+}
+
+/// Lowers a truncation of a variable to its low bits, `y = trunc(x, n)`, into
+/// equations constraining `y` to be `n` bits, the rest of `x`, named `x>>n`,
+/// to be the remaining bits of the scalar field, and `y + 2^n*(x>>n)` to be
+/// `x`.
+///
+/// The bits of `y` are named `bits(y)[0]` to `bits(y)[n - 1]`, and those of
+/// `x>>n` likewise. Both parts are computed from `x` when the values of the
+/// circuit are set. Together, the bits are constrained to be canonical, so
+/// `y` can't be truncated from `x + r` instead.
+fn lower_trunc(node: NodeRef<'_>, env: &Env<'_>) -> Result<Vec<PlangExpr>> {
+    let mut children = node.children();
+    let low = children.next().expect("the grammar defines the truncation");
+    let var = children.next().expect("the grammar defines the truncation");
+    let width = children.next().expect("the grammar defines the truncation");
+
+    let (low, var) = (env.intern(low.as_str()), env.intern(var.as_str()));
+    let n = lower_width(width, env, MAX_BITS - 1)?;
+    let high = env.intern(&format!("{}>>{}", var, n));
+
+    let mut exprs = vec![];
+    let mut all = vec![];
+    for (part, from, width) in [(&low, 0, n), (&high, n, MAX_BITS - n)] {
+        let array = format!("bits({})", part);
+        all.extend((0..width).map(|i| env.intern(&format!("{}[{}]", array, i))));
+
+        let mut bits = decompose(part, width, &array, "i", env);
+        bits[0].hints.insert(
+            0,
+            Hint::Bits {
+                var: part.clone(),
+                of: var.clone(),
+                low: from as usize,
+                width: width as usize,
+            },
+        );
+        exprs.extend(bits);
+    }
+
+    exprs.push(PlangExpr {
+        bis: vec![
+            BiTerm {
+                coeff: Scalar::one(),
+                var: low.clone(),
+            },
+            BiTerm {
+                coeff: Scalar::from(2).pow(n),
+                var: high,
+            },
+            BiTerm {
+                coeff: -Scalar::one(),
+                var,
+            },
+        ],
+        ..PlangExpr::default()
+    });
+    exprs.extend(canonical(&all, &format!("bits({})", low), env));
+
+    Ok(exprs)
+}
+
+/// Lowers a comparison of two variables of at most `n` bits, `lt(a, b, n)` or
+/// `lte(a, b, n)`, into the standard construction on the decomposition of
+/// their difference, named after the comparison, as in `lt(a,b,n)`.
+///
+/// Asserting the comparison holds constrains `b - a - 1`, or `b - a`, to be
+/// `n` bits. Its result, as in `r = lt(a, b, n)`, is the top bit of the
+/// `n + 1` bits of `b - a - 1 + 2^n`, or `b - a + 2^n`, and `r` is equated to
+/// it. Either way, `a` and `b` are assumed to be `n` bits.
+///
+/// `gt(a, b, n)` and `gte(a, b, n)` are lowered as `lt(b, a, n)` and
+/// `lte(b, a, n)`, and named after them.
+///
+/// The difference of a comparison with a result is another number than that
+/// of the same comparison asserted, so it's named after the result as well,
+/// as in `r:lt(a,b,n)`, for the two not to share variables.
+fn lower_compare(node: NodeRef<'_>, env: &Env<'_>) -> Result<Vec<PlangExpr>> {
+    let mut children = node.children().peekable();
+    let result = match children.peek().map(NodeRef::rule) {
+        Some(Rule::var) => children.next().map(|result| result.as_str()),
+        _ => None,
+    };
+    let mut next = || children.next().expect("the grammar defines the comparison");
+    let (op, a, b, width) = (next(), next(), next(), next());
+
+    let (op, a, b) = match (op.as_str(), a.as_str(), b.as_str()) {
+        ("gt", a, b) => ("lt", b, a),
+        ("gte", a, b) => ("lte", b, a),
+        operands => operands,
+    };
+    let n = lower_width(width, env, MAX_BITS - 2)?;
+    let diff = match result {
+        Some(result) => format!("{}:{}({},{},{})", result, op, a, b, n),
+        None => format!("{}({},{},{})", op, a, b, n),
+    };
+    let (diff, a, b) = (env.intern(&diff), env.intern(a), env.intern(b));
+
+    let strict = op == "lt";
+    let (width, offset) = match result {
+        Some(_) => (n + 1, Scalar::from(2).pow(n) - Scalar::from(strict as u64)),
+        None => (n, -Scalar::from(strict as u64)),
+    };
+
+    let mut exprs = vec![PlangExpr {
+        bis: vec![
+            BiTerm {
+                coeff: Scalar::one(),
+                var: diff.clone(),
+            },
+            BiTerm {
+                coeff: -Scalar::one(),
+                var: b.clone(),
+            },
+            BiTerm {
+                coeff: Scalar::one(),
+                var: a.clone(),
+            },
+        ],
+        constant: -offset,
+        hints: vec![Hint::Sum {
+            var: diff.clone(),
+            terms: vec![(Scalar::one(), b), (-Scalar::one(), a)],
+            constant: offset,
+        }],
+        ..PlangExpr::default()
+    }];
+
+    let bits = format!("bits({})", diff);
+    exprs.extend(decompose(&diff, width, &bits, "i", env));
+
+    if let Some(result) = result {
+        let (top, result) = (env.intern(&format!("{}[{}]", bits, n)), env.intern(result));
+        exprs.push(PlangExpr {
+            bis: vec![
+                BiTerm {
+                    coeff: Scalar::one(),
+                    var: top.clone(),
+                },
+                BiTerm {
+                    coeff: -Scalar::one(),
+                    var: result.clone(),
+                },
+            ],
+            hints: vec![Hint::Sum {
+                var: result,
+                terms: vec![(Scalar::one(), top)],
+                constant: Scalar::zero(),
+            }],
+            ..PlangExpr::default()
+        });
+    }
+
+    Ok(exprs)
+}
+
+/// Lowers a Pedersen commitment to a value with a blinder, such as
+/// `C = commit(v, r)`, into equations constraining the point `(C[0], C[1])`
+/// of JubJub to be `v·G + r·H`, for the generators `G` and `H` of
+/// dusk-jubjub.
+///
+/// Each of `v` and `r` is decomposed into canonical bits, named like `C:v[i]`,
+/// and multiplied with its generator by adding the multiples of the generator by
+/// each power of 2 its bits select. The variables of the multiplications and
+/// of the final addition are named after `C` as well, and are computed from
+/// `v` and `r` when the values of the circuit are set.
+fn lower_commit(node: NodeRef<'_>, env: &Env<'_>) -> Result<Vec<PlangExpr>> {
+    let mut children = node.children();
+    let mut next = || children.next().expect("the grammar defines the commitment");
+    let (commitment, value, blinder) = (next().as_str(), next().as_str(), next().as_str());
+
+    let d = jubjub::d();
+    let mut exprs = vec![];
+
+    let mut points = vec![];
+    for (scalar, base, name) in [
+        (value, jubjub::GENERATOR, "v"),
+        (blinder, jubjub::GENERATOR_NUMS, "r"),
+    ] {
+        let name = format!("{}:{}", commitment, name);
+        exprs.extend(decompose(&env.intern(scalar), MAX_BITS, &name, "i", env));
+
+        let bits: Vec<Symbol> = (0..MAX_BITS)
+            .map(|i| env.intern(&format!("{}[{}]", name, i)))
+            .collect();
+        exprs.extend(canonical(&bits, &name, env));
+        points.push(mul_fixed(&mut exprs, &name, base, d, env));
+    }
 
-        for bi in &expr.bis {
-            vars.insert(&bi.var, ());
+    let sum = (
+        env.intern(&format!("{}[0]", commitment)),
+        env.intern(&format!("{}[1]", commitment)),
+    );
+    let name = format!("{}:sum", commitment);
+    add_points(&mut exprs, &name, (&points[0], &points[1]), &sum, d, env);
+
+    Ok(exprs)
+}
+
+/// A point of JubJub, as the variables of its coordinates.
+type PointVars = (Symbol, Symbol);
+
+/// Appends equations multiplying a generator of JubJub by the scalar whose
+/// bits are `name[0]` to `name[MAX_BITS - 1]`, and returns the point the
+/// product is.
+///
+/// The product is accumulated one bit at a time, adding the multiple of the
+/// generator by the power of 2 of the bit, or the identity `(0, 1)` if the
+/// bit is 0. Both coordinates of what's added are then linear in the bit, as
+/// `x = b·X` and `y = 1 + b·(Y - 1)`, so each step takes a few products.
+fn mul_fixed(
+    exprs: &mut Vec<PlangExpr>,
+    name: &str,
+    base: jubjub::Point,
+    d: Scalar,
+    env: &Env<'_>,
+) -> PointVars {
+    let var = |part: &str, i: usize| env.intern(&format!("{}.{}[{}]", name, part, i));
+    let bit = |i: usize| env.intern(&format!("{}[{}]", name, i));
+    let one = Scalar::one();
+
+    let doublings = base.doublings(MAX_BITS as usize);
+
+    let first = doublings[0];
+    exprs.push(linear(var("x", 0), &[(first.x, bit(0))], Scalar::zero()));
+    exprs.push(linear(var("y", 0), &[(first.y - one, bit(0))], one));
+
+    for (i, point) in doublings.iter().enumerate().skip(1) {
+        let (x, y) = (var("x", i - 1), var("y", i - 1));
+        let c = d * point.x * point.y;
+
+        exprs.push(product(var("t", i), &x, &y));
+        exprs.push(product(var("s", i), &var("t", i), &bit(i)));
+        exprs.push(product(var("p", i), &x, &bit(i)));
+        exprs.push(product(var("q", i), &y, &bit(i)));
+
+        // x3 = (x1·y2 + y1·x2) / (1 + d·x1·x2·y1·y2)
+        exprs.push(linear(
+            var("u", i),
+            &[(one, x), (point.y - one, var("p", i))],
+            Scalar::zero(),
+        ));
+        exprs.push(linear(
+            var("xn", i),
+            &[(one, var("u", i)), (point.x, var("q", i))],
+            Scalar::zero(),
+        ));
+        exprs.push(linear(var("xd", i), &[(c, var("s", i))], one));
+        exprs.push(quotient(var("x", i), &var("xn", i), &var("xd", i)));
+
+        // y3 = (y1·y2 + x1·x2) / (1 - d·x1·x2·y1·y2)
+        exprs.push(linear(
+            var("w", i),
+            &[(one, y), (point.y - one, var("q", i))],
+            Scalar::zero(),
+        ));
+        exprs.push(linear(
+            var("yn", i),
+            &[(one, var("w", i)), (point.x, var("p", i))],
+            Scalar::zero(),
+        ));
+        exprs.push(linear(var("yd", i), &[(-c, var("s", i))], one));
+        exprs.push(quotient(var("y", i), &var("yn", i), &var("yd", i)));
+    }
+
+    let last = MAX_BITS as usize - 1;
+    (var("x", last), var("y", last))
+}
+
+/// Appends equations constraining the point `sum` to be the sum of two
+/// points of JubJub, with the complete addition of twisted Edwards curves.
+fn add_points(
+    exprs: &mut Vec<PlangExpr>,
+    name: &str,
+    (p1, p2): (&PointVars, &PointVars),
+    sum: &PointVars,
+    d: Scalar,
+    env: &Env<'_>,
+) {
+    let var = |part: &str| env.intern(&format!("{}.{}", name, part));
+    let (zero, one) = (Scalar::zero(), Scalar::one());
+
+    exprs.push(product(var("x1y2"), &p1.0, &p2.1));
+    exprs.push(product(var("y1x2"), &p1.1, &p2.0));
+    exprs.push(product(var("y1y2"), &p1.1, &p2.1));
+    exprs.push(product(var("x1x2"), &p1.0, &p2.0));
+    exprs.push(product(var("k"), &var("x1x2"), &var("y1y2")));
+
+    exprs.push(linear(
+        var("xn"),
+        &[(one, var("x1y2")), (one, var("y1x2"))],
+        zero,
+    ));
+    exprs.push(linear(var("xd"), &[(d, var("k"))], one));
+    exprs.push(quotient(sum.0.clone(), &var("xn"), &var("xd")));
+
+    exprs.push(linear(
+        var("yn"),
+        &[(one, var("y1y2")), (one, var("x1x2"))],
+        zero,
+    ));
+    exprs.push(linear(var("yd"), &[(-d, var("k"))], one));
+    exprs.push(quotient(sum.1.clone(), &var("yn"), &var("yd")));
+}
+
+/// An equation constraining a variable to be the product of two others.
+fn product(var: Symbol, left: &Symbol, right: &Symbol) -> PlangExpr {
+    PlangExpr {
+        tri: Some(TriTerm {
+            coeff: Scalar::one(),
+            lvar: left.clone(),
+            rvar: right.clone(),
+        }),
+        bis: vec![BiTerm {
+            coeff: -Scalar::one(),
+            var: var.clone(),
+        }],
+        hints: vec![Hint::Product {
+            var,
+            left: left.clone(),
+            right: right.clone(),
+        }],
+        ..PlangExpr::default()
+    }
+}
+
+/// An equation constraining a variable to be the sum of at most two terms and
+/// a constant.
+fn linear(var: Symbol, terms: &[(Scalar, Symbol)], constant: Scalar) -> PlangExpr {
+    let mut bis: Vec<BiTerm> = terms
+        .iter()
+        .map(|(coeff, var)| BiTerm {
+            coeff: *coeff,
+            var: var.clone(),
+        })
+        .collect();
+    bis.push(BiTerm {
+        coeff: -Scalar::one(),
+        var: var.clone(),
+    });
+
+    PlangExpr {
+        bis,
+        constant,
+        hints: vec![Hint::Sum {
+            var,
+            terms: terms.to_vec(),
+            constant,
+        }],
+        ..PlangExpr::default()
+    }
+}
+
+/// An equation constraining a variable to be the quotient of two others, as
+/// `var·denominator - numerator = 0`.
+fn quotient(var: Symbol, numerator: &Symbol, denominator: &Symbol) -> PlangExpr {
+    PlangExpr {
+        tri: Some(TriTerm {
+            coeff: Scalar::one(),
+            lvar: var.clone(),
+            rvar: denominator.clone(),
+        }),
+        bis: vec![BiTerm {
+            coeff: -Scalar::one(),
+            var: numerator.clone(),
+        }],
+        hints: vec![Hint::Quotient {
+            var,
+            numerator: numerator.clone(),
+            denominator: denominator.clone(),
+        }],
+        ..PlangExpr::default()
+    }
+}
+
+/// Decomposes a variable into the given number of bits, named after the
+/// array, and computed from the variable when the values of the circuit are
+/// set.
+///
+/// Each bit `b[i]` is constrained by the equations `b[i] - b[i]' = 0` and
+/// `b[i]*b[i]' - b[i] = 0`, where `b[i]'` is a copy of the bit, since a
+/// variable can't be multiplied with itself. Their sum is expanded like
+/// `sum(i in 0..n, 2^i*b[i]) = x`, but for `x` being a witness, with partial
+/// sums named after the given index.
+fn decompose(var: &Symbol, n: u64, array: &str, index: &str, env: &Env<'_>) -> Vec<PlangExpr> {
+    let bits: Vec<Symbol> = (0..n)
+        .map(|i| env.intern(&format!("{}[{}]", array, i)))
+        .collect();
+    let mut exprs = Vec::with_capacity(3 * n as usize);
+
+    for (i, bit) in bits.iter().enumerate() {
+        let copy = env.intern(&format!("{}'", bit));
+        let hints = vec![
+            Hint::Bits {
+                var: bit.clone(),
+                of: var.clone(),
+                low: i,
+                width: 1,
+            },
+            Hint::Sum {
+                var: copy.clone(),
+                terms: vec![(Scalar::one(), bit.clone())],
+                constant: Scalar::zero(),
+            },
+        ];
+
+        exprs.push(PlangExpr {
+            bis: vec![
+                BiTerm {
+                    coeff: Scalar::one(),
+                    var: bit.clone(),
+                },
+                BiTerm {
+                    coeff: -Scalar::one(),
+                    var: copy.clone(),
+                },
+            ],
+            hints,
+            ..PlangExpr::default()
+        });
+        exprs.push(PlangExpr {
+            tri: Some(TriTerm {
+                coeff: Scalar::one(),
+                lvar: bit.clone(),
+                rvar: copy,
+            }),
+            bis: vec![BiTerm {
+                coeff: -Scalar::one(),
+                var: bit.clone(),
+            }],
+            ..PlangExpr::default()
+        });
+    }
+
+    let two = Scalar::from(2);
+    let terms: Vec<(Scalar, Symbol)> = (0..).zip(bits).map(|(i, bit)| (two.pow(i), bit)).collect();
+    let partial = |k: usize| {
+        env.intern(&format!(
+            "sum({} in 0..{}, 2^{}*{}[{}])",
+            index, k, index, array, index
+        ))
+    };
+    exprs.extend(accumulate(&terms, Total::Witness(var.clone()), partial));
+
+    exprs
+}
+
+/// Constrains bits, least significant first, to be those of an element of the
+/// scalar field, as a number at most `r - 1`, for the modulus `r`. Bits past
+/// the 256th are constrained to be 0.
+///
+/// Going from the most significant bit down, the bits are equal to those of
+/// `r - 1` so far while the product of the bits that are 1 in it is, named
+/// like `name.eq[i]` from the second on. While they are, each bit that's 0 in
+/// `r - 1` is constrained to be 0, as `name.eq[i]*b[j] = 0`.
+fn canonical(bits: &[Symbol], name: &str, env: &Env<'_>) -> Vec<PlangExpr> {
+    let max = (-Scalar::one()).to_bytes();
+    let mut exprs = vec![];
+
+    let mut equal: Option<Symbol> = None;
+    for (i, bit) in bits.iter().enumerate().rev() {
+        let set = i < 256 && max[i / 8] >> (i % 8) & 1 == 1;
+        equal = match (equal, set) {
+            (None, false) => {
+                exprs.push(PlangExpr {
+                    bis: vec![BiTerm {
+                        coeff: Scalar::one(),
+                        var: bit.clone(),
+                    }],
+                    ..PlangExpr::default()
+                });
+                None
+            }
+            (None, true) => Some(bit.clone()),
+            (Some(equal), false) => {
+                exprs.push(PlangExpr {
+                    tri: Some(TriTerm {
+                        coeff: Scalar::one(),
+                        lvar: equal.clone(),
+                        rvar: bit.clone(),
+                    }),
+                    ..PlangExpr::default()
+                });
+                Some(equal)
+            }
+            (Some(equal), true) => {
+                let var = env.intern(&format!("{}.eq[{}]", name, i));
+                exprs.push(product(var.clone(), &equal, bit));
+                Some(var)
+            }
+        };
+    }
+
+    exprs
+}
+
+/// Lowers the width of a decomposition, checking it's not 0, and not more
+/// than the given maximum.
+fn lower_width(node: NodeRef<'_>, env: &Env<'_>, max: u64) -> Result<u64> {
+    let value = node
+        .children()
+        .next()
+        .expect("the grammar defines the width");
+
+    match scope::eval(value, env)?.to_u64() {
+        Some(width) if width > 0 && width <= max => Ok(width),
+        _ => Err(PlangError::Semantic(SemanticError::InvalidWidth(
+            node.as_str().to_owned(),
+        ))),
+    }
+}
+
+/// Checks the index of a summand is the index the sum is over.
+fn check_index(index: &str, used: &str) -> Result<()> {
+    match index == used {
+        true => Ok(()),
+        false => Err(PlangError::Semantic(SemanticError::UnboundIndex(
+            used.to_owned(),
+        ))),
+    }
+}
+
+/// Lowers the range of a sum into its bounds, checking it's not empty, and
+/// not too large.
+fn lower_range(node: NodeRef<'_>, env: &Env<'_>) -> Result<(u64, u64)> {
+    let mut bounds = vec![];
+    for bound in node.children() {
+        let value = bound
+            .children()
+            .next()
+            .expect("the grammar defines the bound");
+        bounds.push(scope::eval(value, env)?.to_u64());
+    }
+
+    match bounds[..] {
+        [Some(lo), Some(hi)] if lo < hi && hi - lo <= MAX_SUM_TERMS => Ok((lo, hi)),
+        _ => Err(PlangError::Semantic(SemanticError::InvalidSumRange(
+            node.as_str().to_owned(),
+        ))),
+    }
+}
+
+/// Lowers an equation into a `PlangExpr`, without annotating it.
+///
+/// The sign in front of each term applies to that term alone, and is folded
+/// into its coefficient. A term without a sign is positive. Coefficients that
+/// are arithmetic on constants are evaluated in the given environment.
+///
+/// An equation names each of its few variables about once, so their names
+/// aren't interned with the environment, and are only shared with the other
+/// equations once they're collected into a circuit.
+pub(crate) fn lower_expr(node: NodeRef<'_>, env: &Env<'_>) -> Result<PlangExpr> {
+    let mut minus = false;
+    let mut public = None;
+
+    let mut tris = vec![];
+    let mut bis = vec![];
+    let mut warnings = vec![];
+
+    for expr_inner in node.children() {
+        let expr_rule = expr_inner.rule();
+        match expr_rule {
+            Rule::sign => minus = expr_inner.as_str() == "-",
+            Rule::tri_term => {
+                let mut coeff = Scalar::one();
+                let mut lvar = None;
+                let mut rvar = None;
+
+                for term_inner in expr_inner.children() {
+                    let term_rule = term_inner.rule();
+                    match term_rule {
+                        Rule::coeff => coeff = lower_coeff(term_inner.as_str(), &mut warnings),
+                        Rule::const_expr => coeff = eval_coeff(term_inner, env, &mut warnings)?,
+                        Rule::var if lvar.is_none() => lvar = Some(term_inner.as_str()),
+                        Rule::var => rvar = Some(term_inner.as_str()),
+                        _ => unreachable!(),
+                    }
+                }
+
+                tris.push(TriTerm {
+                    coeff: signed(minus, coeff),
+                    lvar: Symbol::from(lvar.unwrap()),
+                    rvar: Symbol::from(rvar.unwrap()),
+                });
+                minus = false;
+            }
+            Rule::bi_term => {
+                let mut coeff = Scalar::one();
+                let mut var = None;
+
+                for term_inner in expr_inner.children() {
+                    let term_rule = term_inner.rule();
+                    match term_rule {
+                        Rule::coeff => coeff = lower_coeff(term_inner.as_str(), &mut warnings),
+                        Rule::const_expr => coeff = eval_coeff(term_inner, env, &mut warnings)?,
+                        Rule::var => var = Some(Symbol::from(term_inner.as_str())),
+                        _ => unreachable!(),
+                    }
+                }
+
+                bis.push(BiTerm {
+                    coeff: signed(minus, coeff),
+                    var: var.expect("the grammar defines the term"),
+                });
+                minus = false;
+            }
+            Rule::var => {
+                let var = Symbol::from(expr_inner.as_str());
+                public = Some(Public {
+                    negated: minus,
+                    var,
+                });
+            }
+            _ => {}
         }
+    }
+
+    // The grammar only accepts a single product, so this is a backstop.
+    if tris.len() > 1 {
+        return Err(PlangError::Semantic(SemanticError::TooManyTriTerms {
+            line: env.line(),
+            var: tris.swap_remove(1).lvar.to_string(),
+        }));
+    }
 
-        if vars.len() == 5 {
-            return Err(PlangError::TooManyVars);
+    Ok(PlangExpr {
+        tri: tris.pop(),
+        bis,
+        public,
+        constant: Scalar::zero(),
+        caption: None,
+        line: env.line(),
+        warnings,
+        allowed: vec![],
+        hints: vec![],
+    })
+}
+
+fn signed(minus: bool, coeff: Scalar) -> Scalar {
+    match minus {
+        true => -coeff,
+        false => coeff,
+    }
+}
+
+/// Lowers a coefficient of any size, reducing it modulo the modulus of the
+/// scalar field - with a warning - if it's not less than it. A coefficient of
+/// 0, as written or once reduced, is warned about since its term vanishes.
+fn lower_coeff(literal: &str, warnings: &mut Vec<Warning>) -> Scalar {
+    let (coeff, reduced) =
+        Scalar::from_decimal(literal).expect("the grammar only allows digits in coefficients");
+
+    let zero = coeff == Scalar::zero();
+    match (reduced, zero) {
+        (false, true) => warnings.push(Warning::ZeroCoefficient),
+        (true, true) => warnings.push(Warning::ModulusCoefficient {
+            literal: literal.to_owned(),
+        }),
+        (true, false) => warnings.push(Warning::ReducedCoefficient {
+            literal: literal.to_owned(),
+            reduced: coeff,
+        }),
+        (false, false) => {}
+    }
+    coeff
+}
+
+/// Evaluates a coefficient that is arithmetic on constants. A coefficient of
+/// 0 is warned about, like one written as such.
+fn eval_coeff(node: NodeRef<'_>, env: &Env<'_>, warnings: &mut Vec<Warning>) -> Result<Scalar> {
+    let coeff = scope::eval(node, env)?;
+    if coeff == Scalar::zero() {
+        warnings.push(Warning::ZeroCoefficient);
+    }
+    Ok(coeff)
+}
+
+/// Annotates the equation on the given line with the comments around it - the
+/// comments documenting it, and the warnings allowed for it.
+pub(crate) fn annotate(expr: &mut PlangExpr, lines: &[&str], line: usize) {
+    expr.caption = caption(lines, line);
+
+    for name in lines[..line]
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .take_while(|line| line.starts_with('#'))
+        .filter_map(|line| allow_list(line))
+        .flatten()
+    {
+        if !warning::NAMES.contains(&name) {
+            expr.warnings.push(Warning::UnknownWarning {
+                name: name.to_owned(),
+            });
         }
+        expr.allowed.push(name.to_owned());
     }
+}
 
-    Ok(())
+/// Annotates the equations a statement on the given line is lowered into.
+/// The comments are only checked once, so the warnings about them are only
+/// about the first equation.
+pub(crate) fn annotate_all(exprs: &mut [PlangExpr], lines: &[&str], line: usize) {
+    if let Some((first, rest)) = exprs.split_first_mut() {
+        annotate(first, lines, line);
+        for expr in rest {
+            expr.caption = first.caption.clone();
+            expr.allowed = first.allowed.clone();
+        }
+    }
 }
 
-// Check that there's no terms of the form `q_x · y` where variables are have
-// the same name in the same expression.
-fn check_no_repeat_vars_in_bis(exprs: &[PlangExpr]) -> Result<()> {
-    for expr in exprs {
-        let mut nterms = 0;
-        let mut vars = HashMap::with_capacity(5);
+/// The names of the warnings a comment of the form `#[allow(name, ...)]`
+/// allows, if it's of that form.
+fn allow_list(comment: &str) -> Option<impl Iterator<Item = &str>> {
+    let list = comment
+        .trim()
+        .strip_prefix("#[allow(")?
+        .strip_suffix(")]")?;
+
+    Some(
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty()),
+    )
+}
+
+/// Collects the comments documenting the equation on the given line - the
+/// comment lines directly above it, and a comment at the end of the line.
+/// Comments allowing warnings don't document it.
+fn caption(lines: &[&str], line: usize) -> Option<String> {
+    fn comment(line: &str) -> Option<String> {
+        line.find('#').map(|pos| line[pos + 1..].trim().to_owned())
+    }
+
+    let mut above: Vec<String> = lines[..line]
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .take_while(|line| line.starts_with('#'))
+        .filter(|line| allow_list(line).is_none())
+        .filter_map(comment)
+        .collect();
+    above.reverse();
+
+    let words: Vec<String> = above
+        .into_iter()
+        .chain(lines.get(line).and_then(|line| comment(line)))
+        .filter(|comment| !comment.is_empty())
+        .collect();
+
+    match words.is_empty() {
+        true => None,
+        false => Some(words.join(" ")),
+    }
+}
+
+/// Domain separator for circuit hashes, to be changed whenever the encoding
+/// of the circuit being hashed changes, or the same equations compile to
+/// other gates, so that keys cached under their hash aren't reused.
+const CIRCUIT_HASH_DOMAIN: &[u8] = b"plang-circuit-v2";
+
+fn hash_coeff(hasher: &mut Blake2s, coeff: Scalar) {
+    hasher.update(coeff.to_bytes());
+}
+
+fn hash_var(hasher: &mut Blake2s, var: &str) {
+    hasher.update((var.len() as u32).to_le_bytes());
+    hasher.update(var);
+}
+
+/// Runs the checks a circuit is subject to over a single equation. They don't
+/// depend on the other equations, so a circuit is valid if all its equations
+/// are.
+pub(crate) fn check_expr(expr: &PlangExpr) -> Result<()> {
+    check_different_tri_vars(expr)?;
+    check_less_than_5_vars(expr)?;
+    check_no_repeat_vars_in_bis(expr)?;
+    check_public_different_from_other_vars(expr)
+}
+
+/// Equations are lowered and checked in parallel in chunks of at least this
+/// many, since for small circuits splitting up the work costs more than it
+/// saves.
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_LEN: usize = 512;
+
+/// Maps each item, keeping their order. With the `parallel` feature the items
+/// are mapped in parallel.
+fn par_map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items
+            .par_iter()
+            .with_min_len(MIN_PARALLEL_LEN)
+            .map(f)
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
 
-        for bi in &expr.bis {
-            nterms += 1;
-            vars.insert(&bi.var, ());
+/// Calls `f` on each item, returning the error of the first item it fails
+/// for. With the `parallel` feature the items are gone through in parallel,
+/// still returning the error of the first one.
+fn par_try_for_each<T, F>(items: &[T], f: F) -> Result<()>
+where
+    T: Sync,
+    F: Fn(&T) -> Result<()> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items
+            .par_iter()
+            .with_min_len(MIN_PARALLEL_LEN)
+            .find_map_first(|item| f(item).err())
+            .map_or(Ok(()), Err)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().try_for_each(f)
+    }
+}
+
+// Creates a map of names to the indices of witnesses or public inputs. A
+// variable appearing more than once is what it is in the last place it
+// appears in.
+//
+// Equations are lowered apart from each other, each interning the names of
+// its variables on its own, so the map is the symbol table of the whole
+// circuit: every variable of the equations is made to share its name in it.
+fn vars_from_exprs(exprs: &mut [PlangExpr]) -> (HashMap<Symbol, usize>, Vec<WitnessOrPublic>) {
+    let mut vars: HashMap<Symbol, usize> = HashMap::new();
+    let mut values = vec![];
+
+    let mut insert = |name: &mut Symbol, var: WitnessOrPublic| match vars.get_key_value(name) {
+        Some((shared, &index)) => {
+            *name = shared.clone();
+            values[index] = var;
+        }
+        None => {
+            vars.insert(name.clone(), values.len());
+            values.push(var);
+        }
+    };
+
+    for expr in exprs.iter_mut() {
+        // if there is a PI in the expression (right equation side), also
+        // insert it in the map.
+        if let Some(public) = &mut expr.public {
+            insert(
+                &mut public.var,
+                WitnessOrPublic::PublicInput(Scalar::zero()),
+            );
+        }
+
+        // A term of the form `q_m · a · b` contains two witnesses.
+        if let Some(tri) = &mut expr.tri {
+            insert(&mut tri.lvar, WitnessOrPublic::Witness(Scalar::zero()));
+            insert(&mut tri.rvar, WitnessOrPublic::Witness(Scalar::zero()));
         }
 
-        if vars.len() != nterms {
-            return Err(PlangError::RepeatedVars);
+        // A term of the form `q_x · y` contains one witness.
+        for bi in &mut expr.bis {
+            insert(&mut bi.var, Default::default());
+        }
+    }
+
+    let share = |var: &Symbol| match vars.get_key_value(var) {
+        Some((shared, _)) => shared.clone(),
+        None => var.clone(),
+    };
+    for hint in exprs.iter_mut().flat_map(|expr| &mut expr.hints) {
+        hint.rename(&share);
+    }
+
+    (vars, values)
+}
+
+// Check that `a != b` for an expression of the form `q_m · a · b`.
+fn check_different_tri_vars(expr: &PlangExpr) -> Result<()> {
+    if let Some(tri) = &expr.tri {
+        if tri.lvar == tri.rvar {
+            return Err(PlangError::Semantic(SemanticError::SameTriVars {
+                line: expr.line,
+                var: tri.lvar.to_string(),
+            }));
         }
     }
 
     Ok(())
 }
 
-// Check the public input is different from all other variables.
-fn check_public_different_from_other_vars(exprs: &[PlangExpr]) -> Result<()> {
-    for expr in exprs {
-        if let Some(public) = &expr.public {
-            let mut vars = HashMap::with_capacity(5);
+// Check that an expression has less than 5 vars.
+fn check_less_than_5_vars(expr: &PlangExpr) -> Result<()> {
+    // Expressions have a handful of variables at most, so they're counted
+    // without collecting them anywhere.
+    let mut distinct = expr
+        .vars()
+        .enumerate()
+        .filter(|(i, var)| !expr.vars().take(*i).any(|other| other == *var))
+        .map(|(_, var)| var);
+
+    if let (Some(var), None) = (distinct.nth(4), distinct.next()) {
+        return Err(PlangError::Semantic(SemanticError::TooManyVars {
+            line: expr.line,
+            var: var.to_owned(),
+        }));
+    }
 
-            if let Some(tri) = &expr.tri {
-                vars.insert(&tri.lvar, ());
-                vars.insert(&tri.rvar, ());
-            }
+    Ok(())
+}
 
-            for bi in &expr.bis {
-                vars.insert(&bi.var, ());
-            }
+// Check that there's no terms of the form `q_x · y` where variables are have
+// the same name in the same expression.
+fn check_no_repeat_vars_in_bis(expr: &PlangExpr) -> Result<()> {
+    let bis = &expr.bis;
+    let repeated = (0..bis.len()).find(|&i| bis[..i].iter().any(|bi| bi.var == bis[i].var));
+
+    if let Some(i) = repeated {
+        return Err(PlangError::Semantic(SemanticError::RepeatedVars {
+            line: expr.line,
+            var: bis[i].var.to_string(),
+        }));
+    }
 
-            if vars.contains_key(&public.var) {
-                return Err(PlangError::PublicVarNotSingular);
-            }
+    Ok(())
+}
+
+// Check the public input is different from all other variables.
+fn check_public_different_from_other_vars(expr: &PlangExpr) -> Result<()> {
+    if let Some(public) = &expr.public {
+        let tri = expr.tri.iter().flat_map(|tri| [&tri.lvar, &tri.rvar]);
+        let mut vars = tri.chain(expr.bis.iter().map(|bi| &bi.var));
+
+        if vars.any(|var| *var == public.var) {
+            return Err(PlangError::Semantic(SemanticError::PublicVarNotSingular {
+                line: expr.line,
+                var: public.var.to_string(),
+            }));
         }
     }
 
     Ok(())
 }
 
+#[cfg(feature = "prover")]
 impl Circuit for PlangCircuit {
-    const CIRCUIT_ID: [u8; 32] = [0u8; 32];
+    const CIRCUIT_ID: [u8; 32] = verifier::CIRCUIT_ID;
 
-    // Gadget implementation for a plang circuit.
+    // Gadget implementation for a plang circuit, appending the gates of its
+    // compiled program. The plonk error can't say which equation is at
+    // fault, so it's logged before the gadget fails.
     fn gadget(&mut self, composer: &mut TurboComposer) -> std::result::Result<(), Error> {
-        // Append all witnesses in the map to the composer.
-        let witnesses = {
-            let mut ws = HashMap::new();
+        let _span = debug_span!("gadget", exprs = self.exprs.len()).entered();
 
-            for (vname, wop) in &self.vars {
-                if let WitnessOrPublic::Witness(wval) = wop {
-                    ws.insert(vname, composer.append_witness(*wval));
-                }
-            }
+        self.emit(composer).map_err(|err| {
+            error!(%err, "invalid gadget");
+            Error::CircuitInputsNotFound
+        })
+    }
 
-            ws
-        };
+    /// The values of the public inputs, as set on the circuit, in the order
+    /// of [`public_input_order`](Self::public_input_order).
+    fn public_inputs(&self) -> Vec<PublicInputValue> {
+        verifier::arrange(self.signed_public_inputs(), |name| self.value(name))
+            .expect("every public input of the circuit has a value")
+    }
 
-        // For every expression build the constraint according to the existing terms.
-        for expr in &self.exprs {
-            let mut constraint = Constraint::new();
+    fn padded_gates(&self) -> usize {
+        1 << (self.exprs.len() + 1)
+    }
+}
 
-            // If there is a public input add it as a `.public()` selector.
-            if let Some(public) = &expr.public {
-                let val = match self
-                    .vars
-                    .get(&public.var)
-                    .expect("public input isn't in map")
-                {
-                    WitnessOrPublic::PublicInput(scalar) => scalar,
-                    _ => panic!("public is not as public in map"),
-                };
+/// An equation of the circuit, compiled into a single gate.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct PlangExpr {
+    pub(crate) tri: Option<TriTerm>,
+    pub(crate) bis: Vec<BiTerm>,
+    pub(crate) public: Option<Public>,
+    /// The constant term of the left side. Only gadgets introduce equations
+    /// with one, as equations in the source have none.
+    pub(crate) constant: Scalar,
+    /// The comments documenting the equation in the source, if any.
+    pub(crate) caption: Option<String>,
+    /// The line the equation is on in the source, counting from 0.
+    pub(crate) line: usize,
+    /// The warnings about the equation, found while lowering it.
+    pub(crate) warnings: Vec<Warning>,
+    /// The names of the warnings allowed for the equation, with a
+    /// `#[allow(...)]` comment above it.
+    pub(crate) allowed: Vec<String>,
+    /// How the variables the equation introduces are computed.
+    pub(crate) hints: Vec<Hint>,
+}
 
-                match public.minus {
-                    true => {
-                        constraint = constraint.public(*val);
-                    }
-                    false => {
-                        constraint = constraint.public(-*val);
-                    }
+/// How a variable introduced while lowering is computed from the others.
+///
+/// Hints are only followed when setting values, to prove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(feature = "prover"), allow(dead_code))]
+pub(crate) enum Hint {
+    /// The variable is the sum of the terms and the constant.
+    Sum {
+        var: Symbol,
+        terms: Vec<(Scalar, Symbol)>,
+        constant: Scalar,
+    },
+    /// The variable is the product of two others.
+    Product {
+        var: Symbol,
+        left: Symbol,
+        right: Symbol,
+    },
+    /// The variable is the quotient of two others, or 0 if the denominator
+    /// is 0.
+    Quotient {
+        var: Symbol,
+        numerator: Symbol,
+        denominator: Symbol,
+    },
+    /// The variable is a range of the bits of another, starting from the
+    /// given bit, counting from the least significant.
+    Bits {
+        var: Symbol,
+        of: Symbol,
+        low: usize,
+        width: usize,
+    },
+}
+
+impl Hint {
+    /// Renames the variable computed, and those it's computed from.
+    fn rename<F: Fn(&Symbol) -> Symbol>(&mut self, rename: &F) {
+        match self {
+            Hint::Sum { var, terms, .. } => {
+                *var = rename(var);
+                for (_, term) in terms {
+                    *term = rename(term);
                 }
             }
+            Hint::Product { var, left, right } => {
+                *var = rename(var);
+                *left = rename(left);
+                *right = rename(right);
+            }
+            Hint::Quotient {
+                var,
+                numerator,
+                denominator,
+            } => {
+                *var = rename(var);
+                *numerator = rename(numerator);
+                *denominator = rename(denominator);
+            }
+            Hint::Bits { var, of, .. } => {
+                *var = rename(var);
+                *of = rename(of);
+            }
+        }
+    }
 
-            let mut tri_wits = None;
+    /// The variable computed.
+    pub(crate) fn var(&self) -> &str {
+        match self {
+            Hint::Sum { var, .. }
+            | Hint::Product { var, .. }
+            | Hint::Quotient { var, .. }
+            | Hint::Bits { var, .. } => var,
+        }
+    }
+}
 
-            // If there is a term of the form `q_m · a · b` add it as a
-            // `mult()` selector.
-            if let Some(tri) = &expr.tri {
-                let lwit = witnesses
-                    .get(&tri.lvar)
-                    .expect("tri term witness not in witness map");
-                let rwit = witnesses
-                    .get(&tri.rvar)
-                    .expect("tri term witness not in witness map");
-
-                tri_wits = Some((lwit, rwit));
-
-                match tri.minus {
-                    true => constraint = constraint.mult(-tri.coeff),
-                    false => constraint = constraint.mult(tri.coeff),
-                }
+impl PlangExpr {
+    /// Returns whether the warning with the given name is allowed for the
+    /// equation.
+    pub(crate) fn allows(&self, name: &str) -> bool {
+        self.allowed.iter().any(|allowed| allowed == name)
+    }
 
-                constraint = constraint.a(*lwit);
-                constraint = constraint.b(*rwit);
-            }
+    /// The warnings about the equation that aren't allowed for it.
+    pub(crate) fn reported_warnings(&self) -> impl Iterator<Item = &Warning> {
+        self.warnings
+            .iter()
+            .filter(move |warning| !self.allows(warning.name()))
+    }
 
-            let mut bi_num = 0;
-            for bi in &expr.bis {
-                let wit = witnesses
-                    .get(&bi.var)
-                    .expect("bi term witness not in witness map");
-
-                // If there is a term of the form `q_m · a · b` then if there
-                // is a term of the form `q_l · a` or `q_r · b` add a left
-                // wire, or a right wire selector respectively. If there is
-                // not, then one just adds the selectors sequentially, as it
-                // produces the same mathematical constraint.
-                match tri_wits {
-                    Some((lwit, rwit)) => match (wit == lwit, wit == rwit) {
-                        (false, false) => {
-                            constraint = constraint.o(*wit);
-                            match bi.minus {
-                                true => constraint = constraint.output(bi.coeff),
-                                false => constraint = constraint.output(-bi.coeff),
-                            }
-                        }
-                        (true, false) => match bi.minus {
-                            true => constraint = constraint.left(bi.coeff),
-                            false => constraint = constraint.left(-bi.coeff),
-                        },
-                        (false, true) => match bi.minus {
-                            true => constraint = constraint.right(bi.coeff),
-                            false => constraint = constraint.right(-bi.coeff),
-                        },
-                        _ => panic!("witness is both lwit and rwit"),
-                    },
-                    None => {
-                        match bi_num {
-                            0 => {
-                                constraint = constraint.a(*wit);
-                                match bi.minus {
-                                    true => constraint = constraint.left(-bi.coeff),
-                                    false => constraint = constraint.left(bi.coeff),
-                                }
-                            }
-                            1 => {
-                                constraint = constraint.b(*wit);
-                                match bi.minus {
-                                    true => constraint = constraint.right(-bi.coeff),
-                                    false => constraint = constraint.right(bi.coeff),
-                                }
-                            }
-                            2 => {
-                                constraint = constraint.o(*wit);
-                                match bi.minus {
-                                    true => constraint = constraint.output(-bi.coeff),
-                                    false => constraint = constraint.output(bi.coeff),
-                                }
-                            }
-                            _ => panic!("there should be max 3 bi terms"),
-                        }
+    /// Renames every variable of the equation, including those its hints
+    /// compute from.
+    pub(crate) fn rename<F: Fn(&Symbol) -> Symbol>(&mut self, rename: F) {
+        if let Some(tri) = &mut self.tri {
+            tri.lvar = rename(&tri.lvar);
+            tri.rvar = rename(&tri.rvar);
+        }
+        for bi in &mut self.bis {
+            bi.var = rename(&bi.var);
+        }
+        if let Some(public) = &mut self.public {
+            public.var = rename(&public.var);
+        }
 
-                        bi_num += 1;
-                    }
+        for hint in &mut self.hints {
+            hint.rename(&rename);
+        }
+    }
+
+    /// Iterates over the names of the variables in the expression, in the
+    /// order they appear in.
+    pub(crate) fn vars(&self) -> impl Iterator<Item = &str> {
+        let tri = self
+            .tri
+            .iter()
+            .flat_map(|tri| [tri.lvar.as_str(), tri.rvar.as_str()]);
+        let bis = self.bis.iter().map(|bi| bi.var.as_str());
+        let public = self.public.iter().map(|public| public.var.as_str());
+
+        tri.chain(bis).chain(public)
+    }
+
+    /// Lays the equation out as a gate.
+    #[cfg(feature = "prover")]
+    pub(crate) fn gate(&self) -> GateView<'_> {
+        const PUBLIC_WIRE: usize = 4;
+
+        let mut wires = [None; 5];
+        let mut selectors = [Scalar::zero(); 7];
+
+        if let Some(tri) = &self.tri {
+            wires[0] = Some(tri.lvar.as_str());
+            wires[1] = Some(tri.rvar.as_str());
+            selectors[0] = tri.coeff;
+        }
+
+        for bi in &self.bis {
+            let wire = match wires.iter().position(|w| *w == Some(bi.var.as_str())) {
+                Some(wire) => wire,
+                None => {
+                    let wire = wires[..PUBLIC_WIRE]
+                        .iter()
+                        .position(Option::is_none)
+                        .expect("expressions have at most 4 variables");
+                    wires[wire] = Some(bi.var.as_str());
+                    wire
                 }
-            }
+            };
+            selectors[wire + 1] = bi.coeff;
+        }
 
-            composer.append_gate(constraint);
+        if let Some(public) = &self.public {
+            wires[PUBLIC_WIRE] = Some(public.var.as_str());
+            selectors[PUBLIC_WIRE + 1] = public.coeff();
         }
+        selectors[PUBLIC_WIRE + 2] = self.constant;
 
-        Ok(())
+        GateView { wires, selectors }
     }
+}
 
-    fn public_inputs(&self) -> Vec<PublicInputValue> {
-        let mut named_pinputs: Vec<(&String, PublicInputValue)> = self
-            .vars
-            .iter()
-            .filter_map(|(name, wop)| {
-                if let WitnessOrPublic::PublicInput(pval) = wop {
-                    return Some((name, (*pval).into()));
-                }
-                None
-            })
-            .collect();
+impl fmt::Display for PlangExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
 
-        named_pinputs.sort_by(|(name1, _), (name2, _)| Ord::cmp(name1, name2));
+        if let Some(tri) = &self.tri {
+            let (minus, coeff) = sign_magnitude(tri.coeff);
+            fmt_sign(f, minus, first)?;
+            fmt_coeff(f, &coeff)?;
+            write!(f, "{} * {}", tri.lvar, tri.rvar)?;
+            first = false;
+        }
 
-        let mut pinputs = Vec::with_capacity(named_pinputs.len());
-        pinputs.append(&mut named_pinputs.into_iter().map(|(_, v)| v).collect());
+        for bi in &self.bis {
+            let (minus, coeff) = sign_magnitude(bi.coeff);
+            fmt_sign(f, minus, first)?;
+            fmt_coeff(f, &coeff)?;
+            write!(f, "{}", bi.var)?;
+            first = false;
+        }
+
+        if self.constant != Scalar::zero() {
+            let (minus, constant) = sign_magnitude(self.constant);
+            fmt_sign(f, minus, first)?;
+            write!(f, "{}", constant)?;
+        }
 
-        pinputs
+        match &self.public {
+            Some(public) => {
+                write!(f, " = ")?;
+                fmt_sign(f, public.negated, true)?;
+                write!(f, "{}", public.var)
+            }
+            None => write!(f, " = 0"),
+        }
     }
+}
 
-    fn padded_gates(&self) -> usize {
-        1 << (self.exprs.len() + 1)
+fn fmt_sign(f: &mut fmt::Formatter<'_>, minus: bool, first: bool) -> fmt::Result {
+    match (minus, first) {
+        (true, true) => write!(f, "-"),
+        (false, true) => Ok(()),
+        (true, false) => write!(f, " - "),
+        (false, false) => write!(f, " + "),
     }
 }
 
-#[derive(Debug, Default)]
-struct PlangExpr {
-    tri: Option<TriTerm>,
-    bis: Vec<BiTerm>,
-    public: Option<Public>,
+// Coefficients of one are implicit, so they're left out.
+fn fmt_coeff(f: &mut fmt::Formatter<'_>, coeff: &Scalar) -> fmt::Result {
+    if *coeff != Scalar::one() {
+        write!(f, "{} * ", coeff)?;
+    }
+    Ok(())
 }
 
-// TODO find a better way of dealing with negative coefficients
+/// Formats a scalar in decimal if it fits in a `u64`, and in hex otherwise.
+#[cfg(feature = "prover")]
+pub(crate) fn fmt_scalar<W: Write>(f: &mut W, scalar: &Scalar) -> fmt::Result {
+    let bytes = scalar.to_bytes();
+
+    if bytes[8..].iter().all(|b| *b == 0) {
+        let mut low = [0u8; 8];
+        low.copy_from_slice(&bytes[..8]);
+        return write!(f, "{}", u64::from_le_bytes(low));
+    }
 
-#[derive(Debug)]
-struct TriTerm {
-    minus: bool,
-    coeff: BlsScalar,
-    lvar: String,
-    rvar: String,
+    write!(f, "0x")?;
+    for byte in bytes.iter().rev() {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
 }
 
-#[derive(Debug)]
-struct BiTerm {
-    minus: bool,
-    coeff: BlsScalar,
-    var: String,
+/// Splits a coefficient into a sign and a magnitude, for writing it out.
+/// Coefficients more than half the modulus are written as the negation of a
+/// smaller one.
+pub(crate) fn sign_magnitude(coeff: Scalar) -> (bool, Scalar) {
+    match coeff.is_negative() {
+        true => (true, -coeff),
+        false => (false, coeff),
+    }
 }
 
-#[derive(Debug)]
-struct Public {
-    minus: bool,
-    var: String,
+// Equations are kept in a normal form, with every term on the left side and
+// zero on the right: the sign in front of each term of the left side is
+// folded into its coefficient, and the public input is moved over to the
+// left side. Gates and exports are built from the coefficients as they are,
+// with no signs left to interpret.
+
+/// A term of the form `q_m · a · b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TriTerm {
+    /// The coefficient, signed.
+    pub(crate) coeff: Scalar,
+    pub(crate) lvar: Symbol,
+    pub(crate) rvar: Symbol,
+}
+
+/// A term of the form `q_x · y`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BiTerm {
+    /// The coefficient, signed.
+    pub(crate) coeff: Scalar,
+    pub(crate) var: Symbol,
+}
+
+/// The public input on the right side of an equation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Public {
+    /// Whether it has a minus sign in front of it.
+    pub(crate) negated: bool,
+    pub(crate) var: Symbol,
+}
+
+impl Public {
+    /// The coefficient of the public input once it's moved to the left side
+    /// of the equation - `-1`, or `1` if it's negated.
+    pub(crate) fn coeff(&self) -> Scalar {
+        signed(!self.negated, Scalar::one())
+    }
 }
@@ -0,0 +1,174 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Coverage of the gates of a circuit by assignments of values to it.
+//!
+//! A selector of a gate is exercised by an assignment if the term it selects
+//! contributes to the gate, that is if neither the selector nor the values on
+//! its wires are 0. A gate whose selectors are never exercised holds no
+//! matter what, so the values tested say nothing about it - much like a line
+//! of code never run by the tests.
+
+use crate::backend::SELECTORS;
+use crate::error::{AssignmentError, Error as PlangError, Result};
+use crate::scalar::Scalar;
+use crate::PlangCircuit;
+
+use std::fmt;
+
+use dusk_plonk::prelude::BlsScalar;
+
+/// How many assignments exercised each selector of a gate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateCoverage {
+    /// The index of the equation laid out as the gate.
+    pub equation: usize,
+    /// The line the equation is on, counting from 0.
+    pub line: usize,
+    /// The equation, in normal form.
+    pub text: String,
+    /// For each selector, in the order of [`SELECTORS`], the number of
+    /// assignments exercising it, or `None` if it's 0.
+    pub selectors: [Option<usize>; 7],
+}
+
+impl GateCoverage {
+    /// Whether any assignment exercised any selector of the gate.
+    pub fn is_active(&self) -> bool {
+        self.selectors
+            .iter()
+            .any(|count| matches!(count, Some(n) if *n > 0))
+    }
+
+    /// The names of the selectors that aren't 0, but no assignment exercised.
+    pub fn unexercised(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.selectors
+            .iter()
+            .zip(SELECTORS.iter())
+            .filter(|(count, _)| **count == Some(0))
+            .map(|(_, name)| *name)
+    }
+}
+
+impl fmt::Display for GateCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: `{}`:", self.line + 1, self.text)?;
+
+        let mut first = true;
+        for (count, name) in self.selectors.iter().zip(SELECTORS.iter()) {
+            if let Some(count) = count {
+                write!(f, "{} {} {}", if first { "" } else { "," }, name, count)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The coverage of every gate of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The coverage of each gate, in the order of the equations.
+    pub gates: Vec<GateCoverage>,
+    /// The number of assignments.
+    pub assignments: usize,
+}
+
+impl CoverageReport {
+    /// The gates none of whose selectors any assignment exercised.
+    pub fn inactive(&self) -> impl Iterator<Item = &GateCoverage> {
+        self.gates.iter().filter(|gate| !gate.is_active())
+    }
+
+    /// The number of selectors that aren't 0, and of those some assignment
+    /// exercised.
+    pub fn exercised(&self) -> (usize, usize) {
+        self.gates
+            .iter()
+            .flat_map(|gate| gate.selectors.iter().flatten())
+            .fold((0, 0), |(total, exercised), count| {
+                (total + 1, exercised + usize::from(*count > 0))
+            })
+    }
+}
+
+impl PlangCircuit {
+    /// Sets each assignment on a copy of the circuit, which is left as it is,
+    /// and counts the selectors of each gate it exercises. The assignments
+    /// don't have to satisfy the circuit, nor its assertions.
+    ///
+    /// It returns an error if a value is not in the circuit.
+    pub fn coverage<I, A>(&self, assignments: I) -> Result<CoverageReport>
+    where
+        I: IntoIterator<Item = A>,
+        A: IntoIterator<Item = (String, Scalar)>,
+    {
+        let views = self.gates();
+        let mut gates: Vec<GateCoverage> = views
+            .iter()
+            .zip(self.exprs())
+            .enumerate()
+            .map(|(i, (gate, expr))| {
+                let mut selectors = [None; 7];
+                for (count, selector) in selectors.iter_mut().zip(&gate.selectors) {
+                    if *selector != Scalar::zero() {
+                        *count = Some(0);
+                    }
+                }
+                GateCoverage {
+                    equation: i,
+                    line: expr.line,
+                    text: expr.to_string(),
+                    selectors,
+                }
+            })
+            .collect();
+
+        let mut count = 0;
+        for assignment in assignments {
+            let mut circuit = self.unassigned();
+            let values = assignment
+                .into_iter()
+                .map(|(name, value)| (name, BlsScalar::from(value)));
+            match circuit.set_vals(values) {
+                Ok(()) | Err(PlangError::Assignment(AssignmentError::AssertionFailed { .. })) => {}
+                Err(err) => return Err(err),
+            }
+
+            let value = |var: Option<&str>| {
+                var.and_then(|var| circuit.value(var))
+                    .map_or(Scalar::zero(), Scalar::from)
+            };
+            for (coverage, gate) in gates.iter_mut().zip(&views) {
+                let [a, b, o, d, p] = gate.wires;
+
+                // The value each selector is multiplied by in the gate.
+                let terms = [
+                    value(a) * value(b),
+                    value(a),
+                    value(b),
+                    value(o),
+                    value(d),
+                    value(p),
+                    Scalar::one(),
+                ];
+                for (count, term) in coverage.selectors.iter_mut().zip(&terms) {
+                    if let Some(count) = count {
+                        if *term != Scalar::zero() {
+                            *count += 1;
+                        }
+                    }
+                }
+            }
+            count += 1;
+        }
+
+        Ok(CoverageReport {
+            gates,
+            assignments: count,
+        })
+    }
+}
@@ -0,0 +1,121 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Differential testing of the gadget of a circuit.
+//!
+//! A circuit compiles its equations into gates over the indices of its
+//! variables before appending them to the composer. To check that nothing is
+//! lost along the way, the same constraints are built by hand, straight from
+//! the [`GateView`] of each equation, and both are compiled: the prover keys
+//! and verifier data have to be byte for byte the same.
+//!
+//! [`GateView`]: crate::backend::GateView
+
+use crate::backend::GateView;
+use crate::error::Result;
+use crate::verifier;
+use crate::PlangCircuit;
+
+use std::collections::HashMap;
+
+use dusk_plonk::circuit::{Circuit, PublicInputValue};
+use dusk_plonk::commitment_scheme::PublicParameters;
+use dusk_plonk::prelude::*;
+
+/// A circuit whose gadget appends the gates of a plang circuit one by one,
+/// as its equations lay them out, rather than through its compiled program.
+#[derive(Debug)]
+pub struct HandBuilt<'a> {
+    circuit: &'a PlangCircuit,
+}
+
+impl<'a> HandBuilt<'a> {
+    /// Builds the gates of the given circuit by hand, with the values set on
+    /// it.
+    pub fn new(circuit: &'a PlangCircuit) -> Self {
+        Self { circuit }
+    }
+}
+
+impl Circuit for HandBuilt<'_> {
+    const CIRCUIT_ID: [u8; 32] = verifier::CIRCUIT_ID;
+
+    fn gadget(&mut self, composer: &mut TurboComposer) -> std::result::Result<(), Error> {
+        let circuit = self.circuit;
+        let value = |name: &str| circuit.value(name).unwrap_or_default();
+
+        // Witnesses are appended the first time a gate refers to them.
+        let mut witnesses = HashMap::new();
+
+        for GateView { wires, selectors } in circuit.gates() {
+            let [a, b, o, d, p] = wires;
+            let [q_m, q_l, q_r, q_o, q_4, q_p, q_c] = selectors;
+
+            let mut constraint = Constraint::new()
+                .mult(BlsScalar::from(q_m))
+                .left(BlsScalar::from(q_l))
+                .right(BlsScalar::from(q_r))
+                .output(BlsScalar::from(q_o))
+                .fourth(BlsScalar::from(q_4))
+                .constant(BlsScalar::from(q_c));
+
+            let mut witness = |name: &str| {
+                *witnesses
+                    .entry(name.to_owned())
+                    .or_insert_with(|| composer.append_witness(value(name)))
+            };
+            if let Some(a) = a {
+                constraint = constraint.a(witness(a));
+            }
+            if let Some(b) = b {
+                constraint = constraint.b(witness(b));
+            }
+            if let Some(o) = o {
+                constraint = constraint.o(witness(o));
+            }
+            if let Some(d) = d {
+                constraint = constraint.d(witness(d));
+            }
+            if let Some(p) = p {
+                constraint = constraint.public(BlsScalar::from(q_p) * value(p));
+            }
+
+            composer.append_gate(constraint);
+        }
+
+        Ok(())
+    }
+
+    fn public_inputs(&self) -> Vec<PublicInputValue> {
+        Circuit::public_inputs(self.circuit)
+    }
+
+    fn padded_gates(&self) -> usize {
+        self.circuit.padded_gates()
+    }
+}
+
+/// Compiles the circuit, and its gates built by hand, with the given
+/// parameters.
+///
+/// # Panics
+///
+/// Panics if the prover keys or the verifier data aren't the same.
+pub fn assert_same_keys(circuit: &mut PlangCircuit, pp: &PublicParameters) -> Result<()> {
+    let (pk, vd) = circuit.compile(pp)?;
+    let (hpk, hvd) = HandBuilt::new(circuit).compile(pp)?;
+
+    assert!(
+        pk.to_var_bytes() == hpk.to_var_bytes(),
+        "the prover keys of the circuit and of its gates built by hand differ"
+    );
+    assert!(
+        vd.to_var_bytes() == hvd.to_var_bytes(),
+        "the verifier data of the circuit and of its gates built by hand differ"
+    );
+
+    Ok(())
+}
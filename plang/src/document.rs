@@ -0,0 +1,634 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Incremental parsing of circuits, for editors.
+//!
+//! Plang is line based - each line holds at most one statement - and the
+//! checks an equation is subject to don't depend on the others. A
+//! [`Document`] takes advantage of this by keeping each line parsed and
+//! checked on its own, so an edit only costs the lines it touches - even when
+//! the editor hands over the whole text, with [`Document::update`]. The only
+//! checks spanning lines - that the transcript label is declared at most once,
+//! and which blocks are taken - are cheap to redo.
+//!
+//! The same line by line parsing lets [`PlangCircuit::parse_lenient`] skip
+//! past malformed lines, and still build a circuit from the rest, and
+//! [`PlangCircuit::parse_reader`] parse circuits without ever holding their
+//! whole text in memory.
+
+use crate::assertion::{self, Assertion};
+use crate::circuit::{self, PlangExpr};
+use crate::error::{Error as PlangError, ParseError, Result, SemanticError};
+use crate::grammar::{NodeRef, PlangGrammar, Rule};
+use crate::include;
+use crate::scope::{self, Consts, Directive, Env, Scope};
+use crate::symbol::Symbols;
+use crate::vector::{self, TestVector};
+use crate::warning::LineWarning;
+use crate::PlangCircuit;
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::mem;
+use std::ops::Range;
+
+use pest::error::LineColLocation;
+use serde_json::{json, Value};
+use tracing::info_span;
+
+/// A circuit being edited, parsed and checked line by line.
+#[derive(Debug, Default)]
+pub struct Document {
+    lines: Vec<Line>,
+}
+
+/// A line of a document, with the result of parsing and checking it.
+#[derive(Debug)]
+struct Line {
+    text: String,
+    item: Result<Item>,
+}
+
+/// What a line holds.
+#[derive(Debug, Clone)]
+enum Item {
+    /// A line with nothing in it but whitespace or a comment.
+    Empty,
+    Transcript(Vec<u8>),
+    Test(TestVector),
+    Assertion(Assertion),
+    /// A constant, or the start or end of a block.
+    Directive(Directive),
+    /// An equation, or a sum, lowered into the equations it stands for.
+    Equations(Vec<PlangExpr>),
+    /// An equation, or a sum, using constants, which is only lowered once
+    /// the constants declared above it are known.
+    Deferred,
+}
+
+/// An error in a line of a document.
+#[derive(Debug)]
+pub struct Diagnostic<'a> {
+    /// The line the error is in, counting from 0.
+    pub line: usize,
+    /// The error. Syntax errors point to their position in the document.
+    pub error: &'a PlangError,
+}
+
+impl Diagnostic<'_> {
+    /// Writes the diagnostic as a `{ line, code, message }` object, as the
+    /// bindings and services report them.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "line": self.line,
+            "code": self.error.code(),
+            "message": self.error.to_string(),
+        })
+    }
+}
+
+/// An error in a line skipped over by a lenient parse.
+#[derive(Debug)]
+pub struct Skipped {
+    /// The line the error is in, counting from 0.
+    pub line: usize,
+    /// The error.
+    pub error: PlangError,
+}
+
+impl PlangCircuit {
+    /// Parses a circuit leniently, skipping the lines with errors.
+    ///
+    /// A circuit is built from the remaining equations, which may describe
+    /// different constraints than intended, so it's meant for tooling working
+    /// on files mid-edit, and not for compiling. The errors skipped over are
+    /// returned with it. A transcript label declared after another is skipped
+    /// as well.
+    pub fn parse_lenient<S: AsRef<str>>(text: S) -> (Self, Vec<Skipped>) {
+        Document::new(text).into_lenient()
+    }
+
+    /// Parses a circuit from a reader, a line at a time.
+    ///
+    /// Only the equations lowered so far are kept in memory - not the text,
+    /// nor the syntax tree of more than one line - which bounds the memory
+    /// taken by large, machine generated circuits. The circuit is the same
+    /// [`parse`](Self::parse) builds from the same text, but if the text has
+    /// errors the one returned is the first in the text, syntax error or not.
+    ///
+    /// As in a [`Document`], the text is parsed by the first edition, and
+    /// can't import other circuits - it's meant for generated circuits, which
+    /// need neither. Circuits declaring an edition or importing others are
+    /// parsed with [`parse_with`](Self::parse_with) or
+    /// [`from_file_with`](Self::from_file_with) instead.
+    pub fn parse_reader<R: BufRead>(mut reader: R) -> Result<Self> {
+        let _span = info_span!("parse").entered();
+
+        let mut exprs = vec![];
+        let mut transcript = None;
+        let mut tests = vec![];
+        let mut assertions = vec![];
+        let mut scope = Scope::default();
+
+        // The run of comment lines right above the current one, which
+        // captions the equation on it.
+        let mut comments: Vec<String> = vec![];
+        let mut buf = vec![];
+
+        for line in 0.. {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                break;
+            }
+
+            let mut text = String::from_utf8(mem::take(&mut buf))?;
+            if text.ends_with('\n') {
+                text.pop();
+                if text.ends_with('\r') {
+                    text.pop();
+                }
+            }
+
+            // Lines in blocks that aren't taken are skipped, errors and all.
+            let item = match Line::item(&text, line, Some(scope.consts())) {
+                Err(PlangError::Parse(ParseError::Pest(err))) => {
+                    return Err(PlangError::Parse(ParseError::Pest(err)))
+                }
+                Err(_) if !scope.is_active() => Item::Empty,
+                item => item?,
+            };
+
+            match item {
+                Item::Directive(directive) => scope.apply(&directive, line)?,
+                _ if !scope.is_active() => {}
+                Item::Empty | Item::Deferred => {}
+                Item::Transcript(label) => {
+                    if transcript.is_some() {
+                        return Err(PlangError::Semantic(SemanticError::RepeatedTranscript));
+                    }
+                    transcript = Some(label);
+                }
+                Item::Test(test) => tests.push(test),
+                Item::Assertion(assertion) => assertions.push(assertion),
+                Item::Equations(mut lowered) => {
+                    let lines: Vec<&str> = comments
+                        .iter()
+                        .map(String::as_str)
+                        .chain(Some(text.as_str()))
+                        .collect();
+                    circuit::annotate_all(&mut lowered, &lines, comments.len());
+                    for mut expr in lowered {
+                        expr.line = line;
+                        exprs.push(expr);
+                    }
+                }
+            }
+
+            match text.trim_start().starts_with('#') {
+                true => comments.push(text),
+                false => {
+                    comments.clear();
+                    buf = text.into_bytes();
+                }
+            }
+        }
+
+        scope.finish()?;
+        Ok(PlangCircuit::from_checked_exprs(exprs, transcript)
+            .with_tests(tests)
+            .with_assertions(assertions))
+    }
+}
+
+impl Document {
+    /// Parses and checks a document.
+    pub fn new<S: AsRef<str>>(text: S) -> Self {
+        let mut document = Self::default();
+        document.edit(0..0, text);
+        document
+    }
+
+    /// Replaces the given range of lines with the lines of the given text,
+    /// parsing and checking only those. The range counts from 0 and is
+    /// clamped to the lines of the document.
+    pub fn edit<S: AsRef<str>>(&mut self, lines: Range<usize>, text: S) {
+        let end = lines.end.min(self.lines.len());
+        let start = lines.start.min(end);
+
+        let new: Vec<Line> = text
+            .as_ref()
+            .lines()
+            .enumerate()
+            .map(|(i, line)| Line::parse(line, start + i))
+            .collect();
+        let inserted = new.len();
+        let shift = inserted as isize - (end - start) as isize;
+
+        self.lines.splice(start..end, new);
+
+        // Errors in the lines after the edit point to lines that have moved,
+        // so they're updated to match.
+        if shift != 0 {
+            for line in &mut self.lines[start + inserted..] {
+                match &mut line.item {
+                    Err(PlangError::Parse(ParseError::Pest(err))) => {
+                        shift_line_col(&mut err.line_col, shift);
+                    }
+                    Err(PlangError::Semantic(err)) => {
+                        if let Some(line) = err.line_mut() {
+                            *line = (*line as isize + shift) as usize;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Replaces the whole text of the document, parsing and checking only the
+    /// lines that changed.
+    ///
+    /// Lines are matched to the ones already in the document by their text,
+    /// wherever they moved to, so an editor can hand over the full text on
+    /// every change and only new or modified lines are lowered anew. Lines
+    /// with errors are always rechecked, so their errors point to where they
+    /// are now.
+    pub fn update<S: AsRef<str>>(&mut self, text: S) {
+        let mut lowered: HashMap<String, Item> = HashMap::new();
+        for line in self.lines.drain(..) {
+            if let Ok(item) = line.item {
+                lowered.insert(line.text, item);
+            }
+        }
+
+        self.lines = text
+            .as_ref()
+            .lines()
+            .enumerate()
+            .map(|(i, text)| match lowered.get(text) {
+                Some(item) => Line {
+                    text: text.to_owned(),
+                    item: Ok(item.clone()),
+                },
+                None => Line::parse(text, i),
+            })
+            .collect();
+    }
+
+    /// Returns the number of lines in the document.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns whether the document has no lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the text of the document.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            text.push_str(&line.text);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Returns the errors in the document, in the order of the lines they're
+    /// in. Errors in lines using constants are only found when building the
+    /// circuit, with [`to_circuit`](Self::to_circuit).
+    pub fn diagnostics(&self) -> Vec<Diagnostic<'_>> {
+        let mut diagnostics: Vec<Diagnostic<'_>> = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(line, l)| l.item.as_ref().err().map(|error| (line, error)))
+            .map(|(line, error)| Diagnostic { line, error })
+            .chain(self.repeated_transcript())
+            .collect();
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+        diagnostics
+    }
+
+    /// Returns the warnings about the lines of the document without errors,
+    /// in the order of the lines they're about.
+    pub fn warnings(&self) -> Vec<LineWarning> {
+        let lines: Vec<&str> = self.lines.iter().map(|line| line.text.as_str()).collect();
+
+        let (items, _) = self.resolve();
+
+        let mut warnings = vec![];
+        for (line, item) in items.into_iter().enumerate() {
+            if let Some(Item::Equations(mut exprs)) = item {
+                circuit::annotate_all(&mut exprs, &lines, line);
+
+                for expr in &exprs {
+                    warnings.extend(expr.reported_warnings().map(|warning| LineWarning {
+                        line,
+                        warning: warning.clone(),
+                    }));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// Builds the circuit the document describes - the same circuit that
+    /// [`PlangCircuit::parse`] would build from its text.
+    ///
+    /// If the document has errors, syntax errors are reported before any
+    /// others, as they would be when parsing.
+    pub fn to_circuit(&self) -> Result<PlangCircuit> {
+        let syntax = self
+            .lines
+            .iter()
+            .position(|line| matches!(line.item, Err(PlangError::Parse(ParseError::Pest(_)))));
+        if let Some(line) = syntax {
+            return Err(Line::parse(&self.lines[line].text, line)
+                .item
+                .expect_err("line has a syntax error"));
+        }
+
+        let lines: Vec<&str> = self.lines.iter().map(|line| line.text.as_str()).collect();
+
+        let mut exprs = vec![];
+        let mut transcript = None;
+        let mut tests = vec![];
+        let mut assertions = vec![];
+        let mut scope = Scope::default();
+
+        for (i, line) in self.lines.iter().enumerate() {
+            match &line.item {
+                Ok(Item::Directive(directive)) => scope.apply(directive, i)?,
+                _ if !scope.is_active() => {}
+                Ok(Item::Empty) => {}
+                Ok(Item::Transcript(label)) => {
+                    if transcript.is_some() {
+                        return Err(PlangError::Semantic(SemanticError::RepeatedTranscript));
+                    }
+                    transcript = Some(label.clone());
+                }
+                Ok(Item::Test(test)) => tests.push(TestVector {
+                    line: i,
+                    ..test.clone()
+                }),
+                Ok(Item::Assertion(assertion)) => {
+                    let mut assertion = assertion.clone();
+                    assertion.line = i;
+                    assertions.push(assertion);
+                }
+                Ok(Item::Equations(lowered)) => {
+                    extend_annotated(&mut exprs, lowered.clone(), &lines, i);
+                }
+                Ok(Item::Deferred) => {
+                    if let Item::Equations(lowered) =
+                        Line::item(&line.text, i, Some(scope.consts()))?
+                    {
+                        extend_annotated(&mut exprs, lowered, &lines, i);
+                    }
+                }
+                Err(_) => {
+                    return Err(Line::parse(&line.text, i)
+                        .item
+                        .expect_err("line has an error"))
+                }
+            }
+        }
+        scope.finish()?;
+
+        Ok(PlangCircuit::from_checked_exprs(exprs, transcript)
+            .with_tests(tests)
+            .with_assertions(assertions))
+    }
+
+    /// Builds a circuit from the lines without errors, skipping the others
+    /// like [`PlangCircuit::parse_lenient`] does.
+    pub fn to_circuit_lenient(&self) -> PlangCircuit {
+        let lines: Vec<&str> = self.lines.iter().map(|line| line.text.as_str()).collect();
+
+        let (items, _) = self.resolve();
+
+        let mut exprs = vec![];
+        let mut transcript = None;
+        let mut tests = vec![];
+        let mut assertions = vec![];
+
+        for (i, item) in items.into_iter().enumerate() {
+            match item {
+                Some(Item::Transcript(label)) if transcript.is_none() => {
+                    transcript = Some(label);
+                }
+                Some(Item::Test(test)) => tests.push(TestVector { line: i, ..test }),
+                Some(Item::Assertion(mut assertion)) => {
+                    assertion.line = i;
+                    assertions.push(assertion);
+                }
+                Some(Item::Equations(lowered)) => extend_annotated(&mut exprs, lowered, &lines, i),
+                _ => {}
+            }
+        }
+
+        PlangCircuit::from_checked_exprs(exprs, transcript)
+            .with_tests(tests)
+            .with_assertions(assertions)
+    }
+
+    /// Consumes the document, building a circuit from the lines without
+    /// errors and returning the errors in the others.
+    pub fn into_lenient(self) -> (PlangCircuit, Vec<Skipped>) {
+        let circuit = self.to_circuit_lenient();
+        let repeated = self.repeated_transcript().map(|diagnostic| diagnostic.line);
+        let (_, unresolved) = self.resolve();
+
+        let mut skipped: Vec<Skipped> = self
+            .lines
+            .into_iter()
+            .enumerate()
+            .filter_map(|(line, l)| l.item.err().map(|error| Skipped { line, error }))
+            .chain(repeated.map(|line| Skipped {
+                line,
+                error: PlangError::Semantic(SemanticError::RepeatedTranscript),
+            }))
+            .chain(unresolved)
+            .collect();
+
+        skipped.sort_by_key(|skipped| skipped.line);
+        (circuit, skipped)
+    }
+
+    /// Walks through the lines in order, as they're compiled. The item of
+    /// each line without errors is returned, with lines using constants
+    /// lowered with the constants declared above them, or `None` if the line
+    /// isn't compiled.
+    ///
+    /// Errors in the constants and blocks, and in the lines using constants,
+    /// are returned as well. A directive with an error is skipped, and a
+    /// block left open is reported on the last line.
+    fn resolve(&self) -> (Vec<Option<Item>>, Vec<Skipped>) {
+        let mut scope = Scope::default();
+        let mut items = Vec::with_capacity(self.lines.len());
+        let mut errors = vec![];
+
+        for (line, l) in self.lines.iter().enumerate() {
+            let item = match &l.item {
+                Ok(Item::Directive(directive)) => {
+                    if let Err(error) = scope.apply(directive, line) {
+                        errors.push(Skipped { line, error });
+                    }
+                    None
+                }
+                _ if !scope.is_active() => None,
+                Ok(Item::Deferred) => match Line::item(&l.text, line, Some(scope.consts())) {
+                    Ok(item) => Some(item),
+                    Err(error) => {
+                        errors.push(Skipped { line, error });
+                        None
+                    }
+                },
+                Ok(item) => Some(item.clone()),
+                Err(_) => None,
+            };
+            items.push(item);
+        }
+
+        if let Err(error) = scope.finish() {
+            let line = self.lines.len().saturating_sub(1);
+            errors.push(Skipped { line, error });
+        }
+
+        (items, errors)
+    }
+
+    /// A transcript label declared after another is an error.
+    fn repeated_transcript(&self) -> Option<Diagnostic<'_>> {
+        const REPEATED: &PlangError = &PlangError::Semantic(SemanticError::RepeatedTranscript);
+
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| matches!(line.item, Ok(Item::Transcript(_))))
+            .nth(1)
+            .map(|(line, _)| Diagnostic {
+                line,
+                error: REPEATED,
+            })
+    }
+}
+
+impl Line {
+    /// Parses and checks a line, at the given position in the document.
+    fn parse(text: &str, line: usize) -> Self {
+        Self {
+            text: text.to_owned(),
+            item: Self::item(text, line, None),
+        }
+    }
+
+    /// Parses and checks a line, lowering any arithmetic on constants with
+    /// the given constants. Without constants, a line using them is deferred.
+    fn item(text: &str, line: usize, consts: Option<&Consts>) -> Result<Item> {
+        let text = format!("{}\n", text);
+
+        let grammar = PlangGrammar::new(text).map_err(|err| match err {
+            PlangError::Parse(ParseError::Pest(mut err)) => {
+                shift_line_col(&mut err.line_col, line as isize);
+                PlangError::Parse(ParseError::Pest(err))
+            }
+            err => err,
+        })?;
+
+        for node in grammar.nodes() {
+            if let Some(directive) = scope::lower_directive(node) {
+                return Ok(Item::Directive(directive));
+            }
+
+            match node.rule() {
+                Rule::transcript => return Ok(Item::Transcript(circuit::lower_transcript(node))),
+                Rule::test => return Ok(Item::Test(vector::lower_test(node, line))),
+                Rule::assertion => {
+                    return Ok(Item::Assertion(assertion::lower_assertion(node, line)))
+                }
+                // Documents are edited apart from any file, and so can't
+                // import circuits.
+                Rule::import => {
+                    let (name, _) = include::lower_import(node);
+                    return Err(PlangError::Semantic(SemanticError::UnresolvedImport(
+                        name.to_owned(),
+                    )));
+                }
+                Rule::expr
+                | Rule::sum
+                | Rule::bits
+                | Rule::bytes
+                | Rule::trunc
+                | Rule::compare
+                | Rule::commit => {
+                    let none = Consts::default();
+                    let consts = match consts {
+                        Some(consts) => consts,
+                        None if uses_constants(node) => return Ok(Item::Deferred),
+                        None => &none,
+                    };
+                    // Lines are lowered apart from each other, to be kept as
+                    // they are while others are edited, so each interns the
+                    // names of its variables on its own.
+                    let symbols = Symbols::default();
+                    let exprs = circuit::lower_statement(node, &Env::new(consts, &symbols, line))?;
+                    for expr in &exprs {
+                        circuit::check_expr(expr)?;
+                        if let Some(var) =
+                            expr.vars().find(|var| include::namespace_of(var).is_some())
+                        {
+                            return Err(PlangError::Semantic(SemanticError::UndefinedNamespace(
+                                var.to_owned(),
+                            )));
+                        }
+                    }
+                    return Ok(Item::Equations(exprs));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Item::Empty)
+    }
+}
+
+/// Returns whether the statement has arithmetic on constants in it.
+fn uses_constants(node: NodeRef<'_>) -> bool {
+    node.children()
+        .any(|child| child.rule() == Rule::constant || uses_constants(child))
+}
+
+/// Annotates the equations lowered from the given line, and adds them to the
+/// others.
+fn extend_annotated(
+    exprs: &mut Vec<PlangExpr>,
+    mut lowered: Vec<PlangExpr>,
+    lines: &[&str],
+    line: usize,
+) {
+    circuit::annotate_all(&mut lowered, lines, line);
+    for mut expr in lowered {
+        expr.line = line;
+        exprs.push(expr);
+    }
+}
+
+fn shift_line_col(line_col: &mut LineColLocation, shift: isize) {
+    let shift = |line: &mut usize| *line = (*line as isize + shift) as usize;
+
+    match line_col {
+        LineColLocation::Pos((line, _)) => shift(line),
+        LineColLocation::Span((start, _), (end, _)) => {
+            shift(start);
+            shift(end);
+        }
+    }
+}
@@ -0,0 +1,270 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Proofs bundled with everything needed to verify them.
+//!
+//! A bare proof says nothing about the circuit it proves, nor about the
+//! public inputs and transcript it has to be verified with, so provers and
+//! verifiers have to agree on them some other way. A [`ProofEnvelope`]
+//! carries them along with the proof: the hash of the circuit, the values of
+//! its public inputs in the order verification expects them in, what the
+//! transcript was started with, and the version of plang that produced it.
+//!
+//! Envelopes are written either as an artifact, or as JSON of the form:
+//!
+//! ```json
+//! {
+//!   "circuit_hash": "<hex>",
+//!   "transcript_label": "<hex>",
+//!   "compiler_version": "0.1.0",
+//!   "public_inputs": [{ "name": "c", "value": "2" }],
+//!   "proof": "<hex>"
+//! }
+//! ```
+//!
+//! with the values of the public inputs in decimal.
+//!
+//! The transcript an envelope records isn't taken on trust: whoever writes the
+//! envelope could pick it, and with it the domain the proof is separated into.
+//! Envelopes are verified with the options the verifier expects, and rejected
+//! if they record another transcript.
+
+use crate::artifact::{self, ArtifactKind};
+use crate::error::{Error as PlangError, ProveError, Result};
+use crate::keys::Reader;
+use crate::scalar::Scalar;
+use crate::transcript::ProveOptions;
+use crate::verifier::PlangVerifier;
+#[cfg(feature = "std")]
+use crate::PlangCircuit;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+#[cfg(feature = "std")]
+use serde_json::{json, Value};
+
+/// A proof, together with the circuit it proves and what it's verified with.
+#[derive(Debug)]
+pub struct ProofEnvelope {
+    /// The proof itself.
+    pub proof: Proof,
+    /// The values of the public inputs, in the order verification expects
+    /// them in.
+    pub public_inputs: Vec<(String, Scalar)>,
+    /// Hash of the circuit the proof was produced for.
+    pub circuit_hash: [u8; 32],
+    /// What the transcript was started with - the domain separation
+    /// prefixes, if any, followed by the transcript label.
+    pub label: Vec<u8>,
+    /// Version of plang the proof was produced with.
+    pub compiler_version: String,
+}
+
+impl ProofEnvelope {
+    /// Bundles a proof of the circuit, produced with the given options, with
+    /// the values of the public inputs currently set on the circuit.
+    #[cfg(feature = "prover")]
+    pub fn new(circuit: &PlangCircuit, proof: Proof, options: &ProveOptions) -> Self {
+        let public_inputs = circuit
+            .public_inputs_named()
+            .map(|(name, value)| (name.to_owned(), Scalar::from(value)))
+            .collect();
+
+        Self {
+            proof,
+            public_inputs,
+            circuit_hash: circuit.hash(),
+            label: options.transcript().to_vec(),
+            compiler_version: env!("CARGO_PKG_VERSION").to_owned(),
+        }
+    }
+
+    /// Checks the proof was produced with the transcript the options start,
+    /// the one it's expected to be verified with.
+    pub fn check_options(&self, options: &ProveOptions) -> Result<()> {
+        match self.label == options.transcript() {
+            true => Ok(()),
+            false => Err(PlangError::Prove(ProveError::TranscriptMismatch)),
+        }
+    }
+
+    /// Checks the proof was produced for the given circuit.
+    #[cfg(feature = "std")]
+    pub fn check(&self, circuit: &PlangCircuit) -> Result<()> {
+        match self.circuit_hash == circuit.hash() {
+            true => Ok(()),
+            false => Err(PlangError::Prove(ProveError::CircuitMismatch)),
+        }
+    }
+
+    /// Checks the proof was produced with the given options, and verifies it
+    /// against the public inputs in the envelope. The verifier doesn't know
+    /// the hash of its circuit, so which circuit the proof is for is left to
+    /// the caller to check.
+    pub fn verify(
+        &self,
+        verifier: &PlangVerifier,
+        pp: &PublicParameters,
+        options: &ProveOptions,
+    ) -> Result<()> {
+        self.check_options(options)?;
+
+        let vals = self
+            .public_inputs
+            .iter()
+            .map(|(name, value)| (name.clone(), BlsScalar::from(*value)));
+        verifier.verify(pp, &self.proof, vals, options)
+    }
+
+    /// Checks the proof was produced for the given circuit with the given
+    /// options, and verifies it against the public inputs in the envelope.
+    #[cfg(feature = "prover")]
+    pub fn verify_for(
+        &self,
+        circuit: &PlangCircuit,
+        pp: &PublicParameters,
+        vd: &VerifierData,
+        options: &ProveOptions,
+    ) -> Result<()> {
+        self.check(circuit)?;
+        self.check_options(options)?;
+
+        let vals = self
+            .public_inputs
+            .iter()
+            .map(|(name, value)| (name.clone(), BlsScalar::from(*value)));
+        let pinputs = circuit.public_inputs_from(vals)?;
+        PlangCircuit::verify_with(pp, vd, &self.proof, &pinputs, options)
+    }
+
+    /// Serializes the envelope, including its artifact header.
+    ///
+    /// After the header, the fixed size circuit hash is followed by the
+    /// compiler version, the transcript label and the proof - each prefixed
+    /// by its length as a little endian `u32` - and by each public input: its
+    /// name, prefixed by its length, and its value in 32 bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let proof = self.proof.to_bytes();
+
+        let mut bytes =
+            Vec::with_capacity(44 + self.label.len() + proof.len() + 48 * self.public_inputs.len());
+
+        bytes.extend(&self.circuit_hash);
+        for field in [self.compiler_version.as_bytes(), &self.label, &proof] {
+            bytes.extend(&(field.len() as u32).to_le_bytes());
+            bytes.extend(field);
+        }
+
+        for (name, value) in &self.public_inputs {
+            bytes.extend(&(name.len() as u32).to_le_bytes());
+            bytes.extend(name.as_bytes());
+            bytes.extend(&value.to_bytes());
+        }
+
+        artifact::wrap(ArtifactKind::Envelope, 0, &bytes)
+    }
+
+    /// Deserializes an envelope written by [`to_bytes`](Self::to_bytes).
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let (bytes, _) = artifact::unwrap(ArtifactKind::Envelope, bytes)?;
+        let mut reader = Reader(bytes);
+
+        let invalid = |_| PlangError::Prove(ProveError::InvalidEnvelope);
+
+        let circuit_hash = reader.take_array().map_err(invalid)?;
+        let compiler_version = String::from_utf8(reader.take_field().map_err(invalid)?.to_vec())?;
+        let label = reader.take_field().map_err(invalid)?.to_vec();
+        let proof = read_proof(reader.take_field().map_err(invalid)?)?;
+
+        let mut public_inputs = Vec::new();
+        while !reader.0.is_empty() {
+            let name = String::from_utf8(reader.take_field().map_err(invalid)?.to_vec())?;
+            let value = reader.take_array().map_err(invalid)?;
+            let value =
+                Scalar::from_bytes(&value).ok_or(PlangError::Prove(ProveError::InvalidEnvelope))?;
+            public_inputs.push((name, value));
+        }
+
+        Ok(Self {
+            proof,
+            public_inputs,
+            circuit_hash,
+            label,
+            compiler_version,
+        })
+    }
+
+    /// Writes the envelope as JSON.
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> Value {
+        let public_inputs: Vec<Value> = self
+            .public_inputs
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "value": value.to_string() }))
+            .collect();
+
+        json!({
+            "circuit_hash": hex::encode(self.circuit_hash),
+            "transcript_label": hex::encode(&self.label),
+            "compiler_version": self.compiler_version,
+            "public_inputs": public_inputs,
+            "proof": hex::encode(self.proof.to_bytes()),
+        })
+    }
+
+    /// Reads an envelope written by [`to_json`](Self::to_json).
+    #[cfg(feature = "std")]
+    pub fn from_json(json: &Value) -> Result<Self> {
+        fn string(value: &Value) -> Result<&str> {
+            value
+                .as_str()
+                .ok_or(PlangError::Prove(ProveError::InvalidEnvelope))
+        }
+        fn hex(value: &Value) -> Result<Vec<u8>> {
+            hex::decode(string(value)?).map_err(|_| PlangError::Prove(ProveError::InvalidEnvelope))
+        }
+
+        let circuit_hash = hex(&json["circuit_hash"])?
+            .try_into()
+            .map_err(|_| PlangError::Prove(ProveError::InvalidEnvelope))?;
+        let label = hex(&json["transcript_label"])?;
+        let compiler_version = string(&json["compiler_version"])?.to_owned();
+        let proof = read_proof(&hex(&json["proof"])?)?;
+
+        let public_inputs = json["public_inputs"]
+            .as_array()
+            .ok_or(PlangError::Prove(ProveError::InvalidEnvelope))?
+            .iter()
+            .map(|input| {
+                let name = string(&input["name"])?.to_owned();
+                let value = match Scalar::from_decimal(string(&input["value"])?) {
+                    Some((value, false)) => value,
+                    _ => return Err(PlangError::Prove(ProveError::InvalidEnvelope)),
+                };
+                Ok((name, value))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            proof,
+            public_inputs,
+            circuit_hash,
+            label,
+            compiler_version,
+        })
+    }
+}
+
+fn read_proof(bytes: &[u8]) -> Result<Proof> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| PlangError::Prove(ProveError::InvalidProof))?;
+    Proof::from_bytes(bytes).map_err(|_| PlangError::Prove(ProveError::InvalidProof))
+}
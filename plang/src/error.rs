@@ -4,32 +4,524 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use crate::artifact::ArtifactKind;
+#[cfg(feature = "std")]
 use crate::grammar::Rule;
+#[cfg(feature = "std")]
+use crate::warning::LineWarning;
 
+use alloc::string::{FromUtf8Error, String};
+use core::fmt;
+use core::num::ParseIntError;
+#[cfg(feature = "std")]
 use std::io;
-use std::num::ParseIntError;
-use std::string::FromUtf8Error;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
+#[cfg(feature = "verifier")]
 use dusk_plonk::error::Error as PlonkError;
+#[cfg(feature = "std")]
 use pest::error::Error as PestError;
 
-pub(crate) type Result<T> = std::result::Result<T, Error>;
+pub(crate) type Result<T> = core::result::Result<T, Error>;
 
+/// An error from any part of plang, by the part it comes from.
 #[derive(Debug)]
 pub enum Error {
+    #[cfg(feature = "std")]
     Io(io::Error),
+    /// An error reading or parsing the circuit in a file.
+    #[cfg(feature = "std")]
+    InFile {
+        path: PathBuf,
+        error: Box<Error>,
+    },
+    /// An error in a circuit imported by another, with the line of the
+    /// import.
+    #[cfg(feature = "std")]
+    Imported {
+        path: PathBuf,
+        line: usize,
+        error: Box<Error>,
+    },
+    Parse(ParseError),
+    Semantic(SemanticError),
+    Assignment(AssignmentError),
+    Prove(ProveError),
+    UnknownErrorCode(String),
+}
+
+/// An error reading the text of a circuit, or of a file imported into one.
+#[derive(Debug)]
+pub enum ParseError {
     Utf8(FromUtf8Error),
+    #[cfg(feature = "std")]
     Pest(PestError<Rule>),
     Int(ParseIntError),
-    Plonk(PlonkError),
+    InvalidR1cs(&'static str),
+    InvalidPackage(String),
+}
+
+/// An error in a circuit that parses - in its equations, directives or
+/// constants, or a warning about it denied.
+#[derive(Debug)]
+pub enum SemanticError {
+    TooManyTriTerms {
+        line: usize,
+        var: String,
+    },
+    SameTriVars {
+        line: usize,
+        var: String,
+    },
+    TooManyVars {
+        line: usize,
+        var: String,
+    },
+    RepeatedVars {
+        line: usize,
+        var: String,
+    },
+    PublicVarNotSingular {
+        line: usize,
+        var: String,
+    },
+    RepeatedTranscript,
+    InvalidSumRange(String),
+    UnboundIndex(String),
+    UndefinedConstant(String),
+    RepeatedConstant(String),
+    UnbalancedBlock,
+    InvalidConstExpr(String),
+    InvalidWidth(String),
+    UnresolvedImport(String),
+    RepeatedImport(String),
+    ImportCycle(String),
+    UndefinedNamespace(String),
+    UnknownEdition(String),
+    InvalidImport(String),
+    UnrepresentableCoefficient,
+    UnknownWarning(String),
+    #[cfg(feature = "std")]
+    DeniedWarning(LineWarning),
+}
+
+/// An error in the values assigned to a circuit, or given to verify a proof
+/// of it, naming the variable, equation or test vector at fault.
+#[derive(Debug)]
+pub enum AssignmentError {
     NoSuchValue(String),
-    TooManyTriTerms,
-    SameTriVars,
-    TooManyVars,
-    RepeatedVars,
-    PublicVarNotSingular,
+    InvalidValue(String),
+    NotPublicInput(String),
+    MissingPublicInput(String),
+    PublicInputCount { expected: usize, found: usize },
+    Unsatisfied { line: usize, equation: String },
+    UnexpectedlySatisfied(String),
+    AssertionFailed { line: usize, assertion: String },
+    FailedTests { failed: usize, total: usize },
+}
+
+/// An error compiling, proving or verifying a circuit, or in the keys,
+/// proofs and other artifacts doing so reads and writes.
+#[derive(Debug)]
+pub enum ProveError {
+    #[cfg(feature = "verifier")]
+    Plonk(PlonkError),
+    ParamsTooSmall {
+        required: usize,
+        available: usize,
+    },
+    TooManyGates(usize),
+    KeysMismatch,
+    InvalidKeyArchive,
+    InvalidVerifierArchive,
+    InvalidEnvelope,
+    InvalidManifest(String),
+    CircuitMismatch,
+    TranscriptMismatch,
+    ParamsMismatch,
+    NoProverKey,
+    NoSuchCircuit(String),
+    InvalidProof,
+    InvalidGate {
+        gate: usize,
+        line: usize,
+        reason: String,
+    },
+    TruncatedArtifact,
+    UnknownArtifact(u8),
+    WrongArtifact {
+        expected: ArtifactKind,
+        found: ArtifactKind,
+    },
+    UnsupportedArtifactVersion(u8),
+    ArtifactChecksum,
+}
+
+impl Error {
+    /// Returns the stable code identifying errors in a circuit, or in the
+    /// values assigned to it. A detailed explanation of each is available
+    /// through [`explain`](crate::explain::explain).
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::InFile { error, .. } => error.code(),
+            #[cfg(feature = "std")]
+            Self::Imported { error, .. } => error.code(),
+            Self::Parse(err) => err.code(),
+            Self::Semantic(err) => err.code(),
+            Self::Assignment(err) => err.code(),
+            _ => None,
+        }
+    }
+}
+
+impl ParseError {
+    /// Returns the stable code identifying the error, if it has one.
+    pub fn code(&self) -> Option<&'static str> {
+        Some(match self {
+            #[cfg(feature = "std")]
+            Self::Pest(_) => "P0001",
+            Self::Int(_) => "P0002",
+            _ => return None,
+        })
+    }
+}
+
+impl SemanticError {
+    /// The line of the equation the error is in, if it's in a single one.
+    #[cfg(feature = "std")]
+    pub(crate) fn line_mut(&mut self) -> Option<&mut usize> {
+        match self {
+            Self::TooManyTriTerms { line, .. }
+            | Self::SameTriVars { line, .. }
+            | Self::TooManyVars { line, .. }
+            | Self::RepeatedVars { line, .. }
+            | Self::PublicVarNotSingular { line, .. } => Some(line),
+            _ => None,
+        }
+    }
+
+    /// Returns the stable code identifying the error, if it has one.
+    pub fn code(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::TooManyTriTerms { .. } => "P0003",
+            Self::SameTriVars { .. } => "P0004",
+            Self::RepeatedVars { .. } => "P0005",
+            Self::PublicVarNotSingular { .. } => "P0006",
+            Self::TooManyVars { .. } => "P0007",
+            Self::RepeatedTranscript => "P0008",
+            Self::InvalidSumRange(_) => "P0012",
+            Self::UnboundIndex(_) => "P0013",
+            Self::UndefinedConstant(_) => "P0014",
+            Self::RepeatedConstant(_) => "P0015",
+            Self::UnbalancedBlock => "P0016",
+            Self::InvalidConstExpr(_) => "P0017",
+            Self::InvalidWidth(_) => "P0018",
+            Self::UnresolvedImport(_) => "P0019",
+            Self::RepeatedImport(_) => "P0020",
+            Self::ImportCycle(_) => "P0021",
+            Self::UndefinedNamespace(_) => "P0022",
+            Self::UnknownEdition(_) => "P0023",
+            Self::InvalidImport(_) => "P0024",
+            _ => return None,
+        })
+    }
+}
+
+impl AssignmentError {
+    /// Returns the stable code identifying the error, if it has one.
+    pub fn code(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::NoSuchValue(_) => "P0009",
+            Self::NotPublicInput(_) => "P0010",
+            Self::MissingPublicInput(_) => "P0011",
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(err) => write!(f, "{}", err),
+            #[cfg(feature = "std")]
+            Self::InFile { path, error } => write!(f, "{}: {}", path.display(), error),
+            #[cfg(feature = "std")]
+            Self::Imported { path, line, error } => write!(
+                f,
+                "{}: {}\nimported from line {}",
+                path.display(),
+                error,
+                line + 1
+            ),
+            Self::Parse(err) => write!(f, "{}", err),
+            Self::Semantic(err) => write!(f, "{}", err),
+            Self::Assignment(err) => write!(f, "{}", err),
+            Self::Prove(err) => write!(f, "{}", err),
+            Self::UnknownErrorCode(code) => write!(f, "unknown error code `{}`", code),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8(err) => write!(f, "circuit is not valid UTF-8: {}", err),
+            #[cfg(feature = "std")]
+            Self::Pest(err) => write!(f, "invalid syntax\n{}", err),
+            Self::Int(err) => write!(f, "invalid coefficient: {}", err),
+            Self::InvalidR1cs(reason) => write!(f, "invalid R1CS file: {}", reason),
+            Self::InvalidPackage(reason) => write!(f, "invalid package: {}", reason),
+        }
+    }
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyTriTerms { line, var } => write!(
+                f,
+                "line {}: the equation has a second product of two variables, with `{}`",
+                line + 1,
+                var
+            ),
+            Self::SameTriVars { line, var } => write!(
+                f,
+                "line {}: a product term multiplies `{}` with itself",
+                line + 1,
+                var
+            ),
+            Self::TooManyVars { line, var } => write!(
+                f,
+                "line {}: the equation uses too many distinct variables, `{}` being the fifth",
+                line + 1,
+                var
+            ),
+            Self::RepeatedVars { line, var } => write!(
+                f,
+                "line {}: `{}` appears in more than one linear term of the equation",
+                line + 1,
+                var
+            ),
+            Self::PublicVarNotSingular { line, var } => write!(
+                f,
+                "line {}: the public input `{}` also appears on the left side of the equation",
+                line + 1,
+                var
+            ),
+            Self::RepeatedTranscript => {
+                f.write_str("the circuit declares its transcript label more than once")
+            }
+            Self::InvalidSumRange(range) => {
+                write!(f, "the range `{}` of a sum is empty or too large", range)
+            }
+            Self::UnboundIndex(index) => {
+                write!(f, "the index `{}` is not the index of the sum", index)
+            }
+            Self::UndefinedConstant(name) => write!(f, "constant `{}` is not declared", name),
+            Self::RepeatedConstant(name) => {
+                write!(f, "constant `{}` is declared more than once", name)
+            }
+            Self::UnbalancedBlock => {
+                f.write_str("a block is closed without being opened, or never closed")
+            }
+            Self::InvalidConstExpr(expr) => write!(
+                f,
+                "`{}` shifts or raises to a power that isn't a 64 bit integer",
+                expr
+            ),
+            Self::InvalidWidth(width) => {
+                write!(
+                    f,
+                    "the width `{}` of a decomposition is out of range",
+                    width
+                )
+            }
+            Self::UnresolvedImport(name) => write!(f, "can't find the import \"{}\"", name),
+            Self::RepeatedImport(namespace) => {
+                write!(f, "namespace `{}` is imported more than once", namespace)
+            }
+            Self::ImportCycle(name) => write!(f, "\"{}\" imports itself", name),
+            Self::UndefinedNamespace(var) => {
+                write!(f, "the namespace of `{}` is not imported", var)
+            }
+            Self::UnknownEdition(edition) => write!(f, "unknown edition `{}`", edition),
+            Self::InvalidImport(name) => {
+                write!(f, "the import \"{}\" is outside of the search path", name)
+            }
+            Self::UnrepresentableCoefficient => {
+                f.write_str("a coefficient is too large to be written in a circuit")
+            }
+            Self::UnknownWarning(name) => write!(f, "unknown warning `{}`", name),
+            #[cfg(feature = "std")]
+            Self::DeniedWarning(warning) => write!(
+                f,
+                "line {}: {} (`{}` is denied)",
+                warning.line + 1,
+                warning.warning,
+                warning.warning.name()
+            ),
+        }
+    }
+}
+
+impl fmt::Display for AssignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchValue(name) => write!(f, "variable `{}` is not in the circuit", name),
+            Self::InvalidValue(value) => write!(f, "`{}` is not a valid scalar", value),
+            Self::NotPublicInput(name) => write!(f, "variable `{}` is not a public input", name),
+            Self::MissingPublicInput(name) => {
+                write!(f, "no value given for the public input `{}`", name)
+            }
+            Self::PublicInputCount { expected, found } => write!(
+                f,
+                "the verifier data expects {} public inputs, but {} were given",
+                expected, found
+            ),
+            Self::Unsatisfied { line, equation } => {
+                write!(f, "line {}: `{}` is not satisfied", line + 1, equation)
+            }
+            Self::UnexpectedlySatisfied(name) => write!(
+                f,
+                "test vector `{}` satisfies the circuit, but is expected not to",
+                name
+            ),
+            Self::AssertionFailed { line, assertion } => {
+                write!(f, "line {}: `{}` does not hold", line + 1, assertion)
+            }
+            Self::FailedTests { failed, total } => {
+                write!(f, "{} of {} test vectors failed", failed, total)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ProveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "verifier")]
+            Self::Plonk(err) => write!(f, "{}", err),
+            Self::ParamsTooSmall {
+                required,
+                available,
+            } => write!(
+                f,
+                "the circuit requires public parameters of degree {}, but they only have {}",
+                required, available
+            ),
+            Self::TooManyGates(gates) => write!(
+                f,
+                "a circuit of {} gates is too large for any public parameters",
+                gates
+            ),
+            Self::KeysMismatch => f.write_str("keys do not match the circuit"),
+            Self::InvalidKeyArchive => f.write_str("invalid key archive"),
+            Self::InvalidVerifierArchive => f.write_str("invalid verifier archive"),
+            Self::InvalidEnvelope => f.write_str("invalid proof envelope"),
+            Self::InvalidManifest(reason) => write!(f, "invalid build manifest: {}", reason),
+            Self::CircuitMismatch => f.write_str("keys were compiled from a different circuit"),
+            Self::TranscriptMismatch => {
+                f.write_str("the proof was produced with another transcript than expected")
+            }
+            Self::ParamsMismatch => {
+                f.write_str("keys were compiled with different public parameters")
+            }
+            Self::NoProverKey => f.write_str("the key archive does not contain a prover key"),
+            Self::NoSuchCircuit(name) => write!(f, "no circuit named `{}` in the session", name),
+            Self::InvalidProof => f.write_str("invalid proof"),
+            Self::InvalidGate { gate, line, reason } => write!(
+                f,
+                "equation {} on line {} can't be laid out on a gate: {}",
+                gate,
+                line + 1,
+                reason
+            ),
+            Self::TruncatedArtifact => f.write_str("artifact is truncated"),
+            Self::UnknownArtifact(kind) => write!(f, "unknown kind of artifact {}", kind),
+            Self::WrongArtifact { expected, found } => {
+                write!(f, "expected {}, but found {}", expected, found)
+            }
+            Self::UnsupportedArtifactVersion(version) => {
+                write!(f, "unsupported artifact format version {}", version)
+            }
+            Self::ArtifactChecksum => f.write_str("artifact checksum does not match its contents"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::InFile { error, .. } => Some(error.as_ref()),
+            Self::Imported { error, .. } => Some(error.as_ref()),
+            Self::Parse(err) => Some(err),
+            Self::Semantic(err) => Some(err),
+            Self::Assignment(err) => Some(err),
+            Self::Prove(err) => Some(err),
+            Self::UnknownErrorCode(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Utf8(err) => Some(err),
+            Self::Pest(err) => Some(err),
+            Self::Int(err) => Some(err),
+            Self::InvalidR1cs(_) | Self::InvalidPackage(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SemanticError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AssignmentError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "prover")]
+            Self::Plonk(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<SemanticError> for Error {
+    fn from(err: SemanticError) -> Self {
+        Self::Semantic(err)
+    }
+}
+
+impl From<AssignmentError> for Error {
+    fn from(err: AssignmentError) -> Self {
+        Self::Assignment(err)
+    }
+}
+
+impl From<ProveError> for Error {
+    fn from(err: ProveError) -> Self {
+        Self::Prove(err)
+    }
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(ioerr: io::Error) -> Self {
         Self::Io(ioerr)
@@ -38,24 +530,26 @@ impl From<io::Error> for Error {
 
 impl From<FromUtf8Error> for Error {
     fn from(uerr: FromUtf8Error) -> Self {
-        Self::Utf8(uerr)
+        Self::Parse(ParseError::Utf8(uerr))
     }
 }
 
+#[cfg(feature = "verifier")]
 impl From<PlonkError> for Error {
     fn from(perr: PlonkError) -> Self {
-        Self::Plonk(perr)
+        Self::Prove(ProveError::Plonk(perr))
     }
 }
 
+#[cfg(feature = "std")]
 impl From<PestError<Rule>> for Error {
     fn from(perr: PestError<Rule>) -> Self {
-        Self::Pest(perr)
+        Self::Parse(ParseError::Pest(perr))
     }
 }
 
 impl From<ParseIntError> for Error {
     fn from(ierr: ParseIntError) -> Self {
-        Self::Int(ierr)
+        Self::Parse(ParseError::Int(ierr))
     }
 }
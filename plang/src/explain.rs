@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Detailed explanations of the errors given a code.
+//!
+//! Errors in a circuit, or in the values assigned to it, are identified by a
+//! stable code such as `P0007` - see [`PlangError::code`]. Each code has an
+//! explanation of what the error means, why the restriction behind it exists,
+//! and an example of how to rewrite a circuit to avoid it.
+//!
+//! [`PlangError::code`]: crate::PlangError::code
+
+const EXPLANATIONS: &[(&str, &str)] = &[
+    ("P0001", include_str!("../explain/P0001.md")),
+    ("P0002", include_str!("../explain/P0002.md")),
+    ("P0003", include_str!("../explain/P0003.md")),
+    ("P0004", include_str!("../explain/P0004.md")),
+    ("P0005", include_str!("../explain/P0005.md")),
+    ("P0006", include_str!("../explain/P0006.md")),
+    ("P0007", include_str!("../explain/P0007.md")),
+    ("P0008", include_str!("../explain/P0008.md")),
+    ("P0009", include_str!("../explain/P0009.md")),
+    ("P0010", include_str!("../explain/P0010.md")),
+    ("P0011", include_str!("../explain/P0011.md")),
+    ("P0012", include_str!("../explain/P0012.md")),
+    ("P0013", include_str!("../explain/P0013.md")),
+    ("P0014", include_str!("../explain/P0014.md")),
+    ("P0015", include_str!("../explain/P0015.md")),
+    ("P0016", include_str!("../explain/P0016.md")),
+    ("P0017", include_str!("../explain/P0017.md")),
+    ("P0018", include_str!("../explain/P0018.md")),
+    ("P0019", include_str!("../explain/P0019.md")),
+    ("P0020", include_str!("../explain/P0020.md")),
+    ("P0021", include_str!("../explain/P0021.md")),
+    ("P0022", include_str!("../explain/P0022.md")),
+    ("P0023", include_str!("../explain/P0023.md")),
+    ("P0024", include_str!("../explain/P0024.md")),
+];
+
+/// Returns the explanation of an error code, if it exists. The code is
+/// matched ignoring case.
+pub fn explain(code: &str) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(code))
+        .map(|(_, explanation)| *explanation)
+}
+
+/// Returns all error codes, together with a one line summary of each.
+pub fn codes() -> impl Iterator<Item = (&'static str, &'static str)> {
+    EXPLANATIONS
+        .iter()
+        .map(|(code, explanation)| (*code, explanation.lines().next().unwrap_or_default()))
+}
@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Exporters of circuits and their keys to formats used outside of plang.
+
+pub mod acir;
+#[cfg(feature = "ark")]
+pub mod ark;
+pub mod circom;
+pub mod doc;
+pub mod halo2;
+pub mod recursion;
+pub mod vk;
+#[cfg(feature = "zkinterface")]
+pub mod zkif;
+
+/// The name of a variable with every character that can't be part of an
+/// identifier, such as the brackets of `b[3]`, replaced by `_`.
+fn identifier(var: &str) -> String {
+    var.chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
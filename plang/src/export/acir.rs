@@ -0,0 +1,193 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Export of circuits as ACIR, the intermediate representation executed by
+//! Noir-compatible backends.
+//!
+//! Each variable of the circuit is assigned a witness index, in the order it
+//! first appears in, and each equation becomes an `AssertZero` opcode whose
+//! expression is the left side minus the right side.
+//!
+//! The circuit is written in a JSON format of plang's own, mirroring the
+//! fields of ACIR's `Circuit`. It isn't the serialization Noir's tooling reads,
+//! which is binary and changes with every version of ACVM, so it has to be
+//! converted for a backend to execute it. The format is named and versioned
+//! in the object, in [`FORMAT`] and [`VERSION`]:
+//!
+//! - `format` and `version` - the name and version of the format.
+//! - `current_witness_index` - the highest witness index in use.
+//! - `opcodes` - array of `AssertZero` opcodes, each with `mul_terms` of the
+//!   form `[coefficient, witness, witness]`, `linear_combinations` of the form
+//!   `[coefficient, witness]`, and the constant `q_c`.
+//! - `private_parameters` and `public_parameters` - the indices assigned to
+//!   the witnesses and to the public inputs of the circuit.
+//! - `return_values` - always empty.
+//!
+//! Field elements are encoded as big endian hex. Plang circuits are over the
+//! BLS12-381 scalar field, so the backend executing them must be too.
+//!
+//! Values for the variables of the circuit are translated to a witness map by
+//! [`AcirExport::witness_map`].
+
+use crate::error::{AssignmentError, Error as PlangError, Result};
+use crate::scalar::IntoScalar;
+use crate::PlangCircuit;
+
+use std::collections::{BTreeMap, HashMap};
+
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+use serde_json::{json, Map, Value};
+
+/// The name of the JSON format circuits are exported in, in their `format`
+/// field.
+pub const FORMAT: &str = "plang-acir-json";
+
+/// The version of the JSON format, in the `version` field of exported
+/// circuits. It changes whenever the layout of the object does.
+pub const VERSION: u32 = 1;
+
+/// A circuit exported as ACIR.
+#[derive(Debug)]
+pub struct AcirExport {
+    /// The ACIR circuit, encoded as JSON.
+    pub circuit: Value,
+    /// The witness each variable of the circuit was assigned, in the order of
+    /// their indices.
+    pub witnesses: Vec<Witness>,
+}
+
+/// A variable of the circuit, and the witness it was assigned.
+#[derive(Debug)]
+pub struct Witness {
+    /// Name of the variable in the circuit.
+    pub name: String,
+    /// Index of the witness in ACIR.
+    pub index: u32,
+    /// Whether the witness is a public parameter.
+    pub public: bool,
+}
+
+impl AcirExport {
+    /// Translates values for the variables of the circuit, keyed by name, into
+    /// an ACIR witness map - an object mapping each witness index to its value.
+    /// Any value not given is 0. It returns an error if a value is not in the
+    /// circuit.
+    pub fn witness_map<B: IntoScalar, I: IntoIterator<Item = (String, B)>>(
+        &self,
+        vals: I,
+    ) -> Result<Value> {
+        let indices: HashMap<&str, u32> = self
+            .witnesses
+            .iter()
+            .map(|w| (w.name.as_str(), w.index))
+            .collect();
+
+        let mut map: BTreeMap<u32, BlsScalar> = self
+            .witnesses
+            .iter()
+            .map(|w| (w.index, BlsScalar::zero()))
+            .collect();
+        for (name, val) in vals {
+            let index = indices.get(name.as_str()).ok_or_else(|| {
+                PlangError::Assignment(AssignmentError::NoSuchValue(name.clone()))
+            })?;
+            map.insert(*index, val.into_scalar()?);
+        }
+
+        let map: Map<String, Value> = map
+            .into_iter()
+            .map(|(index, val)| (index.to_string(), field(&val)))
+            .collect();
+        Ok(map.into())
+    }
+}
+
+/// Exports the circuit as ACIR.
+pub fn to_acir(circuit: &PlangCircuit) -> AcirExport {
+    let mut witnesses: Vec<Witness> = vec![];
+    let mut indices = HashMap::new();
+
+    for expr in circuit.exprs() {
+        for var in expr.vars() {
+            if indices.contains_key(var) {
+                continue;
+            }
+            let index = witnesses.len() as u32;
+            indices.insert(var, index);
+            witnesses.push(Witness {
+                name: var.to_owned(),
+                index,
+                public: circuit.is_public(var),
+            });
+        }
+    }
+
+    let opcodes: Vec<Value> = circuit
+        .exprs()
+        .iter()
+        .map(|expr| {
+            let mul_terms: Vec<Value> = expr
+                .tri
+                .iter()
+                .map(|tri| {
+                    json!([
+                        field(&tri.coeff.into()),
+                        indices[tri.lvar.as_str()],
+                        indices[tri.rvar.as_str()],
+                    ])
+                })
+                .collect();
+
+            let linear_combinations: Vec<Value> = expr
+                .bis
+                .iter()
+                .map(|bi| (bi.coeff, bi.var.as_str()))
+                .chain(
+                    expr.public
+                        .iter()
+                        .map(|public| (public.coeff(), public.var.as_str())),
+                )
+                .map(|(coeff, var)| json!([field(&coeff.into()), indices[var]]))
+                .collect();
+
+            json!({
+                "AssertZero": {
+                    "mul_terms": mul_terms,
+                    "linear_combinations": linear_combinations,
+                    "q_c": field(&expr.constant.into()),
+                }
+            })
+        })
+        .collect();
+
+    let parameters = |public: bool| -> Vec<u32> {
+        witnesses
+            .iter()
+            .filter(|w| w.public == public)
+            .map(|w| w.index)
+            .collect()
+    };
+
+    let circuit = json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "current_witness_index": witnesses.len().saturating_sub(1),
+        "opcodes": opcodes,
+        "private_parameters": parameters(false),
+        "public_parameters": parameters(true),
+        "return_values": [],
+    });
+
+    AcirExport { circuit, witnesses }
+}
+
+/// Encodes a field element as big endian hex.
+fn field(scalar: &BlsScalar) -> Value {
+    let mut bytes = scalar.to_bytes();
+    bytes.reverse();
+    hex::encode(bytes).into()
+}
@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Bridge populating an arkworks constraint system from a circuit, so that
+//! arkworks based analysis and provers can be run over the same circuit.
+//!
+//! Each variable is allocated in the order it first appears in - public inputs
+//! as instance variables and witnesses as witness variables - with the value
+//! set in the circuit with [`set_vals`](PlangCircuit::set_vals). Each equation
+//! becomes a rank-1 constraint `A · B = C`, with equations without a product
+//! of variables multiplied by one.
+//!
+//! This module is only available with the `ark` feature.
+
+use crate::circuit::PlangExpr;
+use crate::scalar::Scalar;
+use crate::PlangCircuit;
+
+use std::collections::HashMap;
+
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use ark_relations::lc;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
+};
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+
+/// A circuit that can be synthesized into an arkworks constraint system.
+#[derive(Debug, Clone, Copy)]
+pub struct ArkCircuit<'a>(pub &'a PlangCircuit);
+
+impl ConstraintSynthesizer<Fr> for ArkCircuit<'_> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        populate(self.0, cs)
+    }
+}
+
+/// Allocates the variables of the circuit in the given constraint system, and
+/// enforces its equations.
+pub fn populate(circuit: &PlangCircuit, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+    let mut vars = HashMap::new();
+
+    for expr in circuit.exprs() {
+        for name in expr.vars() {
+            if vars.contains_key(name) {
+                continue;
+            }
+
+            let value = || Ok(to_ark(&circuit.value(name).unwrap_or_default()));
+            let var = match circuit.is_public(name) {
+                true => cs.new_input_variable(value)?,
+                false => cs.new_witness_variable(value)?,
+            };
+            vars.insert(name, var);
+        }
+    }
+
+    for expr in circuit.exprs() {
+        let (a, b, c) = constraint(expr, &vars);
+        cs.enforce_constraint(a, b, c)?;
+    }
+
+    Ok(())
+}
+
+/// Converts a scalar into the arkworks representation of the same element of
+/// the BLS12-381 scalar field.
+pub fn to_ark(scalar: &BlsScalar) -> Fr {
+    Fr::from_le_bytes_mod_order(&scalar.to_bytes())
+}
+
+fn constraint(
+    expr: &PlangExpr,
+    vars: &HashMap<&str, Variable>,
+) -> (
+    LinearCombination<Fr>,
+    LinearCombination<Fr>,
+    LinearCombination<Fr>,
+) {
+    let coeff = |coeff: Scalar| to_ark(&coeff.into());
+
+    // The other terms are moved to the right side, flipping their signs.
+    let c = LinearCombination(
+        expr.bis
+            .iter()
+            .map(|bi| (-coeff(bi.coeff), vars[bi.var.as_str()]))
+            .chain(
+                expr.public
+                    .iter()
+                    .map(|public| (-coeff(public.coeff()), vars[public.var.as_str()])),
+            )
+            .chain(
+                Some((-coeff(expr.constant), Variable::One))
+                    .filter(|_| expr.constant != Scalar::zero()),
+            )
+            .collect(),
+    );
+
+    match &expr.tri {
+        Some(tri) => {
+            let a = lc!() + (coeff(tri.coeff), vars[tri.lvar.as_str()]);
+            let b = lc!() + vars[tri.rvar.as_str()];
+            (a, b, c)
+        }
+        // Without a product, the equation is moved to the left side and
+        // multiplied by one.
+        None => (lc!() - c, lc!() + Variable::One, lc!()),
+    }
+}
@@ -0,0 +1,222 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Export of circuits as circom templates.
+//!
+//! Each variable becomes an input signal of the template, with the public
+//! inputs of the circuit made public in its `main` component, and each
+//! equation becomes a quadratic constraint. Variables whose names are reserved
+//! in circom, or aren't identifiers - like the elements of a sum - are
+//! renamed, which is reported in the [`Signal`]s of the export.
+//!
+//! Plang circuits are over the BLS12-381 scalar field, so the template must be
+//! compiled with `circom --prime bls12381`.
+
+use crate::circuit::{fmt_scalar, sign_magnitude};
+use crate::scalar::Scalar;
+use crate::PlangCircuit;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write};
+
+/// Words reserved in circom, which can't be used as signal names.
+const RESERVED: &[&str] = &[
+    "assert",
+    "bus",
+    "circom",
+    "component",
+    "custom",
+    "custom_templates",
+    "do",
+    "else",
+    "for",
+    "function",
+    "if",
+    "include",
+    "input",
+    "log",
+    "main",
+    "output",
+    "parallel",
+    "pragma",
+    "private",
+    "public",
+    "return",
+    "signal",
+    "template",
+    "var",
+    "while",
+];
+
+/// A circuit exported as a circom template.
+#[derive(Debug)]
+pub struct CircomExport {
+    /// The circom source, including the `main` component.
+    pub source: String,
+    /// The signal each variable of the circuit was mapped to, in the order
+    /// they're declared in.
+    pub signals: Vec<Signal>,
+}
+
+/// A variable of the circuit, and the signal it was mapped to.
+#[derive(Debug)]
+pub struct Signal {
+    /// Name of the variable in the circuit.
+    pub name: String,
+    /// Name of the signal in the template.
+    pub signal: String,
+    /// Whether the signal is a public input.
+    pub public: bool,
+}
+
+/// Exports the circuit as a circom template with the given name.
+pub fn to_circom(circuit: &PlangCircuit, template: &str) -> CircomExport {
+    let signals = map_signals(circuit);
+    let names: HashMap<&str, &str> = signals
+        .iter()
+        .map(|s| (s.name.as_str(), s.signal.as_str()))
+        .collect();
+
+    let mut source = String::new();
+    let _ = write_template(&mut source, circuit, template, &signals, &names);
+
+    CircomExport { source, signals }
+}
+
+/// Maps each variable to a signal, in the order they first appear in, with
+/// witnesses before public inputs.
+fn map_signals(circuit: &PlangCircuit) -> Vec<Signal> {
+    let mut seen = HashSet::new();
+    let mut taken = HashSet::new();
+    let mut signals = vec![];
+
+    for expr in circuit.exprs() {
+        for var in expr.vars() {
+            if !seen.insert(var) {
+                continue;
+            }
+
+            let mut signal = super::identifier(var);
+            while RESERVED.contains(&signal.as_str()) || taken.contains(&signal) {
+                signal.push('_');
+            }
+            taken.insert(signal.clone());
+
+            signals.push(Signal {
+                name: var.to_owned(),
+                signal,
+                public: circuit.is_public(var),
+            });
+        }
+    }
+
+    signals.sort_by_key(|s| s.public);
+    signals
+}
+
+fn write_template(
+    out: &mut String,
+    circuit: &PlangCircuit,
+    template: &str,
+    signals: &[Signal],
+    names: &HashMap<&str, &str>,
+) -> fmt::Result {
+    writeln!(
+        out,
+        "// Generated by plang {} from the circuit {}.",
+        env!("CARGO_PKG_VERSION"),
+        hex::encode(circuit.hash())
+    )?;
+    writeln!(out, "// Compile with `circom --prime bls12381`.")?;
+    writeln!(out, "pragma circom 2.0.0;")?;
+    writeln!(out)?;
+
+    writeln!(out, "template {}() {{", template)?;
+    for signal in signals {
+        writeln!(out, "    signal input {};", signal.signal)?;
+    }
+    writeln!(out)?;
+
+    for expr in circuit.exprs() {
+        write!(out, "    ")?;
+
+        let mut first = true;
+        if let Some(tri) = &expr.tri {
+            write_term(out, tri.coeff, first)?;
+            write!(
+                out,
+                "{} * {}",
+                names[tri.lvar.as_str()],
+                names[tri.rvar.as_str()]
+            )?;
+            first = false;
+        }
+        for bi in &expr.bis {
+            write_term(out, bi.coeff, first)?;
+            write!(out, "{}", names[bi.var.as_str()])?;
+            first = false;
+        }
+        if expr.constant != Scalar::zero() {
+            write_constant(out, expr.constant, first)?;
+        }
+
+        write!(out, " === ")?;
+        match &expr.public {
+            Some(public) => {
+                if public.negated {
+                    write!(out, "-")?;
+                }
+                write!(out, "{}", names[public.var.as_str()])?;
+            }
+            None => write!(out, "0")?,
+        }
+        writeln!(out, ";")?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    let public: Vec<&str> = signals
+        .iter()
+        .filter(|s| s.public)
+        .map(|s| s.signal.as_str())
+        .collect();
+    match public.is_empty() {
+        true => writeln!(out, "component main = {}();", template),
+        false => writeln!(
+            out,
+            "component main {{public [{}]}} = {}();",
+            public.join(", "),
+            template
+        ),
+    }
+}
+
+fn write_term(out: &mut String, coeff: Scalar, first: bool) -> fmt::Result {
+    let coeff = write_sign(out, coeff, first)?;
+
+    if coeff != Scalar::one() {
+        fmt_scalar(out, &coeff)?;
+        write!(out, " * ")?;
+    }
+    Ok(())
+}
+
+fn write_constant(out: &mut String, constant: Scalar, first: bool) -> fmt::Result {
+    let constant = write_sign(out, constant, first)?;
+    fmt_scalar(out, &constant)
+}
+
+/// Writes the sign of a coefficient, returning its magnitude.
+fn write_sign(out: &mut String, coeff: Scalar, first: bool) -> Result<Scalar, fmt::Error> {
+    let (minus, coeff) = sign_magnitude(coeff);
+    match (minus, first) {
+        (true, true) => write!(out, "-")?,
+        (false, true) => {}
+        (true, false) => write!(out, " - ")?,
+        (false, false) => write!(out, " + ")?,
+    }
+    Ok(coeff)
+}
@@ -0,0 +1,275 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Rendering of circuits as Markdown or LaTeX, for specification documents.
+//!
+//! Both render two tables. The first lists the equations, each described by
+//! the comments directly above it in the source, or at the end of its line.
+//! The second lists the gate each equation is compiled into, with the variable
+//! on each wire and the value of each selector, for gates of the form:
+//!
+//! ```text
+//! q_m·a·b + q_l·a + q_r·b + q_o·o + q_4·d + q_p·p + q_c = 0
+//! ```
+//!
+//! The transcript label declared in the circuit, if any, is mentioned in the
+//! caption of the equations.
+
+use crate::backend::{SELECTORS, WIRES};
+use crate::circuit::{fmt_scalar, sign_magnitude, PlangExpr};
+use crate::scalar::Scalar;
+use crate::PlangCircuit;
+
+use std::fmt::{self, Write};
+
+/// Renders the circuit as Markdown.
+pub fn to_markdown(circuit: &PlangCircuit) -> String {
+    let mut out = String::new();
+    let _ = write_markdown(&mut out, circuit);
+    out
+}
+
+/// Renders the circuit as a LaTeX fragment, to be included in a document.
+pub fn to_latex(circuit: &PlangCircuit) -> String {
+    let mut out = String::new();
+    let _ = write_latex(&mut out, circuit);
+    out
+}
+
+fn write_markdown(out: &mut String, circuit: &PlangCircuit) -> fmt::Result {
+    writeln!(out, "# Circuit `{}`", hex::encode(circuit.hash()))?;
+    writeln!(out)?;
+    if let Some(label) = circuit.transcript() {
+        writeln!(
+            out,
+            "Proofs are produced with the transcript label `{}`.",
+            String::from_utf8_lossy(label)
+        )?;
+        writeln!(out)?;
+    }
+
+    writeln!(out, "## Equations")?;
+    writeln!(out)?;
+    writeln!(out, "| gate | equation | description |")?;
+    writeln!(out, "|------|----------|-------------|")?;
+    for (i, expr) in circuit.exprs().iter().enumerate() {
+        let caption = expr.caption.as_deref().unwrap_or_default();
+        writeln!(
+            out,
+            "| {} | `{}` | {} |",
+            i,
+            expr,
+            caption.replace('|', "\\|")
+        )?;
+    }
+    writeln!(out)?;
+
+    writeln!(out, "## Gates")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Each equation is compiled into a gate of the form \
+         `q_m·a·b + q_l·a + q_r·b + q_o·o + q_4·d + q_p·p + q_c = 0`."
+    )?;
+    writeln!(out)?;
+
+    write!(out, "| gate |")?;
+    for name in WIRES.iter().chain(SELECTORS.iter()) {
+        write!(out, " {} |", name)?;
+    }
+    writeln!(out)?;
+    write!(out, "|------|")?;
+    for name in WIRES.iter().chain(SELECTORS.iter()) {
+        write!(out, "{}|", "-".repeat(name.len() + 2))?;
+    }
+    writeln!(out)?;
+
+    for (i, expr) in circuit.exprs().iter().enumerate() {
+        let gate = expr.gate();
+
+        write!(out, "| {} |", i)?;
+        for wire in &gate.wires {
+            match wire {
+                Some(var) => write!(out, " `{}` |", var)?,
+                None => write!(out, " |")?,
+            }
+        }
+        for selector in &gate.selectors {
+            write!(out, " ")?;
+            fmt_signed(out, selector)?;
+            write!(out, " |")?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+fn write_latex(out: &mut String, circuit: &PlangCircuit) -> fmt::Result {
+    let hash = hex::encode(circuit.hash());
+
+    writeln!(
+        out,
+        "% Generated by plang {} from the circuit {}.",
+        env!("CARGO_PKG_VERSION"),
+        hash
+    )?;
+
+    writeln!(out, "\\begin{{table}}[h]")?;
+    writeln!(out, "\\centering")?;
+    writeln!(out, "\\begin{{tabular}}{{rll}}")?;
+    writeln!(out, "gate & equation & description \\\\")?;
+    writeln!(out, "\\hline")?;
+    for (i, expr) in circuit.exprs().iter().enumerate() {
+        write!(out, "{} & $", i)?;
+        write_latex_expr(out, expr)?;
+        let caption = expr.caption.as_deref().unwrap_or_default();
+        writeln!(out, "$ & {} \\\\", escape_latex(caption))?;
+    }
+    writeln!(out, "\\end{{tabular}}")?;
+    write!(
+        out,
+        "\\caption{{Equations of the circuit \\texttt{{{}}}",
+        hash
+    )?;
+    if let Some(label) = circuit.transcript() {
+        write!(
+            out,
+            ", proven with the transcript label \\texttt{{{}}}",
+            escape_latex(&String::from_utf8_lossy(label))
+        )?;
+    }
+    writeln!(out, ".}}")?;
+    writeln!(out, "\\end{{table}}")?;
+    writeln!(out)?;
+
+    writeln!(out, "\\begin{{table}}[h]")?;
+    writeln!(out, "\\centering")?;
+    writeln!(out, "\\begin{{tabular}}{{r|ccccc|rrrrrrr}}")?;
+    write!(out, "gate")?;
+    for name in WIRES.iter().chain(SELECTORS.iter()) {
+        write!(out, " & ${}$", name)?;
+    }
+    writeln!(out, " \\\\")?;
+    writeln!(out, "\\hline")?;
+    for (i, expr) in circuit.exprs().iter().enumerate() {
+        let gate = expr.gate();
+
+        write!(out, "{}", i)?;
+        for wire in &gate.wires {
+            write!(out, " & ")?;
+            if let Some(var) = wire {
+                write!(out, "${}$", latex_var(var))?;
+            }
+        }
+        for selector in &gate.selectors {
+            write!(out, " & $")?;
+            fmt_signed(out, selector)?;
+            write!(out, "$")?;
+        }
+        writeln!(out, " \\\\")?;
+    }
+    writeln!(out, "\\end{{tabular}}")?;
+    writeln!(
+        out,
+        "\\caption{{Gates of the circuit \\texttt{{{}}}, of the form \
+         $q_m a b + q_l a + q_r b + q_o o + q_4 d + q_p p + q_c = 0$.}}",
+        hash
+    )?;
+    writeln!(out, "\\end{{table}}")
+}
+
+fn write_latex_expr(out: &mut String, expr: &PlangExpr) -> fmt::Result {
+    let mut first = true;
+
+    if let Some(tri) = &expr.tri {
+        write_latex_term(out, tri.coeff, first)?;
+        write!(
+            out,
+            "{} \\cdot {}",
+            latex_var(&tri.lvar),
+            latex_var(&tri.rvar)
+        )?;
+        first = false;
+    }
+    for bi in &expr.bis {
+        write_latex_term(out, bi.coeff, first)?;
+        write!(out, "{}", latex_var(&bi.var))?;
+        first = false;
+    }
+    if expr.constant != Scalar::zero() {
+        let (minus, constant) = sign_magnitude(expr.constant);
+        write!(out, "{}", if minus { " - " } else { " + " })?;
+        fmt_scalar(out, &constant)?;
+    }
+
+    write!(out, " = ")?;
+    match &expr.public {
+        Some(public) => {
+            if public.negated {
+                write!(out, "-")?;
+            }
+            write!(out, "{}", latex_var(&public.var))
+        }
+        None => write!(out, "0"),
+    }
+}
+
+fn write_latex_term(out: &mut String, coeff: Scalar, first: bool) -> fmt::Result {
+    let (minus, coeff) = sign_magnitude(coeff);
+    match (minus, first) {
+        (true, true) => write!(out, "-")?,
+        (false, true) => {}
+        (true, false) => write!(out, " - ")?,
+        (false, false) => write!(out, " + ")?,
+    }
+
+    if coeff != Scalar::one() {
+        fmt_scalar(out, &coeff)?;
+        write!(out, " \\cdot ")?;
+    }
+    Ok(())
+}
+
+/// Variables with names longer than a letter are set as a single word, so
+/// they don't read as a product of letters.
+fn latex_var(var: &str) -> String {
+    match var.len() {
+        1 => var.to_owned(),
+        _ => format!("\\mathit{{{}}}", var),
+    }
+}
+
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Formats a scalar as a negative number if its negation fits in a `u64`,
+/// and as [`fmt_scalar`] does otherwise.
+pub(crate) fn fmt_signed<W: Write>(f: &mut W, scalar: &Scalar) -> fmt::Result {
+    let negated = -*scalar;
+
+    match scalar.to_u64().is_none() && negated.to_u64().is_some() {
+        true => {
+            write!(f, "-")?;
+            fmt_scalar(f, &negated)
+        }
+        false => fmt_scalar(f, scalar),
+    }
+}
@@ -0,0 +1,294 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Export of circuits as Rust source implementing a halo2 `Circuit`.
+//!
+//! The generated circuit has a single standard PLONK custom gate over the
+//! advice columns `a`, `b`, `c`, `d` and `p`:
+//!
+//! ```text
+//! q_m·a·b + q_l·a + q_r·b + q_o·c + q_4·d + q_p·p + q_c = 0
+//! ```
+//!
+//! Each equation is laid out in a row of its own, with the selectors in fixed
+//! columns holding its coefficients. The variables of a product go in `a` and
+//! `b`, the other variables in the first free column, and the public input in
+//! `p` - which is constrained to be equal to the instance column, at the row
+//! of its position among the public inputs. Cells of the same variable are
+//! constrained to be equal.
+//!
+//! The circuit is a struct with a `Value` field for each variable. Variables
+//! named after Rust keywords, or whose names aren't identifiers, are renamed,
+//! which is reported in the [`Field`]s of the export.
+//!
+//! The coefficients of a plang circuit are elements of the scalar field of
+//! BLS12-381, so the circuit is over that field, as `bls12_381::Scalar`. The
+//! source depends on `halo2_proofs` 0.3 and `bls12_381` 0.8.
+
+use crate::backend::SELECTORS;
+use crate::scalar::Scalar;
+use crate::PlangCircuit;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write};
+
+/// Words reserved in Rust, which can't be used as field names.
+const RESERVED: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Index of the advice column of the public input.
+const PUBLIC_COLUMN: usize = 4;
+
+/// A circuit exported as halo2 Rust source.
+#[derive(Debug)]
+pub struct Halo2Export {
+    /// The Rust source of the circuit.
+    pub source: String,
+    /// The field each variable of the circuit was mapped to, in the order
+    /// they're declared in.
+    pub fields: Vec<Field>,
+}
+
+/// A variable of the circuit, and the struct field it was mapped to.
+#[derive(Debug)]
+pub struct Field {
+    /// Name of the variable in the circuit.
+    pub name: String,
+    /// Name of the field of the struct.
+    pub field: String,
+    /// Whether the variable is a public input.
+    pub public: bool,
+}
+
+/// Exports the circuit as a halo2 circuit with the given struct name.
+pub fn to_halo2(circuit: &PlangCircuit, name: &str) -> Halo2Export {
+    let fields = map_fields(circuit);
+    let names: HashMap<&str, &str> = fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.field.as_str()))
+        .collect();
+
+    let mut source = String::new();
+    let _ = write_circuit(&mut source, circuit, name, &fields, &names);
+
+    Halo2Export { source, fields }
+}
+
+/// Maps each variable to a field, in the order they first appear in.
+fn map_fields(circuit: &PlangCircuit) -> Vec<Field> {
+    let mut seen = HashSet::new();
+    let mut taken = HashSet::new();
+    let mut fields = vec![];
+
+    for expr in circuit.exprs() {
+        for var in expr.vars() {
+            if !seen.insert(var) {
+                continue;
+            }
+
+            let mut field = super::identifier(var);
+            while RESERVED.contains(&field.as_str()) || taken.contains(&field) {
+                field.push('_');
+            }
+            taken.insert(field.clone());
+
+            fields.push(Field {
+                name: var.to_owned(),
+                field,
+                public: circuit.is_public(var),
+            });
+        }
+    }
+
+    fields
+}
+
+fn write_circuit(
+    out: &mut String,
+    circuit: &PlangCircuit,
+    name: &str,
+    fields: &[Field],
+    names: &HashMap<&str, &str>,
+) -> fmt::Result {
+    writeln!(
+        out,
+        "// Generated by plang {} from the circuit {}.",
+        env!("CARGO_PKG_VERSION"),
+        hex::encode(circuit.hash())
+    )?;
+    writeln!(out, "// Depends on `halo2_proofs` 0.3 and `bls12_381` 0.8.")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "use bls12_381::Scalar as Fr;
+use halo2_proofs::circuit::{{Layouter, SimpleFloorPlanner, Value}};
+use halo2_proofs::plonk::{{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance}};
+use halo2_proofs::poly::Rotation;
+
+/// Columns of the circuit - advice columns `a`, `b`, `c`, `d` and `p`, and
+/// fixed columns with the selectors `q_m`, `q_l`, `q_r`, `q_o`, `q_4`, `q_p`
+/// and `q_c`.
+#[derive(Clone, Debug)]
+pub struct {name}Config {{
+    advice: [Column<Advice>; 5],
+    fixed: [Column<Fixed>; 7],
+    instance: Column<Instance>,
+}}
+",
+        name = name
+    )?;
+
+    writeln!(out, "#[derive(Clone, Debug, Default)]")?;
+    writeln!(out, "#[allow(non_snake_case)]")?;
+    writeln!(out, "pub struct {} {{", name)?;
+    for field in fields {
+        if field.public {
+            writeln!(out, "    /// Public input.")?;
+        }
+        writeln!(out, "    pub {}: Value<Fr>,", field.field)?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    writeln!(
+        out,
+        "impl Circuit<Fr> for {name} {{
+    type Config = {name}Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {{
+        Self::default()
+    }}
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {{
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let fixed = [(); 7].map(|_| meta.fixed_column());
+        let instance = meta.instance_column();
+
+        for column in advice {{
+            meta.enable_equality(column);
+        }}
+        meta.enable_equality(instance);
+
+        meta.create_gate(\"plonk\", |meta| {{
+            let [a, b, c, d, p] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let [q_m, q_l, q_r, q_o, q_4, q_p, q_c] =
+                fixed.map(|column| meta.query_fixed(column, Rotation::cur()));
+
+            vec![q_m * a.clone() * b.clone() + q_l * a + q_r * b + q_o * c + q_4 * d + q_p * p + q_c]
+        }});
+
+        {name}Config {{
+            advice,
+            fixed,
+            instance,
+        }}
+    }}
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {{
+        let publics = layouter.assign_region(
+            || \"plang\",
+            |mut region| {{
+                let mut publics = vec![];",
+        name = name
+    )?;
+
+    let mut first_cells: HashMap<&str, String> = HashMap::new();
+    for (row, expr) in circuit.exprs().iter().enumerate() {
+        let gate = expr.gate();
+
+        writeln!(out)?;
+        writeln!(out, "                // {}", expr)?;
+
+        for (column, var) in gate.wires.iter().enumerate() {
+            let cell = format!("w{}_{}", row, column);
+            match var {
+                Some(var) => {
+                    writeln!(
+                        out,
+                        "                let {} = region.assign_advice(|| \"{}\", config.advice[{}], {}, || self.{})?;",
+                        cell, var, column, row, names[var]
+                    )?;
+                    match first_cells.get(var) {
+                        Some(first) => writeln!(
+                            out,
+                            "                region.constrain_equal({}.cell(), {}.cell())?;",
+                            first, cell
+                        )?,
+                        None => {
+                            first_cells.insert(var, cell.clone());
+                        }
+                    }
+                    if column == PUBLIC_COLUMN {
+                        writeln!(out, "                publics.push({}.cell());", cell)?;
+                    }
+                }
+                None => writeln!(
+                    out,
+                    "                region.assign_advice(|| \"unused\", config.advice[{}], {}, || Value::known(Fr::zero()))?;",
+                    column, row
+                )?,
+            }
+        }
+
+        for (column, coeff) in gate.selectors.iter().enumerate() {
+            write!(
+                out,
+                "                region.assign_fixed(|| \"{}\", config.fixed[{}], {}, || Value::known(",
+                SELECTORS[column], column, row
+            )?;
+            write_coeff(out, *coeff)?;
+            writeln!(out, "))?;")?;
+        }
+    }
+
+    writeln!(
+        out,
+        "
+                Ok(publics)
+            }},
+        )?;
+
+        for (row, cell) in publics.into_iter().enumerate() {{
+            layouter.constrain_instance(cell, config.instance, row)?;
+        }}
+
+        Ok(())
+    }}
+}}"
+    )
+}
+
+/// Writes a coefficient as an element of the field, negated if it's taken as
+/// a negative number.
+fn write_coeff(out: &mut String, coeff: Scalar) -> fmt::Result {
+    let (minus, coeff) = match coeff.is_negative() {
+        true => ("-", -coeff),
+        false => ("", coeff),
+    };
+
+    match coeff.to_u64() {
+        Some(0) => write!(out, "Fr::zero()"),
+        Some(1) => write!(out, "{}Fr::one()", minus),
+        Some(n) => write!(out, "{}Fr::from({})", minus, n),
+        None => {
+            let bytes = coeff.to_bytes();
+            write!(out, "{}Fr::from_raw([", minus)?;
+            for (i, limb) in bytes.chunks_exact(8).enumerate() {
+                let mut le = [0; 8];
+                le.copy_from_slice(limb);
+                let sep = if i == 0 { "" } else { ", " };
+                write!(out, "{}{:#018x}", sep, u64::from_le_bytes(le))?;
+            }
+            write!(out, "])")
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Export of verifier data as scalars, for circuits verifying other proofs.
+//!
+//! A circuit verifying a proof recursively takes the verifier data of the
+//! inner circuit as public inputs or witnesses. These are elements of the
+//! scalar field, while the commitments of the verifier key are compressed
+//! points over the larger base field. Each commitment is split into three
+//! little endian limbs of 128 bits, which always fit in a scalar.
+//!
+//! The verifier data is decomposed into [`VkElement`]s, named as follows:
+//!
+//! - `vk_domain_size` - number of padded gates of the inner circuit.
+//! - `vk_<commitment>_<limb>` - limb `0`, `1` or `2` of the commitment to each
+//!   selector and permutation polynomial, in the order of the [JSON
+//!   export](super::vk).
+//! - `vk_pi_pos_<n>` - the position of the gate of the `n`th public input.
+//!
+//! [`to_plang`] writes equations binding each of them to a public input of
+//! the outer circuit, so the equations verifying the proof can use them.
+
+use crate::scalar::Scalar;
+
+use super::vk::{COMMITMENTS, COMMITMENT_SIZE, GATES_SIZE};
+
+use std::fmt::Write;
+
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+
+/// Size in bytes of each limb a commitment is split into.
+const LIMB_SIZE: usize = 16;
+
+/// Suffix of the public input each element is bound to by [`to_plang`].
+const PUBLIC_SUFFIX: &str = "_pi";
+
+/// A scalar the verifier data is decomposed into.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VkElement {
+    /// The name of the variable holding the scalar in the outer circuit.
+    pub name: String,
+    /// What the scalar is, for the comment above its declaration.
+    pub description: String,
+    /// The scalar itself.
+    pub value: BlsScalar,
+}
+
+/// Decomposes verifier data into scalars, in the order they're described in
+/// the [module documentation](self).
+pub fn decompose(vd: &VerifierData) -> Vec<VkElement> {
+    let key = vd.key().to_bytes();
+
+    let mut elements = vec![VkElement {
+        name: "vk_domain_size".into(),
+        description: "number of padded gates".into(),
+        value: BlsScalar::from(vd.key().padded_gates() as u64),
+    }];
+
+    for (name, commitment) in COMMITMENTS
+        .iter()
+        .zip(key[GATES_SIZE..].chunks(COMMITMENT_SIZE))
+    {
+        for (i, limb) in commitment.chunks(LIMB_SIZE).enumerate() {
+            let mut bytes = [0; 32];
+            bytes[..LIMB_SIZE].copy_from_slice(limb);
+
+            elements.push(VkElement {
+                name: format!("vk_{}_{}", name, i),
+                description: format!("limb {} of the {} commitment", i, name),
+                value: Scalar::from_bytes(&bytes)
+                    .expect("128 bit limbs are less than the modulus")
+                    .into(),
+            });
+        }
+    }
+
+    for (n, pos) in vd.pi_pos().iter().enumerate() {
+        elements.push(VkElement {
+            name: format!("vk_pi_pos_{}", n),
+            description: format!("gate of public input {}", n),
+            value: BlsScalar::from(*pos as u64),
+        });
+    }
+
+    elements
+}
+
+/// Writes an equation for each element, binding the witness named after it
+/// to a public input with the same name followed by `_pi`. Each is preceded
+/// by a comment describing the element.
+pub fn to_plang(elements: &[VkElement]) -> String {
+    let mut text = String::from("# Verifier data of the inner circuit.\n");
+
+    for element in elements {
+        let _ = writeln!(text, "# {}", element.description);
+        let _ = writeln!(text, "{} = {}{}", element.name, element.name, PUBLIC_SUFFIX);
+    }
+
+    text
+}
+
+/// The values to assign to the outer circuit for the equations written by
+/// [`to_plang`] - each witness, followed by the public input it's bound to.
+pub fn values(elements: &[VkElement]) -> Vec<(String, BlsScalar)> {
+    elements
+        .iter()
+        .flat_map(|element| {
+            let public = format!("{}{}", element.name, PUBLIC_SUFFIX);
+            [
+                (element.name.clone(), element.value),
+                (public, element.value),
+            ]
+        })
+        .collect()
+}
@@ -0,0 +1,105 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Export of verifier data as JSON, for verifiers not written in Rust.
+//!
+//! The exported object has the following fields:
+//!
+//! - `format` - always `"plang-vk-v1"`, changed whenever the structure does.
+//! - `compiler_version` - version of plang the export was made with.
+//! - `circuit_hash` - hex encoded [hash](crate::PlangCircuit::hash) of the
+//!   circuit.
+//! - `circuit_id` - hex encoded [ID](crate::PlangCircuit::id) of the circuit,
+//!   which is its hash unless it was given another.
+//! - `name` and `version` - the [metadata](crate::artifact::Metadata) of the
+//!   circuit, or `null` if it has none.
+//! - `transcript_label` - hex encoded transcript label to verify with.
+//! - `domain_size` - number of padded gates of the circuit, which is the size
+//!   of the evaluation domain.
+//! - `commitments` - object mapping the name of each selector and permutation
+//!   polynomial to its commitment, a hex encoded compressed BLS12-381 G1 point.
+//! - `public_inputs` - array with an object for each public input, in the
+//!   order their values are expected for verification. Each has the `name` of
+//!   the variable, the `position` of the gate it's in, and whether it's
+//!   `negated`. The public input polynomial evaluates to minus the value of a
+//!   public input at its position, unless it's negated - meaning it has a
+//!   minus sign in front of it in the circuit.
+//! - `public_input_order` - the names of the public inputs alone, in the
+//!   [same order](crate::PlangCircuit::public_input_order), for verifiers
+//!   that only need to arrange the values.
+
+use crate::PlangCircuit;
+
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+use serde_json::{json, Map, Value};
+
+/// Identifies the structure of the exported object.
+pub const FORMAT: &str = "plang-vk-v1";
+
+/// Size of the number of gates the serialized verifier key starts with.
+pub(crate) const GATES_SIZE: usize = 8;
+/// Size of a compressed BLS12-381 G1 point.
+pub(crate) const COMMITMENT_SIZE: usize = 48;
+
+/// Names of the commitments in the verifier key, in the order they're
+/// serialized in after the size of the circuit.
+pub(crate) const COMMITMENTS: [&str; 15] = [
+    "q_m",
+    "q_l",
+    "q_r",
+    "q_o",
+    "q_4",
+    "q_c",
+    "q_arith",
+    "q_logic",
+    "q_range",
+    "q_fixed_group_add",
+    "q_variable_group_add",
+    "left_sigma",
+    "right_sigma",
+    "out_sigma",
+    "fourth_sigma",
+];
+
+/// Exports the verifier data of the given circuit as JSON.
+pub fn to_json(circuit: &PlangCircuit, vd: &VerifierData, label: &[u8]) -> Value {
+    let key = vd.key().to_bytes();
+
+    let mut commitments = Map::new();
+    for (name, commitment) in COMMITMENTS
+        .iter()
+        .zip(key[GATES_SIZE..].chunks(COMMITMENT_SIZE))
+    {
+        commitments.insert((*name).into(), hex::encode(commitment).into());
+    }
+
+    let public_inputs: Vec<Value> = circuit
+        .signed_public_inputs()
+        .zip(vd.pi_pos())
+        .map(|((name, negated), position)| {
+            json!({
+                "name": name,
+                "position": position,
+                "negated": negated,
+            })
+        })
+        .collect();
+
+    json!({
+        "format": FORMAT,
+        "compiler_version": env!("CARGO_PKG_VERSION"),
+        "circuit_hash": hex::encode(circuit.hash()),
+        "circuit_id": hex::encode(circuit.id()),
+        "name": circuit.metadata().name,
+        "version": circuit.metadata().version,
+        "transcript_label": hex::encode(label),
+        "domain_size": vd.key().padded_gates(),
+        "commitments": commitments,
+        "public_inputs": public_inputs,
+        "public_input_order": circuit.public_input_order(),
+    })
+}
@@ -0,0 +1,265 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Export of circuits as zkinterface messages, for interoperability with tools
+//! built around the zkinterface standard.
+//!
+//! A circuit is exported as three size prefixed flatbuffers messages, which
+//! can be written one after the other into a single `.zkif` file:
+//!
+//! - A `CircuitHeader`, declaring the public inputs as instance variables,
+//!   together with their values.
+//! - A `ConstraintSystem`, with each equation as a bilinear constraint
+//!   `A · B = C`. Equations without a product of variables are multiplied by
+//!   the constant one variable.
+//! - A `Witness`, assigning values to the witnesses.
+//!
+//! Variable `0` is the constant one, followed by the public inputs and then
+//! the witnesses, in the order they first appear in. Values are taken from the
+//! circuit, as set with [`set_vals`](PlangCircuit::set_vals).
+//!
+//! This module is only available with the `zkinterface` feature.
+
+use crate::scalar::Scalar;
+use crate::PlangCircuit;
+
+use std::collections::{HashMap, HashSet};
+
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+use flatbuffers::{FlatBufferBuilder, WIPOffset};
+
+/// File identifier of zkinterface messages.
+const IDENTIFIER: &str = "zkif";
+
+/// Largest element of the BLS12-381 scalar field, as little endian bytes.
+const FIELD_MAXIMUM: [u8; 32] = [
+    0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0x02, 0xa4, 0xbd, 0x53,
+    0x05, 0xd8, 0xa1, 0x09, 0x08, 0xd8, 0x39, 0x33, 0x48, 0x7d, 0x9d, 0x29, 0x53, 0xa7, 0xed, 0x73,
+];
+
+// Types of the messages in the `Message` union.
+const CIRCUIT_HEADER: u8 = 1;
+const CONSTRAINT_SYSTEM: u8 = 2;
+const WITNESS: u8 = 3;
+
+/// A circuit exported as zkinterface messages.
+#[derive(Debug)]
+pub struct ZkifExport {
+    /// The `CircuitHeader` message.
+    pub header: Vec<u8>,
+    /// The `ConstraintSystem` message.
+    pub constraint_system: Vec<u8>,
+    /// The `Witness` message.
+    pub witness: Vec<u8>,
+    /// The variable each variable of the circuit was mapped to, in the order
+    /// of their ids.
+    pub variables: Vec<Variable>,
+}
+
+/// A variable of the circuit, and the zkinterface variable it was mapped to.
+#[derive(Debug)]
+pub struct Variable {
+    /// Name of the variable in the circuit.
+    pub name: String,
+    /// Id of the zkinterface variable.
+    pub id: u64,
+    /// Whether the variable is an instance variable.
+    pub public: bool,
+}
+
+impl ZkifExport {
+    /// Returns the three messages one after the other, as they're written to a
+    /// `.zkif` file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            self.header.len() + self.constraint_system.len() + self.witness.len(),
+        );
+
+        bytes.extend(&self.header);
+        bytes.extend(&self.constraint_system);
+        bytes.extend(&self.witness);
+
+        bytes
+    }
+}
+
+/// Linear combination of variables, as ids with their coefficients.
+type Combination = Vec<(u64, BlsScalar)>;
+
+/// Exports the circuit as zkinterface messages.
+pub fn to_zkif(circuit: &PlangCircuit) -> ZkifExport {
+    let variables = map_variables(circuit);
+    let ids: HashMap<&str, u64> = variables.iter().map(|v| (v.name.as_str(), v.id)).collect();
+
+    let (public, witness): (Vec<_>, Vec<_>) = variables.iter().partition(|v| v.public);
+    let assignment = |vars: Vec<&Variable>| -> Combination {
+        vars.into_iter()
+            .map(|v| (v.id, circuit.value(&v.name).unwrap_or_default()))
+            .collect()
+    };
+
+    let free_variable_id = variables.len() as u64 + 1;
+    let header = write_header(&assignment(public), free_variable_id);
+    let witness = write_witness(&assignment(witness));
+
+    let constraints: Vec<[Combination; 3]> = circuit
+        .exprs()
+        .iter()
+        .map(|expr| {
+            // The other terms are moved to the right side, flipping their
+            // signs.
+            let mut c: Combination = expr
+                .bis
+                .iter()
+                .map(|bi| (ids[bi.var.as_str()], -BlsScalar::from(bi.coeff)))
+                .collect();
+            if let Some(public) = &expr.public {
+                c.push((ids[public.var.as_str()], -BlsScalar::from(public.coeff())));
+            }
+            // The variable 0 is the constant one.
+            if expr.constant != Scalar::zero() {
+                c.push((0, -BlsScalar::from(expr.constant)));
+            }
+
+            match &expr.tri {
+                Some(tri) => {
+                    let a = vec![(ids[tri.lvar.as_str()], BlsScalar::from(tri.coeff))];
+                    let b = vec![(ids[tri.rvar.as_str()], BlsScalar::one())];
+                    [a, b, c]
+                }
+                None => {
+                    // Without a product, the equation is moved to the left
+                    // side and multiplied by one.
+                    let a = c.into_iter().map(|(id, coeff)| (id, -coeff)).collect();
+                    let b = vec![(0, BlsScalar::one())];
+                    [a, b, vec![]]
+                }
+            }
+        })
+        .collect();
+    let constraint_system = write_constraint_system(&constraints);
+
+    ZkifExport {
+        header,
+        constraint_system,
+        witness,
+        variables,
+    }
+}
+
+/// Maps each variable to an id, in the order they first appear in, with
+/// public inputs before witnesses.
+fn map_variables(circuit: &PlangCircuit) -> Vec<Variable> {
+    let mut seen = HashSet::new();
+    let mut vars = vec![];
+    for expr in circuit.exprs() {
+        for var in expr.vars() {
+            if seen.insert(var) {
+                vars.push(var);
+            }
+        }
+    }
+    vars.sort_by_key(|var| !circuit.is_public(var));
+
+    vars.into_iter()
+        .enumerate()
+        .map(|(i, var)| Variable {
+            name: var.to_owned(),
+            id: i as u64 + 1,
+            public: circuit.is_public(var),
+        })
+        .collect()
+}
+
+fn write_header(instance: &[(u64, BlsScalar)], free_variable_id: u64) -> Vec<u8> {
+    let mut builder = FlatBufferBuilder::new();
+
+    let instance_variables = variables(&mut builder, instance);
+    let field_maximum = builder.create_vector(&FIELD_MAXIMUM);
+
+    let start = builder.start_table();
+    builder.push_slot_always(4, instance_variables);
+    builder.push_slot(6, free_variable_id, 0);
+    builder.push_slot_always(8, field_maximum);
+    let header = builder.end_table(start);
+
+    finish(builder, CIRCUIT_HEADER, header)
+}
+
+fn write_constraint_system(constraints: &[[Combination; 3]]) -> Vec<u8> {
+    let mut builder = FlatBufferBuilder::new();
+
+    let constraints: Vec<_> = constraints
+        .iter()
+        .map(|[a, b, c]| {
+            let a = variables(&mut builder, a);
+            let b = variables(&mut builder, b);
+            let c = variables(&mut builder, c);
+
+            let start = builder.start_table();
+            builder.push_slot_always(4, a);
+            builder.push_slot_always(6, b);
+            builder.push_slot_always(8, c);
+            builder.end_table(start)
+        })
+        .collect();
+    let constraints = builder.create_vector(&constraints);
+
+    let start = builder.start_table();
+    builder.push_slot_always(4, constraints);
+    let constraint_system = builder.end_table(start);
+
+    finish(builder, CONSTRAINT_SYSTEM, constraint_system)
+}
+
+fn write_witness(assigned: &[(u64, BlsScalar)]) -> Vec<u8> {
+    let mut builder = FlatBufferBuilder::new();
+
+    let assigned_variables = variables(&mut builder, assigned);
+
+    let start = builder.start_table();
+    builder.push_slot_always(4, assigned_variables);
+    let witness = builder.end_table(start);
+
+    finish(builder, WITNESS, witness)
+}
+
+/// Writes a `Variables` table with the given ids and values, the values
+/// concatenated as little endian bytes.
+fn variables<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    vars: &[(u64, BlsScalar)],
+) -> WIPOffset<flatbuffers::TableFinishedWIPOffset> {
+    let ids: Vec<u64> = vars.iter().map(|(id, _)| *id).collect();
+    let values: Vec<u8> = vars
+        .iter()
+        .flat_map(|(_, value)| value.to_bytes())
+        .collect();
+
+    let ids = builder.create_vector(&ids);
+    let values = builder.create_vector(&values);
+
+    let start = builder.start_table();
+    builder.push_slot_always(4, ids);
+    builder.push_slot_always(6, values);
+    builder.end_table(start)
+}
+
+/// Wraps the message in the `Root` table, and finishes it with a size prefix.
+fn finish(
+    mut builder: FlatBufferBuilder,
+    message_type: u8,
+    message: WIPOffset<flatbuffers::TableFinishedWIPOffset>,
+) -> Vec<u8> {
+    let start = builder.start_table();
+    builder.push_slot_always(6, message);
+    builder.push_slot(4, message_type, 0);
+    let root = builder.end_table(start);
+
+    builder.finish_size_prefixed(root, Some(IDENTIFIER));
+    builder.finished_data().to_vec()
+}
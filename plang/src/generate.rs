@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Generation of random, valid circuits, for benchmarks.
+//!
+//! Circuits generated together with values satisfying them, with more control
+//! over what they look like, are in [`testgen`](crate::testgen).
+
+use crate::testgen::{self, Config};
+
+use rand_chacha::rand_core::RngCore;
+
+/// Generates the text of a random circuit with the given number of equations.
+///
+/// The circuit passes every check, so it can be compiled and proven. Its
+/// witnesses are drawn from a pool that grows with the number of equations, so
+/// equations share variables the way those of real circuits do, and about half
+/// the equations are equal to a public input of their own. The same random
+/// number generator, seeded the same, generates the same circuit.
+pub fn circuit<R: RngCore>(rng: &mut R, equations: usize) -> String {
+    let config = Config {
+        witnesses: (equations / 2).max(4),
+        ..Config::new(equations)
+    };
+    testgen::generate(rng, &config).text
+}
@@ -4,26 +4,364 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use crate::error::Result;
+//! The grammar of plang, and the syntax trees parsed with it.
 
-use pest::iterators::Pairs;
-use pest::Parser;
+use crate::error::{Error as PlangError, Result, SemanticError};
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use pest::error::{Error as PestError, ErrorVariant};
+use pest::iterators::Pair;
+use pest::{Parser, Span};
 use pest_derive::Parser;
 
-/// This is the output of the pest parser for plang.
+/// The pest parser for plang.
 #[derive(Debug, Parser)]
 #[grammar = "../plang.pest"]
-pub struct PlangGrammar<'a> {
-    pairs: Pairs<'a, Rule>,
+pub struct PlangParser;
+
+/// An edition of the language, selecting the rules circuits are parsed by.
+///
+/// Editions let the language reserve new keywords, or change its syntax,
+/// without changing the meaning of the circuits written before. A circuit
+/// declares its edition with `edition 2024`, before any other statement, and
+/// is otherwise parsed by the rules of the edition it's parsed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Edition {
+    /// The first edition, where variables can be named after keywords. Text
+    /// is parsed by it unless another is given, since it was written before
+    /// there were editions.
+    E2021,
+    /// Reserves the keywords, so that variables, constants, arrays and
+    /// namespaces can't be named after them, and adds the `gt` and `gte`
+    /// comparisons.
+    E2024,
 }
 
-impl<'a> PlangGrammar<'a> {
-    pub fn new(text: &'a str) -> Result<Self> {
-        let pairs = Self::parse(Rule::main, text)?;
-        Ok(Self { pairs })
+impl Edition {
+    /// The latest edition.
+    pub const LATEST: Self = Self::E2024;
+
+    /// The year naming the edition.
+    pub fn year(self) -> u16 {
+        match self {
+            Self::E2021 => 2021,
+            Self::E2024 => 2024,
+        }
+    }
+
+    /// Returns whether variables, constants, arrays and namespaces can't be
+    /// named after keywords.
+    fn reserves_keywords(self) -> bool {
+        self >= Self::E2024
+    }
+}
+
+impl FromStr for Edition {
+    type Err = PlangError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text {
+            "2021" => Ok(Self::E2021),
+            "2024" => Ok(Self::E2024),
+            _ => Err(PlangError::Semantic(SemanticError::UnknownEdition(
+                text.to_owned(),
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Edition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.year())
+    }
+}
+
+/// This is the output of the pest parser for plang, detached from pest and
+/// owning the text it was parsed from.
+///
+/// Pest's pairs borrow the text they're parsed from, so they're copied into a
+/// syntax tree of [`Node`]s, which only record the range of the text they
+/// span. [`NodeRef`]s pair them back up with the text, with much the same
+/// interface as pest's.
+///
+/// The nodes of the tree are laid out in a single vector, in the order they
+/// appear in the text, so the tree takes a single allocation however large it
+/// is, and is walked through in the order it's laid out in memory.
+#[derive(Debug, Clone)]
+pub struct PlangGrammar {
+    text: String,
+    /// The edition the text is parsed by.
+    edition: Edition,
+    /// The byte offset each line of the text starts at.
+    line_starts: Vec<usize>,
+    /// Every node of the tree, each followed by its descendants.
+    nodes: Vec<Node>,
+}
+
+/// A node of the syntax tree, spanning a range of the text.
+#[derive(Debug, Clone)]
+struct Node {
+    rule: Rule,
+    span: Range<usize>,
+    /// The index past the node's last descendant, which is where its next
+    /// sibling is, if it has one.
+    end: usize,
+}
+
+/// Appends a pair and all of its descendants to the nodes.
+fn push_pair(nodes: &mut Vec<Node>, pair: Pair<'_, Rule>) {
+    let index = nodes.len();
+    let span = pair.as_span();
+
+    nodes.push(Node {
+        rule: pair.as_rule(),
+        span: span.start()..span.end(),
+        end: 0,
+    });
+    for child in pair.into_inner() {
+        push_pair(nodes, child);
     }
 
-    pub fn pairs(&self) -> Pairs<'a, Rule> {
-        self.pairs.clone()
+    nodes[index].end = nodes.len();
+}
+
+/// Fails with an error pointing at the first term that doesn't fit in the
+/// gate of its equation, if any.
+///
+/// The grammar only accepts equations that fit in a gate, but parses the terms
+/// past them as excess terms, so they're reported here, at the term itself,
+/// rather than where the parser happened to give up.
+fn check_shape(text: &str, nodes: &[Node]) -> Result<()> {
+    let mut product = false;
+
+    for (i, node) in nodes.iter().enumerate() {
+        let message = match node.rule {
+            Rule::expr => {
+                product = false;
+                continue;
+            }
+            Rule::tri_term => {
+                product = true;
+                continue;
+            }
+            Rule::excess_term if product && nodes[i + 1].rule == Rule::tri_term => {
+                "an equation has at most one product of two variables"
+            }
+            Rule::excess_term => "an equation has at most four terms on its left side",
+            Rule::excess_public => "the right side of an equation is a single public input",
+            _ => continue,
+        };
+
+        return Err(node_error(text, node, message.to_owned()));
+    }
+
+    Ok(())
+}
+
+/// An error pointing at the text a node spans.
+fn node_error(text: &str, node: &Node, message: String) -> PlangError {
+    let span = Span::new(text, node.span.start, node.span.end)
+        .expect("nodes span the text they're parsed from");
+    PestError::new_from_span(ErrorVariant::CustomError { message }, span).into()
+}
+
+/// The edition declared in the text, if any. It fails if the declaration
+/// isn't the first statement.
+fn declared_edition(text: &str, nodes: &[Node]) -> Result<Option<Edition>> {
+    let mut edition = None;
+    let mut next = 0;
+
+    while let Some(node) = nodes.get(next) {
+        if node.rule == Rule::edition {
+            if next != 0 {
+                let message = "the edition is declared before any other statement".to_owned();
+                return Err(node_error(text, node, message));
+            }
+            let year = &nodes[next + 1];
+            edition = Some(text[year.span.clone()].parse()?);
+        }
+        next = node.end;
+    }
+
+    Ok(edition)
+}
+
+/// Returns whether the name is a keyword.
+fn is_keyword(name: &str) -> bool {
+    let keyword = PlangParser::parse(Rule::keyword, name)
+        .ok()
+        .and_then(|mut pairs| pairs.next());
+    keyword.map(|pair| pair.as_str().len()) == Some(name.len())
+}
+
+/// The edition a node's form was introduced in, and what the form is, if it
+/// wasn't in the first edition.
+fn introduced_in(text: &str, node: &Node) -> Option<(Edition, &'static str)> {
+    match (node.rule, &text[node.span.clone()]) {
+        (Rule::compare_op, "gt") | (Rule::compare_op, "gte") => {
+            Some((Edition::E2024, "`gt` and `gte` comparisons"))
+        }
+        _ => None,
+    }
+}
+
+/// Fails with an error pointing at the first node the edition doesn't allow,
+/// if any - a form introduced in a later edition, or a name that's a keyword
+/// reserved by the edition.
+///
+/// Every edition is parsed by the same grammar, taking in the forms of all of
+/// them, and held to its own rules here, so that the grammar isn't written
+/// out again for each edition.
+fn check_edition(text: &str, nodes: &[Node], edition: Edition) -> Result<()> {
+    for node in nodes {
+        if let Some((introduced, form)) = introduced_in(text, node) {
+            if edition < introduced {
+                let message = format!(
+                    "{} are only in edition {} and later, declared with `edition {}`",
+                    form, introduced, introduced
+                );
+                return Err(node_error(text, node, message));
+            }
+        }
+
+        if !edition.reserves_keywords()
+            || !matches!(
+                node.rule,
+                Rule::var | Rule::array | Rule::constant | Rule::index | Rule::namespace
+            )
+        {
+            continue;
+        }
+
+        let name = &text[node.span.clone()];
+        let name = name.split('[').next().unwrap_or(name);
+        if let Some(keyword) = name.split("::").find(|part| is_keyword(part)) {
+            let message = format!("`{}` is a keyword in edition {}", keyword, edition);
+            return Err(node_error(text, node, message));
+        }
+    }
+
+    Ok(())
+}
+
+impl PlangGrammar {
+    /// Parses the text by the rules of the edition it declares, or of the
+    /// first edition if it declares none.
+    pub fn new<S: Into<String>>(text: S) -> Result<Self> {
+        Self::with_edition(text, Edition::E2021)
+    }
+
+    /// Parses the text by the rules of the edition it declares, or of the
+    /// given edition if it declares none.
+    pub fn with_edition<S: Into<String>>(text: S, edition: Edition) -> Result<Self> {
+        let text = text.into();
+
+        let mut nodes = vec![];
+        for pair in PlangParser::parse(Rule::main, &text)? {
+            push_pair(&mut nodes, pair);
+        }
+
+        check_shape(&text, &nodes)?;
+
+        let edition = declared_edition(&text, &nodes)?.unwrap_or(edition);
+        check_edition(&text, &nodes, edition)?;
+
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(pos, _)| pos + 1))
+            .collect();
+
+        Ok(Self {
+            text,
+            edition,
+            line_starts,
+            nodes,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The edition the text is parsed by.
+    pub fn edition(&self) -> Edition {
+        self.edition
+    }
+
+    /// The top level nodes - the transcript directives, equations and sums,
+    /// in the order they appear in.
+    pub fn nodes(&self) -> Siblings<'_> {
+        Siblings {
+            grammar: self,
+            next: 0,
+            end: self.nodes.len(),
+        }
+    }
+}
+
+/// A node of a grammar, together with the grammar it's in.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeRef<'g> {
+    grammar: &'g PlangGrammar,
+    index: usize,
+}
+
+impl<'g> NodeRef<'g> {
+    fn node(&self) -> &'g Node {
+        &self.grammar.nodes[self.index]
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.node().rule
+    }
+
+    /// The text the node spans.
+    pub fn as_str(&self) -> &'g str {
+        &self.grammar.text[self.node().span.clone()]
+    }
+
+    /// The line the node starts on, counting from 0.
+    pub fn line(&self) -> usize {
+        let start = self.node().span.start;
+        self.grammar
+            .line_starts
+            .partition_point(|&pos| pos <= start)
+            - 1
+    }
+
+    pub fn children(&self) -> Siblings<'g> {
+        Siblings {
+            grammar: self.grammar,
+            next: self.index + 1,
+            end: self.node().end,
+        }
+    }
+}
+
+/// An iterator over consecutive siblings in the syntax tree.
+#[derive(Debug, Clone)]
+pub struct Siblings<'g> {
+    grammar: &'g PlangGrammar,
+    next: usize,
+    end: usize,
+}
+
+impl<'g> Iterator for Siblings<'g> {
+    type Item = NodeRef<'g>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+
+        let node = NodeRef {
+            grammar: self.grammar,
+            index: self.next,
+        };
+        self.next = self.grammar.nodes[self.next].end;
+
+        Some(node)
     }
 }
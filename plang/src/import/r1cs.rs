@@ -0,0 +1,408 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Import of rank-1 constraint systems in the binary `.r1cs` format written by
+//! circom and read by snarkjs.
+//!
+//! Each constraint `A · B = C` between linear combinations of wires is turned
+//! into one or more equations:
+//!
+//! - Wire `i` becomes the variable `wi`.
+//! - The constant one wire and the public wires can't appear on the left side
+//!   of an equation, so wire `i` among them is also exposed as the public
+//!   input `pi`, constrained by `wi = pi`. Proofs must be verified with `p0`
+//!   set to one.
+//! - Linear combinations wider than an equation allows are split up over
+//!   intermediate variables `t0`, `t1`, and so on.
+//!
+//! The constraint system must be over the BLS12-381 scalar field, meaning the
+//! circom circuit was compiled with `--prime bls12381`.
+
+use crate::circuit::{BiTerm, PlangExpr, Public, TriTerm};
+use crate::error::{Error as PlangError, ParseError, Result};
+use crate::symbol::{Symbol, Symbols};
+use crate::PlangCircuit;
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fmt::Write;
+
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+
+/// Magic bytes at the start of an R1CS file.
+const MAGIC: &[u8; 4] = b"r1cs";
+/// Version of the R1CS format supported.
+const VERSION: u32 = 1;
+
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINTS_SECTION: u32 = 2;
+
+/// The BLS12-381 scalar field modulus, as little endian bytes.
+const MODULUS: [u8; 32] = [
+    0x01, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0x02, 0xa4, 0xbd, 0x53,
+    0x05, 0xd8, 0xa1, 0x09, 0x08, 0xd8, 0x39, 0x33, 0x48, 0x7d, 0x9d, 0x29, 0x53, 0xa7, 0xed, 0x73,
+];
+
+/// Linear combination of wires.
+type Combination = Vec<(u32, BlsScalar)>;
+
+/// A rank-1 constraint `A · B = C`.
+#[derive(Debug)]
+struct Constraint {
+    a: Combination,
+    b: Combination,
+    c: Combination,
+}
+
+/// A rank-1 constraint system read from an `.r1cs` file.
+#[derive(Debug)]
+pub struct R1cs {
+    /// Number of public wires, outputs and inputs, following the constant one
+    /// wire.
+    public_wires: u32,
+    constraints: Vec<Constraint>,
+}
+
+impl R1cs {
+    /// Reads a constraint system from the contents of an `.r1cs` file.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader(bytes);
+
+        if reader.take(4)? != MAGIC {
+            return Err(PlangError::Parse(ParseError::InvalidR1cs(
+                "not an R1CS file",
+            )));
+        }
+        if reader.take_u32()? != VERSION {
+            return Err(PlangError::Parse(ParseError::InvalidR1cs(
+                "unsupported version",
+            )));
+        }
+
+        let mut header = None;
+        let mut constraints = None;
+
+        let sections = reader.take_u32()?;
+        for _ in 0..sections {
+            let kind = reader.take_u32()?;
+            let size = reader.take_u64()?;
+            let section = reader.take(size as usize)?;
+
+            match kind {
+                HEADER_SECTION => header = Some(section),
+                CONSTRAINTS_SECTION => constraints = Some(section),
+                _ => {}
+            }
+        }
+
+        let mut header =
+            Reader(header.ok_or(PlangError::Parse(ParseError::InvalidR1cs("missing header")))?);
+
+        let field_size = header.take_u32()?;
+        if field_size != 32 || header.take(32)? != MODULUS {
+            return Err(PlangError::Parse(ParseError::InvalidR1cs(
+                "not over the BLS12-381 scalar field",
+            )));
+        }
+
+        let _wires = header.take_u32()?;
+        let public_outputs = header.take_u32()?;
+        let public_inputs = header.take_u32()?;
+        let _private_inputs = header.take_u32()?;
+        let _labels = header.take_u64()?;
+        let num_constraints = header.take_u32()?;
+
+        let mut reader = Reader(constraints.ok_or(PlangError::Parse(ParseError::InvalidR1cs(
+            "missing constraints",
+        )))?);
+
+        let constraints = (0..num_constraints)
+            .map(|_| {
+                Ok(Constraint {
+                    a: reader.take_combination()?,
+                    b: reader.take_combination()?,
+                    c: reader.take_combination()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            public_wires: public_outputs + public_inputs,
+            constraints,
+        })
+    }
+
+    /// Returns the number of rank-1 constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Converts the constraint system into a circuit.
+    pub fn to_circuit(&self) -> Result<PlangCircuit> {
+        PlangCircuit::from_exprs(self.lower(), None)
+    }
+
+    /// Converts the constraint system into the source of a circuit.
+    pub fn to_plang(&self) -> Result<String> {
+        let exprs = self.lower();
+
+        let mut text = String::new();
+        let _ = writeln!(
+            text,
+            "# Imported from an R1CS with {} constraints.",
+            self.constraints.len()
+        );
+        let _ = writeln!(text, "# The public input p0 must be verified as 1.");
+
+        for expr in &exprs {
+            let _ = writeln!(text, "{}", expr);
+        }
+
+        Ok(text)
+    }
+
+    fn lower(&self) -> Vec<PlangExpr> {
+        let mut lowering = Lowering::default();
+
+        for wire in 0..=self.public_wires {
+            lowering.exprs.push(PlangExpr {
+                tri: None,
+                bis: vec![bi_term(lowering.wire(wire), BlsScalar::one())],
+                public: Some(Public {
+                    negated: false,
+                    var: lowering.symbols.intern(&format!("p{}", wire)),
+                }),
+                ..Default::default()
+            });
+        }
+
+        for constraint in &self.constraints {
+            lowering.constraint(constraint);
+        }
+
+        lowering.exprs
+    }
+}
+
+/// Named linear combination, where variables are either wires or
+/// intermediates.
+type Terms = Vec<(Symbol, BlsScalar)>;
+
+#[derive(Default)]
+struct Lowering {
+    exprs: Vec<PlangExpr>,
+    intermediates: usize,
+    symbols: Symbols,
+}
+
+impl Lowering {
+    fn constraint(&mut self, constraint: &Constraint) {
+        let a = self.named(&constraint.a);
+        let b = self.named(&constraint.b);
+        let c = self.named(&constraint.c);
+
+        match (constant(&a), constant(&b)) {
+            // If either side of the product is constant, the constraint is
+            // linear.
+            (Some(k), _) => self.linear(scale(b, k).into_iter().chain(negate(c)).collect()),
+            (_, Some(k)) => self.linear(scale(a, k).into_iter().chain(negate(c)).collect()),
+            (None, None) => {
+                let (lvar, lcoeff) = self.single(a);
+                let (mut rvar, rcoeff) = self.single(b);
+
+                // Squares are expressed through a copy of the variable.
+                if lvar == rvar {
+                    let copy = self.intermediate();
+                    self.linear(vec![
+                        (lvar.clone(), BlsScalar::one()),
+                        (copy.clone(), -BlsScalar::one()),
+                    ]);
+                    rvar = copy;
+                }
+
+                let c = match c.len() {
+                    0 | 1 => c,
+                    _ => vec![self.single(c)],
+                };
+
+                self.exprs.push(PlangExpr {
+                    tri: Some(TriTerm {
+                        coeff: (lcoeff * rcoeff).into(),
+                        lvar,
+                        rvar,
+                    }),
+                    bis: negate(c)
+                        .into_iter()
+                        .map(|(var, coeff)| bi_term(var, coeff))
+                        .collect(),
+                    public: None,
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    /// Constrains the sum of the terms to zero, splitting it over as many
+    /// equations as needed.
+    fn linear(&mut self, terms: Terms) {
+        let mut terms = merge(terms);
+
+        while terms.len() > 3 {
+            let acc = self.intermediate();
+
+            let rest = terms.split_off(2);
+            terms.push((acc.clone(), -BlsScalar::one()));
+            self.push_linear(terms);
+
+            terms = rest;
+            terms.insert(0, (acc, BlsScalar::one()));
+        }
+
+        if !terms.is_empty() {
+            self.push_linear(terms);
+        }
+    }
+
+    fn push_linear(&mut self, terms: Terms) {
+        self.exprs.push(PlangExpr {
+            tri: None,
+            bis: terms
+                .into_iter()
+                .map(|(var, coeff)| bi_term(var, coeff))
+                .collect(),
+            public: None,
+            ..Default::default()
+        });
+    }
+
+    /// Reduces a linear combination to a single variable times a
+    /// coefficient, introducing an intermediate for it if needed.
+    fn single(&mut self, terms: Terms) -> (Symbol, BlsScalar) {
+        let mut terms = merge(terms);
+        if terms.len() == 1 {
+            return terms.remove(0);
+        }
+
+        let t = self.intermediate();
+        terms.push((t.clone(), -BlsScalar::one()));
+        self.linear(terms);
+
+        (t, BlsScalar::one())
+    }
+
+    fn intermediate(&mut self) -> Symbol {
+        let name = self.symbols.intern(&format!("t{}", self.intermediates));
+        self.intermediates += 1;
+        name
+    }
+
+    fn wire(&self, wire: u32) -> Symbol {
+        self.symbols.intern(&format!("w{}", wire))
+    }
+
+    fn named(&self, combination: &[(u32, BlsScalar)]) -> Terms {
+        merge(
+            combination
+                .iter()
+                .map(|(wire, coeff)| (self.wire(*wire), *coeff))
+                .collect(),
+        )
+    }
+}
+
+/// Sums the coefficients of repeated variables, dropping those that end up
+/// being zero.
+fn merge(terms: Terms) -> Terms {
+    let mut merged: Terms = vec![];
+    let mut positions: HashMap<Symbol, usize> = HashMap::new();
+
+    for (var, coeff) in terms {
+        match positions.get(&var) {
+            Some(&i) => merged[i].1 += coeff,
+            None => {
+                positions.insert(var.clone(), merged.len());
+                merged.push((var, coeff));
+            }
+        }
+    }
+
+    merged.retain(|(_, coeff)| *coeff != BlsScalar::zero());
+    merged
+}
+
+/// Returns the value of a linear combination if it's constant, meaning it's
+/// either empty or only involves the constant one wire.
+fn constant(terms: &[(Symbol, BlsScalar)]) -> Option<BlsScalar> {
+    match terms {
+        [] => Some(BlsScalar::zero()),
+        [(var, coeff)] if *var == "w0" => Some(*coeff),
+        _ => None,
+    }
+}
+
+fn scale(terms: Terms, k: BlsScalar) -> Terms {
+    terms
+        .into_iter()
+        .map(|(var, coeff)| (var, coeff * k))
+        .collect()
+}
+
+fn negate(terms: Terms) -> Terms {
+    scale(terms, -BlsScalar::one())
+}
+
+fn bi_term(var: Symbol, coeff: BlsScalar) -> BiTerm {
+    BiTerm {
+        coeff: coeff.into(),
+        var,
+    }
+}
+
+/// Reads consecutive little endian values out of a byte slice.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(PlangError::Parse(ParseError::InvalidR1cs(
+                "unexpected end of file",
+            )));
+        }
+
+        let (chunk, rest) = self.0.split_at(n);
+        self.0 = rest;
+
+        Ok(chunk)
+    }
+
+    fn take_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(
+            self.take(4)?.try_into().expect("took exactly 4 bytes"),
+        ))
+    }
+
+    fn take_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(
+            self.take(8)?.try_into().expect("took exactly 8 bytes"),
+        ))
+    }
+
+    fn take_combination(&mut self) -> Result<Combination> {
+        let factors = self.take_u32()?;
+
+        (0..factors)
+            .map(|_| {
+                let wire = self.take_u32()?;
+                let bytes = self.take(32)?.try_into().expect("took exactly 32 bytes");
+                let coeff = BlsScalar::from_bytes(bytes).map_err(|_| {
+                    PlangError::Parse(ParseError::InvalidR1cs("coefficient is not in the field"))
+                })?;
+                Ok((wire, coeff))
+            })
+            .collect()
+    }
+}
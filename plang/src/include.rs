@@ -0,0 +1,220 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Circuits importing the equations of others.
+//!
+//! A circuit can import another with `import "range.plang" as range`, on a
+//! line of its own. The equations of the imported circuit are added to it,
+//! with their variables prefixed by the namespace they're imported as, such
+//! as `range::x`, so they never collide with the variables of the circuit
+//! importing them, nor with those of other imports. The circuit refers to them
+//! by those names to constrain them further, and the public inputs of the
+//! imported circuit are public inputs of the circuit importing it.
+//!
+//! The constants, test vectors, assertions and transcript label of an
+//! imported circuit are its own, and aren't imported.
+//!
+//! Imports are looked up relative to the file importing them, and then in
+//! each directory of a [`SearchPath`] in turn, and then among the exports of
+//! its [packages](crate::package). Their names are relative paths that don't
+//! go up a directory, so a circuit can't import files outside of those.
+//! Errors in an imported circuit are returned with its path and the line it's
+//! imported on.
+
+use crate::circuit::{ParseOptions, PlangExpr};
+use crate::error::{Error as PlangError, Result, SemanticError};
+use crate::grammar::{NodeRef, PlangGrammar, Rule};
+use crate::package::{self, Package};
+use crate::symbol::Symbols;
+use crate::PlangCircuit;
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// The directories and packages imports are looked up in, after the
+/// directory of the file importing them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchPath {
+    dirs: Vec<PathBuf>,
+    packages: Vec<Package>,
+}
+
+impl SearchPath {
+    /// A search path of the given directories, looked in in order.
+    pub fn new<I, P>(dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self {
+            dirs: dirs.into_iter().map(Into::into).collect(),
+            packages: vec![],
+        }
+    }
+
+    /// Adds a directory to look in, after the others.
+    pub fn push<P: Into<PathBuf>>(&mut self, dir: P) {
+        self.dirs.push(dir.into());
+    }
+
+    /// Adds a package to import the exports of, after the others.
+    pub fn push_package(&mut self, package: Package) {
+        self.packages.push(package);
+    }
+
+    /// Adds a directory to look in, opening it as a package if it has the
+    /// `.plangpkg` extension.
+    pub fn include<P: Into<PathBuf>>(&mut self, dir: P) -> Result<()> {
+        let dir = dir.into();
+
+        if dir.extension() == Some(OsStr::new(package::EXTENSION)) {
+            self.push_package(Package::open(dir)?);
+        } else {
+            self.push(dir);
+        }
+        Ok(())
+    }
+
+    /// The directories looked in, in order.
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// The packages looked in, in order.
+    pub fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+
+    /// Finds the file imported with the given name by a circuit in the given
+    /// file, if the circuit is in one.
+    pub fn resolve(&self, name: &str, from: Option<&Path>) -> Option<PathBuf> {
+        let dir = from.and_then(Path::parent);
+
+        dir.into_iter()
+            .chain(self.dirs.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+            .or_else(|| {
+                self.packages
+                    .iter()
+                    .find_map(|package| package.resolve(name))
+            })
+    }
+}
+
+/// Imports circuits into the one being parsed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Importer<'a> {
+    options: &'a ParseOptions,
+    /// The file of the circuit being parsed, if it's in one.
+    file: Option<&'a Path>,
+    /// The files importing the circuit being parsed, directly or not.
+    parents: &'a [PathBuf],
+}
+
+impl<'a> Importer<'a> {
+    pub(crate) fn new(options: &'a ParseOptions, file: Option<&'a Path>) -> Self {
+        Self {
+            options,
+            file,
+            parents: &[],
+        }
+    }
+
+    /// Parses the circuit imported with the given name, and returns its
+    /// equations with their variables in the namespace, on the line of the
+    /// import.
+    pub(crate) fn import(
+        &self,
+        name: &str,
+        namespace: &str,
+        line: usize,
+    ) -> Result<Vec<PlangExpr>> {
+        let contained = Path::new(name)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if !contained {
+            return Err(PlangError::Semantic(SemanticError::InvalidImport(
+                name.to_owned(),
+            )));
+        }
+
+        let path = self
+            .options
+            .search
+            .resolve(name, self.file)
+            .ok_or_else(|| {
+                PlangError::Semantic(SemanticError::UnresolvedImport(name.to_owned()))
+            })?;
+
+        // Files are told apart by their canonical path, so that a file is
+        // recognized however it's imported.
+        let canonical = fs::canonicalize(&path)?;
+        let mut parents = self.parents.to_vec();
+        if let Some(file) = self.file {
+            parents.push(fs::canonicalize(file)?);
+        }
+        if parents.contains(&canonical) {
+            return Err(PlangError::Semantic(SemanticError::ImportCycle(
+                name.to_owned(),
+            )));
+        }
+
+        let parse = || -> Result<Vec<PlangExpr>> {
+            let text = String::from_utf8(fs::read(&path)?)?;
+            // Imported circuits are parsed by the edition they declare, which
+            // can differ from that of the circuit importing them.
+            let grammar = PlangGrammar::with_edition(text, self.options.edition)?;
+            let importer = Importer {
+                options: self.options,
+                file: Some(&path),
+                parents: &parents,
+            };
+            let circuit = PlangCircuit::from_grammar_in(grammar, importer)?;
+            Ok(circuit.exprs().to_vec())
+        };
+        let mut exprs = parse().map_err(|err| PlangError::Imported {
+            path: path.clone(),
+            line,
+            error: Box::new(err),
+        })?;
+
+        let symbols = Symbols::default();
+        for expr in &mut exprs {
+            expr.rename(|var| symbols.intern(&format!("{}::{}", namespace, var)));
+            expr.line = line;
+        }
+        Ok(exprs)
+    }
+}
+
+/// Lowers an `import` statement into the name of the file imported and the
+/// namespace it's imported as.
+pub(crate) fn lower_import(node: NodeRef<'_>) -> (&str, &str) {
+    let mut name = "";
+    let mut namespace = "";
+
+    for child in node.children() {
+        match child.rule() {
+            Rule::string => {
+                name = child
+                    .children()
+                    .find(|inner| inner.rule() == Rule::label)
+                    .map_or("", |label| label.as_str());
+            }
+            Rule::namespace => namespace = child.as_str(),
+            _ => {}
+        }
+    }
+
+    (name, namespace)
+}
+
+/// The namespace a variable is in, if it's in one.
+pub(crate) fn namespace_of(var: &str) -> Option<&str> {
+    var.find("::").map(|end| &var[..end])
+}
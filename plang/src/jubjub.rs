@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Points of JubJub, the twisted Edwards curve embedded in BLS12-381.
+//!
+//! The coordinates of JubJub points are elements of the scalar field of
+//! BLS12-381, so circuits can compute on them. Only what the commitment
+//! gadget needs while lowering is implemented - the generators dusk-jubjub
+//! commits with, and the addition of points in affine coordinates - favouring
+//! simplicity over speed, like [`Scalar`] itself.
+
+use crate::scalar::Scalar;
+
+/// A point of the curve, in affine coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Point {
+    pub(crate) x: Scalar,
+    pub(crate) y: Scalar,
+}
+
+/// The generator values are committed to with, as in dusk-jubjub.
+pub(crate) const GENERATOR: Point = Point {
+    x: Scalar::from_raw([
+        0x4df7_b7ff_ec7b_eaca,
+        0x2e3e_bb21_fd6c_54ed,
+        0xf1fb_f02d_0fd6_cce6,
+        0x3fd2_814c_43ac_65a6,
+    ]),
+    y: Scalar::from_raw([0x12, 0, 0, 0]),
+};
+
+/// The generator blinders are committed to with, which nobody knows the
+/// discrete logarithm of to the base of [`GENERATOR`], as in dusk-jubjub.
+pub(crate) const GENERATOR_NUMS: Point = Point {
+    x: Scalar::from_raw([
+        0x9217_1017_9df7_6377,
+        0x931e_316a_39fe_4541,
+        0xbd95_14c7_73fd_4456,
+        0x5e67_b8f3_16f4_14f7,
+    ]),
+    y: Scalar::from_raw([
+        0x6705_b707_162e_3ef8,
+        0x9949_ba0f_82a5_507a,
+        0x7b16_2dbe_eb3b_34fd,
+        0x43d8_0eb3_b2f3_eb1b,
+    ]),
+};
+
+/// The `d` parameter of the curve equation `-x² + y² = 1 + d·x²·y²`, which is
+/// `-(10240/10241)`.
+pub(crate) fn d() -> Scalar {
+    let inverse = Scalar::from(10241)
+        .invert()
+        .expect("10241 isn't zero in the field");
+    -(Scalar::from(10240) * inverse)
+}
+
+impl Point {
+    /// Adds two points. The addition is complete, since `d` isn't a square,
+    /// so it holds for any two points, the same or not.
+    pub(crate) fn add(self, other: Self, d: Scalar) -> Self {
+        let product = d * self.x * other.x * self.y * other.y;
+        let x = (self.x * other.y + self.y * other.x) * invert(Scalar::one() + product);
+        let y = (self.y * other.y + self.x * other.x) * invert(Scalar::one() - product);
+        Self { x, y }
+    }
+
+    /// The multiples of the point by each power of 2, up to `2^(n - 1)`.
+    pub(crate) fn doublings(self, n: usize) -> Vec<Self> {
+        let d = d();
+        let mut point = self;
+        let mut doublings = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            doublings.push(point);
+            point = point.add(point, d);
+        }
+        doublings
+    }
+}
+
+fn invert(scalar: Scalar) -> Scalar {
+    scalar
+        .invert()
+        .expect("the denominators of the addition are never zero")
+}
@@ -0,0 +1,202 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::artifact::{self, ArtifactKind, Metadata};
+use crate::error::{Error as PlangError, ProveError, Result};
+#[cfg(feature = "prover")]
+use crate::{params, PlangCircuit};
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use dusk_plonk::prelude::*;
+
+/// The keys of a compiled circuit, bundled with what is needed to tell which
+/// circuit and setup they belong to.
+///
+/// The prover key is optional, since verifier deployments have no use for it.
+#[derive(Debug)]
+pub struct PlangKeys {
+    /// The prover key, if the archive is meant for proving.
+    pub prover_key: Option<ProverKey>,
+    /// The verifier data.
+    pub verifier_data: VerifierData,
+    /// Hash of the circuit the keys were compiled from.
+    pub circuit_hash: [u8; 32],
+    /// Version of plang the keys were compiled with.
+    pub compiler_version: String,
+    /// Transcript label to use when proving and verifying.
+    pub label: Vec<u8>,
+    /// Digest of the public parameters the keys were compiled with.
+    pub params_digest: [u8; 32],
+    /// ID of the circuit, which is its hash unless it was given another.
+    pub circuit_id: [u8; 32],
+    /// Metadata attached to the circuit.
+    pub metadata: Metadata,
+}
+
+impl PlangKeys {
+    /// Bundles the keys compiled from the given circuit and parameters.
+    #[cfg(feature = "prover")]
+    pub fn new(
+        circuit: &PlangCircuit,
+        pp: &PublicParameters,
+        prover_key: Option<ProverKey>,
+        verifier_data: VerifierData,
+        label: Vec<u8>,
+    ) -> Self {
+        Self {
+            prover_key,
+            verifier_data,
+            circuit_hash: circuit.hash(),
+            compiler_version: env!("CARGO_PKG_VERSION").to_owned(),
+            label,
+            params_digest: params::digest(pp),
+            circuit_id: circuit.id(),
+            metadata: circuit.metadata().clone(),
+        }
+    }
+
+    /// Checks the keys were compiled from the given circuit and parameters.
+    #[cfg(feature = "prover")]
+    pub fn check(&self, circuit: &PlangCircuit, pp: &PublicParameters) -> Result<()> {
+        if self.circuit_hash != circuit.hash() {
+            return Err(PlangError::Prove(ProveError::CircuitMismatch));
+        }
+        if self.params_digest != params::digest(pp) {
+            return Err(PlangError::Prove(ProveError::ParamsMismatch));
+        }
+        Ok(())
+    }
+
+    /// Deserializes an archive, checking it was compiled from the given
+    /// circuit and parameters.
+    #[cfg(feature = "prover")]
+    pub fn read_for_circuit(
+        bytes: &[u8],
+        circuit: &PlangCircuit,
+        pp: &PublicParameters,
+    ) -> Result<Self> {
+        let keys = Self::from_slice(bytes)?;
+        keys.check(circuit, pp)?;
+        Ok(keys)
+    }
+
+    /// Serializes the archive, including its artifact header.
+    ///
+    /// After the header, the fixed size circuit hash and parameters digest are
+    /// followed by the compiler version, the transcript label, the verifier
+    /// data and the prover key - each prefixed by its length as a little
+    /// endian `u32`. A missing prover key is written with length zero.
+    ///
+    /// They're followed by the circuit ID, and the name and version of the
+    /// circuit, prefixed by their length like the fields before. Archives
+    /// written before circuits had IDs end after the prover key, and are read
+    /// with the circuit hash as the ID and no metadata.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let vd = self.verifier_data.to_var_bytes();
+        let pk = self
+            .prover_key
+            .as_ref()
+            .map(ProverKey::to_var_bytes)
+            .unwrap_or_default();
+
+        let mut bytes = Vec::with_capacity(64 + vd.len() + pk.len() + 64);
+
+        bytes.extend(&self.circuit_hash);
+        bytes.extend(&self.params_digest);
+
+        for field in [self.compiler_version.as_bytes(), &self.label, &vd, &pk] {
+            bytes.extend(&(field.len() as u32).to_le_bytes());
+            bytes.extend(field);
+        }
+
+        bytes.extend(&self.circuit_id);
+        for field in [&self.metadata.name, &self.metadata.version] {
+            let field = field.as_deref().unwrap_or_default().as_bytes();
+            bytes.extend(&(field.len() as u32).to_le_bytes());
+            bytes.extend(field);
+        }
+
+        artifact::wrap(ArtifactKind::Keys, 0, &bytes)
+    }
+
+    /// Deserializes an archive written by [`to_bytes`](Self::to_bytes).
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let (bytes, _) = artifact::unwrap(ArtifactKind::Keys, bytes)?;
+        let mut reader = Reader(bytes);
+
+        let circuit_hash = reader.take_array()?;
+        let params_digest = reader.take_array()?;
+
+        let compiler_version = String::from_utf8(reader.take_field()?.to_vec())?;
+        let label = reader.take_field()?.to_vec();
+        let verifier_data = VerifierData::from_slice(reader.take_field()?)?;
+        let prover_key = match reader.take_field()? {
+            [] => None,
+            pk => Some(ProverKey::from_slice(pk)?),
+        };
+
+        let (circuit_id, metadata) = if reader.0.is_empty() {
+            (circuit_hash, Metadata::default())
+        } else {
+            let circuit_id = reader.take_array()?;
+            let name = reader.take_string()?;
+            let version = reader.take_string()?;
+            (circuit_id, Metadata { name, version })
+        };
+
+        if !reader.0.is_empty() {
+            return Err(PlangError::Prove(ProveError::InvalidKeyArchive));
+        }
+
+        Ok(Self {
+            prover_key,
+            verifier_data,
+            circuit_hash,
+            compiler_version,
+            label,
+            params_digest,
+            circuit_id,
+            metadata,
+        })
+    }
+}
+
+/// Reads consecutive chunks out of a byte slice.
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(PlangError::Prove(ProveError::InvalidKeyArchive));
+        }
+
+        let (chunk, rest) = self.0.split_at(n);
+        self.0 = rest;
+
+        Ok(chunk)
+    }
+
+    pub(crate) fn take_array(&mut self) -> Result<[u8; 32]> {
+        Ok(self.take(32)?.try_into().expect("took exactly 32 bytes"))
+    }
+
+    pub(crate) fn take_field(&mut self) -> Result<&'a [u8]> {
+        let len = self.take(4)?;
+        let len = u32::from_le_bytes(len.try_into().expect("took exactly 4 bytes"));
+        self.take(len as usize)
+    }
+
+    /// Takes a length prefixed string, which is absent if it's empty.
+    fn take_string(&mut self) -> Result<Option<String>> {
+        match self.take_field()? {
+            [] => Ok(None),
+            field => Ok(Some(String::from_utf8(field.to_vec())?)),
+        }
+    }
+}
@@ -4,11 +4,110 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-mod grammar;
+//! Plang, a language for writing PLONK circuits one equation at a time.
+//!
+//! Everything touching dusk-plonk - compiling, proving and verifying, keys,
+//! proofs and public parameters, and the exports and imports working on field
+//! elements - is behind the default `prover` feature. Without it, and with the
+//! `std` feature, plang is a parser and analyser of circuits only, for tooling
+//! that has no use for the proving stack and its curve arithmetic.
+//!
+//! The `verifier` feature on its own builds under `no_std` with `alloc`. It
+//! leaves out the parser, and keeps what verifying a proof takes: reading key
+//! archives, proofs and verifier archives, arranging public inputs in the
+//! order the circuit expects, and checking the proof.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod assertion;
+#[cfg(feature = "std")]
 mod circuit;
 mod error;
+#[cfg(feature = "std")]
+mod jubjub;
+#[cfg(feature = "verifier")]
+mod keys;
+#[cfg(feature = "prover")]
+mod program;
+#[cfg(feature = "verifier")]
+mod proof;
+#[cfg(feature = "std")]
+mod scope;
+#[cfg(feature = "std")]
+mod symbol;
+
+pub mod artifact;
+#[cfg(feature = "prover")]
+pub mod backend;
+#[cfg(feature = "prover")]
+pub mod cache;
+#[cfg(feature = "prover")]
+pub mod coverage;
+#[cfg(feature = "prover")]
+pub mod differential;
+#[cfg(feature = "std")]
+pub mod document;
+#[cfg(feature = "verifier")]
+pub mod envelope;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "prover")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod generate;
+#[cfg(feature = "std")]
+pub mod grammar;
+#[cfg(feature = "prover")]
+pub mod import;
+#[cfg(feature = "std")]
+pub mod include;
+#[cfg(feature = "std")]
+pub mod package;
+#[cfg(feature = "prover")]
+pub mod params;
+#[cfg(feature = "prover")]
+pub mod probe;
+#[cfg(feature = "prover")]
+pub mod prover;
+#[cfg(feature = "prover")]
+pub mod report;
+#[cfg(feature = "prover")]
+pub mod repro;
+pub mod scalar;
+#[cfg(feature = "prover")]
+pub mod session;
+#[cfg(feature = "test-utils")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod testgen;
+#[cfg(feature = "std")]
+pub mod tokens;
+#[cfg(feature = "verifier")]
+pub mod transcript;
+#[cfg(feature = "std")]
+pub mod usage;
+#[cfg(feature = "std")]
+pub mod vector;
+#[cfg(feature = "verifier")]
+pub mod verifier;
+#[cfg(feature = "std")]
+pub mod warning;
 
-pub use circuit::PlangCircuit;
+#[cfg(feature = "prover")]
+pub use circuit::Assignment;
+#[cfg(feature = "std")]
+pub use circuit::{AssignmentReport, ParseOptions, PlangCircuit};
+#[cfg(feature = "verifier")]
 pub use dusk_plonk;
-pub use error::Error as PlangError;
+pub use error::{AssignmentError, Error as PlangError, ParseError, ProveError, SemanticError};
+#[cfg(feature = "verifier")]
+pub use keys::PlangKeys;
+#[cfg(feature = "verifier")]
+pub use proof::PlangProof;
+#[cfg(feature = "verifier")]
+pub use transcript::ProveOptions;
+#[cfg(feature = "verifier")]
+pub use verifier::PlangVerifier;
@@ -0,0 +1,154 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Packages of gadgets, shared between projects.
+//!
+//! A package is a directory with the `.plangpkg` extension, holding the
+//! sources of its circuits and a `manifest.json` of the form:
+//!
+//! ```json
+//! {
+//!   "name": "range",
+//!   "version": "1.0.0",
+//!   "exports": ["range.plang", "bits/bits.plang"]
+//! }
+//! ```
+//!
+//! Once a package is added to a [`SearchPath`](crate::include::SearchPath), circuits import its exports
+//! by the name of the package followed by their path in it, such as
+//! `import "range/bits/bits.plang" as bits`. The other circuits of the package
+//! can only be imported by those in it, relative to their own file.
+
+use crate::error::{Error as PlangError, ParseError, Result};
+use crate::{ParseOptions, PlangCircuit};
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::Value;
+
+/// The extension of package directories.
+pub const EXTENSION: &str = "plangpkg";
+
+/// The name of the manifest of a package, in its directory.
+pub const MANIFEST: &str = "manifest.json";
+
+/// A package of circuits, read from its directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    dir: PathBuf,
+    name: String,
+    version: String,
+    exports: Vec<String>,
+}
+
+impl Package {
+    /// Opens the package in the given directory, reading its manifest and
+    /// checking every circuit it exports is in it.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let json: Value = serde_json::from_slice(&fs::read(dir.join(MANIFEST))?)
+            .map_err(|err| invalid(dir, &err.to_string()))?;
+
+        Self::from_json(&json, dir)
+    }
+
+    /// Reads the manifest of the package in the given directory from JSON.
+    pub fn from_json(json: &Value, dir: &Path) -> Result<Self> {
+        let string = |field: &str| -> Result<String> {
+            json.get(field)
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+                .ok_or_else(|| invalid(dir, &format!("`{}` is not a string", field)))
+        };
+
+        let name = string("name")?;
+        let version = string("version")?;
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(invalid(dir, &format!("`{}` is not a package name", name)));
+        }
+
+        let exports = json
+            .get("exports")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid(dir, "`exports` is not a list"))?
+            .iter()
+            .map(|export| {
+                let export = export
+                    .as_str()
+                    .ok_or_else(|| invalid(dir, "`exports` is not a list of paths"))?;
+                // Exports can't reach out of the package.
+                let inside = Path::new(export)
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)));
+                if !inside || !dir.join(export).is_file() {
+                    return Err(invalid(dir, &format!("no such export `{}`", export)));
+                }
+                Ok(export.to_owned())
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            dir: dir.to_owned(),
+            name,
+            version,
+            exports,
+        })
+    }
+
+    /// The directory of the package.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The name of the package, its exports are imported under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The version of the package.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The paths of the circuits the package exports, relative to its
+    /// directory.
+    pub fn exports(&self) -> &[String] {
+        &self.exports
+    }
+
+    /// Finds the circuit imported with the given name, such as
+    /// `range/bits.plang`, if the package exports it.
+    pub fn resolve(&self, name: &str) -> Option<PathBuf> {
+        let mut parts = name.splitn(2, '/');
+        let (package, export) = (parts.next()?, parts.next()?);
+
+        if package == self.name && self.exports.iter().any(|e| e == export) {
+            Some(self.dir.join(export))
+        } else {
+            None
+        }
+    }
+
+    /// Reads and parses a circuit the package exports, given its path in the
+    /// package, with the given options. Its imports are looked up relative to
+    /// it, and then in the search path of the options.
+    pub fn circuit(&self, export: &str, options: &ParseOptions) -> Result<PlangCircuit> {
+        if !self.exports.iter().any(|e| e == export) {
+            return Err(invalid(&self.dir, &format!("`{}` is not exported", export)));
+        }
+
+        PlangCircuit::from_file_with(self.dir.join(export), options)
+    }
+}
+
+fn invalid(dir: &Path, reason: &str) -> PlangError {
+    PlangError::Parse(ParseError::InvalidPackage(format!(
+        "{}: {}",
+        dir.display(),
+        reason
+    )))
+}
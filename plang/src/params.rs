@@ -0,0 +1,115 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Generation and inspection of public parameters.
+
+use crate::artifact::{self, ArtifactKind};
+use crate::error::{Error as PlangError, ProveError, Result};
+use crate::PlangCircuit;
+
+use std::path::Path;
+
+use blake2::{Blake2s, Digest};
+use dusk_plonk::prelude::{Circuit, PublicParameters};
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// Reads public parameters from a file written by `plangc generate-params`,
+/// returning them together with the flags in their artifact header.
+///
+/// With the `mmap` feature the file is memory mapped, and the parameters are
+/// deserialized straight out of the mapping, so the file isn't held in memory
+/// whole next to the parameters. Without it the file is read into memory
+/// first, which for large setups doubles the peak memory used.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<(PublicParameters, u8)> {
+    #[cfg(feature = "mmap")]
+    let bytes = {
+        let file = std::fs::File::open(path)?;
+        // Safety: the mapping is only read, and only while the parameters are
+        // deserialized. A file truncated in the meantime faults the process,
+        // and one modified in the meantime is as good as a corrupt one.
+        unsafe { memmap2::Mmap::map(&file)? }
+    };
+    #[cfg(not(feature = "mmap"))]
+    let bytes = std::fs::read(path)?;
+
+    from_slice(&bytes)
+}
+
+/// Reads public parameters written as an artifact, returning them together
+/// with the flags in their artifact header.
+pub fn from_slice(bytes: &[u8]) -> Result<(PublicParameters, u8)> {
+    let (payload, flags) = artifact::unwrap(ArtifactKind::Params, bytes)?;
+    let pp = PublicParameters::from_slice(payload)?;
+
+    Ok((pp, flags))
+}
+
+/// Reads public parameters written as an artifact, checking they're large
+/// enough to compile the given circuit.
+pub fn read_for_circuit(bytes: &[u8], circuit: &PlangCircuit) -> Result<PublicParameters> {
+    let (pp, _) = from_slice(bytes)?;
+    check_degree(&pp, circuit)?;
+    Ok(pp)
+}
+
+/// Generates public parameters of the given degree deterministically from a
+/// seed.
+///
+/// The randomness is drawn from a ChaCha20 RNG seeded with `seed`, meaning
+/// anyone who knows the seed can recompute the secret used in the setup.
+/// These parameters are therefore **insecure** and only meant for testing
+/// environments where reproducibility matters.
+pub fn setup_seeded(max_degree: usize, seed: [u8; 32]) -> Result<PublicParameters> {
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    Ok(PublicParameters::setup(max_degree, &mut rng)?)
+}
+
+/// Returns the degree public parameters must have to be able to compile
+/// circuits of up to `gates` gates, or an error if no degree is large enough.
+pub fn degree_for_gates(gates: usize) -> Result<usize> {
+    gates
+        .checked_next_power_of_two()
+        .and_then(|gates| gates.checked_mul(2))
+        .ok_or(PlangError::Prove(ProveError::TooManyGates(gates)))
+}
+
+/// Returns the maximum number of gates a circuit can have to be compiled with
+/// parameters of the given degree.
+pub fn gates_for_degree(degree: usize) -> usize {
+    match degree >> 1 {
+        0 => 0,
+        gates => 1 << (usize::BITS - 1 - gates.leading_zeros()),
+    }
+}
+
+/// Returns the maximum degree of the given public parameters, that of the
+/// largest polynomial their commit key can commit to.
+pub fn max_degree(pp: &PublicParameters) -> usize {
+    pp.max_degree()
+}
+
+/// Returns a digest of the public parameters, identifying the setup they
+/// come from.
+pub fn digest(pp: &PublicParameters) -> [u8; 32] {
+    Blake2s::digest(&pp.to_var_bytes()).into()
+}
+
+/// Checks that the public parameters are large enough to compile the given
+/// circuit.
+pub fn check_degree(pp: &PublicParameters, circuit: &PlangCircuit) -> Result<()> {
+    let required = degree_for_gates(circuit.padded_gates())?;
+    let available = max_degree(pp);
+
+    if available < required {
+        return Err(PlangError::Prove(ProveError::ParamsTooSmall {
+            required,
+            available,
+        }));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Mutation testing of the soundness of a circuit.
+//!
+//! Test vectors expected not to satisfy a circuit show that it rejects values
+//! it should. Mutating one of its equations - dropping it, flipping the sign
+//! of a coefficient, or zeroing one - makes a different circuit, which should
+//! accept at least one of those values. A mutant that still rejects them all
+//! goes unnoticed: none of the test vectors depends on what was mutated. The
+//! equations with surviving mutants are worth a second look, as either they
+//! constrain nothing the others don't, or nothing tests them.
+
+use crate::backend::SELECTORS;
+use crate::error::{AssignmentError, Error as PlangError, Result};
+use crate::scalar::Scalar;
+use crate::vector::TestVector;
+use crate::PlangCircuit;
+
+use std::fmt;
+
+use dusk_plonk::prelude::BlsScalar;
+
+/// A change to an equation of a circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// The equation is dropped.
+    Drop,
+    /// The selector at the given index in [`SELECTORS`] is negated.
+    FlipSign(usize),
+    /// The selector at the given index in [`SELECTORS`] is set to 0.
+    Zero(usize),
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Drop => write!(f, "dropping it"),
+            Self::FlipSign(selector) => write!(f, "flipping the sign of {}", SELECTORS[*selector]),
+            Self::Zero(selector) => write!(f, "zeroing {}", SELECTORS[*selector]),
+        }
+    }
+}
+
+/// A circuit with one of its equations mutated, and the test vector
+/// noticing, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutant {
+    /// The index of the equation mutated.
+    pub equation: usize,
+    /// The line the equation is on, counting from 0.
+    pub line: usize,
+    /// The equation, in normal form.
+    pub text: String,
+    /// How the equation is mutated.
+    pub mutation: Mutation,
+    /// The name of the first test vector satisfying the mutant, if any.
+    pub killed_by: Option<String>,
+}
+
+impl Mutant {
+    /// Whether no test vector noticed the mutation.
+    pub fn survived(&self) -> bool {
+        self.killed_by.is_none()
+    }
+}
+
+impl fmt::Display for Mutant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: `{}`: {}",
+            self.line + 1,
+            self.text,
+            self.mutation
+        )?;
+        match &self.killed_by {
+            Some(name) => write!(f, " is caught by \"{}\"", name),
+            None => write!(f, " goes unnoticed"),
+        }
+    }
+}
+
+/// The mutants of a circuit, checked against its test vectors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeReport {
+    /// Every mutant, in the order of the equations mutated.
+    pub mutants: Vec<Mutant>,
+    /// The number of test vectors the mutants were checked against.
+    pub vectors: usize,
+}
+
+impl ProbeReport {
+    /// The mutants no test vector noticed.
+    pub fn survivors(&self) -> impl Iterator<Item = &Mutant> {
+        self.mutants.iter().filter(|mutant| mutant.survived())
+    }
+}
+
+impl PlangCircuit {
+    /// Mutates each equation of the circuit in every way there is, and checks
+    /// each mutant against the test vectors expected not to satisfy the
+    /// circuit. Those expected to satisfy it are ignored, as are those only
+    /// an assertion rejects, as they say nothing about the equations.
+    ///
+    /// It returns an error if a test vector expected not to satisfy the
+    /// circuit does, or if a value is not in the circuit.
+    pub fn probe(&self, tests: &[TestVector]) -> Result<ProbeReport> {
+        let gates = self.gates();
+
+        // The values of each test vector, with the equations they don't
+        // satisfy.
+        let mut runs = vec![];
+        for test in tests.iter().filter(|test| !test.satisfied) {
+            let mut circuit = self.unassigned();
+            let values = test
+                .values
+                .iter()
+                .map(|(name, value)| (name.clone(), BlsScalar::from(*value)));
+            let asserted = match circuit.set_vals(values) {
+                Ok(()) => true,
+                Err(PlangError::Assignment(AssignmentError::AssertionFailed { .. })) => false,
+                Err(err) => return Err(err),
+            };
+
+            let value = |var: &str| circuit.value(var).map_or(Scalar::zero(), Scalar::from);
+            let unsatisfied: Vec<usize> = (0..gates.len())
+                .filter(|i| gates[*i].evaluate(value) != Scalar::zero())
+                .collect();
+
+            match (unsatisfied.is_empty(), asserted) {
+                (true, true) => {
+                    return Err(PlangError::Assignment(
+                        AssignmentError::UnexpectedlySatisfied(test.name.clone()),
+                    ))
+                }
+                (true, false) => {}
+                (false, _) => runs.push((test, circuit, unsatisfied)),
+            }
+        }
+
+        let mut mutants = vec![];
+        for (i, (gate, expr)) in gates.iter().zip(self.exprs()).enumerate() {
+            let mut mutations = vec![Mutation::Drop];
+            for (selector, value) in gate.selectors.iter().enumerate() {
+                if *value != Scalar::zero() {
+                    mutations.push(Mutation::FlipSign(selector));
+                    mutations.push(Mutation::Zero(selector));
+                }
+            }
+
+            for mutation in mutations {
+                let mut mutated = *gate;
+                match mutation {
+                    Mutation::Drop => mutated.selectors = [Scalar::zero(); 7],
+                    Mutation::FlipSign(selector) => {
+                        mutated.selectors[selector] = -mutated.selectors[selector]
+                    }
+                    Mutation::Zero(selector) => mutated.selectors[selector] = Scalar::zero(),
+                }
+
+                // The mutant is satisfied if only the equation mutated
+                // wasn't, and the mutation makes it hold.
+                let killed_by = runs.iter().find(|(_, circuit, unsatisfied)| {
+                    let value = |var: &str| circuit.value(var).map_or(Scalar::zero(), Scalar::from);
+                    unsatisfied.iter().all(|j| *j == i) && mutated.evaluate(value) == Scalar::zero()
+                });
+
+                mutants.push(Mutant {
+                    equation: i,
+                    line: expr.line,
+                    text: expr.to_string(),
+                    mutation,
+                    killed_by: killed_by.map(|(test, ..)| test.name.clone()),
+                });
+            }
+        }
+
+        Ok(ProbeReport {
+            mutants,
+            vectors: runs.len(),
+        })
+    }
+}
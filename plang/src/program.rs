@@ -0,0 +1,208 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! The equations of a circuit compiled down to gates over the indices of its
+//! variables.
+//!
+//! The gadget of a circuit is built on every compilation and every proof. The
+//! equations refer to their variables by name, so the gates are resolved from
+//! them once, when the circuit is built, and the gadget only has to append
+//! them to the composer.
+
+use crate::circuit::PlangExpr;
+use crate::error::{Error as PlangError, ProveError, Result};
+use crate::scalar::Scalar;
+use crate::symbol::Symbol;
+
+use std::collections::HashMap;
+
+/// The gates of a circuit, over the indices of its variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GateProgram {
+    /// The variables appended to the composer as witnesses, in order. Gates
+    /// refer to witnesses by their position in here.
+    pub(crate) witnesses: Vec<usize>,
+    /// A gate for each equation, in the same order.
+    pub(crate) gates: Vec<CompiledGate>,
+    /// The first equation that can't be laid out on a gate, if any. It's
+    /// returned as an error when the gadget is built.
+    pub(crate) invalid: Option<InvalidGate>,
+}
+
+/// An equation that can't be laid out on a gate, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InvalidGate {
+    /// The index of the equation.
+    pub(crate) gate: usize,
+    /// The line the equation is on, counting from 0.
+    pub(crate) line: usize,
+    /// Which invariant the equation breaks, naming the variable at fault.
+    pub(crate) reason: String,
+}
+
+/// A gate, with its selectors and wires resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CompiledGate {
+    /// The `q_m`, `q_l`, `q_r`, `q_o` and `q_4` selectors.
+    pub(crate) selectors: [Scalar; 5],
+    /// The witnesses on the `a`, `b`, `o` and `d` wires, if any.
+    pub(crate) wires: [Option<usize>; 4],
+    /// The variable appended as a public input, and the coefficient its
+    /// value is multiplied by when it is.
+    pub(crate) public: Option<(usize, Scalar)>,
+    /// The `q_c` selector, the constant term.
+    pub(crate) constant: Scalar,
+}
+
+const MULT: usize = 0;
+const LEFT: usize = 1;
+const RIGHT: usize = 2;
+const OUTPUT: usize = 3;
+const FOURTH: usize = 4;
+
+const A: usize = 0;
+const B: usize = 1;
+const O: usize = 2;
+const D: usize = 3;
+
+impl GateProgram {
+    /// Compiles the equations into gates, given the index of each variable
+    /// and whether the variable at each index is a public input.
+    pub(crate) fn compile(
+        exprs: &[PlangExpr],
+        vars: &HashMap<Symbol, usize>,
+        public: &[bool],
+    ) -> Self {
+        let mut slots = vec![None; public.len()];
+        let mut witnesses = vec![];
+
+        for (var, public) in public.iter().enumerate() {
+            if !public {
+                slots[var] = Some(witnesses.len());
+                witnesses.push(var);
+            }
+        }
+
+        let mut gates = Vec::with_capacity(exprs.len());
+        let mut invalid = None;
+
+        for (i, expr) in exprs.iter().enumerate() {
+            match compile_gate(expr, vars, public, &slots) {
+                Ok(gate) => gates.push(gate),
+                Err(reason) => {
+                    invalid = Some(InvalidGate {
+                        gate: i,
+                        line: expr.line,
+                        reason,
+                    });
+                    break;
+                }
+            }
+        }
+
+        Self {
+            witnesses,
+            gates,
+            invalid,
+        }
+    }
+
+    /// Checks every equation could be laid out on a gate.
+    pub(crate) fn check(&self) -> Result<()> {
+        match &self.invalid {
+            Some(invalid) => Err(PlangError::Prove(ProveError::InvalidGate {
+                gate: invalid.gate,
+                line: invalid.line,
+                reason: invalid.reason.clone(),
+            })),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Lays an equation out on a gate, with the signed coefficients of its terms
+/// as the selectors.
+fn compile_gate(
+    expr: &PlangExpr,
+    vars: &HashMap<Symbol, usize>,
+    public: &[bool],
+    slots: &[Option<usize>],
+) -> core::result::Result<CompiledGate, String> {
+    let mut gate = CompiledGate {
+        constant: expr.constant,
+        ..CompiledGate::default()
+    };
+
+    // If there is a public input it's appended with the `q_p` selector.
+    if let Some(pi) = &expr.public {
+        let var = *vars
+            .get(&pi.var)
+            .ok_or_else(|| format!("`{}` is not a variable of the circuit", pi.var))?;
+        if !public[var] {
+            return Err(format!("`{}` is not a public input", pi.var));
+        }
+        gate.public = Some((var, pi.coeff()));
+    }
+
+    let slot = |name: &str| {
+        vars.get(name)
+            .and_then(|var| slots[*var])
+            .ok_or_else(|| format!("`{}` is not a witness", name))
+    };
+
+    // A term of the form `q_m · a · b` goes on the `a` and `b` wires.
+    let mut tri_slots = None;
+    if let Some(tri) = &expr.tri {
+        let lslot = slot(&tri.lvar)?;
+        let rslot = slot(&tri.rvar)?;
+        tri_slots = Some((lslot, rslot));
+
+        gate.selectors[MULT] = tri.coeff;
+        gate.wires[A] = Some(lslot);
+        gate.wires[B] = Some(rslot);
+    }
+
+    for (bi_num, bi) in expr.bis.iter().enumerate() {
+        let wit = slot(&bi.var)?;
+
+        // If there is a term of the form `q_m · a · b` then if there is a term
+        // of the form `q_l · a` or `q_r · b` it's a left, or a right wire
+        // selector respectively, and terms of other variables take up the `o`
+        // and `d` wires in order. If there is not, the terms take up the wires
+        // in order, as it produces the same mathematical constraint.
+        match tri_slots {
+            Some((lslot, rslot)) => match (wit == lslot, wit == rslot) {
+                (false, false) => {
+                    let (wire, selector) = match gate.wires[O] {
+                        None => (O, OUTPUT),
+                        Some(_) if gate.wires[D].is_none() => (D, FOURTH),
+                        Some(_) => {
+                            return Err(format!("`{}` is a fifth variable of a gate", bi.var))
+                        }
+                    };
+                    gate.wires[wire] = Some(wit);
+                    gate.selectors[selector] = bi.coeff;
+                }
+                (true, false) => gate.selectors[LEFT] = bi.coeff,
+                (false, true) => gate.selectors[RIGHT] = bi.coeff,
+                _ => return Err(format!("`{}` is on both the a and b wires", bi.var)),
+            },
+            None => {
+                let (wire, selector) = match bi_num {
+                    0 => (A, LEFT),
+                    1 => (B, RIGHT),
+                    2 => (O, OUTPUT),
+                    3 => (D, FOURTH),
+                    _ => return Err(format!("`{}` is a fifth term of one variable", bi.var)),
+                };
+                gate.wires[wire] = Some(wit);
+                gate.selectors[selector] = bi.coeff;
+            }
+        }
+    }
+
+    Ok(gate)
+}
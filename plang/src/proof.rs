@@ -0,0 +1,86 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::artifact::{self, ArtifactKind};
+use crate::error::{Error as PlangError, ProveError, Result};
+
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+
+/// A proof, together with the transcript label it was produced with.
+#[derive(Debug)]
+pub struct PlangProof {
+    /// The proof itself.
+    pub proof: Proof,
+    /// Transcript label the proof was produced with. Proofs written before
+    /// labels were recorded don't have one.
+    pub label: Option<Vec<u8>>,
+}
+
+impl PlangProof {
+    /// Bundles a proof with the transcript label it was produced with.
+    pub fn new(proof: Proof, label: Vec<u8>) -> Self {
+        Self {
+            proof,
+            label: Some(label),
+        }
+    }
+
+    /// Serializes the proof, including its artifact header.
+    ///
+    /// After the header, the transcript label prefixed by its length as a
+    /// little endian `u32` is followed by the proof.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let label = self.label.as_deref().unwrap_or_default();
+
+        let mut bytes = Vec::with_capacity(4 + label.len() + Proof::SIZE);
+
+        bytes.extend(&(label.len() as u32).to_le_bytes());
+        bytes.extend(label);
+        bytes.extend(&self.proof.to_bytes());
+
+        artifact::wrap(ArtifactKind::Proof, 0, &bytes)
+    }
+
+    /// Deserializes a proof written by [`to_bytes`](Self::to_bytes), or a
+    /// bare proof as written before labels were recorded.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let (bytes, _) = artifact::unwrap(ArtifactKind::Proof, bytes)?;
+
+        if bytes.len() == Proof::SIZE {
+            return Ok(Self {
+                proof: read_proof(bytes)?,
+                label: None,
+            });
+        }
+
+        if bytes.len() < 4 {
+            return Err(PlangError::Prove(ProveError::InvalidProof));
+        }
+        let (len, rest) = bytes.split_at(4);
+        let len = u32::from_le_bytes(len.try_into().expect("split at 4 bytes")) as usize;
+
+        if rest.len() != len + Proof::SIZE {
+            return Err(PlangError::Prove(ProveError::InvalidProof));
+        }
+        let (label, proof) = rest.split_at(len);
+
+        Ok(Self {
+            proof: read_proof(proof)?,
+            label: Some(label.to_vec()),
+        })
+    }
+}
+
+fn read_proof(bytes: &[u8]) -> Result<Proof> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| PlangError::Prove(ProveError::InvalidProof))?;
+    Proof::from_bytes(bytes).map_err(|_| PlangError::Prove(ProveError::InvalidProof))
+}
@@ -0,0 +1,78 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Proving many assignments of the same circuit at once.
+
+use crate::error::Result;
+use crate::scalar::IntoScalar;
+use crate::PlangCircuit;
+
+use dusk_plonk::prelude::*;
+use tracing::info_span;
+
+/// Generates a proof for each of the given assignments of values to the
+/// circuit, in the order they're given in.
+///
+/// The circuit is parsed and checked once, and each assignment is proven on a
+/// copy of it with the same prover key. With the `parallel` feature the
+/// proofs are generated in parallel, on rayon's worker threads.
+///
+/// The transcript of each proof is started with the given label, which
+/// [`ProveOptions::transcript`](crate::ProveOptions::transcript) composes out
+/// of the options to prove with.
+///
+/// It returns the error of the first assignment that fails to be proven, if
+/// any - an assignment that sets a value not in the circuit, for instance.
+pub fn prove_many<I, A, B>(
+    circuit: &PlangCircuit,
+    assignments: I,
+    pk: &ProverKey,
+    pp: &PublicParameters,
+    label: &'static [u8],
+) -> Result<Vec<Proof>>
+where
+    I: IntoIterator<Item = A>,
+    A: IntoIterator<Item = (String, B)> + Send,
+    B: IntoScalar,
+{
+    prove_each(circuit, assignments, pk, pp, label)
+        .into_iter()
+        .collect()
+}
+
+/// Generates a proof for each of the given assignments as [`prove_many`]
+/// does, but returns whether each was proven, in the order they're given in.
+pub fn prove_each<I, A, B>(
+    circuit: &PlangCircuit,
+    assignments: I,
+    pk: &ProverKey,
+    pp: &PublicParameters,
+    label: &'static [u8],
+) -> Vec<Result<Proof>>
+where
+    I: IntoIterator<Item = A>,
+    A: IntoIterator<Item = (String, B)> + Send,
+    B: IntoScalar,
+{
+    let assignments: Vec<A> = assignments.into_iter().collect();
+    let _span = info_span!("prove_many", assignments = assignments.len()).entered();
+
+    let prove = |assignment: A| -> Result<Proof> {
+        let mut circuit = circuit.unassigned();
+        circuit.set_vals(assignment)?;
+        Ok(circuit.prove(pp, pk, label)?)
+    };
+
+    #[cfg(feature = "parallel")]
+    let proofs: Vec<Result<Proof>> = {
+        use rayon::prelude::*;
+        assignments.into_par_iter().map(prove).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let proofs: Vec<Result<Proof>> = assignments.into_iter().map(prove).collect();
+
+    proofs
+}
@@ -0,0 +1,171 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Auditable records of verifications.
+//!
+//! [`verify`] verifies proofs of a circuit like
+//! [`verify_batch`](crate::verifier::verify_batch) does, and records what
+//! they were verified against: the circuit hash, digests of the public
+//! parameters and verifier data, the transcript, and the public inputs of
+//! each proof - along with the outcome, when it happened and how long it
+//! took. A failed verification is recorded too, rather than returned as an
+//! error.
+
+use crate::error::{Error as PlangError, Result};
+use crate::scalar::Scalar;
+use crate::transcript::ProveOptions;
+use crate::{params, verifier, PlangCircuit};
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use blake2::{Blake2s, Digest};
+use dusk_bytes::Serializable;
+use dusk_plonk::prelude::*;
+use serde_json::{json, Value};
+
+/// A proof verified, by the digest of its bytes, with the values of the
+/// public inputs it was verified against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofRecord {
+    /// BLAKE2s digest of the proof.
+    pub proof_digest: [u8; 32],
+    /// The values of the public inputs, in the order verification expects
+    /// them in.
+    pub public_inputs: Vec<(String, Scalar)>,
+}
+
+/// The record of a verification of one or more proofs of a circuit.
+#[derive(Debug)]
+pub struct VerificationReport {
+    /// Why verification failed, if it did.
+    pub error: Option<PlangError>,
+    /// Hash of the circuit.
+    pub circuit_hash: [u8; 32],
+    /// Digest of the public parameters, as [`params::digest`] computes it.
+    pub params_digest: [u8; 32],
+    /// BLAKE2s digest of the verifier data.
+    pub verifier_data_digest: [u8; 32],
+    /// What the transcript was started with.
+    pub transcript: Vec<u8>,
+    /// The proofs verified, in the order they were given in.
+    pub proofs: Vec<ProofRecord>,
+    /// Version of plang the proofs were verified with.
+    pub compiler_version: String,
+    /// When verification started.
+    pub started: SystemTime,
+    /// How long verification took.
+    pub duration: Duration,
+}
+
+impl VerificationReport {
+    /// Whether every proof verified.
+    pub fn verified(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The outcome of the verification, as an error if it failed.
+    pub fn into_result(self) -> Result<()> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the report as JSON, with digests in hex, values in decimal,
+    /// the time verification started at in seconds since the Unix epoch, and
+    /// how long it took in seconds.
+    pub fn to_json(&self) -> Value {
+        let proofs: Vec<Value> = self
+            .proofs
+            .iter()
+            .map(|proof| {
+                let public_inputs: Vec<Value> = proof
+                    .public_inputs
+                    .iter()
+                    .map(|(name, value)| json!({ "name": name, "value": value.to_string() }))
+                    .collect();
+                json!({
+                    "proof_digest": hex::encode(proof.proof_digest),
+                    "public_inputs": public_inputs,
+                })
+            })
+            .collect();
+
+        let started = self
+            .started
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        json!({
+            "verified": self.verified(),
+            "error": self.error.as_ref().map(ToString::to_string),
+            "circuit_hash": hex::encode(self.circuit_hash),
+            "params_digest": hex::encode(self.params_digest),
+            "verifier_data_digest": hex::encode(self.verifier_data_digest),
+            "transcript_label": hex::encode(&self.transcript),
+            "compiler_version": self.compiler_version,
+            "started": started,
+            "timings": {
+                "verify": self.duration.as_secs_f64(),
+            },
+            "proofs": proofs,
+        })
+    }
+}
+
+/// Verifies proofs of the circuit, each against its public input values keyed
+/// by name, starting the transcript as the options say, and records it.
+///
+/// It returns an error if a value is not a public input of the circuit, or
+/// if a public input is missing a value, before verifying anything.
+pub fn verify(
+    circuit: &PlangCircuit,
+    pp: &PublicParameters,
+    vd: &VerifierData,
+    proofs: &[(Proof, Vec<(String, Scalar)>)],
+    options: &ProveOptions,
+) -> Result<VerificationReport> {
+    let mut arranged = Vec::with_capacity(proofs.len());
+    let mut records = Vec::with_capacity(proofs.len());
+    for (proof, vals) in proofs {
+        let named: HashMap<&str, Scalar> = vals
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect();
+        let pinputs = vals
+            .iter()
+            .map(|(name, value)| (name.clone(), BlsScalar::from(*value)));
+        arranged.push((*proof, circuit.public_inputs_from(pinputs)?));
+
+        records.push(ProofRecord {
+            proof_digest: Blake2s::digest(&proof.to_bytes()).into(),
+            public_inputs: circuit
+                .public_input_order()
+                .into_iter()
+                .map(|name| (name.to_owned(), named[name]))
+                .collect(),
+        });
+    }
+
+    let started = SystemTime::now();
+    let start = Instant::now();
+    let result = verifier::verify_batch(pp, vd, &arranged, options.transcript());
+    let duration = start.elapsed();
+
+    Ok(VerificationReport {
+        error: result.err(),
+        circuit_hash: circuit.hash(),
+        params_digest: params::digest(pp),
+        verifier_data_digest: Blake2s::digest(&vd.to_var_bytes()).into(),
+        transcript: options.transcript().to_vec(),
+        proofs: records,
+        compiler_version: env!("CARGO_PKG_VERSION").to_owned(),
+        started,
+        duration,
+    })
+}
@@ -0,0 +1,169 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Reproducibility of the keys compiled from circuits.
+//!
+//! Compiling a circuit is deterministic, so the keys released for it can be
+//! checked by compiling its source again with the same public parameters, and
+//! comparing them byte for byte. [`check`] does so for a single circuit, and
+//! [`check_manifest`] for every circuit listed in a build manifest - a JSON
+//! file of the form:
+//!
+//! ```json
+//! {
+//!   "params": "setup.pp",
+//!   "circuits": [
+//!     { "source": "transfer.plang", "pk": "transfer.pk", "vd": "transfer.vd" },
+//!     { "source": "stake.plang", "vd": "stake.vd", "params": "other.pp" }
+//!   ]
+//! }
+//! ```
+//!
+//! Paths are relative to the directory of the manifest. Each circuit is
+//! compiled with its own parameters, if it names any, or with those of the
+//! manifest otherwise, and either of its keys can be left out.
+
+use crate::artifact::{self, ArtifactKind};
+use crate::error::{Error as PlangError, ProveError, Result};
+use crate::{params, PlangCircuit};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use dusk_plonk::prelude::*;
+use serde_json::Value;
+
+/// Compiles the source of a circuit with the given public parameters, and
+/// checks the prover key and verifier data compiled are the same as the ones
+/// given, byte for byte. The keys can be given with or without their artifact
+/// header.
+///
+/// It returns [`KeysMismatch`](PlangError::Prove(ProveError::KeysMismatch)) if either isn't.
+pub fn check(source: &str, pp: &PublicParameters, pk: &[u8], vd: &[u8]) -> Result<()> {
+    check_keys(source, pp, Some(pk), Some(vd))
+}
+
+fn check_keys(
+    source: &str,
+    pp: &PublicParameters,
+    pk: Option<&[u8]>,
+    vd: Option<&[u8]>,
+) -> Result<()> {
+    let mut circuit = PlangCircuit::parse(source)?;
+    let (cpk, cvd) = circuit.compile(pp)?;
+
+    if let Some(pk) = pk {
+        let (pk, _) = artifact::unwrap(ArtifactKind::ProverKey, pk)?;
+        if pk != cpk.to_var_bytes() {
+            return Err(PlangError::Prove(ProveError::KeysMismatch));
+        }
+    }
+    if let Some(vd) = vd {
+        let (vd, _) = artifact::unwrap(ArtifactKind::VerifierData, vd)?;
+        if vd != cvd.to_var_bytes() {
+            return Err(PlangError::Prove(ProveError::KeysMismatch));
+        }
+    }
+
+    Ok(())
+}
+
+/// A circuit listed in a build manifest, with the keys released for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// The source of the circuit.
+    pub source: PathBuf,
+    /// The public parameters the keys were compiled with.
+    pub params: PathBuf,
+    /// The prover key, if it's released.
+    pub pk: Option<PathBuf>,
+    /// The verifier data, if it's released.
+    pub vd: Option<PathBuf>,
+}
+
+/// The circuits of a build, with the keys released for each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// The circuits, in the order they're listed in.
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Reads a manifest from a file, resolving the paths in it against the
+    /// directory of the file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json: Value = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|err| PlangError::Prove(ProveError::InvalidManifest(err.to_string())))?;
+
+        Self::from_json(&json, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Reads a manifest from JSON, resolving the paths in it against the
+    /// given directory.
+    pub fn from_json(json: &Value, base: &Path) -> Result<Self> {
+        let invalid = |msg: &str| PlangError::Prove(ProveError::InvalidManifest(msg.to_owned()));
+        let path = |value: &Value, field: &str| -> Result<Option<PathBuf>> {
+            match value.get(field) {
+                None => Ok(None),
+                Some(Value::String(path)) => Ok(Some(base.join(path))),
+                Some(_) => Err(invalid(&format!("`{}` is not a path", field))),
+            }
+        };
+
+        let params = path(json, "params")?;
+        let circuits = json
+            .get("circuits")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("`circuits` is not a list"))?;
+
+        let entries = circuits
+            .iter()
+            .map(|circuit| {
+                let source =
+                    path(circuit, "source")?.ok_or_else(|| invalid("a circuit has no `source`"))?;
+                let params = path(circuit, "params")?
+                    .or_else(|| params.clone())
+                    .ok_or_else(|| invalid(&format!("no `params` for {}", source.display())))?;
+
+                Ok(ManifestEntry {
+                    params,
+                    pk: path(circuit, "pk")?,
+                    vd: path(circuit, "vd")?,
+                    source,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { entries })
+    }
+}
+
+/// Checks the keys of every circuit in the manifest reproduce, as [`check`]
+/// does, returning the outcome for each, in order. Parameters shared between
+/// circuits are only read once.
+pub fn check_manifest(manifest: &Manifest) -> Vec<Result<()>> {
+    let mut pps: HashMap<&Path, PublicParameters> = HashMap::new();
+
+    manifest
+        .entries
+        .iter()
+        .map(|entry| {
+            let source = fs::read_to_string(&entry.source)?;
+
+            if !pps.contains_key(entry.params.as_path()) {
+                let (pp, _) = params::read_file(&entry.params)?;
+                pps.insert(&entry.params, pp);
+            }
+            let pp = &pps[entry.params.as_path()];
+
+            let pk = entry.pk.as_ref().map(fs::read).transpose()?;
+            let vd = entry.vd.as_ref().map(fs::read).transpose()?;
+            check_keys(&source, pp, pk.as_deref(), vd.as_deref())
+        })
+        .collect()
+}
@@ -0,0 +1,584 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Scalars of the BLS12-381 curve, independent of any proving backend.
+//!
+//! Coefficients and values are kept as [`Scalar`]s, so parsing and analysing
+//! circuits doesn't depend on dusk-plonk and its curve arithmetic. Only what
+//! the language itself needs - the arithmetic of coefficients computed while
+//! lowering, and their canonical encoding - is implemented, favouring
+//! simplicity over speed. With the `verifier` feature they convert to and
+//! from dusk-plonk's `BlsScalar`, through the same encoding.
+//!
+//! Values given by callers, as integers, decimal or hex text, or bytes, are
+//! [`ScalarValue`]s, and are all converted to `BlsScalar`s by
+//! [`ScalarValue::to_scalar`]. Whatever is assigned to a circuit is converted
+//! through [`IntoScalar`].
+
+use core::fmt;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use zeroize::Zeroize;
+
+/// The modulus of the scalar field, in little endian 64 bit limbs.
+const MODULUS: [u64; 4] = [
+    0xffff_ffff_0000_0001,
+    0x53bd_a402_fffe_5bfe,
+    0x3339_d808_09a1_d805,
+    0x73ed_a753_299d_7d48,
+];
+
+/// The modulus, negated and inverted modulo `2^64`, for Montgomery reduction.
+const INV: u64 = 0xffff_fffe_ffff_ffff;
+
+/// `2^512` modulo the modulus, which takes a Montgomery product back to the
+/// canonical form.
+const R2: [u64; 4] = [
+    0xc999_e990_f3f2_9c6d,
+    0x2b6c_edcb_8792_5c23,
+    0x05d3_1496_7254_398f,
+    0x0748_d9d9_9f59_ff11,
+];
+
+/// An element of the scalar field of BLS12-381, in canonical form.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scalar([u64; 4]);
+
+impl Scalar {
+    pub const fn zero() -> Self {
+        Self([0; 4])
+    }
+
+    pub const fn one() -> Self {
+        Self([1, 0, 0, 0])
+    }
+
+    /// A scalar from its little endian 64 bit limbs, which must already be
+    /// less than the modulus.
+    #[cfg(feature = "std")]
+    pub(crate) const fn from_raw(limbs: [u64; 4]) -> Self {
+        Self(limbs)
+    }
+
+    /// Encodes the scalar in 32 little endian bytes, as `BlsScalar` does.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(&self.0) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a scalar encoded by [`to_bytes`](Self::to_bytes), or returns
+    /// `None` if the bytes encode a number that isn't less than the modulus.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let mut limbs = [0; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut le = [0; 8];
+            le.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(le);
+        }
+
+        // Limbs are compared from the most significant one down.
+        for (limb, modulus) in limbs.iter().rev().zip(MODULUS.iter().rev()) {
+            if limb != modulus {
+                return match limb < modulus {
+                    true => Some(Self(limbs)),
+                    false => None,
+                };
+            }
+        }
+
+        // The bytes encode the modulus itself.
+        None
+    }
+
+    /// Parses a number written in decimal, of any size, reducing it modulo
+    /// the modulus. Whether the number had to be reduced - whether it's not
+    /// less than the modulus - is returned with it.
+    ///
+    /// Returns `None` if the text is empty, or has anything but the digits
+    /// `0` to `9`.
+    pub fn from_decimal(text: &str) -> Option<(Self, bool)> {
+        if text.is_empty() {
+            return None;
+        }
+
+        let mut limbs = [0; 4];
+        let mut reduced = false;
+
+        for digit in text.bytes() {
+            if !digit.is_ascii_digit() {
+                return None;
+            }
+
+            // The number so far times ten, plus the digit, takes five limbs.
+            let mut wide = [0; 5];
+            let mut carry = u128::from(digit - b'0');
+            for (wide, limb) in wide.iter_mut().zip(&limbs) {
+                let product = u128::from(*limb) * 10 + carry;
+                *wide = product as u64;
+                carry = product >> 64;
+            }
+            wide[4] = carry as u64;
+
+            // It's less than eleven times the modulus, so subtracting it a
+            // few times is enough to reduce it.
+            while !less_than_modulus(&wide) {
+                sub_modulus(&mut wide);
+                reduced = true;
+            }
+            limbs.copy_from_slice(&wide[..4]);
+        }
+
+        Some((Self(limbs), reduced))
+    }
+
+    /// Reduces a number of any size, encoded in little endian bytes, modulo
+    /// the modulus.
+    pub fn from_bytes_wide(bytes: &[u8]) -> Self {
+        let base = Self::from(256);
+        bytes.iter().rev().fold(Self::zero(), |acc, byte| {
+            acc * base + Self::from(u64::from(*byte))
+        })
+    }
+
+    /// Returns whether the scalar is more than half the modulus, meaning its
+    /// negation is less than it. Such scalars are taken as negative numbers.
+    pub fn is_negative(&self) -> bool {
+        let negated = -*self;
+
+        // Limbs are compared from the most significant one down.
+        for (limb, negated) in self.0.iter().rev().zip(negated.0.iter().rev()) {
+            if limb != negated {
+                return limb > negated;
+            }
+        }
+        false
+    }
+
+    /// Raises the scalar to the given power.
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut acc = Self::one();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = acc * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        acc
+    }
+
+    /// Returns the inverse of the scalar, or `None` if it's zero.
+    pub fn invert(self) -> Option<Self> {
+        if self == Self::zero() {
+            return None;
+        }
+
+        // By Fermat's little theorem, the scalar to the power of the modulus
+        // minus two, which doesn't borrow past its lowest limb.
+        let mut exp = MODULUS;
+        exp[0] -= 2;
+
+        let mut acc = Self::one();
+        for limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                acc = acc * acc;
+                if (limb >> bit) & 1 == 1 {
+                    acc = acc * self;
+                }
+            }
+        }
+        Some(acc)
+    }
+
+    /// Returns the scalar as a `u64`, if it fits in one.
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.0[1..].iter().all(|limb| *limb == 0) {
+            true => Some(self.0[0]),
+            false => None,
+        }
+    }
+}
+
+/// Whether a number in five little endian limbs is less than the modulus.
+fn less_than_modulus(wide: &[u64; 5]) -> bool {
+    if wide[4] != 0 {
+        return false;
+    }
+
+    for (limb, modulus) in wide[..4].iter().rev().zip(MODULUS.iter().rev()) {
+        if limb != modulus {
+            return limb < modulus;
+        }
+    }
+    false
+}
+
+/// Subtracts the modulus from a number in five little endian limbs, which
+/// must not be less than it.
+fn sub_modulus(wide: &mut [u64; 5]) {
+    let mut borrow = false;
+    for (limb, modulus) in wide.iter_mut().zip(&MODULUS) {
+        let (diff, b1) = limb.overflowing_sub(*modulus);
+        let (diff, b2) = diff.overflowing_sub(borrow as u64);
+        *limb = diff;
+        borrow = b1 || b2;
+    }
+    wide[4] -= borrow as u64;
+}
+
+/// Formats the scalar in decimal.
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The largest power of ten that fits in a `u64`. The scalar is divided
+        // by it repeatedly, giving 19 digits at a time from the least
+        // significant ones up, and fits in five such chunks.
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+
+        let mut limbs = self.0;
+        let mut chunks = [0u64; 5];
+        let mut len = 0;
+
+        loop {
+            let mut rem = 0u128;
+            for limb in limbs.iter_mut().rev() {
+                let cur = (rem << 64) | u128::from(*limb);
+                *limb = (cur / CHUNK) as u64;
+                rem = cur % CHUNK;
+            }
+
+            chunks[len] = rem as u64;
+            len += 1;
+
+            if limbs == [0; 4] {
+                break;
+            }
+        }
+
+        write!(f, "{}", chunks[len - 1])?;
+        for chunk in chunks[..len - 1].iter().rev() {
+            write!(f, "{:019}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<u64> for Scalar {
+    fn from(n: u64) -> Self {
+        Self([n, 0, 0, 0])
+    }
+}
+
+impl Zeroize for Scalar {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        if self == Self::zero() {
+            return self;
+        }
+
+        // The modulus minus the scalar, which is less than the modulus.
+        let mut limbs = [0; 4];
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (diff, b1) = MODULUS[i].overflowing_sub(self.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+
+        Self(limbs)
+    }
+}
+
+impl Add for Scalar {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut wide = [0; 5];
+        let mut carry = false;
+        for (i, limb) in wide[..4].iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        wide[4] = carry as u64;
+
+        // Both are less than the modulus, so their sum is less than twice it.
+        if !less_than_modulus(&wide) {
+            sub_modulus(&mut wide);
+        }
+
+        let mut limbs = [0; 4];
+        limbs.copy_from_slice(&wide[..4]);
+        Self(limbs)
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for Scalar {
+    type Output = Self;
+
+    /// Multiplies with Montgomery reduction, which divides the product by
+    /// `2^256`, so the product is multiplied by `2^512` the same way.
+    fn mul(self, rhs: Self) -> Self {
+        Self(mont_mul(&mont_mul(&self.0, &rhs.0), &R2))
+    }
+}
+
+/// Multiplies two numbers less than the modulus, and divides the product by
+/// `2^256`, modulo the modulus.
+fn mont_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut wide = [0; 5];
+
+    for limb in b {
+        // Adds a times the limb, then a multiple of the modulus that clears
+        // the lowest limb, and shifts the lowest limb out.
+        let mut carry = 0;
+        for (wide, a) in wide.iter_mut().zip(a) {
+            let sum = u128::from(*wide) + u128::from(*a) * u128::from(*limb) + carry;
+            *wide = sum as u64;
+            carry = sum >> 64;
+        }
+        let top = u128::from(wide[4]) + carry;
+
+        let m = wide[0].wrapping_mul(INV);
+        let mut carry = (u128::from(wide[0]) + u128::from(m) * u128::from(MODULUS[0])) >> 64;
+        for i in 1..4 {
+            let sum = u128::from(wide[i]) + u128::from(m) * u128::from(MODULUS[i]) + carry;
+            wide[i - 1] = sum as u64;
+            carry = sum >> 64;
+        }
+        let top = top + carry;
+        wide[3] = top as u64;
+        wide[4] = (top >> 64) as u64;
+    }
+
+    // It's less than twice the modulus.
+    if !less_than_modulus(&wide) {
+        sub_modulus(&mut wide);
+    }
+
+    let mut limbs = [0; 4];
+    limbs.copy_from_slice(&wide[..4]);
+    limbs
+}
+
+#[cfg(feature = "verifier")]
+mod convert {
+    use super::Scalar;
+
+    use dusk_bytes::Serializable;
+    use dusk_plonk::prelude::BlsScalar;
+
+    impl From<Scalar> for BlsScalar {
+        fn from(scalar: Scalar) -> Self {
+            BlsScalar::from_bytes(&scalar.to_bytes()).expect("scalars are canonical")
+        }
+    }
+
+    impl From<BlsScalar> for Scalar {
+        fn from(scalar: BlsScalar) -> Self {
+            Scalar::from_bytes(&scalar.to_bytes()).expect("scalars are canonical")
+        }
+    }
+}
+
+#[cfg(feature = "verifier")]
+pub use value::{IntoScalar, ScalarValue};
+
+#[cfg(feature = "verifier")]
+mod value {
+    use super::Scalar;
+    use crate::error::{AssignmentError, Error as PlangError, Result};
+
+    use alloc::format;
+    use alloc::string::{String, ToString};
+    use core::str::FromStr;
+
+    use dusk_plonk::prelude::BlsScalar;
+
+    /// A value given for a variable, in any of the forms it's commonly
+    /// written in.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ScalarValue {
+        /// An integer.
+        U64(u64),
+        /// A number in hex, most significant digit first, with or without a
+        /// `0x` prefix.
+        Hex(String),
+        /// A number in decimal, with a leading `-` if it's negative, standing
+        /// for its additive inverse.
+        Decimal(String),
+        /// A scalar in its canonical encoding - 32 little endian bytes.
+        Bytes([u8; 32]),
+        /// A scalar.
+        Scalar(BlsScalar),
+    }
+
+    impl ScalarValue {
+        /// Converts the value into a scalar.
+        ///
+        /// It returns an error if the text isn't a number, or if the number
+        /// or the bytes aren't less than the modulus - numbers are never
+        /// reduced.
+        pub fn to_scalar(&self) -> Result<BlsScalar> {
+            let scalar = match self {
+                Self::U64(n) => Some(Scalar::from(*n)),
+                Self::Hex(text) => {
+                    let digits = text
+                        .strip_prefix("0x")
+                        .or_else(|| text.strip_prefix("0X"))
+                        .unwrap_or(text);
+                    from_hex(digits)
+                }
+                Self::Decimal(text) => {
+                    let (minus, digits) = match text.strip_prefix('-') {
+                        Some(digits) => (true, digits),
+                        None => (false, text.as_str()),
+                    };
+                    match Scalar::from_decimal(digits) {
+                        Some((scalar, false)) if minus => Some(-scalar),
+                        Some((scalar, false)) => Some(scalar),
+                        _ => None,
+                    }
+                }
+                Self::Bytes(bytes) => Scalar::from_bytes(bytes),
+                Self::Scalar(scalar) => return Ok(*scalar),
+            };
+
+            scalar.map(BlsScalar::from).ok_or_else(|| {
+                PlangError::Assignment(AssignmentError::InvalidValue(self.to_string()))
+            })
+        }
+
+        /// Reads a value from JSON - an integer, negative ones standing for
+        /// their additive inverse, or a string holding a number in decimal
+        /// or, with a `0x` prefix, in hex.
+        #[cfg(feature = "std")]
+        pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+            match json {
+                serde_json::Value::Number(n) => n
+                    .as_u64()
+                    .map(Self::from)
+                    .or_else(|| n.as_i64().map(Self::from)),
+                serde_json::Value::String(text) => text.parse().ok(),
+                _ => None,
+            }
+        }
+    }
+
+    /// A number of up to 64 hex digits, most significant first, if it's less
+    /// than the modulus.
+    fn from_hex(digits: &str) -> Option<Scalar> {
+        if digits.is_empty() || digits.len() > 64 {
+            return None;
+        }
+
+        let mut bytes = [0; 32];
+        for (i, digit) in digits.chars().rev().enumerate() {
+            bytes[i / 2] |= (digit.to_digit(16)? as u8) << (4 * (i % 2));
+        }
+        Scalar::from_bytes(&bytes)
+    }
+
+    /// Formats the value as it was given, with bytes in hex.
+    impl core::fmt::Display for ScalarValue {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self {
+                Self::U64(n) => write!(f, "{}", n),
+                Self::Hex(text) | Self::Decimal(text) => f.write_str(text),
+                Self::Bytes(bytes) => bytes.iter().try_for_each(|b| write!(f, "{:02x}", b)),
+                Self::Scalar(scalar) => write!(f, "{}", Scalar::from(*scalar)),
+            }
+        }
+    }
+
+    /// Parses a number, in hex if it has a `0x` prefix, and in decimal
+    /// otherwise.
+    impl FromStr for ScalarValue {
+        type Err = PlangError;
+
+        fn from_str(text: &str) -> Result<Self> {
+            let value = match text.starts_with("0x") || text.starts_with("0X") {
+                true => Self::Hex(text.to_string()),
+                false => Self::Decimal(text.to_string()),
+            };
+            value.to_scalar()?;
+            Ok(value)
+        }
+    }
+
+    impl From<u64> for ScalarValue {
+        fn from(n: u64) -> Self {
+            Self::U64(n)
+        }
+    }
+
+    /// Negative integers stand for their additive inverse.
+    impl From<i64> for ScalarValue {
+        fn from(n: i64) -> Self {
+            match n.is_negative() {
+                true => Self::Decimal(format!("-{}", n.unsigned_abs())),
+                false => Self::U64(n as u64),
+            }
+        }
+    }
+
+    impl From<[u8; 32]> for ScalarValue {
+        fn from(bytes: [u8; 32]) -> Self {
+            Self::Bytes(bytes)
+        }
+    }
+
+    impl From<BlsScalar> for ScalarValue {
+        fn from(scalar: BlsScalar) -> Self {
+            Self::Scalar(scalar)
+        }
+    }
+
+    /// A value that can be assigned to a variable, converting into a scalar
+    /// unless it's invalid.
+    pub trait IntoScalar {
+        fn into_scalar(self) -> Result<BlsScalar>;
+    }
+
+    impl<T: Into<BlsScalar>> IntoScalar for T {
+        fn into_scalar(self) -> Result<BlsScalar> {
+            Ok(self.into())
+        }
+    }
+
+    impl IntoScalar for ScalarValue {
+        fn into_scalar(self) -> Result<BlsScalar> {
+            self.to_scalar()
+        }
+    }
+
+    impl IntoScalar for &ScalarValue {
+        fn into_scalar(self) -> Result<BlsScalar> {
+            self.to_scalar()
+        }
+    }
+}
@@ -0,0 +1,378 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Constants, and the blocks of lines conditional on them.
+//!
+//! A circuit can declare constants, such as `const N = 64`, and wrap lines in
+//! `if N > 16 {` and `}`, optionally with `} else {` in between, so a single
+//! source can describe circuits of different shapes. Each of these directives
+//! is a line of its own, and is lowered on its own like any other line. The
+//! lines are then walked through in order with a [`Scope`], which keeps track
+//! of the constants declared so far and of the blocks the current line is in,
+//! to tell whether the line is compiled.
+//!
+//! Coefficients and the bounds of sums can be arithmetic on constants, such as
+//! `(N - 1)`, which is evaluated in the field with [`eval`], in an [`Env`] of
+//! the constants declared above the line.
+
+use crate::error::{Error as PlangError, Result, SemanticError};
+use crate::grammar::{NodeRef, Rule};
+use crate::scalar::Scalar;
+use crate::symbol::{Symbol, Symbols};
+
+use std::collections::HashMap;
+
+/// A directive declaring a constant, or opening or closing a block.
+#[derive(Debug, Clone)]
+pub(crate) enum Directive {
+    Const { name: String, value: String },
+    If(Condition),
+    Else,
+    End,
+}
+
+/// A comparison between two constants or integers.
+#[derive(Debug, Clone)]
+pub(crate) struct Condition {
+    left: Operand,
+    cmp: Cmp,
+    right: Operand,
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Constant(String),
+    Value(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Cmp {
+    Eq,
+    Ne,
+    Le,
+    Ge,
+    Lt,
+    Gt,
+}
+
+/// Lowers a node into the directive it is, if it's a directive on constants
+/// or blocks.
+///
+/// Values are kept as written, and only parsed once they're used, so that
+/// values in blocks that aren't taken are never parsed.
+pub(crate) fn lower_directive(node: NodeRef<'_>) -> Option<Directive> {
+    let directive = match node.rule() {
+        Rule::const_decl => {
+            let mut children = node.children();
+            let name = children.next().expect("the grammar defines the constant");
+            let value = children.next().expect("the grammar defines the constant");
+
+            Directive::Const {
+                name: name.as_str().to_owned(),
+                value: value.as_str().to_owned(),
+            }
+        }
+        Rule::if_open => {
+            let condition = node.children().next().expect("the grammar defines the if");
+            Directive::If(lower_condition(condition))
+        }
+        Rule::else_open => Directive::Else,
+        Rule::block_end => Directive::End,
+        _ => return None,
+    };
+
+    Some(directive)
+}
+
+fn lower_condition(node: NodeRef<'_>) -> Condition {
+    let mut children = node.children();
+    let mut next = || children.next().expect("the grammar defines the condition");
+
+    let left = lower_operand(next());
+    let cmp = match next().as_str() {
+        "==" => Cmp::Eq,
+        "!=" => Cmp::Ne,
+        "<=" => Cmp::Le,
+        ">=" => Cmp::Ge,
+        "<" => Cmp::Lt,
+        ">" => Cmp::Gt,
+        _ => unreachable!(),
+    };
+    let right = lower_operand(next());
+
+    Condition { left, cmp, right }
+}
+
+fn lower_operand(node: NodeRef<'_>) -> Operand {
+    match node.rule() {
+        Rule::constant => Operand::Constant(node.as_str().to_owned()),
+        _ => Operand::Value(node.as_str().to_owned()),
+    }
+}
+
+/// The constants declared so far, and the blocks the current line is in.
+#[derive(Debug, Default)]
+pub(crate) struct Scope {
+    consts: Consts,
+    blocks: Vec<Block>,
+}
+
+/// Constants, with their values and the lines they're declared on.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Consts(HashMap<String, (usize, u64)>);
+
+impl Consts {
+    /// Collects the constants the directives among the nodes declare, in the
+    /// blocks that are taken. Directives with errors are skipped, since the
+    /// errors are reported when the nodes are walked through in order.
+    pub(crate) fn collect<'g, I: IntoIterator<Item = NodeRef<'g>>>(nodes: I) -> Self {
+        let mut scope = Scope::default();
+        for node in nodes {
+            if let Some(directive) = lower_directive(node) {
+                let _ = scope.apply(&directive, node.line());
+            }
+        }
+        scope.consts
+    }
+
+    /// The value of the constant, if it's declared above the given line.
+    fn get(&self, name: &str, line: usize) -> Option<u64> {
+        self.0
+            .get(name)
+            .filter(|(declared, _)| *declared < line)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// What the names in arithmetic on constants on a line stand for - the
+/// constants declared above the line, and the index of the sum the line is,
+/// if any - along with the table the names of variables are interned in.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Env<'a> {
+    consts: &'a Consts,
+    symbols: &'a Symbols,
+    line: usize,
+    index: Option<(&'a str, u64)>,
+}
+
+impl<'a> Env<'a> {
+    /// The names on the given line.
+    pub(crate) fn new(consts: &'a Consts, symbols: &'a Symbols, line: usize) -> Self {
+        Self {
+            consts,
+            symbols,
+            line,
+            index: None,
+        }
+    }
+
+    /// The names, with the index of a sum standing for the given value.
+    pub(crate) fn with_index(self, index: &'a str, value: u64) -> Self {
+        Self {
+            index: Some((index, value)),
+            ..self
+        }
+    }
+
+    /// The line the names are on.
+    pub(crate) fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The symbol for the name of a variable.
+    pub(crate) fn intern(&self, name: &str) -> Symbol {
+        self.symbols.intern(name)
+    }
+
+    fn get(&self, name: &str) -> Result<u64> {
+        match self.index {
+            Some((index, value)) if index == name => Ok(value),
+            _ => self.consts.get(name, self.line).ok_or_else(|| {
+                PlangError::Semantic(SemanticError::UndefinedConstant(name.to_owned()))
+            }),
+        }
+    }
+}
+
+/// Evaluates arithmetic on constants in the field - a `value`, a `constant`,
+/// or a `const_expr` - in the given environment.
+///
+/// Shifts and exponents are repeated multiplication, so their right operand
+/// has to be an integer that fits in a `u64`.
+pub(crate) fn eval(node: NodeRef<'_>, env: &Env<'_>) -> Result<Scalar> {
+    match node.rule() {
+        Rule::value => Ok(Scalar::from_decimal(node.as_str())
+            .expect("the grammar only allows digits in values")
+            .0),
+        Rule::constant => Ok(Scalar::from(env.get(node.as_str())?)),
+        Rule::const_expr => {
+            let mut operands = vec![];
+            let mut ops = vec![];
+            for child in node.children() {
+                match child.rule() {
+                    Rule::const_op => ops.push(child.as_str()),
+                    _ => operands.push(eval(child, env)?),
+                }
+            }
+            climb(&operands, &ops, node.as_str())
+        }
+        rule => unreachable!("{:?} is not arithmetic on constants", rule),
+    }
+}
+
+/// How tightly each operator binds.
+fn precedence(op: &str) -> u8 {
+    match op {
+        "^" => 3,
+        "*" | "·" => 2,
+        "+" | "-" => 1,
+        _ => 0,
+    }
+}
+
+/// Evaluates the operands with the operators between them, binding the
+/// operators by precedence - all left associative, but for `^`.
+fn climb(operands: &[Scalar], ops: &[&str], text: &str) -> Result<Scalar> {
+    // The operator binding the loosest, and last among those, splits the
+    // operands in two - or the first, for the right associative `^`.
+    let split = ops
+        .iter()
+        .enumerate()
+        .min_by_key(|(i, op)| {
+            let position = match **op {
+                "^" => ops.len() - i,
+                _ => *i,
+            };
+            (precedence(op), usize::MAX - position)
+        })
+        .map(|(i, _)| i);
+
+    let i = match split {
+        None => return Ok(operands[0]),
+        Some(i) => i,
+    };
+
+    let left = climb(&operands[..=i], &ops[..i], text)?;
+    let right = climb(&operands[i + 1..], &ops[i + 1..], text)?;
+    let exponent = || {
+        right
+            .to_u64()
+            .ok_or_else(|| PlangError::Semantic(SemanticError::InvalidConstExpr(text.to_owned())))
+    };
+
+    Ok(match ops[i] {
+        "+" => left + right,
+        "-" => left - right,
+        "<<" => left * Scalar::from(2).pow(exponent()?),
+        "^" => left.pow(exponent()?),
+        _ => left * right,
+    })
+}
+
+/// A block opened by an `if`.
+#[derive(Debug)]
+struct Block {
+    /// Whether the condition of the block holds.
+    holds: bool,
+    /// Whether the `else` of the block was reached.
+    other: bool,
+}
+
+impl Block {
+    fn is_active(&self) -> bool {
+        self.holds != self.other
+    }
+}
+
+impl Scope {
+    /// Returns whether the current line is compiled - that is, whether it's
+    /// in the taken branch of every block it's in.
+    pub(crate) fn is_active(&self) -> bool {
+        self.blocks.iter().all(Block::is_active)
+    }
+
+    /// Applies a directive on the given line.
+    ///
+    /// Constants declared in blocks that aren't taken aren't declared, and
+    /// conditions in them aren't evaluated, but blocks are still opened and
+    /// closed.
+    pub(crate) fn apply(&mut self, directive: &Directive, line: usize) -> Result<()> {
+        let active = self.is_active();
+
+        match directive {
+            Directive::Const { name, value } if active => {
+                let value = (line, value.parse()?);
+                if self.consts.0.insert(name.clone(), value).is_some() {
+                    return Err(PlangError::Semantic(SemanticError::RepeatedConstant(
+                        name.clone(),
+                    )));
+                }
+            }
+            Directive::Const { .. } => {}
+            Directive::If(condition) => {
+                let holds = active && self.holds(condition)?;
+                self.blocks.push(Block {
+                    holds,
+                    other: false,
+                });
+            }
+            Directive::Else => match self.blocks.last_mut() {
+                Some(block) if !block.other => block.other = true,
+                _ => return Err(PlangError::Semantic(SemanticError::UnbalancedBlock)),
+            },
+            Directive::End => {
+                self.blocks
+                    .pop()
+                    .ok_or(PlangError::Semantic(SemanticError::UnbalancedBlock))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks every block opened was closed, once all lines are walked
+    /// through.
+    pub(crate) fn finish(&self) -> Result<()> {
+        match self.blocks.is_empty() {
+            true => Ok(()),
+            false => Err(PlangError::Semantic(SemanticError::UnbalancedBlock)),
+        }
+    }
+
+    fn holds(&self, condition: &Condition) -> Result<bool> {
+        let left = self.operand(&condition.left)?;
+        let right = self.operand(&condition.right)?;
+
+        Ok(match condition.cmp {
+            Cmp::Eq => left == right,
+            Cmp::Ne => left != right,
+            Cmp::Le => left <= right,
+            Cmp::Ge => left >= right,
+            Cmp::Lt => left < right,
+            Cmp::Gt => left > right,
+        })
+    }
+
+    /// The constants declared so far.
+    pub(crate) fn consts(&self) -> &Consts {
+        &self.consts
+    }
+
+    fn operand(&self, operand: &Operand) -> Result<u64> {
+        match operand {
+            Operand::Constant(name) => {
+                self.consts
+                    .0
+                    .get(name)
+                    .map(|(_, value)| *value)
+                    .ok_or_else(|| {
+                        PlangError::Semantic(SemanticError::UndefinedConstant(name.clone()))
+                    })
+            }
+            Operand::Value(value) => Ok(value.parse()?),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Proving and verifying with several circuits over the same parameters.
+//!
+//! Applications built on a handful of related circuits would otherwise keep a
+//! copy of the public parameters next to each of them. A [`ProvingSession`]
+//! holds one copy for all of them, together with the keys each circuit was
+//! compiled into, and proves and verifies by circuit name.
+
+use crate::error::{Error as PlangError, ProveError, Result};
+use crate::scalar::IntoScalar;
+use crate::{params, PlangCircuit, PlangKeys, ProveOptions};
+
+use std::collections::HashMap;
+
+use dusk_plonk::prelude::*;
+use tracing::info_span;
+
+/// Public parameters shared by several compiled circuits, each known by name.
+#[derive(Debug)]
+pub struct ProvingSession {
+    pp: PublicParameters,
+    circuits: HashMap<String, Compiled>,
+}
+
+/// A circuit of a session, with its keys.
+#[derive(Debug)]
+struct Compiled {
+    circuit: PlangCircuit,
+    prover_key: ProverKey,
+    verifier_data: VerifierData,
+    options: ProveOptions,
+}
+
+impl ProvingSession {
+    /// Starts a session with no circuits, over the given parameters.
+    pub fn new(pp: PublicParameters) -> Self {
+        Self {
+            pp,
+            circuits: HashMap::new(),
+        }
+    }
+
+    /// The parameters the circuits of the session are compiled with.
+    pub fn pp(&self) -> &PublicParameters {
+        &self.pp
+    }
+
+    /// Compiles a circuit, and adds it to the session under the given name.
+    /// It's proven with the transcript label it declares, or otherwise
+    /// "dusk_plang".
+    ///
+    /// A circuit already in the session under the same name is replaced.
+    pub fn add<S: Into<String>>(&mut self, name: S, circuit: PlangCircuit) -> Result<()> {
+        let options = ProveOptions::for_circuit(&circuit);
+        self.add_with_options(name, circuit, options)
+    }
+
+    /// Compiles a circuit, and adds it to the session under the given name,
+    /// to be proven and verified with the given options.
+    ///
+    /// A circuit already in the session under the same name is replaced.
+    pub fn add_with_options<S: Into<String>>(
+        &mut self,
+        name: S,
+        mut circuit: PlangCircuit,
+        options: ProveOptions,
+    ) -> Result<()> {
+        let name = name.into();
+        let _span = info_span!("session_add", name = %name).entered();
+
+        params::check_degree(&self.pp, &circuit)?;
+        let (prover_key, verifier_data) = circuit.compile(&self.pp)?;
+
+        self.insert(name, circuit, prover_key, verifier_data, options);
+        Ok(())
+    }
+
+    /// Adds a circuit to the session under the given name, with keys it was
+    /// compiled into before. It's proven with the transcript label recorded
+    /// in the key archive.
+    ///
+    /// It returns an error if the keys weren't compiled from the circuit and
+    /// the parameters of the session, or if they don't include a prover key.
+    pub fn add_compiled<S: Into<String>>(
+        &mut self,
+        name: S,
+        circuit: PlangCircuit,
+        keys: PlangKeys,
+    ) -> Result<()> {
+        keys.check(&circuit, &self.pp)?;
+        let prover_key = keys
+            .prover_key
+            .ok_or(PlangError::Prove(ProveError::NoProverKey))?;
+
+        self.insert(
+            name.into(),
+            circuit,
+            prover_key,
+            keys.verifier_data,
+            ProveOptions::new(keys.label),
+        );
+        Ok(())
+    }
+
+    fn insert(
+        &mut self,
+        name: String,
+        circuit: PlangCircuit,
+        prover_key: ProverKey,
+        verifier_data: VerifierData,
+        options: ProveOptions,
+    ) {
+        let compiled = Compiled {
+            circuit,
+            prover_key,
+            verifier_data,
+            options,
+        };
+        self.circuits.insert(name, compiled);
+    }
+
+    /// The names of the circuits in the session, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.circuits.keys().map(String::as_str)
+    }
+
+    /// The circuit with the given name, if it's in the session.
+    pub fn circuit(&self, name: &str) -> Option<&PlangCircuit> {
+        self.circuits.get(name).map(|compiled| &compiled.circuit)
+    }
+
+    /// The verifier data of the circuit with the given name, if it's in the
+    /// session.
+    pub fn verifier_data(&self, name: &str) -> Option<&VerifierData> {
+        self.circuits
+            .get(name)
+            .map(|compiled| &compiled.verifier_data)
+    }
+
+    /// Proves the circuit with the given name, with the given values. Any
+    /// value not set is 0, regardless of the values of earlier proofs.
+    pub fn prove<B, I>(&mut self, name: &str, vals: I) -> Result<Proof>
+    where
+        B: IntoScalar,
+        I: IntoIterator<Item = (String, B)>,
+    {
+        let _span = info_span!("session_prove", name).entered();
+
+        let compiled = self
+            .circuits
+            .get_mut(name)
+            .ok_or_else(|| PlangError::Prove(ProveError::NoSuchCircuit(name.to_owned())))?;
+
+        // The values are set on the circuit kept in the session, rather than
+        // on a copy of it, and wiped once it's proven.
+        compiled.circuit.clear_secrets();
+        let pp = &self.pp;
+        let proof = compiled.circuit.set_vals(vals).and_then(|_| {
+            compiled
+                .circuit
+                .prove_with(pp, &compiled.prover_key, &compiled.options)
+        });
+        compiled.circuit.clear_secrets();
+
+        proof
+    }
+
+    /// Verifies a proof of the circuit with the given name, against the given
+    /// public input values, keyed by name.
+    pub fn verify<B, I>(&self, name: &str, proof: &Proof, vals: I) -> Result<()>
+    where
+        B: IntoScalar,
+        I: IntoIterator<Item = (String, B)>,
+    {
+        let _span = info_span!("session_verify", name).entered();
+
+        let compiled = self
+            .circuits
+            .get(name)
+            .ok_or_else(|| PlangError::Prove(ProveError::NoSuchCircuit(name.to_owned())))?;
+
+        let pinputs = compiled.circuit.public_inputs_from(vals)?;
+        PlangCircuit::verify_with(
+            &self.pp,
+            &compiled.verifier_data,
+            proof,
+            &pinputs,
+            &compiled.options,
+        )
+    }
+}
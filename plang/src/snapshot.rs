@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Snapshot testing of circuits, for regression tests of projects embedding
+//! them.
+//!
+//! A snapshot of a circuit holds its hash, its equations in normal form, and
+//! the gate each of them is compiled into. [`assert_snapshot`] compares it
+//! with the one stored in a file, and panics with a diff of the lines that
+//! changed, so a change to the statement a circuit proves can't go unnoticed.
+//!
+//! Snapshots missing from disk are written out, as are all of them when the
+//! `PLANG_UPDATE_SNAPSHOTS` environment variable is set, to accept the
+//! changes.
+
+use crate::backend::{SELECTORS, WIRES};
+use crate::export::doc::fmt_signed;
+use crate::PlangCircuit;
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// The environment variable that, when set, has snapshots written out rather
+/// than compared.
+pub const UPDATE_VAR: &str = "PLANG_UPDATE_SNAPSHOTS";
+
+/// The hash of the circuit, in hex.
+pub fn hash(circuit: &PlangCircuit) -> String {
+    hex::encode(circuit.hash())
+}
+
+/// The equations of the circuit in normal form, one per line, as they're
+/// hashed. Formatting and comments in the source make no difference.
+pub fn circuit_text(circuit: &PlangCircuit) -> String {
+    let mut text = String::new();
+    for expr in circuit.exprs() {
+        let _ = writeln!(text, "{}", expr);
+    }
+    text
+}
+
+/// The gate each equation is compiled into, one per line, with the variable
+/// on each wire and the value of each selector, in aligned columns.
+pub fn selector_table(circuit: &PlangCircuit) -> String {
+    let header = WIRES.iter().chain(SELECTORS.iter());
+    let mut rows = vec![Some("gate")
+        .into_iter()
+        .chain(header.copied())
+        .map(str::to_owned)
+        .collect::<Vec<_>>()];
+
+    for (i, gate) in circuit.gates().iter().enumerate() {
+        let mut row = vec![i.to_string()];
+        row.extend(gate.wires.iter().map(|wire| wire.unwrap_or("-").to_owned()));
+        for selector in &gate.selectors {
+            let mut cell = String::new();
+            let _ = fmt_signed(&mut cell, selector);
+            row.push(cell);
+        }
+        rows.push(row);
+    }
+
+    let mut widths = vec![0; rows[0].len()];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    for row in &rows {
+        let mut line = String::new();
+        for (cell, width) in row.iter().zip(&widths) {
+            let _ = write!(line, "{:<width$}  ", cell, width = width);
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// The snapshot of the circuit: its hash, its equations and its gates.
+pub fn render(circuit: &PlangCircuit) -> String {
+    format!(
+        "hash: {}\n\n{}\n{}",
+        hash(circuit),
+        circuit_text(circuit),
+        selector_table(circuit)
+    )
+}
+
+/// Compares the snapshot of the circuit with the one stored at the given
+/// path, as [`assert_snapshot`] does.
+pub fn assert_circuit_snapshot<P: AsRef<Path>>(path: P, circuit: &PlangCircuit) {
+    assert_snapshot(path, &render(circuit))
+}
+
+/// Compares a snapshot with the one stored at the given path. If there's
+/// none, or if [`UPDATE_VAR`] is set, the snapshot is written out instead.
+///
+/// # Panics
+///
+/// Panics with a diff of the two if they aren't the same, or if the snapshot
+/// can't be read or written.
+pub fn assert_snapshot<P: AsRef<Path>>(path: P, actual: &str) {
+    let path = path.as_ref();
+
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) if env::var_os(UPDATE_VAR).is_none() => expected,
+        _ => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).expect("the snapshot directory can be created");
+            }
+            fs::write(path, actual).expect("the snapshot can be written");
+            return;
+        }
+    };
+
+    if expected != actual {
+        panic!(
+            "snapshot {} doesn't match, set {} to update it\n{}",
+            path.display(),
+            UPDATE_VAR,
+            diff(&expected, actual)
+        );
+    }
+}
+
+/// A diff of the lines of two texts, with the line numbers of the lines
+/// removed from the first one, and of those added in the second.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // The length of the longest common subsequence of the lines from each
+    // pair of positions onwards.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match old[i] == new[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            let _ = writeln!(out, "-{:>4} | {}", i + 1, old[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(out, "+{:>4} | {}", j + 1, new[j]);
+            j += 1;
+        }
+    }
+    out
+}
@@ -0,0 +1,101 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! The names of variables, interned.
+//!
+//! A variable appears in as many terms as there are equations constraining
+//! it, so rather than each term owning a copy of its name, the names are
+//! interned in a [`Symbols`] table while lowering, and every term shares the
+//! single allocation of the name.
+
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// The name of a variable. Cloning it shares the name rather than copying
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Symbol(Arc<str>);
+
+impl Symbol {
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Symbols hash as the names they stand for, so they're looked up by name.
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Self(name.into())
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Self {
+        Self(name.into())
+    }
+}
+
+/// A table the names of variables are interned in.
+///
+/// Equations are lowered in parallel, each interning its names in a table of
+/// its own, and the variables of all of them are made to share their names
+/// once they're collected into a circuit.
+#[derive(Debug, Default)]
+pub(crate) struct Symbols {
+    symbols: RefCell<HashSet<Symbol>>,
+}
+
+impl Symbols {
+    /// The symbol for the name, allocated the first time the name is
+    /// interned and shared after.
+    pub(crate) fn intern(&self, name: &str) -> Symbol {
+        let mut symbols = self.symbols.borrow_mut();
+        match symbols.get(name) {
+            Some(symbol) => symbol.clone(),
+            None => {
+                let symbol = Symbol::from(name);
+                symbols.insert(symbol.clone());
+                symbol
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Generation of random, valid circuits together with values satisfying them,
+//! for fuzzing and integration tests.
+//!
+//! A generated circuit passes every check, so it can be compiled and proven,
+//! and the values generated with it satisfy every equation, so the proof
+//! verifies. How many equations it has, and what they look like, is set by a
+//! [`Config`]. Downstream tests can set the values generated on a circuit
+//! parsed from the text, or turn some of them into a vector of values that
+//! shouldn't satisfy it.
+
+use crate::scalar::Scalar;
+
+use std::fmt::Write;
+
+use rand_chacha::rand_core::RngCore;
+
+/// The shape of an equation of a generated circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// A term of the form `q_m · a · b`, and up to one term of each of its
+    /// variables and of two others, as in `3 * a * b - a + 2 * c - e`.
+    Product,
+    /// Up to three terms of the form `q · a`, as in `3 * a - b + 2 * c`.
+    Linear,
+}
+
+/// What the circuits generated look like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// The number of equations.
+    pub equations: usize,
+    /// The number of witnesses the first equations draw their variables from.
+    /// Every equation that isn't equal to a public input solves for a new
+    /// witness, which later equations draw from as well.
+    pub witnesses: usize,
+    /// The shapes of the equations, each equation taking one of them at
+    /// random.
+    pub shapes: Vec<Shape>,
+    /// The chance, in percent, of an equation being equal to a public input
+    /// of its own, rather than to 0.
+    pub public_percent: u32,
+}
+
+impl Config {
+    /// Circuits with the given number of equations, of every shape, about
+    /// half of them equal to a public input.
+    pub fn new(equations: usize) -> Self {
+        Self {
+            equations,
+            witnesses: 4,
+            shapes: vec![Shape::Product, Shape::Linear],
+            public_percent: 50,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+/// The text of a generated circuit, with values satisfying it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated {
+    /// The text of the circuit.
+    pub text: String,
+    /// The values of the witnesses and public inputs of the circuit, in the
+    /// order they first appear in.
+    pub values: Vec<(String, Scalar)>,
+}
+
+/// A term of an equation being generated, by the index of its witnesses.
+enum Term {
+    Product(usize, usize),
+    Single(usize),
+}
+
+/// Generates a random circuit as the configuration says, with values
+/// satisfying it. The same random number generator, seeded the same,
+/// generates the same circuit.
+///
+/// # Panics
+///
+/// Panics if the configuration has no shapes.
+pub fn generate<R: RngCore>(rng: &mut R, config: &Config) -> Generated {
+    assert!(!config.shapes.is_empty(), "no shapes to generate");
+
+    let mut text = String::with_capacity(config.equations * 32);
+    let mut values = vec![];
+
+    // The witnesses drawn from, with their values and whether they're used.
+    let mut witnesses: Vec<(Scalar, bool)> = (0..config.witnesses.max(4))
+        .map(|_| (Scalar::from(rng.next_u64()), false))
+        .collect();
+    let mut publics = 0;
+
+    for _ in 0..config.equations {
+        let shape = config.shapes[rng.next_u32() as usize % config.shapes.len()];
+        let public = rng.next_u32() % 100 < config.public_percent;
+
+        // Distinct witnesses for the terms, the third of which is solved for
+        // unless the equation is equal to a public input.
+        let mut vars: Vec<usize> = vec![];
+        while vars.len() < 4 {
+            let var = rng.next_u32() as usize % witnesses.len();
+            if !vars.contains(&var) {
+                vars.push(var);
+            }
+        }
+        if !public {
+            vars[2] = witnesses.len();
+            witnesses.push((Scalar::zero(), false));
+        }
+
+        let mut terms = vec![];
+        match shape {
+            Shape::Product => {
+                terms.push(Term::Product(vars[0], vars[1]));
+                for var in &vars[..2] {
+                    if coin(rng) {
+                        terms.push(Term::Single(*var));
+                    }
+                }
+                // With a public input a fourth witness would make five
+                // variables, one too many for a gate, and the left side has
+                // room for four terms.
+                if !public && terms.len() < 3 && coin(rng) {
+                    terms.push(Term::Single(vars[3]));
+                }
+                if !public || coin(rng) {
+                    terms.push(Term::Single(vars[2]));
+                }
+            }
+            Shape::Linear => {
+                let n = 1 + rng.next_u32() as usize % 3;
+                let start = if public { 0 } else { 3 - n };
+                terms.extend(vars[start..start + n].iter().map(|var| Term::Single(*var)));
+            }
+        }
+
+        // The value of the left side, but for the witness solved for.
+        let mut left = Scalar::zero();
+        let mut solved = None;
+        for (i, term) in terms.iter().enumerate() {
+            let (minus, magnitude) = coeff(rng);
+            let coeff = match minus {
+                true => -magnitude,
+                false => magnitude,
+            };
+
+            match (i, minus) {
+                (0, false) => {}
+                (0, true) => text.push('-'),
+                (_, false) => text.push_str(" + "),
+                (_, true) => text.push_str(" - "),
+            }
+            if magnitude != Scalar::one() {
+                let _ = write!(text, "{} * ", magnitude);
+            }
+
+            match *term {
+                Term::Product(a, b) => {
+                    let _ = write!(text, "w{} * w{}", a, b);
+                    left = left + coeff * witnesses[a].0 * witnesses[b].0;
+                }
+                Term::Single(var) if !public && var == vars[2] => {
+                    let _ = write!(text, "w{}", var);
+                    solved = Some((var, coeff));
+                }
+                Term::Single(var) => {
+                    let _ = write!(text, "w{}", var);
+                    left = left + coeff * witnesses[var].0;
+                }
+            }
+        }
+
+        if let Some((var, coeff)) = solved {
+            let inverse = coeff.invert().expect("coefficients are never 0");
+            witnesses[var].0 = -left * inverse;
+        }
+        for term in &terms {
+            let (a, b) = match *term {
+                Term::Product(a, b) => (a, Some(b)),
+                Term::Single(var) => (var, None),
+            };
+            for var in Some(a).into_iter().chain(b) {
+                if !witnesses[var].1 {
+                    witnesses[var].1 = true;
+                    values.push((format!("w{}", var), witnesses[var].0));
+                }
+            }
+        }
+
+        // Public inputs are never shared between equations, nor used as
+        // witnesses.
+        match (public, coin(rng)) {
+            (true, false) => {
+                let _ = writeln!(text, " = p{}", publics);
+                values.push((format!("p{}", publics), left));
+                publics += 1;
+            }
+            (true, true) => {
+                let _ = writeln!(text, " = -p{}", publics);
+                values.push((format!("p{}", publics), -left));
+                publics += 1;
+            }
+            (false, _) => text.push_str(" = 0\n"),
+        }
+    }
+
+    Generated { text, values }
+}
+
+/// Flips a coin.
+fn coin<R: RngCore>(rng: &mut R) -> bool {
+    rng.next_u32() & 1 == 0
+}
+
+/// A coefficient for a term, as a sign and a magnitude. The magnitude is 1 a
+/// quarter of the time, so it's left out.
+fn coeff<R: RngCore>(rng: &mut R) -> (bool, Scalar) {
+    let magnitude = match rng.next_u32() % 4 {
+        0 => Scalar::one(),
+        _ => Scalar::from(1 + u64::from(rng.next_u32() % 1000)),
+    };
+    (coin(rng), magnitude)
+}
@@ -0,0 +1,77 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Tokenization of circuits, for syntax highlighting.
+//!
+//! The tokens are matched by the same rules of the grammar used to parse
+//! circuits, so highlighting agrees with parsing. Tokenizing never fails - text
+//! that can't start a token, as is common mid-edit, is tokenized one character
+//! at a time as [`TokenKind::Unknown`].
+
+use crate::grammar::{PlangParser, Rule};
+
+use std::ops::Range;
+
+use pest::Parser;
+
+/// The kind of a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A keyword, such as `transcript`.
+    Keyword,
+    /// The name of a variable.
+    Variable,
+    /// A coefficient, or the zero on the right side of an equation.
+    Coefficient,
+    /// One of `+`, `-`, `*` or `=`.
+    Operator,
+    /// A quoted string, such as a transcript label.
+    String,
+    /// A comment, from `#` to the end of the line.
+    Comment,
+    /// A character that can't start a token.
+    Unknown,
+}
+
+/// A token of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// The kind of the token.
+    pub kind: TokenKind,
+    /// The range of bytes of the text the token spans.
+    pub span: Range<usize>,
+    /// The text of the token.
+    pub text: &'a str,
+}
+
+/// Splits the text into tokens, in the order they appear in. Whitespace and
+/// line breaks aren't tokens.
+pub fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let pairs = PlangParser::parse(Rule::tokens, text).expect("any text can be tokenized");
+
+    pairs
+        .flatten()
+        .filter_map(|pair| {
+            let kind = match pair.as_rule() {
+                Rule::keyword => TokenKind::Keyword,
+                Rule::var => TokenKind::Variable,
+                Rule::coeff => TokenKind::Coefficient,
+                Rule::sign | Rule::operator => TokenKind::Operator,
+                Rule::string => TokenKind::String,
+                Rule::comment => TokenKind::Comment,
+                Rule::unknown => TokenKind::Unknown,
+                _ => return None,
+            };
+
+            let span = pair.as_span();
+            Some(Token {
+                kind,
+                span: span.start()..span.end(),
+                text: span.as_str(),
+            })
+        })
+        .collect()
+}
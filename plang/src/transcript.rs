@@ -0,0 +1,152 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! How the Fiat-Shamir transcript of a proof is initialized.
+//!
+//! dusk-plonk builds the transcript of every proof with Merlin, and the only
+//! part of it left to its users is the label the transcript is started with.
+//! [`ProveOptions`] composes that label out of the transcript label of the
+//! circuit and any number of domain separation prefixes, so protocols that
+//! separate their proofs by domain can do so without encoding it themselves.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use spin::Mutex;
+
+#[cfg(feature = "std")]
+use crate::PlangCircuit;
+#[cfg(feature = "prover")]
+use crate::{error::Result, verifier};
+
+#[cfg(feature = "prover")]
+use dusk_plonk::prelude::*;
+
+/// The transcript label of circuits that don't declare one.
+pub const DEFAULT_LABEL: &[u8] = b"dusk_plang";
+
+/// The options a proof is produced and verified with.
+///
+/// Plonk requires the transcript to be started with a static label, so the
+/// label is composed and leaked when the options are created. Each transcript
+/// is only leaked the first time options starting it are created, by any
+/// thread, so creating options for every proof doesn't leak memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProveOptions {
+    label: Vec<u8>,
+    domains: Vec<Vec<u8>>,
+    transcript: &'static [u8],
+}
+
+impl ProveOptions {
+    /// Options starting the transcript with the given label.
+    pub fn new<L: Into<Vec<u8>>>(label: L) -> Self {
+        Self::with_domains(label, Vec::<Vec<u8>>::new())
+    }
+
+    /// Options starting the transcript with the given domain separation
+    /// prefixes, in order, followed by the label.
+    ///
+    /// Each prefix is preceded by its length as a little endian `u32`, so no
+    /// two lists of prefixes start the transcript the same. Without prefixes
+    /// the transcript is started with the label alone.
+    pub fn with_domains<L, I, D>(label: L, domains: I) -> Self
+    where
+        L: Into<Vec<u8>>,
+        I: IntoIterator<Item = D>,
+        D: Into<Vec<u8>>,
+    {
+        let label = label.into();
+        let domains: Vec<Vec<u8>> = domains.into_iter().map(Into::into).collect();
+
+        let mut transcript = Vec::new();
+        for domain in &domains {
+            transcript.extend(&(domain.len() as u32).to_le_bytes());
+            transcript.extend(domain);
+        }
+        transcript.extend(&label);
+
+        Self {
+            label,
+            domains,
+            transcript: leak(transcript),
+        }
+    }
+
+    /// Options with the transcript label the circuit declares, or
+    /// [`DEFAULT_LABEL`] if it doesn't declare one.
+    #[cfg(feature = "std")]
+    pub fn for_circuit(circuit: &PlangCircuit) -> Self {
+        Self::new(circuit.transcript().unwrap_or(DEFAULT_LABEL))
+    }
+
+    /// The transcript label.
+    pub fn label(&self) -> &[u8] {
+        &self.label
+    }
+
+    /// The domain separation prefixes, in the order they're in the
+    /// transcript.
+    pub fn domains(&self) -> &[Vec<u8>] {
+        &self.domains
+    }
+
+    /// What the transcript is started with - the domain separation prefixes,
+    /// followed by the label.
+    pub fn transcript(&self) -> &'static [u8] {
+        self.transcript
+    }
+}
+
+/// The transcripts leaked so far, sorted. A spin lock is used so that they're
+/// shared between threads without `std`, and it's held only to look one up.
+static TRANSCRIPTS: Mutex<Vec<&'static [u8]>> = Mutex::new(Vec::new());
+
+/// Leaks a transcript, unless the same one was leaked before.
+fn leak(transcript: Vec<u8>) -> &'static [u8] {
+    let mut leaked = TRANSCRIPTS.lock();
+    match leaked.binary_search(&transcript.as_slice()) {
+        Ok(i) => leaked[i],
+        Err(i) => {
+            let transcript: &'static [u8] = Box::leak(transcript.into_boxed_slice());
+            leaked.insert(i, transcript);
+            transcript
+        }
+    }
+}
+
+impl Default for ProveOptions {
+    fn default() -> Self {
+        Self::new(DEFAULT_LABEL)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl PlangCircuit {
+    /// Proves the circuit with the values currently set, starting the
+    /// transcript as the options say.
+    pub fn prove_with(
+        &mut self,
+        pp: &PublicParameters,
+        pk: &ProverKey,
+        options: &ProveOptions,
+    ) -> Result<Proof> {
+        Ok(self.prove(pp, pk, options.transcript())?)
+    }
+
+    /// Verifies a proof of the circuit against public input values in the
+    /// order verification expects them in, starting the transcript as the
+    /// options say.
+    pub fn verify_with(
+        pp: &PublicParameters,
+        vd: &VerifierData,
+        proof: &Proof,
+        pinputs: &[PublicInputValue],
+        options: &ProveOptions,
+    ) -> Result<()> {
+        verifier::verify(pp, vd, proof, pinputs, options.transcript())
+    }
+}
@@ -0,0 +1,94 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Where the variables of a circuit are used.
+//!
+//! Each equation is compiled into a gate of its own, so a variable is used in
+//! a gate for each equation it appears in. Finding references to a variable,
+//! or spotting ones that only appear once - and so are constrained by a
+//! single equation - comes down to going through the equations in order.
+
+use crate::PlangCircuit;
+
+use std::collections::HashMap;
+
+/// The kind of term a variable appears in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// A product of two variables, `q_m · a · b`.
+    Product,
+    /// A linear term, `q_x · y`.
+    Linear,
+    /// The public input, on the right side of an equation.
+    Public,
+}
+
+/// A place a variable is used in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    /// The gate, which is the index of the equation among the equations of
+    /// the circuit.
+    pub gate: usize,
+    /// The line the equation is on in the source, counting from 0.
+    pub line: usize,
+    /// The kind of term the variable appears in.
+    pub position: Position,
+}
+
+impl PlangCircuit {
+    /// Returns every place the variable with the given name is used in, in
+    /// the order of the equations. A variable appearing twice in the same
+    /// product is used twice. Variables not in the circuit are used nowhere.
+    pub fn usages(&self, name: &str) -> Vec<Usage> {
+        let mut usages = vec![];
+
+        for (gate, expr) in self.exprs().iter().enumerate() {
+            let mut push = |position| {
+                usages.push(Usage {
+                    gate,
+                    line: expr.line,
+                    position,
+                })
+            };
+
+            if let Some(tri) = &expr.tri {
+                for var in [&tri.lvar, &tri.rvar] {
+                    if var == name {
+                        push(Position::Product);
+                    }
+                }
+            }
+            for _ in expr.bis.iter().filter(|bi| bi.var == name) {
+                push(Position::Linear);
+            }
+            if expr.public.iter().any(|public| public.var == name) {
+                push(Position::Public);
+            }
+        }
+
+        usages
+    }
+
+    /// Returns the variables used exactly once, in the order they appear in.
+    ///
+    /// A witness used once is only constrained by a single equation, which
+    /// is often a sign of a typo in its name, or of an equation missing.
+    pub fn used_once(&self) -> Vec<&str> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut order = vec![];
+
+        for var in self.exprs().iter().flat_map(|expr| expr.vars()) {
+            let count = counts.entry(var).or_insert(0);
+            if *count == 0 {
+                order.push(var);
+            }
+            *count += 1;
+        }
+
+        order.retain(|var| counts[var] == 1);
+        order
+    }
+}
@@ -0,0 +1,130 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Test vectors declared in the source of a circuit.
+//!
+//! A circuit can declare values next to its equations, expecting them to
+//! satisfy it, or not:
+//!
+//! ```text
+//! a * b + c = d
+//! test satisfied "basic" { a = 2; b = 3; c = 1; d = 7; }
+//! test unsatisfied "off by one" { a = 2; b = 3; c = 1; d = 8; }
+//! ```
+//!
+//! The examples of how a circuit is meant to be used are then executable, and
+//! can't drift apart from its constraints. Test vectors stand for no
+//! equations, so they don't change the hash of the circuit.
+
+use crate::grammar::{NodeRef, Rule};
+use crate::scalar::Scalar;
+#[cfg(feature = "prover")]
+use crate::{
+    error::{AssignmentError, Error as PlangError, Result},
+    PlangCircuit,
+};
+
+#[cfg(feature = "prover")]
+use dusk_plonk::prelude::BlsScalar;
+
+/// Values for the variables of a circuit, with whether they're expected to
+/// satisfy it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    /// The name of the test vector.
+    pub name: String,
+    /// Whether the values are expected to satisfy the circuit.
+    pub satisfied: bool,
+    /// The values, in the order they're written in. Variables not given a
+    /// value are 0, as when proving.
+    pub values: Vec<(String, Scalar)>,
+    /// The line the test vector is on, counting from 0.
+    pub line: usize,
+}
+
+/// Lowers a `test` block on the given line into the test vector it declares.
+pub(crate) fn lower_test(node: NodeRef<'_>, line: usize) -> TestVector {
+    let mut name = String::new();
+    let mut satisfied = false;
+    let mut values = vec![];
+
+    for child in node.children() {
+        match child.rule() {
+            Rule::outcome => satisfied = child.as_str() == "satisfied",
+            Rule::string => {
+                name = child
+                    .children()
+                    .find(|inner| inner.rule() == Rule::label)
+                    .map_or("", |label| label.as_str())
+                    .to_owned();
+            }
+            Rule::assignment => values.push(lower_assignment(child)),
+            _ => {}
+        }
+    }
+
+    TestVector {
+        name,
+        satisfied,
+        values,
+        line,
+    }
+}
+
+/// Lowers `a = -3` into the variable and its value, reduced into the field.
+fn lower_assignment(node: NodeRef<'_>) -> (String, Scalar) {
+    let mut var = "";
+    let mut negated = false;
+    let mut value = Scalar::zero();
+
+    for child in node.children() {
+        match child.rule() {
+            Rule::var => var = child.as_str(),
+            Rule::sign => negated = child.as_str() == "-",
+            Rule::value => {
+                value = Scalar::from_decimal(child.as_str())
+                    .expect("the grammar only allows digits in values")
+                    .0
+            }
+            _ => {}
+        }
+    }
+
+    match negated {
+        true => (var.to_owned(), -value),
+        false => (var.to_owned(), value),
+    }
+}
+
+#[cfg(feature = "prover")]
+impl PlangCircuit {
+    /// Checks the circuit against a test vector, setting its values on a
+    /// copy of the circuit, which is left as it is.
+    ///
+    /// It returns an error if the values don't satisfy the circuit while
+    /// they're expected to, or the other way around, or if a value is not in
+    /// the circuit.
+    pub fn run_test(&self, test: &TestVector) -> Result<()> {
+        let mut circuit = self.unassigned();
+        let values = test
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), BlsScalar::from(*value)));
+        let result = circuit
+            .set_vals(values)
+            .and_then(|_| circuit.check_satisfied());
+
+        // Values an assertion rejects don't satisfy the circuit either.
+        match (result, test.satisfied) {
+            (Ok(()), false) => Err(PlangError::Assignment(
+                AssignmentError::UnexpectedlySatisfied(test.name.clone()),
+            )),
+            (Err(PlangError::Assignment(AssignmentError::Unsatisfied { .. })), false) => Ok(()),
+            (Err(PlangError::Assignment(AssignmentError::AssertionFailed { .. })), false) => Ok(()),
+            (result, _) => result,
+        }
+    }
+}
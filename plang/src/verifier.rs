@@ -0,0 +1,273 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Verification of proofs, without the circuit they were produced from.
+//!
+//! Verifying a proof takes the verifier data of the circuit and its public
+//! input values, in the order of the equations they appear in and signed as
+//! they are in them. A [`PlangVerifier`] records that order next to the
+//! verifier data, so a proof can be checked against values keyed by name
+//! without parsing the circuit. Proofs of the same circuit can also be
+//! verified in a batch with [`verify_batch`]. This module builds under
+//! `no_std` with `alloc`, for embedded and smart contract environments.
+
+use crate::artifact::{self, ArtifactKind};
+use crate::error::{AssignmentError, Error as PlangError, ProveError, Result};
+use crate::keys::Reader;
+use crate::scalar::IntoScalar;
+use crate::transcript::ProveOptions;
+#[cfg(feature = "std")]
+use crate::PlangCircuit;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use dusk_plonk::prelude::*;
+
+/// The id every plang circuit is compiled and verified with.
+pub(crate) const CIRCUIT_ID: [u8; 32] = [0; 32];
+
+/// The verifier data of a circuit, together with the order and signs of its
+/// public inputs.
+#[derive(Debug)]
+pub struct PlangVerifier {
+    /// The verifier data.
+    pub verifier_data: VerifierData,
+    /// The name of each public input in the order their values are expected,
+    /// and whether it has a minus sign in front.
+    pub public_inputs: Vec<(String, bool)>,
+}
+
+impl PlangVerifier {
+    /// Bundles the verifier data compiled from a circuit with the order of its
+    /// public inputs.
+    #[cfg(feature = "std")]
+    pub fn new(circuit: &PlangCircuit, verifier_data: VerifierData) -> Self {
+        let public_inputs = circuit
+            .signed_public_inputs()
+            .map(|(name, minus)| (name.to_owned(), minus))
+            .collect();
+
+        Self {
+            verifier_data,
+            public_inputs,
+        }
+    }
+
+    /// Serializes the archive, including its artifact header.
+    ///
+    /// After the header, the verifier data prefixed by its length as a little
+    /// endian `u32` is followed by each public input: a byte set to one if it
+    /// has a minus sign in front, and its name, prefixed by its length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let vd = self.verifier_data.to_var_bytes();
+
+        let mut bytes = Vec::with_capacity(4 + vd.len() + 16 * self.public_inputs.len());
+
+        bytes.extend(&(vd.len() as u32).to_le_bytes());
+        bytes.extend(&vd);
+
+        for (name, minus) in &self.public_inputs {
+            bytes.push(*minus as u8);
+            bytes.extend(&(name.len() as u32).to_le_bytes());
+            bytes.extend(name.as_bytes());
+        }
+
+        artifact::wrap(ArtifactKind::Verifier, 0, &bytes)
+    }
+
+    /// Deserializes an archive written by [`to_bytes`](Self::to_bytes).
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let (bytes, _) = artifact::unwrap(ArtifactKind::Verifier, bytes)?;
+        let mut reader = Reader(bytes);
+
+        let invalid = |_| PlangError::Prove(ProveError::InvalidVerifierArchive);
+
+        let verifier_data = VerifierData::from_slice(reader.take_field().map_err(invalid)?)?;
+
+        let mut public_inputs = Vec::with_capacity(verifier_data.pi_pos().len());
+        while let Some((&minus, rest)) = reader.0.split_first() {
+            reader.0 = rest;
+
+            let minus = match minus {
+                0 => false,
+                1 => true,
+                _ => return Err(PlangError::Prove(ProveError::InvalidVerifierArchive)),
+            };
+            let name = String::from_utf8(reader.take_field().map_err(invalid)?.to_vec())?;
+
+            public_inputs.push((name, minus));
+        }
+
+        if public_inputs.len() != verifier_data.pi_pos().len() {
+            return Err(PlangError::Prove(ProveError::InvalidVerifierArchive));
+        }
+
+        Ok(Self {
+            verifier_data,
+            public_inputs,
+        })
+    }
+
+    /// Returns the names of the public inputs in the order their values are
+    /// expected when verifying a proof.
+    pub fn public_input_order(&self) -> impl Iterator<Item = &str> {
+        self.public_inputs.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Arranges the given public input values, keyed by name, in the order
+    /// expected when verifying a proof. It returns an error if a value is not
+    /// a public input of the circuit, or if a public input is missing a value.
+    pub fn public_inputs_from<B: IntoScalar, I: IntoIterator<Item = (String, B)>>(
+        &self,
+        vals: I,
+    ) -> Result<Vec<PublicInputValue>> {
+        let mut named = BTreeMap::new();
+        for (name, val) in vals {
+            if !self.public_input_order().any(|public| public == name) {
+                return Err(PlangError::Assignment(AssignmentError::NotPublicInput(
+                    name,
+                )));
+            }
+            named.insert(name, val.into_scalar()?);
+        }
+
+        let layout = self
+            .public_inputs
+            .iter()
+            .map(|(name, minus)| (name.as_str(), *minus));
+        arrange(layout, |name| named.get(name).copied())
+    }
+
+    /// Verifies a proof against the given public input values, keyed by name,
+    /// starting the transcript the same as when it was produced.
+    pub fn verify<B: IntoScalar, I: IntoIterator<Item = (String, B)>>(
+        &self,
+        pp: &PublicParameters,
+        proof: &Proof,
+        vals: I,
+        options: &ProveOptions,
+    ) -> Result<()> {
+        let pinputs = self.public_inputs_from(vals)?;
+        verify(
+            pp,
+            &self.verifier_data,
+            proof,
+            &pinputs,
+            options.transcript(),
+        )
+    }
+}
+
+/// Verifies many proofs of the same circuit, each against its own public
+/// input values, already arranged in the order verification expects them in.
+///
+/// dusk-plonk checks the pairings of a proof as part of verifying it, without
+/// exposing them, so they can't be combined into a single randomized check.
+/// The proofs are verified one by one instead - with the `parallel` feature,
+/// in parallel on rayon's worker threads.
+///
+/// The transcript of each proof is started with the given label, which
+/// [`ProveOptions::transcript`] composes for the proofs produced with them.
+///
+/// It returns the error of the first proof that fails to verify, if any.
+pub fn verify_batch(
+    pp: &PublicParameters,
+    vd: &VerifierData,
+    proofs: &[(Proof, Vec<PublicInputValue>)],
+    label: &'static [u8],
+) -> Result<()> {
+    verify_each(pp, vd, proofs, label).into_iter().collect()
+}
+
+/// Verifies many proofs of the same circuit as [`verify_batch`] does, but
+/// returns whether each verified, in the order they're given in.
+pub fn verify_each(
+    pp: &PublicParameters,
+    vd: &VerifierData,
+    proofs: &[(Proof, Vec<PublicInputValue>)],
+    label: &'static [u8],
+) -> Vec<Result<()>> {
+    let verify =
+        |(proof, pinputs): &(Proof, Vec<PublicInputValue>)| verify(pp, vd, proof, pinputs, label);
+
+    #[cfg(feature = "parallel")]
+    let results: Vec<Result<()>> = {
+        use rayon::prelude::*;
+        proofs.par_iter().map(verify).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<Result<()>> = proofs.iter().map(verify).collect();
+
+    results
+}
+
+/// Verifies a proof, checking it's given as many public inputs as the verifier
+/// data expects first.
+pub(crate) fn verify(
+    pp: &PublicParameters,
+    vd: &VerifierData,
+    proof: &Proof,
+    pinputs: &[PublicInputValue],
+    label: &'static [u8],
+) -> Result<()> {
+    let expected = vd.pi_pos().len();
+    if pinputs.len() != expected {
+        return Err(PlangError::Assignment(AssignmentError::PublicInputCount {
+            expected,
+            found: pinputs.len(),
+        }));
+    }
+
+    Verifying::verify(pp, vd, proof, pinputs, label)?;
+    Ok(())
+}
+
+/// Arranges public input values in the order of the given public inputs,
+/// each with whether it has a minus sign in front.
+pub(crate) fn arrange<'a, L, F>(layout: L, value: F) -> Result<Vec<PublicInputValue>>
+where
+    L: Iterator<Item = (&'a str, bool)>,
+    F: Fn(&str) -> Option<BlsScalar>,
+{
+    layout
+        .map(|(name, minus)| {
+            let val = value(name).ok_or_else(|| {
+                PlangError::Assignment(AssignmentError::MissingPublicInput(name.into()))
+            })?;
+
+            // Verification negates the values it is given, matching how the
+            // gadget appends public inputs. One with a minus sign in front
+            // is appended as it is, so its value is negated here to match.
+            Ok(match minus {
+                true => -val,
+                false => val,
+            }
+            .into())
+        })
+        .collect()
+}
+
+/// Verification only depends on the id of a circuit, so it's done through a
+/// circuit that can't be built, and has the id every plang circuit has.
+enum Verifying {}
+
+impl Circuit for Verifying {
+    const CIRCUIT_ID: [u8; 32] = CIRCUIT_ID;
+
+    fn gadget(&mut self, _: &mut TurboComposer) -> core::result::Result<(), Error> {
+        match *self {}
+    }
+
+    fn public_inputs(&self) -> Vec<PublicInputValue> {
+        match *self {}
+    }
+
+    fn padded_gates(&self) -> usize {
+        match *self {}
+    }
+}
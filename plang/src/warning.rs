@@ -0,0 +1,207 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Warnings about circuits that are valid, but may not describe what was
+//! meant.
+//!
+//! Unlike errors, warnings don't stop a circuit from being built. They're
+//! gathered while lowering each equation, and reported by
+//! [`PlangCircuit::warnings`](crate::PlangCircuit::warnings) and
+//! [`Document::warnings`](crate::document::Document::warnings) together with
+//! the line they're about.
+//!
+//! A warning can be allowed for a single equation with a comment above it
+//! naming it, such as `#[allow(zero_coefficient)]`. The allowed warnings are
+//! kept with the equation, and listed by
+//! [`PlangCircuit::suppressions`](crate::PlangCircuit::suppressions).
+//!
+//! How each other warning is treated is up to a [`CheckConfig`]. Deployments with
+//! no tolerance for them can deny some, or all of them with
+//! [`CheckConfig::strict`], turning them into errors. The checks equations
+//! fail with errors are not configurable, since the gates they're compiled
+//! into rely on them.
+
+use crate::error::{Error as PlangError, Result, SemanticError};
+use crate::scalar::Scalar;
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// The names of all warnings.
+pub const NAMES: &[&str] = &[
+    "reduced_coefficient",
+    "zero_coefficient",
+    "modulus_coefficient",
+    "wrapped_negative_value",
+    "unknown_warning",
+];
+
+/// Something valid in a circuit, that is likely a mistake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// A coefficient is not less than the modulus of the scalar field, and is
+    /// reduced modulo it.
+    ReducedCoefficient {
+        /// The coefficient, as written in the circuit.
+        literal: String,
+        /// What the coefficient is reduced to.
+        reduced: Scalar,
+    },
+    /// A coefficient is 0, so its term vanishes.
+    ZeroCoefficient,
+    /// A coefficient is a multiple of the modulus of the scalar field, so it's
+    /// reduced to 0 and its term vanishes.
+    ModulusCoefficient {
+        /// The coefficient, as written in the circuit.
+        literal: String,
+    },
+    /// The value of a public input looks like a negative number converted to
+    /// a `u64`.
+    WrappedNegativeValue {
+        /// The name of the public input.
+        name: String,
+        /// The value it's set to.
+        value: u64,
+    },
+    /// An `#[allow(...)]` comment names a warning that doesn't exist.
+    UnknownWarning {
+        /// The name of the warning.
+        name: String,
+    },
+}
+
+/// A warning about a line of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineWarning {
+    /// The line the warning is about, counting from 0.
+    pub line: usize,
+    /// The warning.
+    pub warning: Warning,
+}
+
+/// A warning allowed for an equation with an `#[allow(...)]` comment above
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    /// The line of the equation, counting from 0.
+    pub line: usize,
+    /// The name of the warning.
+    pub name: String,
+}
+
+/// How a warning is treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// The warning is ignored.
+    Allow,
+    /// The warning is reported.
+    Warn,
+    /// The warning is an error.
+    Deny,
+}
+
+/// The level of each warning, for checking circuits.
+///
+/// By default every warning is reported without failing the check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckConfig {
+    default: Level,
+    levels: HashMap<&'static str, Level>,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            default: Level::Warn,
+            levels: HashMap::new(),
+        }
+    }
+}
+
+impl CheckConfig {
+    /// A configuration denying every warning.
+    pub fn strict() -> Self {
+        Self {
+            default: Level::Deny,
+            levels: HashMap::new(),
+        }
+    }
+
+    /// Sets the level of the warning with the given [name](Warning::name).
+    /// It returns an error if there is no warning with the name.
+    pub fn set(&mut self, name: &str, level: Level) -> Result<&mut Self> {
+        let name = NAMES
+            .iter()
+            .find(|known| **known == name)
+            .ok_or_else(|| PlangError::Semantic(SemanticError::UnknownWarning(name.to_owned())))?;
+
+        self.levels.insert(name, level);
+        Ok(self)
+    }
+
+    /// The level of the given warning.
+    pub fn level(&self, warning: &Warning) -> Level {
+        self.levels
+            .get(warning.name())
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Checks warnings against the configuration, returning the ones to
+    /// report. It returns an error with the first denied warning, if any.
+    pub fn check(&self, warnings: Vec<LineWarning>) -> Result<Vec<LineWarning>> {
+        let mut reported = Vec::with_capacity(warnings.len());
+
+        for warning in warnings {
+            match self.level(&warning.warning) {
+                Level::Allow => {}
+                Level::Warn => reported.push(warning),
+                Level::Deny => {
+                    return Err(PlangError::Semantic(SemanticError::DeniedWarning(warning)))
+                }
+            }
+        }
+
+        Ok(reported)
+    }
+}
+
+impl Warning {
+    /// Returns the stable name of the warning.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ReducedCoefficient { .. } => "reduced_coefficient",
+            Self::ZeroCoefficient => "zero_coefficient",
+            Self::ModulusCoefficient { .. } => "modulus_coefficient",
+            Self::WrappedNegativeValue { .. } => "wrapped_negative_value",
+            Self::UnknownWarning { .. } => "unknown_warning",
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReducedCoefficient { literal, reduced } => write!(
+                f,
+                "coefficient `{}` is not less than the modulus of the scalar field, and is reduced to {}",
+                literal, reduced
+            ),
+            Self::ZeroCoefficient => f.write_str("coefficient is 0, so its term vanishes"),
+            Self::ModulusCoefficient { literal } => write!(
+                f,
+                "coefficient `{}` is a multiple of the modulus of the scalar field, so it's reduced to 0 and its term vanishes",
+                literal
+            ),
+            Self::WrappedNegativeValue { name, value } => write!(
+                f,
+                "public input `{}` is {}, which is {} as a signed 64 bit integer - if that's what was meant, give it as a negative number",
+                name, value, *value as i64
+            ),
+            Self::UnknownWarning { name } => write!(f, "unknown warning `{}` is allowed", name),
+        }
+    }
+}
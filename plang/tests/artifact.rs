@@ -0,0 +1,67 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use plang::artifact::{self, ArtifactKind};
+use plang::{PlangError, ProveError};
+
+#[test]
+fn roundtrip() {
+    let payload = b"some payload";
+    let bytes = artifact::wrap(ArtifactKind::Proof, artifact::FLAG_INSECURE, payload);
+
+    let (unwrapped, flags) =
+        artifact::unwrap(ArtifactKind::Proof, &bytes).expect("artifact should be valid");
+
+    assert_eq!(unwrapped, payload);
+    assert_eq!(flags, artifact::FLAG_INSECURE);
+}
+
+#[test]
+fn wrong_kind() {
+    let bytes = artifact::wrap(ArtifactKind::VerifierData, 0, b"some payload");
+
+    match artifact::unwrap(ArtifactKind::ProverKey, &bytes) {
+        Err(PlangError::Prove(ProveError::WrongArtifact {
+            expected: ArtifactKind::ProverKey,
+            found: ArtifactKind::VerifierData,
+        })) => {}
+        res => panic!("expected wrong artifact error, got {:?}", res),
+    }
+}
+
+#[test]
+fn corrupted_payload() {
+    let mut bytes = artifact::wrap(ArtifactKind::Params, 0, b"some payload");
+
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+
+    match artifact::unwrap(ArtifactKind::Params, &bytes) {
+        Err(PlangError::Prove(ProveError::ArtifactChecksum)) => {}
+        res => panic!("expected checksum error, got {:?}", res),
+    }
+}
+
+#[test]
+fn truncated() {
+    let bytes = artifact::wrap(ArtifactKind::Params, 0, b"some payload");
+
+    match artifact::unwrap(ArtifactKind::Params, &bytes[..bytes.len() - 1]) {
+        Err(PlangError::Prove(ProveError::TruncatedArtifact)) => {}
+        res => panic!("expected truncated error, got {:?}", res),
+    }
+}
+
+#[test]
+fn legacy_without_header() {
+    let payload = b"raw bytes written before headers";
+
+    let (unwrapped, flags) =
+        artifact::unwrap(ArtifactKind::Params, payload).expect("legacy artifacts are accepted");
+
+    assert_eq!(unwrapped, payload);
+    assert_eq!(flags, 0);
+}
@@ -4,10 +4,34 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+#![cfg(feature = "prover")]
+
+use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 
+use pest::error::LineColLocation;
+use plang::artifact::{self, ArtifactKind, Metadata};
+use plang::backend::{Backend, Gate};
+use plang::cache::KeyCache;
+use plang::document::Document;
 use plang::dusk_plonk::prelude::*;
-use plang::{PlangCircuit, PlangError};
+use plang::envelope::ProofEnvelope;
+use plang::grammar::{Edition, PlangGrammar};
+use plang::include::SearchPath;
+use plang::package::Package;
+use plang::probe::{Mutant, Mutation};
+use plang::repro::{self, Manifest, ManifestEntry};
+use plang::scalar::Scalar;
+use plang::session::ProvingSession;
+use plang::warning::{CheckConfig, Level, Suppression, Warning};
+use plang::{
+    differential, generate, params, prover, report, testgen, verifier, AssignmentError, ParseError,
+    ParseOptions, PlangCircuit, PlangError, PlangKeys, PlangProof, PlangVerifier, ProveError,
+    ProveOptions, SemanticError,
+};
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 
 type Result<T> = std::result::Result<T, PlangError>;
 
@@ -65,6 +89,25 @@ fn produces_same_keys() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn builds_same_keys_by_hand() -> Result<()> {
+    let text = String::from_utf8(fs::read("./test.plang")?)?;
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    differential::assert_same_keys(&mut PlangCircuit::parse(text)?, &pp)?;
+
+    for seed in 0..4 {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let generated = testgen::generate(&mut rng, &testgen::Config::new(6));
+        let mut circuit = PlangCircuit::parse(&generated.text)?;
+
+        let degree = params::degree_for_gates(circuit.padded_gates())?;
+        let pp = params::setup_seeded(degree, [seed as u8; 32])?;
+        differential::assert_same_keys(&mut circuit, &pp)?;
+    }
+
+    Ok(())
+}
+
 #[test]
 fn produces_same_valid_proof() -> Result<()> {
     let bytes = fs::read("./test.plang")?;
@@ -114,3 +157,2229 @@ fn produces_same_valid_proof() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn prove_many_matches_prove() -> Result<()> {
+    let bytes = fs::read("./test.plang")?;
+
+    let text = String::from_utf8(bytes)?;
+    let mut circuit = PlangCircuit::parse(text)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, _) = circuit.compile(&pp)?;
+
+    // Solutions to `test.plang`
+    let assignments = vec![
+        vec![
+            ("a".to_owned(), 1),
+            ("b".to_owned(), 1),
+            ("c".to_owned(), 2),
+            ("d".to_owned(), 1),
+        ],
+        vec![
+            ("a".to_owned(), 2),
+            ("b".to_owned(), 3),
+            ("c".to_owned(), 5),
+            ("d".to_owned(), 6),
+        ],
+    ];
+
+    let proofs = prover::prove_many(&circuit, assignments.clone(), &pk, &pp, b"test")?;
+    assert_eq!(proofs.len(), 2);
+
+    for (assignment, proof) in assignments.into_iter().zip(proofs) {
+        circuit.set_vals(assignment)?;
+        assert_eq!(proof, circuit.prove(&pp, &pk, b"test")?);
+    }
+
+    let unknown = vec![vec![("e".to_owned(), 1)]];
+    assert!(matches!(
+        prover::prove_many(&circuit, unknown, &pk, &pp, b"test"),
+        Err(PlangError::Assignment(AssignmentError::NoSuchValue(name))) if name == "e"
+    ));
+
+    // Each assignment is proven on its own.
+    let solution = vec![
+        ("a".to_owned(), 1),
+        ("b".to_owned(), 1),
+        ("c".to_owned(), 2),
+        ("d".to_owned(), 1),
+    ];
+    let mixed = vec![solution, vec![("e".to_owned(), 1)]];
+    let results = prover::prove_each(&circuit, mixed, &pk, &pp, b"test");
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+
+    Ok(())
+}
+
+#[test]
+fn key_archive_roundtrip() -> Result<()> {
+    let bytes = fs::read("./test.plang")?;
+
+    let text = String::from_utf8(bytes)?;
+    let mut circuit = PlangCircuit::parse(text)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    let keys = PlangKeys::new(&circuit, &pp, Some(pk), vd, b"test".to_vec());
+    let keys = PlangKeys::from_slice(&keys.to_bytes())?;
+
+    keys.check(&circuit, &pp)?;
+
+    let (pk, vd) = circuit.compile(&pp)?;
+    assert_eq!(
+        keys.prover_key.map(|pk| pk.to_var_bytes()),
+        Some(pk.to_var_bytes())
+    );
+    assert_eq!(keys.verifier_data.to_var_bytes(), vd.to_var_bytes());
+    assert_eq!(keys.label, b"test");
+
+    Ok(())
+}
+
+#[test]
+fn key_archive_records_circuit_id() -> Result<()> {
+    let circuit = PlangCircuit::parse(fs::read_to_string("./test.plang")?)?;
+    let hash = circuit.hash();
+    assert_eq!(circuit.id(), hash);
+
+    let metadata = Metadata {
+        name: Some("transfer".into()),
+        version: Some("2".into()),
+    };
+    let mut circuit = circuit.with_id([7; 32]).with_metadata(metadata.clone());
+    assert_eq!(circuit.id(), [7; 32]);
+    assert_eq!(circuit.hash(), hash);
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    let keys = PlangKeys::new(&circuit, &pp, Some(pk), vd, b"test".to_vec());
+    let keys = PlangKeys::from_slice(&keys.to_bytes())?;
+
+    keys.check(&circuit, &pp)?;
+    assert_eq!(keys.circuit_hash, hash);
+    assert_eq!(keys.circuit_id, [7; 32]);
+    assert_eq!(keys.metadata, metadata);
+
+    Ok(())
+}
+
+#[test]
+fn key_cache_reuses_keys() -> Result<()> {
+    let bytes = fs::read("./test.plang")?;
+
+    let text = String::from_utf8(bytes)?;
+    let mut circuit = PlangCircuit::parse(text)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+
+    let dir = std::env::temp_dir().join(format!("plang-key-cache-{}", std::process::id()));
+    let cache = KeyCache::new(&dir);
+
+    let (pk, vd) = cache.compile(&mut circuit, &pp)?;
+    let path = cache.path(circuit.hash(), params::digest(&pp));
+    assert!(path.exists());
+
+    let (cpk, cvd) = cache.compile(&mut circuit, &pp)?;
+    assert_eq!(cpk.to_var_bytes(), pk.to_var_bytes());
+    assert_eq!(cvd.to_var_bytes(), vd.to_var_bytes());
+
+    // A corrupt entry is compiled anew
+    fs::write(&path, b"corrupt")?;
+    let (cpk, _) = cache.compile(&mut circuit, &pp)?;
+    assert_eq!(cpk.to_var_bytes(), pk.to_var_bytes());
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn generated_circuits_are_valid() -> Result<()> {
+    for seed in 0..16 {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let text = generate::circuit(&mut rng, 256);
+
+        let mut circuit = PlangCircuit::parse(text)?;
+        circuit.gadget(&mut TurboComposer::new())?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn generates_satisfied_circuits() -> Result<()> {
+    let configs = [
+        testgen::Config::new(64),
+        testgen::Config {
+            shapes: vec![testgen::Shape::Linear],
+            public_percent: 0,
+            ..testgen::Config::new(64)
+        },
+        testgen::Config {
+            witnesses: 32,
+            shapes: vec![testgen::Shape::Product],
+            public_percent: 100,
+            ..testgen::Config::new(64)
+        },
+    ];
+
+    for (seed, config) in configs.iter().enumerate() {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed as u64);
+        let generated = testgen::generate(&mut rng, config);
+        assert_eq!(generated.text.lines().count(), 64);
+
+        let mut circuit = PlangCircuit::parse(&generated.text)?;
+        let report = circuit.validate_assignment(&generated.values.iter().cloned().collect());
+        assert!(report.is_valid());
+
+        let values = generated
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), *value));
+        circuit.set_vals(values.map(|(name, value)| (name, BlsScalar::from(value))))?;
+        circuit.check_satisfied()?;
+
+        // Changing any value breaks an equation
+        let (name, value) = generated.values[seed * 7].clone();
+        let wrong = BlsScalar::from(value + Scalar::one());
+        circuit.set_vals(vec![(name, wrong)])?;
+        assert!(circuit.check_satisfied().is_err());
+    }
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let linear = testgen::Config {
+        shapes: vec![testgen::Shape::Linear],
+        ..testgen::Config::new(64)
+    };
+    let text = testgen::generate(&mut rng, &linear).text;
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    assert!(tokens
+        .windows(2)
+        .all(|pair| !(pair[0].trim_start_matches('-').starts_with('w') && pair[1] == "*")));
+
+    Ok(())
+}
+
+/// A backend evaluating each gate with the values of its witnesses.
+#[derive(Default)]
+struct Evaluator {
+    gates: Vec<BlsScalar>,
+}
+
+impl Backend for Evaluator {
+    type Witness = BlsScalar;
+
+    fn append_witness(&mut self, value: BlsScalar) -> BlsScalar {
+        value
+    }
+
+    fn append_gate(&mut self, gate: Gate<BlsScalar>) {
+        let a = gate.a.unwrap_or_default();
+        let b = gate.b.unwrap_or_default();
+        let o = gate.o.unwrap_or_default();
+        let d = gate.d.unwrap_or_default();
+        let public = gate.public.unwrap_or_default();
+
+        self.gates.push(
+            gate.q_m * a * b
+                + gate.q_l * a
+                + gate.q_r * b
+                + gate.q_o * o
+                + gate.q_4 * d
+                + public
+                + gate.q_c,
+        );
+    }
+}
+
+#[test]
+fn emits_into_any_backend() -> Result<()> {
+    let bytes = fs::read("./test.plang")?;
+
+    let text = String::from_utf8(bytes)?;
+    let mut circuit = PlangCircuit::parse(text)?;
+
+    // Solution to `test.plang`
+    let vals = vec![
+        ("a".to_owned(), 1),
+        ("b".to_owned(), 1),
+        ("c".to_owned(), 2),
+        ("d".to_owned(), 1),
+    ];
+    circuit.set_vals(vals)?;
+
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    assert_eq!(evaluator.gates, [BlsScalar::zero(); 2]);
+
+    // Any other value breaks a gate
+    circuit.set_vals(vec![("d".to_owned(), 2)])?;
+
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    assert_ne!(evaluator.gates[1], BlsScalar::zero());
+
+    Ok(())
+}
+
+/// Evaluates the gate of a circuit of a single equation, with the given
+/// values.
+fn evaluate(text: &str, vals: &[(&str, BlsScalar)]) -> Result<BlsScalar> {
+    let mut circuit = PlangCircuit::parse(text)?;
+    circuit.set_vals(vals.iter().map(|(name, val)| ((*name).to_owned(), *val)))?;
+
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    Ok(evaluator.gates[0])
+}
+
+#[test]
+fn signs_match_reference_gates() -> Result<()> {
+    let (a, b, c, d) = (
+        BlsScalar::from(5),
+        BlsScalar::from(7),
+        BlsScalar::from(11),
+        BlsScalar::from(13),
+    );
+    let vals = [("a", a), ("b", b), ("c", c), ("d", d)];
+    let without_c = [("a", a), ("b", b), ("d", d)];
+
+    let sign = |minus: bool| if minus { "-" } else { "+" };
+    let signed = |minus: bool, coeff: u64| match minus {
+        true => -BlsScalar::from(coeff),
+        false => BlsScalar::from(coeff),
+    };
+    let reference = |gate: Gate<BlsScalar>| {
+        let mut evaluator = Evaluator::default();
+        evaluator.append_gate(gate);
+        evaluator.gates[0]
+    };
+    let zero = BlsScalar::zero();
+
+    for signs in 0..8 {
+        let (first, second, public) = (signs & 1 != 0, signs & 2 != 0, signs & 4 != 0);
+        // The public input is moved to the left side of the gate.
+        let pi = Some(signed(!public, 1) * d);
+
+        // A product and a linear term on another variable.
+        let text = format!(
+            "{}2*a*b {} 3*c = {}d\n",
+            sign(first),
+            sign(second),
+            sign(public)
+        );
+        let gate = Gate {
+            q_m: signed(first, 2),
+            q_l: zero,
+            q_r: zero,
+            q_o: signed(second, 3),
+            q_4: zero,
+            a: Some(a),
+            b: Some(b),
+            o: Some(c),
+            d: None,
+            public: pi,
+            q_c: zero,
+        };
+        assert_eq!(evaluate(&text, &vals)?, reference(gate), "{}", text);
+
+        // A product and a linear term on one of its variables.
+        let text = format!(
+            "{}2*a*b {} 3*a = {}d\n",
+            sign(first),
+            sign(second),
+            sign(public)
+        );
+        let gate = Gate {
+            q_m: signed(first, 2),
+            q_l: signed(second, 3),
+            q_r: zero,
+            q_o: zero,
+            q_4: zero,
+            a: Some(a),
+            b: Some(b),
+            o: None,
+            d: None,
+            public: pi,
+            q_c: zero,
+        };
+        assert_eq!(evaluate(&text, &without_c)?, reference(gate), "{}", text);
+
+        // Linear terms only.
+        let text = format!(
+            "{}2*a {} 3*b = {}d\n",
+            sign(first),
+            sign(second),
+            sign(public)
+        );
+        let gate = Gate {
+            q_m: zero,
+            q_l: signed(first, 2),
+            q_r: signed(second, 3),
+            q_o: zero,
+            q_4: zero,
+            a: Some(a),
+            b: Some(b),
+            o: None,
+            d: None,
+            public: pi,
+            q_c: zero,
+        };
+        assert_eq!(evaluate(&text, &without_c)?, reference(gate), "{}", text);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn constrains_every_variable_of_a_gate() -> Result<()> {
+    let (a, b, c, e) = (
+        BlsScalar::from(5),
+        BlsScalar::from(7),
+        BlsScalar::from(11),
+        BlsScalar::from(13),
+    );
+
+    // Both variables besides those of the product are on a wire, so changing
+    // either breaks the gate.
+    for text in ["a*b - c - 3*e = 0\n", "a - b - c + e = 0\n"] {
+        let e = match text.contains('*') {
+            true => BlsScalar::from(8),
+            false => e,
+        };
+        assert_eq!(
+            evaluate(text, &[("a", a), ("b", b), ("c", c), ("e", e)])?,
+            BlsScalar::zero()
+        );
+
+        let wrong_c = [("a", a), ("b", b), ("c", c + BlsScalar::one()), ("e", e)];
+        assert_ne!(evaluate(text, &wrong_c)?, BlsScalar::zero(), "{}", text);
+        let wrong_e = [("a", a), ("b", b), ("c", c), ("e", e + BlsScalar::one())];
+        assert_ne!(evaluate(text, &wrong_e)?, BlsScalar::zero(), "{}", text);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn renders_dot_graphs() -> Result<()> {
+    let circuit = PlangCircuit::parse("a + 2*b = c\na * b - e = -d\n")?;
+
+    // Each gate is connected to the variables of its terms, labelled with the
+    // kind of term.
+    assert_eq!(
+        circuit.to_dot(false),
+        "graph circuit {
+    \"a\";
+    \"b\";
+    \"c\";
+    \"e\";
+    \"d\";
+    g0 [shape=box, label=\"a + 2 * b = c\"];
+    g0 -- \"a\" [label=lin];
+    g0 -- \"b\" [label=lin];
+    g0 -- \"c\" [label=pub];
+    g1 [shape=box, label=\"a * b - e = -d\"];
+    g1 -- \"a\" [label=mul];
+    g1 -- \"b\" [label=mul];
+    g1 -- \"e\" [label=lin];
+    g1 -- \"d\" [label=pub];
+}
+"
+    );
+
+    // Only the public inputs are filled when highlighted
+    let highlighted = circuit.to_dot(true);
+    let filled: Vec<_> = highlighted
+        .lines()
+        .filter(|line| line.ends_with("[style=filled, fillcolor=lightblue];"))
+        .collect();
+    assert_eq!(
+        filled,
+        [
+            "    \"c\" [style=filled, fillcolor=lightblue];",
+            "    \"d\" [style=filled, fillcolor=lightblue];"
+        ]
+    );
+    assert_eq!(
+        highlighted.replace(" [style=filled, fillcolor=lightblue]", ""),
+        circuit.to_dot(false)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transcript_directive() -> Result<()> {
+    let circuit = PlangCircuit::parse("transcript \"my label\"\na + b = c\n")?;
+    assert_eq!(circuit.transcript(), Some(&b"my label"[..]));
+
+    let circuit = PlangCircuit::parse("a + b = c\n")?;
+    assert_eq!(circuit.transcript(), None);
+
+    match PlangCircuit::parse("transcript \"a\"\ntranscript \"b\"\na + b = c\n") {
+        Err(PlangError::Semantic(SemanticError::RepeatedTranscript)) => Ok(()),
+        res => panic!("expected a repeated transcript error, got {:?}", res),
+    }
+}
+
+#[test]
+fn accepts_zero_on_the_right_side() -> Result<()> {
+    let circuit = PlangCircuit::parse("a + b - c = 0\na*b - d = 0\n")?;
+    assert_eq!(circuit.public_inputs_named().count(), 0);
+
+    // Equations without a public input display with `0` on the right side,
+    // and parse back to the same circuit.
+    let text = circuit.to_string();
+    assert_eq!(text.matches(" = 0\n").count(), 2);
+    assert_eq!(PlangCircuit::parse(&text)?.to_string(), text);
+
+    // Only a bare `0` stands for no public input.
+    for text in [
+        "a + b = -0\n",
+        "a + b = 00\n",
+        "a + b = 1\n",
+        "a + b = 0 + c\n",
+    ] {
+        match PlangCircuit::parse(text) {
+            Err(PlangError::Parse(ParseError::Pest(_))) => (),
+            res => panic!("expected a syntax error for {:?}, got {:?}", text, res),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn reports_the_first_invalid_equation() {
+    // Enough equations to be checked in parallel, with the invalid ones far
+    // apart
+    let mut text = String::new();
+    for i in 0..4096 {
+        match i {
+            1000 => text.push_str("a + b + c + d = e\n"),
+            3000 => text.push_str("a * a = b\n"),
+            _ => text.push_str(&format!("x{} + y = z\n", i)),
+        }
+    }
+
+    for _ in 0..8 {
+        match PlangCircuit::parse(&text) {
+            Err(PlangError::Semantic(SemanticError::TooManyVars { line, var })) => {
+                assert_eq!(line, 1000);
+                assert_eq!(var, "e");
+            }
+            res => panic!("expected too many variables, got {:?}", res),
+        }
+    }
+
+    let errors = [
+        (
+            "a * a = b\n",
+            "line 1: a product term multiplies `a` with itself",
+        ),
+        (
+            "\na + b + a = c\n",
+            "line 2: `a` appears in more than one linear term of the equation",
+        ),
+        (
+            "a + b = a\n",
+            "line 1: the public input `a` also appears on the left side of the equation",
+        ),
+    ];
+    for (text, message) in errors.iter() {
+        let err = PlangCircuit::parse(*text).unwrap_err();
+        assert_eq!(err.to_string(), *message);
+    }
+}
+
+#[test]
+fn points_at_terms_that_dont_fit_in_a_gate() {
+    let span_of = |text: &str| match PlangCircuit::parse(text) {
+        Err(PlangError::Parse(ParseError::Pest(err))) => match err.line_col {
+            LineColLocation::Span(start, end) => (start, end, err.to_string()),
+            LineColLocation::Pos(pos) => (pos, pos, err.to_string()),
+        },
+        res => panic!("expected a syntax error, got {:?}", res.map(|c| c.hash())),
+    };
+
+    let (start, end, message) = span_of("a + b = c\na*b + c*d = e\n");
+    assert_eq!((start, end), ((2, 7), (2, 10)));
+    assert!(message.contains("one product"));
+
+    let (start, _, message) = span_of("a + b + c + d + e = f\n");
+    assert_eq!(start, (1, 17));
+    assert!(message.contains("four terms"));
+
+    let (start, _, message) = span_of("a + b = c + d\n");
+    assert_eq!(start, (1, 13));
+    assert!(message.contains("single public input"));
+
+    assert!(PlangCircuit::parse("a - b + c - 3*d*e = f\n").is_ok());
+}
+
+#[test]
+fn expands_sums() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("sum(i in 0..4, 2^i * b[i]) = x\n")?;
+    assert_eq!(circuit.public_input_order(), ["x"]);
+
+    // x = 0b1101, from its bits
+    let bits = [1, 0, 1, 1];
+    let vals = bits
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| (format!("b[{}]", i), BlsScalar::from(*bit)))
+        .chain(Some(("x".to_owned(), BlsScalar::from(13))));
+    circuit.set_vals(vals)?;
+
+    assert_eq!(
+        circuit.value("sum(i in 0..3, 2^i*b[i])"),
+        Some(BlsScalar::from(5))
+    );
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    assert_eq!(evaluator.gates, [BlsScalar::zero(); 3]);
+
+    let assignment: HashMap<String, u64> = (0..4).map(|i| (format!("b[{}]", i), 0)).collect();
+    assert_eq!(circuit.validate_assignment(&assignment).unassigned, ["x"]);
+
+    // A single term is an equation of its own.
+    assert_eq!(
+        PlangCircuit::parse("sum(i in 7..8, 3*c[i]) = -y\n")?.hash(),
+        PlangCircuit::parse("3*c[7] = -y\n")?.hash()
+    );
+
+    match PlangCircuit::parse("sum(i in 8..8, b[i]) = x\n") {
+        Err(err @ PlangError::Semantic(SemanticError::InvalidSumRange(_))) => {
+            assert_eq!(err.code(), Some("P0012"))
+        }
+        res => panic!("expected an invalid range, got {:?}", res.map(|c| c.hash())),
+    }
+    match PlangCircuit::parse("sum(i in 0..8, 2^j * b[i]) = x\n") {
+        Err(PlangError::Semantic(SemanticError::UnboundIndex(index))) => assert_eq!(index, "j"),
+        res => panic!("expected an unbound index, got {:?}", res.map(|c| c.hash())),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn decomposes_into_bits() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("const N = 4\nbits(x, N) -> b[]\nb[3] = y\n")?;
+    assert_eq!(circuit.public_input_order(), ["y"]);
+
+    // The bits are computed from x alone.
+    let vals = vec![("x", 13), ("y", 1)];
+    circuit.set_vals(
+        vals.into_iter()
+            .map(|(k, v)| (k.to_owned(), BlsScalar::from(v))),
+    )?;
+    for (i, bit) in [1, 0, 1, 1].iter().enumerate() {
+        assert_eq!(
+            circuit.value(&format!("b[{}]", i)),
+            Some(BlsScalar::from(*bit))
+        );
+    }
+
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    assert_eq!(evaluator.gates, vec![BlsScalar::zero(); 4 * 2 + 3 + 1]);
+
+    let assignment: HashMap<String, u64> = Some(("y".to_owned(), 1)).into_iter().collect();
+    assert_eq!(circuit.validate_assignment(&assignment).unassigned, ["x"]);
+
+    for width in ["0", "256"] {
+        match PlangCircuit::parse(&format!("bits(x, {}) -> b[]\n", width)) {
+            Err(err @ PlangError::Semantic(SemanticError::InvalidWidth(_))) => {
+                assert_eq!(err.code(), Some("P0018"))
+            }
+            res => panic!("expected an invalid width, got {:?}", res.map(|c| c.hash())),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn decomposes_into_bytes() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("bytes(x, 2) -> l[]\n")?;
+
+    // x = 0x1234, so l[0] = 0x34 = 0b00110100
+    circuit.set_vals(vec![("x".to_owned(), BlsScalar::from(0x1234))])?;
+    assert_eq!(circuit.value("l[0]"), Some(BlsScalar::from(0x34)));
+    assert_eq!(circuit.value("l[1]"), Some(BlsScalar::from(0x12)));
+    assert_eq!(circuit.value("l[0][2]"), Some(BlsScalar::one()));
+    assert_eq!(circuit.value("l[0][3]"), Some(BlsScalar::zero()));
+
+    // 8 bits of 2 gates and their sum of 7 in each limb, and the sum of limbs
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    assert_eq!(
+        evaluator.gates,
+        vec![BlsScalar::zero(); 2 * (8 * 2 + 7) + 1]
+    );
+
+    match PlangCircuit::parse("bytes(x, 33) -> l[]\n") {
+        Err(PlangError::Semantic(SemanticError::InvalidWidth(width))) => assert_eq!(width, "33"),
+        res => panic!("expected an invalid width, got {:?}", res.map(|c| c.hash())),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn truncates_to_low_bits() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("y = trunc(x, 8)\ny - z = 0\n")?;
+
+    let vals = vec![("x", 0x1234), ("z", 0x34)];
+    circuit.set_vals(
+        vals.into_iter()
+            .map(|(k, v)| (k.to_owned(), BlsScalar::from(v))),
+    )?;
+    assert_eq!(circuit.value("y"), Some(BlsScalar::from(0x34)));
+    assert_eq!(circuit.value("x>>8"), Some(BlsScalar::from(0x12)));
+    assert_eq!(circuit.value("bits(x>>8)[1]"), Some(BlsScalar::one()));
+
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    assert!(evaluator
+        .gates
+        .iter()
+        .all(|gate| *gate == BlsScalar::zero()));
+
+    // Values of any size are truncated, and the low byte of -1 is 0.
+    let vals = vec![
+        ("x".to_owned(), -BlsScalar::one()),
+        ("z".to_owned(), BlsScalar::zero()),
+    ];
+    circuit.set_vals(vals)?;
+    assert_eq!(circuit.value("y"), Some(BlsScalar::zero()));
+
+    match PlangCircuit::parse("y = trunc(x, 255)\n") {
+        Err(PlangError::Semantic(SemanticError::InvalidWidth(width))) => assert_eq!(width, "255"),
+        res => panic!("expected an invalid width, got {:?}", res.map(|c| c.hash())),
+    }
+
+    Ok(())
+}
+
+/// The bits of `x + r`, for the modulus `r` of the scalar field, least
+/// significant first - a second decomposition of `x` into 256 bits.
+fn bits_past_modulus(x: u64) -> Vec<bool> {
+    let mut bytes = (-Scalar::one()).to_bytes();
+    let mut carry = u128::from(x) + 1;
+    for byte in bytes.iter_mut() {
+        carry += u128::from(*byte);
+        *byte = carry as u8;
+        carry >>= 8;
+    }
+
+    (0..256).map(|i| bytes[i / 8] >> (i % 8) & 1 == 1).collect()
+}
+
+/// Whether the gates of a circuit hold with the given variables fixed to any
+/// values, not necessarily those the circuit derives. Every other variable is
+/// solved for, in the order of the gates, from a gate it's the only variable
+/// without a value of, as a prover forging an assignment would.
+fn holds_with(circuit: &PlangCircuit, mut values: HashMap<String, Scalar>) -> bool {
+    let gates = circuit.gates();
+
+    let mut solved = true;
+    while solved {
+        solved = false;
+        for gate in &gates {
+            let mut unknown = gate
+                .wires
+                .iter()
+                .flatten()
+                .filter(|var| !values.contains_key(**var));
+            let var = match (unknown.next(), unknown.next()) {
+                (Some(var), None) => *var,
+                _ => continue,
+            };
+
+            // Gates are linear in each variable, since a variable can't be
+            // multiplied with itself.
+            let at = |x: Scalar| gate.evaluate(|name| if name == var { x } else { values[name] });
+            let (zero, slope) = (at(Scalar::zero()), at(Scalar::one()) - at(Scalar::zero()));
+            if let Some(inverse) = slope.invert() {
+                values.insert(var.to_owned(), -zero * inverse);
+                solved = true;
+            }
+        }
+    }
+
+    gates.iter().all(|gate| {
+        let value = |name: &str| values.get(name).copied().unwrap_or_else(Scalar::zero);
+        gate.evaluate(value) == Scalar::zero()
+    })
+}
+
+#[test]
+fn decomposes_canonically() -> Result<()> {
+    let circuit = PlangCircuit::parse("bits(x, 255) -> b[]\n")?;
+
+    let x = 0x1234;
+    let bytes = Scalar::from(x).to_bytes();
+    let honest: Vec<bool> = (0..256).map(|i| bytes[i / 8] >> (i % 8) & 1 == 1).collect();
+
+    for (bits, holds) in [(honest, true), (bits_past_modulus(x), false)] {
+        let mut values = HashMap::new();
+        values.insert("x".to_owned(), Scalar::from(x));
+        for (i, bit) in bits.iter().enumerate().take(255) {
+            values.insert(format!("b[{}]", i), Scalar::from(*bit as u64));
+        }
+
+        assert_eq!(holds_with(&circuit, values), holds);
+    }
+
+    // Any 254 bits are canonical.
+    let circuit = PlangCircuit::parse("bits(x, 254) -> b[]\n")?;
+    assert!(!circuit
+        .gates()
+        .iter()
+        .flat_map(|gate| gate.wires.iter().flatten())
+        .any(|var| var.starts_with("b.eq")));
+
+    Ok(())
+}
+
+#[test]
+fn decomposes_bytes_canonically() -> Result<()> {
+    let circuit = PlangCircuit::parse("bytes(x, 32) -> l[]\n")?;
+
+    let x = 0x1234;
+    let bytes = Scalar::from(x).to_bytes();
+    let honest: Vec<bool> = (0..256).map(|i| bytes[i / 8] >> (i % 8) & 1 == 1).collect();
+
+    for (bits, holds) in [(honest, true), (bits_past_modulus(x), false)] {
+        let mut values = HashMap::new();
+        values.insert("x".to_owned(), Scalar::from(x));
+        for (i, bit) in bits.iter().enumerate() {
+            values.insert(
+                format!("l[{}][{}]", i / 8, i % 8),
+                Scalar::from(*bit as u64),
+            );
+        }
+
+        assert_eq!(holds_with(&circuit, values), holds);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn commits_canonically() -> Result<()> {
+    let circuit = PlangCircuit::parse("C = commit(v, r)\nC[0] = cx\nC[1] = cy\n")?;
+
+    let v = 12345;
+    let bytes = Scalar::from(v).to_bytes();
+    let honest: Vec<bool> = (0..256).map(|i| bytes[i / 8] >> (i % 8) & 1 == 1).collect();
+
+    // `v + r` would commit to another point than `v`.
+    for (bits, holds) in [(honest, true), (bits_past_modulus(v), false)] {
+        let mut values = HashMap::new();
+        values.insert("v".to_owned(), Scalar::from(v));
+        values.insert("r".to_owned(), Scalar::zero());
+        for (i, bit) in bits.iter().enumerate().take(255) {
+            values.insert(format!("C:v[{}]", i), Scalar::from(*bit as u64));
+            values.insert(format!("C:r[{}]", i), Scalar::zero());
+        }
+
+        assert_eq!(holds_with(&circuit, values), holds);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn truncates_canonically() -> Result<()> {
+    let circuit = PlangCircuit::parse("y = trunc(x, 8)\n")?;
+
+    // The bits of `x + r` would truncate `x = 0x1234` to `0x35`, and only the
+    // canonical ones hold.
+    let x = 0x1234;
+    let forged = bits_past_modulus(x);
+    let bytes = Scalar::from(x).to_bytes();
+    let honest: Vec<bool> = (0..256).map(|i| bytes[i / 8] >> (i % 8) & 1 == 1).collect();
+    assert!(forged[..8] != honest[..8]);
+
+    for (bits, holds) in [(honest, true), (forged, false)] {
+        let mut values = HashMap::new();
+        values.insert("x".to_owned(), Scalar::from(x));
+        for (i, bit) in bits.iter().enumerate().take(255) {
+            let var = match i {
+                0..=7 => format!("bits(y)[{}]", i),
+                _ => format!("bits(x>>8)[{}]", i - 8),
+            };
+            values.insert(var, Scalar::from(*bit as u64));
+        }
+
+        assert_eq!(holds_with(&circuit, values), holds);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compares_variables() -> Result<()> {
+    let satisfied = |text: &str, a: u64, b: u64| -> Result<bool> {
+        let mut circuit = PlangCircuit::parse(text)?;
+        let vals = vec![
+            ("a".to_owned(), BlsScalar::from(a)),
+            ("b".to_owned(), BlsScalar::from(b)),
+        ];
+        circuit.set_vals(vals)?;
+
+        let mut evaluator = Evaluator::default();
+        circuit.emit(&mut evaluator)?;
+        Ok(evaluator
+            .gates
+            .iter()
+            .all(|gate| *gate == BlsScalar::zero()))
+    };
+
+    assert!(satisfied("lt(a, b, 8)\n", 3, 5)?);
+    assert!(!satisfied("lt(a, b, 8)\n", 5, 5)?);
+    assert!(satisfied("lte(a, b, 8)\n", 5, 5)?);
+    assert!(!satisfied("lte(a, b, 8)\n", 6, 5)?);
+
+    for (text, a, b, result) in [
+        ("r = lt(a, b, 8)\n", 3, 5, 1),
+        ("r = lt(a, b, 8)\n", 5, 5, 0),
+        ("r = lte(a, b, 8)\n", 5, 5, 1),
+        ("r = lte(a, b, 8)\n", 255, 0, 0),
+    ] {
+        let mut circuit = PlangCircuit::parse(text)?;
+        let vals = vec![
+            ("a".to_owned(), BlsScalar::from(a)),
+            ("b".to_owned(), BlsScalar::from(b)),
+        ];
+        circuit.set_vals(vals)?;
+        assert_eq!(
+            circuit.value("r"),
+            Some(BlsScalar::from(result)),
+            "{}",
+            text
+        );
+        assert!(satisfied(text, a, b)?, "{}", text);
+    }
+
+    // Asserting a comparison and taking its result don't share the variables
+    // of the difference, since they're different numbers.
+    let text = "lt(a, b, 8)\nr = lt(a, b, 8)\ns = lt(a, b, 8)\n";
+    let mut circuit = PlangCircuit::parse(text)?;
+    circuit.set_vals(vec![("a".to_owned(), 1u64), ("b".to_owned(), 5)])?;
+    assert_eq!(circuit.value("r"), Some(BlsScalar::one()));
+    assert_eq!(circuit.value("s"), Some(BlsScalar::one()));
+    assert!(satisfied(text, 1, 5)?);
+    assert!(!satisfied(text, 5, 1)?);
+
+    match PlangCircuit::parse("lt(a, b, 254)\n") {
+        Err(PlangError::Semantic(SemanticError::InvalidWidth(width))) => assert_eq!(width, "254"),
+        res => panic!("expected an invalid width, got {:?}", res.map(|c| c.hash())),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn commits_to_values() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("C = commit(v, r)\nC[0] = cx\nC[1] = cy\n")?;
+    assert_eq!(circuit.public_input_order(), ["cx", "cy"]);
+
+    let generator = |x: &str, y: &str| {
+        let coordinate = |c: &str| BlsScalar::from(Scalar::from_decimal(c).expect("valid").0);
+        (coordinate(x), coordinate(y))
+    };
+    let g = generator(
+        "28867639725710769449342053336011988556061781325688749245863888315629457631946",
+        "18",
+    );
+    let h = generator(
+        "42700669489511343562940319950550898567331865717607291475117984988973470540663",
+        "30686701290196217645860924063646103734500695227286560569912982001592340201208",
+    );
+
+    for (v, r, expected) in [(1, 0, Some(g)), (0, 1, Some(h)), (12345, 678, None)] {
+        circuit.set_vals(vec![
+            ("v".to_owned(), BlsScalar::from(v)),
+            ("r".to_owned(), BlsScalar::from(r)),
+        ])?;
+        let commitment = (
+            circuit.value("C[0]").expect("computed"),
+            circuit.value("C[1]").expect("computed"),
+        );
+        if let Some(expected) = expected {
+            assert_eq!(commitment, expected);
+        }
+
+        let vals = vec![
+            ("v".to_owned(), BlsScalar::from(v)),
+            ("r".to_owned(), BlsScalar::from(r)),
+            ("cx".to_owned(), commitment.0),
+            ("cy".to_owned(), commitment.1),
+        ];
+        circuit.set_vals(vals)?;
+
+        let mut evaluator = Evaluator::default();
+        circuit.emit(&mut evaluator)?;
+        assert!(evaluator
+            .gates
+            .iter()
+            .all(|gate| *gate == BlsScalar::zero()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn reduces_large_coefficients() -> Result<()> {
+    // Two more than the modulus of the scalar field.
+    let text = "a + b = c\n\
+        52435875175126190479447740508185965837690552500527637822603658699938581184515 * a = c\n";
+
+    let circuit = PlangCircuit::parse(text)?;
+    assert_eq!(
+        circuit.hash(),
+        PlangCircuit::parse("a + b = c\n2 * a = c\n")?.hash()
+    );
+
+    let warnings = circuit.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].line, 1);
+    assert_eq!(warnings[0].warning.name(), "reduced_coefficient");
+
+    assert!(PlangCircuit::parse("a + b = c\n")?.warnings().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn warns_about_vanishing_terms() -> Result<()> {
+    let text = "0 * a + b = c\n\
+        a + 52435875175126190479447740508185965837690552500527637822603658699938581184513 * b = d\n";
+
+    let names: Vec<_> = PlangCircuit::parse(text)?
+        .warnings()
+        .iter()
+        .map(|warning| (warning.line, warning.warning.name()))
+        .collect();
+    assert_eq!(names, [(0, "zero_coefficient"), (1, "modulus_coefficient")]);
+
+    Ok(())
+}
+
+#[test]
+fn allows_warnings_per_equation() -> Result<()> {
+    let text = "# scaled away on purpose\n\
+        #[allow(zero_coefficient)]\n\
+        0 * a + b = c\n\
+        0 * a + b = d\n\
+        #[allow(no_such_warning)]\n\
+        a + b = e\n";
+
+    let circuit = PlangCircuit::parse(text)?;
+    let warnings: Vec<_> = circuit
+        .warnings()
+        .iter()
+        .map(|warning| (warning.line, warning.warning.name()))
+        .collect();
+    assert_eq!(warnings, [(3, "zero_coefficient"), (5, "unknown_warning")]);
+
+    assert_eq!(
+        circuit.suppressions(),
+        [
+            Suppression {
+                line: 2,
+                name: "zero_coefficient".to_owned()
+            },
+            Suppression {
+                line: 5,
+                name: "no_such_warning".to_owned()
+            },
+        ]
+    );
+    assert_eq!(Document::new(text).warnings(), circuit.warnings(),);
+
+    Ok(())
+}
+
+#[test]
+fn warns_about_wrapped_negative_values() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a + b = c\na * b = d\na - b = c\n")?;
+
+    circuit.set_vals(vec![("c".to_owned(), -1i64 as u64), ("d".to_owned(), 42)])?;
+    let warnings = circuit.value_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].line, 0);
+    assert_eq!(
+        warnings[0].warning,
+        Warning::WrappedNegativeValue {
+            name: "c".to_owned(),
+            value: u64::MAX,
+        }
+    );
+
+    circuit.set_vals(vec![("c".to_owned(), -BlsScalar::one())])?;
+    assert!(circuit.value_warnings().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn warning_levels_are_configurable() -> Result<()> {
+    let circuit = PlangCircuit::parse(
+        "52435875175126190479447740508185965837690552500527637822603658699938581184515 * a = c\n",
+    )?;
+
+    assert_eq!(circuit.check_warnings(&CheckConfig::default())?.len(), 1);
+
+    match circuit.check_warnings(&CheckConfig::strict()) {
+        Err(PlangError::Semantic(SemanticError::DeniedWarning(warning))) => {
+            assert_eq!(warning.line, 0)
+        }
+        res => panic!("expected a denied warning error, got {:?}", res),
+    }
+
+    let mut checks = CheckConfig::strict();
+    checks.set("reduced_coefficient", Level::Allow)?;
+    assert!(circuit.check_warnings(&checks)?.is_empty());
+
+    match checks.set("no_such_warning", Level::Deny) {
+        Err(PlangError::Semantic(SemanticError::UnknownWarning(name))) => {
+            assert_eq!(name, "no_such_warning")
+        }
+        res => panic!("expected an unknown warning error, got {:?}", res),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn clears_secrets() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a * b = c\n")?;
+    circuit.set_vals(vec![("a".to_owned(), 3), ("b".to_owned(), 5)])?;
+
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    assert_ne!(evaluator.gates[0], BlsScalar::zero());
+
+    // With every value wiped the gate holds again.
+    circuit.clear_secrets();
+
+    let mut evaluator = Evaluator::default();
+    circuit.emit(&mut evaluator)?;
+    assert_eq!(evaluator.gates, [BlsScalar::zero()]);
+
+    Ok(())
+}
+
+#[test]
+fn proof_records_label() -> Result<()> {
+    let bytes = fs::read("./test.plang")?;
+
+    let text = String::from_utf8(bytes)?;
+    let mut circuit = PlangCircuit::parse(text)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, _) = circuit.compile(&pp)?;
+
+    let proof = circuit.prove(&pp, &pk, b"test")?;
+
+    let envelope = PlangProof::new(proof, b"test".to_vec());
+    let envelope = PlangProof::from_slice(&envelope.to_bytes())?;
+
+    assert_eq!(envelope.proof, proof);
+    assert_eq!(envelope.label.as_deref(), Some(&b"test"[..]));
+
+    Ok(())
+}
+
+#[test]
+fn envelope_carries_what_to_verify_with() -> Result<()> {
+    let mut circuit = PlangCircuit::parse(fs::read_to_string("./test.plang")?)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    circuit.set_vals(vec![
+        ("a".to_owned(), 1),
+        ("b".to_owned(), 1),
+        ("c".to_owned(), 2),
+        ("d".to_owned(), 1),
+    ])?;
+    let options = ProveOptions::with_domains(&b"test"[..], vec![&b"domain"[..]]);
+    let proof = circuit.prove_with(&pp, &pk, &options)?;
+
+    let envelope = ProofEnvelope::new(&circuit, proof, &options);
+    assert_eq!(
+        envelope.public_inputs,
+        [
+            ("c".to_owned(), Scalar::from(2)),
+            ("d".to_owned(), Scalar::from(1))
+        ]
+    );
+    assert_eq!(envelope.circuit_hash, circuit.hash());
+    assert_eq!(envelope.label, options.transcript());
+
+    for envelope in [
+        ProofEnvelope::from_slice(&envelope.to_bytes())?,
+        ProofEnvelope::from_json(&envelope.to_json())?,
+    ] {
+        assert_eq!(envelope.proof, proof);
+        envelope.verify_for(&circuit, &pp, &vd, &options)?;
+        let vd = VerifierData::from_slice(&vd.to_var_bytes())?;
+        envelope.verify(&PlangVerifier::new(&circuit, vd), &pp, &options)?;
+    }
+
+    let other = PlangCircuit::parse("a + b = c\n")?;
+    assert!(matches!(
+        envelope.verify_for(&other, &pp, &vd, &options),
+        Err(PlangError::Prove(ProveError::CircuitMismatch))
+    ));
+
+    // The transcript is the verifier's to pick, so envelopes recording another
+    // one are rejected, whatever they record.
+    for expected in [
+        ProveOptions::new(&b"test"[..]),
+        ProveOptions::with_domains(&b"test"[..], vec![&b"other"[..]]),
+        ProveOptions::for_circuit(&circuit),
+    ] {
+        assert!(matches!(
+            envelope.verify_for(&circuit, &pp, &vd, &expected),
+            Err(PlangError::Prove(ProveError::TranscriptMismatch))
+        ));
+    }
+    let mut forged = ProofEnvelope::from_slice(&envelope.to_bytes())?;
+    forged.label = b"forged".to_vec();
+    assert!(matches!(
+        forged.verify_for(&circuit, &pp, &vd, &options),
+        Err(PlangError::Prove(ProveError::TranscriptMismatch))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn reports_verification() -> Result<()> {
+    let mut circuit = PlangCircuit::parse(fs::read_to_string("./test.plang")?)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    circuit.set_vals(vec![
+        ("a".to_owned(), 1),
+        ("b".to_owned(), 1),
+        ("c".to_owned(), 2),
+        ("d".to_owned(), 1),
+    ])?;
+    let options = ProveOptions::new(&b"test"[..]);
+    let proof = circuit.prove_with(&pp, &pk, &options)?;
+
+    // Public inputs are recorded in the order verification expects them in
+    let vals = vec![
+        ("d".to_owned(), Scalar::from(1)),
+        ("c".to_owned(), Scalar::from(2)),
+    ];
+    let report = report::verify(&circuit, &pp, &vd, &[(proof, vals)], &options)?;
+    assert!(report.verified());
+    assert_eq!(report.circuit_hash, circuit.hash());
+    assert_eq!(report.params_digest, params::digest(&pp));
+    assert_eq!(
+        report.proofs[0].public_inputs,
+        [
+            ("c".to_owned(), Scalar::from(2)),
+            ("d".to_owned(), Scalar::from(1))
+        ]
+    );
+
+    let json = report.to_json();
+    assert_eq!(json["verified"], true);
+    assert_eq!(json["transcript_label"], hex::encode(b"test"));
+    assert_eq!(json["proofs"][0]["public_inputs"][1]["value"], "1");
+
+    // Failing to verify is recorded
+    let vals = vec![
+        ("c".to_owned(), Scalar::from(3)),
+        ("d".to_owned(), Scalar::from(1)),
+    ];
+    let report = report::verify(&circuit, &pp, &vd, &[(proof, vals)], &options)?;
+    assert!(!report.verified());
+    assert!(report.to_json()["error"].is_string());
+    assert!(matches!(
+        report.into_result(),
+        Err(PlangError::Prove(ProveError::Plonk(
+            Error::ProofVerificationError
+        )))
+    ));
+
+    // Values that can't be arranged aren't
+    let vals = vec![("c".to_owned(), Scalar::from(2))];
+    assert!(matches!(
+        report::verify(&circuit, &pp, &vd, &[(proof, vals)], &options),
+        Err(PlangError::Assignment(AssignmentError::MissingPublicInput(
+            _
+        )))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn reproduces_keys() -> Result<()> {
+    let source = fs::read_to_string("./test.plang")?;
+    let mut circuit = PlangCircuit::parse(&source)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    let (pk, vd) = (pk.to_var_bytes(), vd.to_var_bytes());
+    repro::check(&source, &pp, &pk, &vd)?;
+
+    // Keys are accepted with their artifact header too
+    let wrapped = artifact::wrap(ArtifactKind::VerifierData, 0, &vd);
+    repro::check(&source, &pp, &pk, &wrapped)?;
+
+    let source = source.replace("a + b = c", "a + b + 1 = c");
+    assert!(matches!(
+        repro::check(&source, &pp, &pk, &vd),
+        Err(PlangError::Prove(ProveError::KeysMismatch))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn reads_build_manifests() -> Result<()> {
+    let json = serde_json::json!({
+        "params": "setup.pp",
+        "circuits": [
+            { "source": "a.plang", "pk": "a.pk", "vd": "a.vd" },
+            { "source": "b.plang", "vd": "b.vd", "params": "other.pp" },
+        ],
+    });
+    let manifest = Manifest::from_json(&json, Path::new("build"))?;
+    assert_eq!(
+        manifest.entries,
+        [
+            ManifestEntry {
+                source: "build/a.plang".into(),
+                params: "build/setup.pp".into(),
+                pk: Some("build/a.pk".into()),
+                vd: Some("build/a.vd".into()),
+            },
+            ManifestEntry {
+                source: "build/b.plang".into(),
+                params: "build/other.pp".into(),
+                pk: None,
+                vd: Some("build/b.vd".into()),
+            },
+        ]
+    );
+
+    // Every circuit has to be compiled with some parameters
+    let json = serde_json::json!({ "circuits": [{ "source": "a.plang", "vd": "a.vd" }] });
+    assert!(matches!(
+        Manifest::from_json(&json, Path::new("")),
+        Err(PlangError::Prove(ProveError::InvalidManifest(_)))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn rejects_invalid_envelopes() {
+    let bytes = artifact::wrap(ArtifactKind::Envelope, 0, &[0; 16]);
+    assert!(matches!(
+        ProofEnvelope::from_slice(&bytes),
+        Err(PlangError::Prove(ProveError::InvalidEnvelope))
+    ));
+
+    let bytes = artifact::wrap(ArtifactKind::Proof, 0, &[0; 16]);
+    assert!(matches!(
+        ProofEnvelope::from_slice(&bytes),
+        Err(PlangError::Prove(ProveError::WrongArtifact { .. }))
+    ));
+
+    let json = serde_json::json!({ "circuit_hash": "00", "proof": "00" });
+    assert!(matches!(
+        ProofEnvelope::from_json(&json),
+        Err(PlangError::Prove(ProveError::InvalidEnvelope))
+    ));
+}
+
+#[test]
+fn clones_circuits_across_threads() -> Result<()> {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PlangCircuit>();
+
+    let circuit = PlangCircuit::parse("a * b = c\na + b = d\n")?;
+    assert_eq!(circuit, circuit.clone());
+    assert_eq!(circuit, PlangCircuit::parse("a * b = c\na + b = d\n")?);
+    assert_ne!(circuit, PlangCircuit::parse("a * b = c\nb + a = d\n")?);
+
+    // Each thread sets its own values on its own copy
+    let circuit = std::sync::Arc::new(circuit);
+    let handles: Vec<_> = (1..4)
+        .map(|a| {
+            let circuit = std::sync::Arc::clone(&circuit);
+            std::thread::spawn(move || -> Result<PlangCircuit> {
+                let mut circuit = (*circuit).clone();
+                circuit.set_vals(vec![
+                    ("a".to_owned(), a),
+                    ("b".to_owned(), 2),
+                    ("c".to_owned(), 2 * a),
+                    ("d".to_owned(), a + 2),
+                ])?;
+                Ok(circuit)
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        let assigned = handle.join().expect("thread panicked")?;
+        assert_ne!(assigned, *circuit);
+        assert!(assigned.check_satisfied().is_ok());
+    }
+    assert_eq!(circuit.value("a"), Some(BlsScalar::zero()));
+
+    Ok(())
+}
+
+#[test]
+fn public_inputs_in_equation_order() -> Result<()> {
+    let circuit = PlangCircuit::parse("a + b = z\na * b = c\n")?;
+    assert_eq!(circuit.public_input_order(), vec!["z", "c"]);
+
+    match circuit.public_inputs_from(vec![("z".to_owned(), 2)]) {
+        Err(PlangError::Assignment(AssignmentError::MissingPublicInput(name))) => {
+            assert_eq!(name, "c")
+        }
+        res => panic!("expected a missing public input error, got {:?}", res),
+    }
+    match circuit.public_inputs_from(vec![("a".to_owned(), 1)]) {
+        Err(PlangError::Assignment(AssignmentError::NotPublicInput(name))) => assert_eq!(name, "a"),
+        res => panic!("expected a not public input error, got {:?}", res),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn snapshots_and_resets_values() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a * b = c\na + b = d\n")?;
+    circuit.set_vals(vec![
+        ("a".to_owned(), 2),
+        ("b".to_owned(), 3),
+        ("c".to_owned(), 6),
+        ("d".to_owned(), 5),
+    ])?;
+
+    let assignment = circuit.assignment();
+    assert_eq!(
+        assignment.witnesses.into_iter().collect::<Vec<_>>(),
+        [
+            ("a".to_owned(), BlsScalar::from(2)),
+            ("b".to_owned(), BlsScalar::from(3))
+        ]
+    );
+    assert_eq!(
+        assignment.public_inputs.into_iter().collect::<Vec<_>>(),
+        [
+            ("c".to_owned(), BlsScalar::from(6)),
+            ("d".to_owned(), BlsScalar::from(5))
+        ]
+    );
+
+    // Nothing of the previous values is left once reset
+    let assignment = circuit.assignment();
+    circuit.reset_values();
+    assert!(circuit
+        .assignment()
+        .witnesses
+        .values()
+        .chain(circuit.assignment().public_inputs.values())
+        .all(|value| *value == BlsScalar::zero()));
+    assert_eq!(circuit, PlangCircuit::parse("a * b = c\na + b = d\n")?);
+
+    // and they can be set back
+    circuit.set_vals(assignment.witnesses.clone())?;
+    circuit.set_vals(assignment.public_inputs.clone())?;
+    assert_eq!(circuit.assignment(), assignment);
+
+    Ok(())
+}
+
+#[test]
+fn names_public_inputs_with_values() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a + b = z\na * b = c\nb + a = z\n")?;
+    circuit.set_vals(vec![
+        ("a".to_owned(), 2),
+        ("b".to_owned(), 3),
+        ("z".to_owned(), 5),
+        ("c".to_owned(), 6),
+    ])?;
+
+    let named: Vec<(&str, BlsScalar)> = circuit.public_inputs_named().collect();
+    assert_eq!(
+        named,
+        [
+            ("z", BlsScalar::from(5)),
+            ("c", BlsScalar::from(6)),
+            ("z", BlsScalar::from(5))
+        ]
+    );
+
+    let names: Vec<&str> = named.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, circuit.public_input_order());
+
+    Ok(())
+}
+
+#[test]
+fn public_inputs_follow_declaration_order() -> Result<()> {
+    // Sorted by name, `a` would come before `z`.
+    let mut circuit = PlangCircuit::parse("x + y = z\nx * y = a\ny + x = z\n")?;
+    assert_eq!(circuit.public_input_order(), vec!["z", "a", "z"]);
+
+    circuit.set_vals(vec![
+        ("x".to_owned(), 3),
+        ("y".to_owned(), 2),
+        ("z".to_owned(), 5),
+        ("a".to_owned(), 6),
+    ])?;
+    let expected = circuit.public_inputs_from(vec![("z".to_owned(), 5), ("a".to_owned(), 6)])?;
+    assert_eq!(
+        format!("{:?}", circuit.public_inputs()),
+        format!("{:?}", expected)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validates_assignments_without_setting_them() -> Result<()> {
+    let circuit = PlangCircuit::parse("a + b = z\na * b = c\n")?;
+
+    let assignment: HashMap<String, u64> = vec![
+        ("b".to_owned(), 1),
+        ("x".to_owned(), 2),
+        ("c".to_owned(), 3),
+        ("w".to_owned(), 4),
+    ]
+    .into_iter()
+    .collect();
+
+    let report = circuit.validate_assignment(&assignment);
+    assert_eq!(report.unknown, ["w", "x"]);
+    assert_eq!(report.unassigned, ["a", "z"]);
+    assert!(report.not_public.is_empty());
+    assert!(!report.is_valid());
+
+    let report = circuit.validate_public_assignment(&assignment);
+    assert_eq!(report.unknown, ["w", "x"]);
+    assert_eq!(report.unassigned, ["z"]);
+    assert_eq!(report.not_public, ["b"]);
+
+    let public: HashMap<String, u64> = vec![("z".to_owned(), 1), ("c".to_owned(), 2)]
+        .into_iter()
+        .collect();
+    assert!(circuit.validate_public_assignment(&public).is_valid());
+
+    Ok(())
+}
+
+#[test]
+fn verifies_with_named_public_inputs() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a + b = z\na * b = c\n")?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    circuit.set_vals(vec![
+        ("a".to_owned(), 2),
+        ("b".to_owned(), 3),
+        ("z".to_owned(), 5),
+        ("c".to_owned(), 6),
+    ])?;
+    let proof = circuit.prove(&pp, &pk, b"test")?;
+
+    let pinputs = circuit.public_inputs_from(vec![("c".to_owned(), 6), ("z".to_owned(), 5)])?;
+    PlangCircuit::verify(&pp, &vd, &proof, &pinputs, b"test")?;
+
+    Ok(())
+}
+
+#[test]
+fn verifies_a_batch() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a + b = z\na * b = c\n")?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    let assignments = vec![(2, 3), (4, 5)].into_iter().map(|(a, b)| {
+        vec![
+            ("a".to_owned(), a),
+            ("b".to_owned(), b),
+            ("z".to_owned(), a + b),
+            ("c".to_owned(), a * b),
+        ]
+    });
+    let proofs = prover::prove_many(&circuit, assignments, &pk, &pp, b"test")?;
+
+    let pinputs =
+        |z: u64, c: u64| circuit.public_inputs_from(vec![("z".to_owned(), z), ("c".to_owned(), c)]);
+    let batch = vec![(proofs[0], pinputs(5, 6)?), (proofs[1], pinputs(9, 20)?)];
+    verifier::verify_batch(&pp, &vd, &batch, b"test")?;
+
+    // A single proof with the wrong public inputs fails the whole batch.
+    let batch = vec![(proofs[0], pinputs(5, 6)?), (proofs[1], pinputs(5, 6)?)];
+    assert!(verifier::verify_batch(&pp, &vd, &batch, b"test").is_err());
+
+    // Each proof is verified on its own.
+    let results = verifier::verify_each(&pp, &vd, &batch, b"test");
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+
+    Ok(())
+}
+
+#[test]
+fn session_proves_by_name() -> Result<()> {
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+
+    let mut session = ProvingSession::new(pp);
+    session.add("sum", PlangCircuit::parse("a + b = z\n")?)?;
+    session.add("product", PlangCircuit::parse("a * b = c\n")?)?;
+
+    let mut names: Vec<&str> = session.names().collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["product", "sum"]);
+
+    let vals = vec![
+        ("a".to_owned(), 2),
+        ("b".to_owned(), 3),
+        ("c".to_owned(), 6),
+    ];
+    let product = session.prove("product", vals)?;
+    session.verify("product", &product, vec![("c".to_owned(), 6)])?;
+
+    let vals = vec![
+        ("a".to_owned(), 2),
+        ("b".to_owned(), 3),
+        ("z".to_owned(), 5),
+    ];
+    let sum = session.prove("sum", vals)?;
+    session.verify("sum", &sum, vec![("z".to_owned(), 5)])?;
+    assert!(session
+        .verify("sum", &product, vec![("z".to_owned(), 5)])
+        .is_err());
+
+    // Values of earlier proofs aren't carried over, so `z` is back to 0.
+    let zero = session.prove("sum", vec![("a".to_owned(), 0), ("b".to_owned(), 0)])?;
+    session.verify("sum", &zero, vec![("z".to_owned(), 0)])?;
+
+    match session.prove("difference", Vec::<(String, u64)>::new()) {
+        Err(PlangError::Prove(ProveError::NoSuchCircuit(name))) => assert_eq!(name, "difference"),
+        res => panic!("expected a no such circuit error, got {:?}", res),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn composes_transcript_with_domains() {
+    assert_eq!(ProveOptions::new("test").transcript(), b"test");
+    assert_eq!(ProveOptions::default().transcript(), b"dusk_plang");
+
+    let options = ProveOptions::with_domains("test", vec!["v1", "transfer"]);
+    assert_eq!(options.label(), b"test");
+    assert_eq!(options.domains(), [b"v1".to_vec(), b"transfer".to_vec()]);
+    assert_eq!(options.transcript(), b"\x02\0\0\0v1\x08\0\0\0transfertest");
+
+    // The same transcript is only leaked once, whichever thread creates it
+    let again = ProveOptions::with_domains("test", vec!["v1", "transfer"]);
+    assert!(std::ptr::eq(again.transcript(), options.transcript()));
+    let elsewhere = std::thread::spawn(|| {
+        ProveOptions::with_domains("test", vec!["v1", "transfer"]).transcript()
+    })
+    .join()
+    .expect("the thread doesn't panic");
+    assert!(std::ptr::eq(elsewhere, options.transcript()));
+}
+
+#[test]
+fn proves_with_domain_separation() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a * b = c\n")?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    circuit.set_vals(vec![
+        ("a".to_owned(), 2),
+        ("b".to_owned(), 3),
+        ("c".to_owned(), 6),
+    ])?;
+
+    let options = ProveOptions::with_domains("test", vec!["v1"]);
+    let proof = circuit.prove_with(&pp, &pk, &options)?;
+
+    let pinputs = circuit.public_inputs_from(vec![("c".to_owned(), 6)])?;
+    PlangCircuit::verify_with(&pp, &vd, &proof, &pinputs, &options)?;
+
+    let label_only = ProveOptions::new("test");
+    assert!(PlangCircuit::verify_with(&pp, &vd, &proof, &pinputs, &label_only).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn verifies_with_verifier_archive() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a + b = z\na * b = -c\n")?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    circuit.set_vals(vec![
+        ("a".to_owned(), BlsScalar::from(2)),
+        ("b".to_owned(), BlsScalar::from(3)),
+        ("z".to_owned(), BlsScalar::from(5)),
+        ("c".to_owned(), -BlsScalar::from(6)),
+    ])?;
+    let proof = circuit.prove(&pp, &pk, b"test")?;
+
+    let verifier = PlangVerifier::from_slice(&PlangVerifier::new(&circuit, vd).to_bytes())?;
+    assert_eq!(
+        verifier.public_input_order().collect::<Vec<_>>(),
+        vec!["z", "c"]
+    );
+
+    let vals = vec![
+        ("c".to_owned(), -BlsScalar::from(6)),
+        ("z".to_owned(), 5.into()),
+    ];
+    assert_eq!(
+        format!("{:?}", verifier.public_inputs_from(vals.clone())?),
+        format!("{:?}", circuit.public_inputs_from(vals.clone())?),
+    );
+    verifier.verify(&pp, &proof, vals, &ProveOptions::new("test"))?;
+
+    match verifier.public_inputs_from(vec![("a".to_owned(), 2)]) {
+        Err(PlangError::Assignment(AssignmentError::NotPublicInput(name))) => assert_eq!(name, "a"),
+        res => panic!("expected a not public input error, got {:?}", res),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn runs_test_vectors() -> Result<()> {
+    let circuit = PlangCircuit::parse(fs::read_to_string("./test.plang")?)?;
+    assert_eq!(circuit.tests().len(), 2);
+    for test in circuit.tests() {
+        circuit.run_test(test)?;
+    }
+
+    // Test vectors don't change the constraints
+    let text = "a * b + c = d\n\
+                test satisfied \"basic\" { a = 2; b = 3; c = -1; d = 5; }\n\
+                test unsatisfied \"off by one\" { a = 2; b = 3; c = 1; d = 8; }\n\
+                test satisfied \"wrong\" { a = 2; b = 3; d = 7; }\n\
+                test unsatisfied \"zeros\" {}\n";
+    let circuit = PlangCircuit::parse(text)?;
+    assert_eq!(
+        circuit.hash(),
+        PlangCircuit::parse("a * b + c = d\n")?.hash()
+    );
+
+    let tests = circuit.tests();
+    assert_eq!(tests.len(), 4);
+    assert_eq!(tests[0].name, "basic");
+    assert!(tests[0].satisfied);
+    assert_eq!(tests[0].values[2], ("c".to_owned(), -Scalar::one()));
+    assert_eq!(tests[3].line, 4);
+    assert!(tests[3].values.is_empty());
+
+    circuit.run_test(&tests[0])?;
+    circuit.run_test(&tests[1])?;
+    match circuit.run_test(&tests[2]) {
+        Err(PlangError::Assignment(AssignmentError::Unsatisfied { line, equation })) => {
+            assert_eq!(line, 0);
+            assert_eq!(equation, "a * b + c = d");
+        }
+        _ => panic!("expected the equation not to be satisfied"),
+    }
+    match circuit.run_test(&tests[3]) {
+        Err(PlangError::Assignment(AssignmentError::UnexpectedlySatisfied(name))) => {
+            assert_eq!(name, "zeros")
+        }
+        _ => panic!("expected the test vector to satisfy the circuit"),
+    }
+
+    // Values not in the circuit are errors, and vectors in blocks that
+    // aren't taken are skipped
+    let text = "const N = 1\n\
+                a = b\n\
+                test satisfied \"unknown\" { e = 1; }\n\
+                if N > 1 {\n\
+                test satisfied \"skipped\" {}\n\
+                }\n";
+    let circuit = PlangCircuit::parse(text)?;
+    assert_eq!(circuit.tests().len(), 1);
+    assert!(matches!(
+        circuit.run_test(&circuit.tests()[0]),
+        Err(PlangError::Assignment(AssignmentError::NoSuchValue(name))) if name == "e"
+    ));
+
+    // Values set for proving are checked the same
+    let mut circuit = PlangCircuit::parse("a * b = c\n")?;
+    circuit.set_vals(vec![("a".to_owned(), 3), ("b".to_owned(), 5)])?;
+    assert!(circuit.check_satisfied().is_err());
+    circuit.set_vals(vec![("c".to_owned(), 15)])?;
+    circuit.check_satisfied()?;
+
+    Ok(())
+}
+
+#[test]
+fn probes_mutants() -> Result<()> {
+    let circuit = PlangCircuit::parse(fs::read_to_string("./test.plang")?)?;
+    let report = circuit.probe(circuit.tests())?;
+    assert_eq!(report.vectors, 1);
+
+    // "wrong sum" only breaks the first equation, so only dropping it is
+    // noticed
+    let killed: Vec<&Mutant> = report.mutants.iter().filter(|m| !m.survived()).collect();
+    assert_eq!(killed.len(), 1);
+    assert_eq!(killed[0].mutation, Mutation::Drop);
+    assert_eq!(killed[0].equation, 0);
+    assert_eq!(killed[0].killed_by.as_deref(), Some("wrong sum"));
+    assert!(report
+        .survivors()
+        .any(|m| m.equation == 1 && m.mutation == Mutation::Drop));
+
+    // A sign error in the sum is accepted by the mutant flipping it
+    let text = "a + b = c
+                test unsatisfied \"difference\" { a = 3; b = 1; c = 2; }
+";
+    let circuit = PlangCircuit::parse(text)?;
+    let report = circuit.probe(circuit.tests())?;
+    let killed: Vec<Mutation> = report
+        .mutants
+        .iter()
+        .filter(|m| !m.survived())
+        .map(|m| m.mutation)
+        .collect();
+    assert_eq!(killed, [Mutation::Drop, Mutation::FlipSign(2)]);
+    assert_eq!(
+        report
+            .survivors()
+            .next()
+            .map(ToString::to_string)
+            .as_deref(),
+        Some("line 1: `a + b = c`: flipping the sign of q_l goes unnoticed")
+    );
+
+    // Test vectors expected not to satisfy the circuit have to not satisfy it
+    let text = "a + b = c
+test unsatisfied \"sum\" { a = 1; b = 1; c = 2; }
+";
+    let circuit = PlangCircuit::parse(text)?;
+    match circuit.probe(circuit.tests()) {
+        Err(PlangError::Assignment(AssignmentError::UnexpectedlySatisfied(name))) => {
+            assert_eq!(name, "sum")
+        }
+        _ => panic!("expected the test vector to satisfy the circuit"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn reports_coverage() -> Result<()> {
+    let circuit = PlangCircuit::parse("a * b + c = d\na + 3 * e = f\n")?;
+    let vals = |vals: &[(&str, u64)]| -> Vec<(String, Scalar)> {
+        vals.iter()
+            .map(|(name, value)| (name.to_string(), Scalar::from(*value)))
+            .collect()
+    };
+
+    let report = circuit.coverage(vec![
+        vals(&[("a", 2), ("b", 0), ("c", 1), ("d", 1)]),
+        vals(&[("a", 2), ("b", 3), ("d", 6)]),
+    ])?;
+    assert_eq!(report.assignments, 2);
+    assert_eq!(
+        report.gates[0].selectors,
+        [Some(1), None, None, Some(1), None, Some(2), None]
+    );
+    assert_eq!(
+        report.gates[0].to_string(),
+        "line 1: `a * b + c = d`: q_m 1, q_o 1, q_p 2"
+    );
+
+    // `e` and `f` are never set, so only `a` is exercised
+    let unexercised: Vec<&str> = report.gates[1].unexercised().collect();
+    assert_eq!(unexercised, ["q_r", "q_p"]);
+    assert!(report.inactive().next().is_none());
+    assert_eq!(report.exercised(), (6, 4));
+
+    let report = circuit.coverage(vec![vals(&[("c", 1), ("d", 1)])])?;
+    let inactive: Vec<usize> = report.inactive().map(|gate| gate.equation).collect();
+    assert_eq!(inactive, [1]);
+
+    assert!(matches!(
+        circuit.coverage(vec![vals(&[("z", 1)])]),
+        Err(PlangError::Assignment(AssignmentError::NoSuchValue(_)))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn checks_assertions() -> Result<()> {
+    let text = "a * b = c\n\
+                assert b != 0;\n\
+                assert c == a\n\
+                test satisfied \"identity\" { a = 3; b = 1; c = 3; }\n\
+                test unsatisfied \"zero\" {}\n";
+    let mut circuit = PlangCircuit::parse(text)?;
+    assert_eq!(circuit.hash(), PlangCircuit::parse("a * b = c\n")?.hash());
+
+    for test in circuit.tests() {
+        circuit.run_test(test)?;
+    }
+
+    // Assertions are checked when values are set, and before the equations
+    match circuit.set_vals(vec![("a".to_owned(), 2), ("b".to_owned(), 0)]) {
+        Err(PlangError::Assignment(AssignmentError::AssertionFailed { line, assertion })) => {
+            assert_eq!(line, 1);
+            assert_eq!(assertion, "assert b != 0");
+        }
+        _ => panic!("expected the assertion not to hold"),
+    }
+    match circuit.check_satisfied() {
+        Err(PlangError::Assignment(AssignmentError::AssertionFailed { line, .. })) => {
+            assert_eq!(line, 1)
+        }
+        _ => panic!("expected the assertion not to hold"),
+    }
+
+    circuit.set_vals(vec![("b".to_owned(), 2), ("c".to_owned(), 2)])?;
+    assert!(matches!(
+        circuit.check_satisfied(),
+        Err(PlangError::Assignment(AssignmentError::Unsatisfied {
+            line: 0,
+            ..
+        }))
+    ));
+
+    // Assertions on values, and on variables introduced while lowering
+    let text = "bits(x, 2) -> b[]\nassert b[1] == -1\nassert e != 0\n";
+    let mut circuit = PlangCircuit::parse(text)?;
+    match circuit.set_vals(vec![("x".to_owned(), 2)]) {
+        Err(PlangError::Assignment(AssignmentError::AssertionFailed { line, .. })) => {
+            assert_eq!(line, 1)
+        }
+        _ => panic!("expected the assertion not to hold"),
+    }
+
+    let text = "bits(x, 2) -> b[]\nassert b[1] == 1\nassert e != 0\n";
+    let mut circuit = PlangCircuit::parse(text)?;
+    assert!(matches!(
+        circuit.set_vals(vec![("x".to_owned(), 2)]),
+        Err(PlangError::Assignment(AssignmentError::NoSuchValue(name))) if name == "e"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn splits_errors_by_subsystem() -> Result<()> {
+    match PlangCircuit::parse("a*a = d\n") {
+        Err(PlangError::Semantic(err)) => {
+            assert_eq!(PlangError::from(err).code(), Some("P0004"))
+        }
+        res => panic!("expected a semantic error, got {:?}", res.map(|c| c.hash())),
+    }
+    assert!(matches!(
+        PlangCircuit::parse("a*b*c = d\n"),
+        Err(PlangError::Parse(ParseError::Pest(_)))
+    ));
+
+    let mut circuit = PlangCircuit::parse("a + b = c\n")?;
+    assert!(matches!(
+        circuit.set_vals(vec![("d".to_owned(), 1)]),
+        Err(PlangError::Assignment(AssignmentError::NoSuchValue(name))) if name == "d"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn imports_are_namespaced() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("plang-imports-{}", std::process::id()));
+    fs::create_dir_all(dir.join("lib"))?;
+    fs::write(dir.join("square.plang"), "a * b = c\n")?;
+    fs::write(dir.join("lib").join("sum.plang"), "a + b = c\n")?;
+    fs::write(
+        dir.join("main.plang"),
+        "import \"square.plang\" as sq\nimport \"sum.plang\" as sum\nsq::a + sum::a = y\n",
+    )?;
+    fs::write(dir.join("cycle.plang"), "import \"cycle.plang\" as c\n")?;
+    fs::write(dir.join("invalid.plang"), "a*a = c\n")?;
+    fs::write(
+        dir.join("imports_invalid.plang"),
+        "x + y = z\nimport \"invalid.plang\" as bad\n",
+    )?;
+
+    // Imports are looked up next to the importing file, then in the search path
+    assert!(PlangCircuit::from_file(dir.join("main.plang")).is_err());
+    let options = ParseOptions {
+        search: SearchPath::new([dir.join("lib")]),
+        ..ParseOptions::default()
+    };
+    let mut circuit = PlangCircuit::from_file_with(dir.join("main.plang"), &options)?;
+    assert_eq!(circuit.public_input_order(), ["sq::c", "sum::c", "y"]);
+
+    let vals = vec![
+        ("sq::a", 2),
+        ("sq::b", 3),
+        ("sq::c", 6),
+        ("sum::a", 1),
+        ("sum::b", 4),
+        ("sum::c", 5),
+        ("y", 3),
+    ];
+    circuit.set_vals(vals.into_iter().map(|(name, val)| (name.to_owned(), val)))?;
+    circuit.check_satisfied()?;
+
+    let options = ParseOptions {
+        search: SearchPath::new([&dir]),
+        ..ParseOptions::default()
+    };
+    assert!(matches!(
+        PlangCircuit::parse_with("import \"square.plang\" as sq\nimport \"square.plang\" as sq\n", &options),
+        Err(PlangError::Semantic(SemanticError::RepeatedImport(ns))) if ns == "sq"
+    ));
+    assert!(matches!(
+        PlangCircuit::parse_with("sq::a = b\n", &options),
+        Err(PlangError::Semantic(SemanticError::UndefinedNamespace(var))) if var == "sq::a"
+    ));
+
+    // Text parsed without a search path imports nothing, not even from the
+    // working directory, and no import escapes the search path
+    assert!(matches!(
+        PlangCircuit::parse("import \"test.plang\" as t\n"),
+        Err(PlangError::Semantic(SemanticError::UnresolvedImport(_)))
+    ));
+    let outside = dir.join("lib").join("..").join("square.plang");
+    for name in ["lib/../square.plang", outside.to_str().expect("utf-8")] {
+        let text = format!("import \"{}\" as sq\n", name);
+        assert!(matches!(
+            PlangCircuit::parse_with(text, &options),
+            Err(PlangError::Semantic(SemanticError::InvalidImport(import))) if import == name
+        ));
+    }
+
+    match PlangCircuit::from_file(dir.join("cycle.plang")) {
+        Err(PlangError::InFile { error, .. }) => assert!(matches!(
+            *error,
+            PlangError::Semantic(SemanticError::ImportCycle(_))
+        )),
+        res => panic!("expected an import cycle, got {:?}", res.map(|c| c.hash())),
+    }
+
+    // Errors in an imported circuit are returned with where it's imported
+    match PlangCircuit::from_file(dir.join("imports_invalid.plang")) {
+        Err(PlangError::InFile { error, .. }) => match *error {
+            PlangError::Imported { path, line, error } => {
+                assert_eq!(path, dir.join("invalid.plang"));
+                assert_eq!(line, 1);
+                assert_eq!(error.code(), Some("P0004"));
+            }
+            err => panic!("expected an imported error, got {}", err),
+        },
+        res => panic!(
+            "expected an imported error, got {:?}",
+            res.map(|c| c.hash())
+        ),
+    }
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn packages_export_circuits() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("plang-packages-{}", std::process::id()));
+    let pkg = dir.join("sums.plangpkg");
+    fs::create_dir_all(&pkg)?;
+    fs::write(
+        pkg.join("manifest.json"),
+        r#"{ "name": "sums", "version": "1.0.0", "exports": ["sum.plang"] }"#,
+    )?;
+    fs::write(
+        pkg.join("sum.plang"),
+        "import \"pair.plang\" as p\np::a + b = c\n",
+    )?;
+    fs::write(pkg.join("pair.plang"), "a + e = b\n")?;
+
+    let package = Package::open(&pkg)?;
+    assert_eq!(package.name(), "sums");
+    assert_eq!(package.version(), "1.0.0");
+    assert_eq!(package.exports(), ["sum.plang"]);
+
+    // Circuits of a package import each other, but only exports are imported
+    // from outside of it
+    let circuit = package.circuit("sum.plang", &ParseOptions::default())?;
+    assert_eq!(circuit.public_input_order(), ["p::b", "c"]);
+    assert!(package
+        .circuit("pair.plang", &ParseOptions::default())
+        .is_err());
+
+    let mut options = ParseOptions::default();
+    options.search.include(&pkg)?;
+    let circuit = PlangCircuit::parse_with("import \"sums/sum.plang\" as s\ns::b = x\n", &options)?;
+    assert_eq!(circuit.public_input_order(), ["s::p::b", "s::c", "x"]);
+    assert!(matches!(
+        PlangCircuit::parse_with("import \"sums/pair.plang\" as p\n", &options),
+        Err(PlangError::Semantic(SemanticError::UnresolvedImport(_)))
+    ));
+
+    fs::write(
+        pkg.join("manifest.json"),
+        r#"{ "name": "sums", "version": "1.0.0", "exports": ["../sum.plang"] }"#,
+    )?;
+    assert!(matches!(
+        Package::open(&pkg),
+        Err(PlangError::Parse(ParseError::InvalidPackage(_)))
+    ));
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn editions_reserve_keywords() -> Result<()> {
+    // Variables named after keywords are only reserved from edition 2024 on
+    PlangCircuit::parse("if + a = b\n")?;
+    assert!(matches!(
+        PlangCircuit::parse("edition 2024\nif + a = b\n"),
+        Err(PlangError::Parse(ParseError::Pest(_)))
+    ));
+    assert!(matches!(
+        PlangCircuit::parse("edition 2024\nsum(i in 0..2, in[i]) = x\n"),
+        Err(PlangError::Parse(ParseError::Pest(_)))
+    ));
+
+    // The edition declared takes precedence over the one given
+    let options = ParseOptions {
+        edition: Edition::E2024,
+        ..ParseOptions::default()
+    };
+    assert!(PlangCircuit::parse_with("if + a = b\n", &options).is_err());
+    PlangCircuit::parse_with("edition 2021\nif + a = b\n", &options)?;
+
+    let grammar = PlangGrammar::new("edition 2024\na + b = c\n")?;
+    assert_eq!(grammar.edition(), Edition::E2024);
+    assert_eq!(PlangGrammar::new("a + b = c\n")?.edition(), Edition::E2021);
+
+    assert!(matches!(
+        PlangCircuit::parse("a + b = c\nedition 2024\n"),
+        Err(PlangError::Parse(ParseError::Pest(_)))
+    ));
+    assert!(matches!(
+        PlangCircuit::parse("edition 2023\n"),
+        Err(PlangError::Semantic(SemanticError::UnknownEdition(edition))) if edition == "2023"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn editions_gate_their_statements() -> Result<()> {
+    // `gt` and `gte` are only compared with from edition 2024 on
+    for text in ["gt(a, b, 8)\n", "r = gte(a, b, 8)\n"] {
+        assert!(
+            matches!(
+                PlangCircuit::parse(text),
+                Err(PlangError::Parse(ParseError::Pest(_)))
+            ),
+            "{}",
+            text
+        );
+        assert!(matches!(
+            PlangCircuit::parse(&format!("edition 2021\n{}", text)),
+            Err(PlangError::Parse(ParseError::Pest(_)))
+        ));
+        PlangCircuit::parse(&format!("edition 2024\n{}", text))?;
+    }
+
+    let options = ParseOptions {
+        edition: Edition::E2024,
+        ..ParseOptions::default()
+    };
+    PlangCircuit::parse_with("gt(a, b, 8)\n", &options)?;
+    assert!(PlangCircuit::parse_with("edition 2021\ngt(a, b, 8)\n", &options).is_err());
+
+    // Edition 2021 still takes them for variables, which edition 2024 reserves
+    PlangCircuit::parse("gt + gte = b\n")?;
+    assert!(matches!(
+        PlangCircuit::parse("edition 2024\ngt + a = b\n"),
+        Err(PlangError::Parse(ParseError::Pest(_)))
+    ));
+
+    let satisfied = |text: &str, a: u64, b: u64| -> Result<bool> {
+        let mut circuit = PlangCircuit::parse(text)?;
+        let vals = vec![
+            ("a".to_owned(), BlsScalar::from(a)),
+            ("b".to_owned(), BlsScalar::from(b)),
+        ];
+        circuit.set_vals(vals)?;
+
+        let mut evaluator = Evaluator::default();
+        circuit.emit(&mut evaluator)?;
+        Ok(evaluator
+            .gates
+            .iter()
+            .all(|gate| *gate == BlsScalar::zero()))
+    };
+
+    assert!(satisfied("edition 2024\ngt(a, b, 8)\n", 5, 3)?);
+    assert!(!satisfied("edition 2024\ngt(a, b, 8)\n", 5, 5)?);
+    assert!(satisfied("edition 2024\ngte(a, b, 8)\n", 5, 5)?);
+    assert!(!satisfied("edition 2024\ngte(a, b, 8)\n", 5, 6)?);
+
+    for (text, a, b, result) in [
+        ("edition 2024\nr = gt(a, b, 8)\n", 5, 3, 1),
+        ("edition 2024\nr = gt(a, b, 8)\n", 5, 5, 0),
+        ("edition 2024\nr = gte(a, b, 8)\n", 5, 5, 1),
+        ("edition 2024\nr = gte(a, b, 8)\n", 0, 255, 0),
+    ] {
+        let mut circuit = PlangCircuit::parse(text)?;
+        let vals = vec![
+            ("a".to_owned(), BlsScalar::from(a)),
+            ("b".to_owned(), BlsScalar::from(b)),
+        ];
+        circuit.set_vals(vals)?;
+        assert_eq!(
+            circuit.value("r"),
+            Some(BlsScalar::from(result)),
+            "{}",
+            text
+        );
+        assert!(satisfied(text, a, b)?, "{}", text);
+    }
+
+    // `gt(a, b, n)` is `lt(b, a, n)`, and compiles to the same gates
+    let gt = PlangCircuit::parse("edition 2024\ngt(a, b, 8)\n")?;
+    let lt = PlangCircuit::parse("edition 2024\nlt(b, a, 8)\n")?;
+    assert_eq!(gt.hash(), lt.hash());
+
+    Ok(())
+}
@@ -0,0 +1,328 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg(feature = "std")]
+
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+
+use pest::error::LineColLocation;
+use plang::document::Document;
+use plang::{ParseError, PlangCircuit, PlangError, SemanticError};
+
+type Result<T> = std::result::Result<T, PlangError>;
+
+#[test]
+fn same_circuit_as_parsing() -> Result<()> {
+    let bytes = fs::read("./test.plang")?;
+    let text = String::from_utf8(bytes)?;
+
+    let document = Document::new(&text);
+    assert!(document.diagnostics().is_empty());
+
+    let circuit = PlangCircuit::parse(&text)?;
+    assert_eq!(document.to_circuit()?.hash(), circuit.hash());
+    assert_eq!(document.to_circuit()?.tests(), circuit.tests());
+
+    Ok(())
+}
+
+#[test]
+fn streaming_parse() -> Result<()> {
+    let bytes = fs::read("./test.plang")?;
+
+    let circuit = PlangCircuit::parse(String::from_utf8(bytes.clone())?)?;
+    let streamed = PlangCircuit::parse_reader(&bytes[..])?;
+    assert_eq!(streamed.hash(), circuit.hash());
+    assert_eq!(streamed.public_input_order(), circuit.public_input_order());
+    assert_eq!(streamed.tests(), circuit.tests());
+
+    // Errors point to their line in the text
+    let text = "# sum\r\na + b = c\r\n\na + = d\n";
+    match PlangCircuit::parse_reader(text.as_bytes()) {
+        Err(PlangError::Parse(ParseError::Pest(err))) => assert!(err.to_string().contains("4:")),
+        _ => panic!("expected a syntax error"),
+    }
+
+    let text = "transcript \"a\"\na + b = c\ntranscript \"b\"";
+    assert!(matches!(
+        PlangCircuit::parse_reader(text.as_bytes()),
+        Err(PlangError::Semantic(SemanticError::RepeatedTranscript))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn displays_equations_with_roles() -> Result<()> {
+    let circuit = PlangCircuit::parse("# sum\na + b = c\n3*a*b - b = -d\n")?;
+    assert_eq!(
+        circuit.to_string(),
+        "# public inputs: c, d\n# witnesses: a, b\na + b = c\n3 * a * b - b = -d\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parses_from_files_and_strings() -> Result<()> {
+    let text = fs::read_to_string("./test.plang")?;
+    let circuit = PlangCircuit::parse(&text)?;
+
+    assert_eq!(PlangCircuit::from_file("./test.plang")?, circuit);
+    assert_eq!(text.parse::<PlangCircuit>()?, circuit);
+    assert_eq!(PlangCircuit::try_from(text.as_str())?, circuit);
+
+    // Errors are returned with the path of the file
+    match PlangCircuit::from_file("./missing.plang") {
+        Err(PlangError::InFile { path, error }) => {
+            assert_eq!(path, Path::new("./missing.plang"));
+            assert!(matches!(*error, PlangError::Io(_)));
+        }
+        res => panic!("expected an error reading the file, got {:?}", res),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn sums_are_expanded_line_by_line() -> Result<()> {
+    let text = "# bits\nsum(i in 0..4, 2^i*b[i]) = x\na * b[0] = y\n";
+    let circuit = PlangCircuit::parse(text)?;
+
+    let document = Document::new(text);
+    assert_eq!(document.to_circuit()?.hash(), circuit.hash());
+    assert_eq!(
+        PlangCircuit::parse_reader(text.as_bytes())?.hash(),
+        circuit.hash()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn compiles_blocks_conditionally() -> Result<()> {
+    let text = "const N = 32\n\
+        if N > 16 {\n\
+        a * b = c\n\
+        if N == 64 {\n\
+        const M = 1\n\
+        a + b = d\n\
+        } else {\n\
+        a + b = e\n\
+        }\n\
+        } else {\n\
+        a + b = c\n\
+        }\n";
+    let circuit = PlangCircuit::parse(text)?;
+    let expected = PlangCircuit::parse("\n\na * b = c\n\n\n\n\na + b = e\n")?;
+    assert_eq!(circuit.hash(), expected.hash());
+
+    assert_eq!(Document::new(text).to_circuit()?.hash(), circuit.hash());
+    assert_eq!(
+        PlangCircuit::parse_reader(text.as_bytes())?.hash(),
+        circuit.hash()
+    );
+
+    // Errors in lines that aren't compiled are skipped.
+    let text = "const N = 1\nif N < 1 {\na + a = b\n}\na + b = c\n";
+    assert!(PlangCircuit::parse(text).is_ok());
+    assert!(Document::new(text).to_circuit().is_ok());
+
+    assert!(matches!(
+        PlangCircuit::parse("if N > 1 {\n}\n"),
+        Err(PlangError::Semantic(SemanticError::UndefinedConstant(_)))
+    ));
+    assert!(matches!(
+        PlangCircuit::parse("const N = 1\nconst N = 2\n"),
+        Err(PlangError::Semantic(SemanticError::RepeatedConstant(_)))
+    ));
+    for text in [
+        "const N = 1\nif N > 1 {\n",
+        "}\n",
+        "const N = 1\nif N > 1 {\n} else {\n} else {\n}\n",
+    ] {
+        assert!(matches!(
+            PlangCircuit::parse(text),
+            Err(PlangError::Semantic(SemanticError::UnbalancedBlock))
+        ));
+        assert!(matches!(
+            Document::new(text).to_circuit(),
+            Err(PlangError::Semantic(SemanticError::UnbalancedBlock))
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn evaluates_arithmetic_on_constants() -> Result<()> {
+    let hash = |text: &str| PlangCircuit::parse(text).map(|circuit| circuit.hash());
+
+    let text = "const N = 4\n(N - 1)*a + (2^N)*b = c\nsum(i in 0..N, (1 << i)*d[i]) = e\n";
+    assert_eq!(
+        hash(text)?,
+        hash("\n3*a + 16*b = c\nsum(i in 0..4, (1 << i)*d[i]) = e\n")?
+    );
+    assert_eq!(Document::new(text).to_circuit()?.hash(), hash(text)?);
+    assert_eq!(
+        PlangCircuit::parse_reader(text.as_bytes())?.hash(),
+        hash(text)?
+    );
+
+    // Precedence, associativity, and subtraction wrapping around the modulus
+    assert_eq!(
+        hash("(1 + 2*3^2 << 1)*a + (2^3^2)*b = c\n")?,
+        hash("38*a + 512*b = c\n")?
+    );
+    assert_eq!(hash("(0 - 1)*a + b = c\n")?, hash("-a + b = c\n")?);
+
+    assert!(matches!(
+        hash("(N)*a = b\nconst N = 1\n"),
+        Err(PlangError::Semantic(SemanticError::UndefinedConstant(_)))
+    ));
+    assert!(matches!(
+        Document::new("(N)*a = b\nconst N = 1\n").to_circuit(),
+        Err(PlangError::Semantic(SemanticError::UndefinedConstant(_)))
+    ));
+    assert!(matches!(
+        hash("(1 << (0 - 1))*a = b\n"),
+        Err(PlangError::Semantic(SemanticError::InvalidConstExpr(_)))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn edits_are_rechecked() -> Result<()> {
+    let mut document = Document::new("a + b = c\na * b = d\n");
+
+    // Break the second line, then insert a line above it
+    document.edit(1..2, "a * a = d");
+    document.edit(1..1, "transcript \"edited\"\n# product\n");
+
+    let diagnostics = document.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 3);
+    assert!(matches!(
+        &diagnostics[0].error,
+        PlangError::Semantic(SemanticError::SameTriVars { line: 3, var }) if var == "a"
+    ));
+    let json = diagnostics[0].to_json();
+    assert_eq!(json["line"], 3);
+    assert_eq!(json["code"], "P0004");
+    assert_eq!(json["message"], diagnostics[0].error.to_string());
+    assert!(matches!(
+        document.to_circuit(),
+        Err(PlangError::Semantic(SemanticError::SameTriVars {
+            line: 3,
+            ..
+        }))
+    ));
+
+    // A syntax error is reported at its line in the document
+    document.edit(0..1, "a + = c");
+    let diagnostics = document.diagnostics();
+    assert_eq!(diagnostics[0].line, 0);
+    match document.to_circuit() {
+        Err(PlangError::Parse(ParseError::Pest(err))) => assert!(err.to_string().contains("1:")),
+        _ => panic!("expected a syntax error"),
+    }
+
+    document.edit(0..1, "a + b = c");
+    document.edit(3..4, "a * b = d");
+    assert!(document.diagnostics().is_empty());
+
+    let circuit = PlangCircuit::parse(document.text())?;
+    assert_eq!(document.to_circuit()?.hash(), circuit.hash());
+    assert_eq!(document.to_circuit()?.transcript(), Some(&b"edited"[..]));
+
+    Ok(())
+}
+
+#[test]
+fn updates_match_a_fresh_parse() -> Result<()> {
+    let mut document = Document::new("a + b = c\na * b = d\na + = e\n");
+
+    // Lines move, change and repeat, and the syntax error moves with them
+    let text = "# product\na * b = d\na + = e\na + b = c\na * c = f\na + b = c\n";
+    document.update(text);
+
+    let fresh = Document::new(text);
+    assert_eq!(document.text(), fresh.text());
+
+    let lines = |document: &Document| -> Vec<usize> {
+        document.diagnostics().iter().map(|d| d.line).collect()
+    };
+    assert_eq!(lines(&document), [2]);
+    assert_eq!(lines(&document), lines(&fresh));
+
+    document.update(text.replace("a + = e", "a + b = e"));
+    assert!(document.diagnostics().is_empty());
+
+    let circuit = PlangCircuit::parse(document.text())?;
+    assert_eq!(document.to_circuit()?.hash(), circuit.hash());
+
+    Ok(())
+}
+
+#[test]
+fn syntax_errors_track_their_line() {
+    let line_of = |document: &Document| match document.diagnostics()[0].error {
+        PlangError::Parse(ParseError::Pest(err)) => match err.line_col {
+            LineColLocation::Pos((line, _)) | LineColLocation::Span((line, _), _) => line,
+        },
+        _ => panic!("expected a syntax error"),
+    };
+
+    let mut document = Document::new("a + b = c\na + = d\n");
+    assert_eq!(line_of(&document), 2);
+
+    // Lines inserted above the error move it down
+    document.edit(0..0, "# sum\n\n");
+    assert_eq!(line_of(&document), 4);
+
+    document.edit(0..2, "");
+    assert_eq!(line_of(&document), 2);
+}
+
+#[test]
+fn repeated_transcript() {
+    let document = Document::new("transcript \"a\"\na + b = c\ntranscript \"b\"\n");
+
+    let diagnostics = document.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 2);
+    assert!(matches!(
+        document.to_circuit(),
+        Err(PlangError::Semantic(SemanticError::RepeatedTranscript))
+    ));
+}
+
+#[test]
+fn lenient_parse_skips_errors() {
+    let text = "a + b = c\na + = d\na * a = e\ntranscript \"x\"\ntranscript \"y\"\na * b = f\n";
+    let (circuit, skipped) = PlangCircuit::parse_lenient(text);
+
+    let lines: Vec<_> = skipped.iter().map(|s| s.line).collect();
+    assert_eq!(lines, [1, 2, 4]);
+    assert!(matches!(
+        skipped[0].error,
+        PlangError::Parse(ParseError::Pest(_))
+    ));
+    assert!(matches!(
+        skipped[1].error,
+        PlangError::Semantic(SemanticError::SameTriVars { line: 2, .. })
+    ));
+    assert!(matches!(
+        skipped[2].error,
+        PlangError::Semantic(SemanticError::RepeatedTranscript)
+    ));
+
+    assert_eq!(circuit.public_input_order(), ["c", "f"]);
+    assert_eq!(circuit.transcript(), Some(&b"x"[..]));
+}
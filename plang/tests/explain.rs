@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg(feature = "std")]
+
+use plang::{explain, PlangCircuit};
+
+#[test]
+fn circuit_errors_are_explained() {
+    for text in [
+        "a + b == c\n",
+        "a*b + c*d = e\n",
+        "a*a = c\n",
+        "a + a = c\n",
+        "a + b = a\n",
+        "a + b + d + e = c\n",
+        "transcript \"a\"\ntranscript \"b\"\n",
+        "import \"missing.plang\" as m\n",
+        "import \"../missing.plang\" as m\n",
+        "import \"test.plang\" as t\nimport \"test.plang\" as t\n",
+        "m::x = y\n",
+        "edition 2023\n",
+    ] {
+        let err = PlangCircuit::parse(text).expect_err("circuit should be invalid");
+        let code = err.code().expect("circuit errors should have a code");
+
+        assert!(
+            explain::explain(code).is_some(),
+            "{} should be explained",
+            code
+        );
+    }
+}
+
+#[test]
+fn codes_are_case_insensitive() {
+    assert_eq!(explain::explain("p0007"), explain::explain("P0007"));
+    assert!(explain::explain("P9999").is_none());
+}
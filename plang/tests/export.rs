@@ -0,0 +1,336 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg(feature = "prover")]
+
+use std::fs;
+
+use plang::dusk_plonk::prelude::*;
+use plang::export;
+use plang::{AssignmentError, PlangCircuit, PlangError};
+use serde_json::json;
+
+type Result<T> = std::result::Result<T, PlangError>;
+
+#[test]
+fn vk_json() -> Result<()> {
+    let bytes = fs::read("./test.plang")?;
+
+    let text = String::from_utf8(bytes)?;
+    let mut circuit = PlangCircuit::parse(text)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (_, vd) = circuit.compile(&pp)?;
+
+    let json = export::vk::to_json(&circuit, &vd, b"test");
+
+    assert_eq!(json["format"], export::vk::FORMAT);
+    assert_eq!(json["circuit_hash"], hex::encode(circuit.hash()));
+    assert_eq!(json["transcript_label"], hex::encode(b"test"));
+    assert_eq!(json["domain_size"], vd.key().padded_gates());
+    assert_eq!(json["commitments"].as_object().map(|c| c.len()), Some(15));
+
+    let public_inputs = json["public_inputs"]
+        .as_array()
+        .expect("public inputs should be an array");
+    let names: Vec<_> = public_inputs.iter().map(|pi| &pi["name"]).collect();
+    assert_eq!(names, ["c", "d"]);
+    assert_eq!(json["public_input_order"], json!(["c", "d"]));
+
+    Ok(())
+}
+
+#[test]
+fn vk_recursion() -> Result<()> {
+    let mut circuit = PlangCircuit::parse(fs::read_to_string("./test.plang")?)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (_, vd) = circuit.compile(&pp)?;
+
+    // The domain size, three limbs of each commitment, and the position of
+    // each public input.
+    let elements = export::recursion::decompose(&vd);
+    assert_eq!(elements.len(), 1 + 3 * 15 + 2);
+    assert_eq!(elements[0].name, "vk_domain_size");
+    assert_eq!(
+        elements[0].value,
+        BlsScalar::from(vd.key().padded_gates() as u64)
+    );
+    assert_eq!(elements[1].name, "vk_q_m_0");
+    assert_eq!(elements[47 - 1].name, "vk_pi_pos_1");
+
+    // The declarations make up a circuit of their own, which the values
+    // satisfy.
+    let mut outer = PlangCircuit::parse(export::recursion::to_plang(&elements))?;
+    assert_eq!(outer.public_input_order().len(), elements.len());
+    assert_eq!(outer.public_input_order()[0], "vk_domain_size_pi");
+    outer.set_vals(export::recursion::values(&elements))?;
+
+    Ok(())
+}
+
+#[test]
+fn circom_template() -> Result<()> {
+    let circuit = PlangCircuit::parse("signal + 2*b = c\na * b = d\n")?;
+    let export = export::circom::to_circom(&circuit, "Test");
+
+    assert!(export.source.contains("template Test() {"));
+    assert!(export.source.contains("    signal_ + 2 * b === c;\n"));
+    assert!(export.source.contains("    a * b === d;\n"));
+    assert!(export
+        .source
+        .contains("component main {public [c, d]} = Test();"));
+
+    let renamed = export
+        .signals
+        .iter()
+        .find(|s| s.name == "signal")
+        .expect("variable should be mapped");
+    assert_eq!(renamed.signal, "signal_");
+    assert!(!renamed.public);
+
+    Ok(())
+}
+
+#[test]
+fn acir_circuit() -> Result<()> {
+    let circuit = PlangCircuit::parse("a + 2*b = c\na * b = -d\n")?;
+    let export = export::acir::to_acir(&circuit);
+
+    let names: Vec<_> = export.witnesses.iter().map(|w| w.name.as_str()).collect();
+    assert_eq!(names, ["a", "b", "c", "d"]);
+
+    let one = format!("{:064x}", 1);
+    let two = format!("{:064x}", 2);
+    let minus_one = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000";
+
+    let acir = &export.circuit;
+    assert_eq!(acir["format"], export::acir::FORMAT);
+    assert_eq!(acir["version"], export::acir::VERSION);
+    assert_eq!(acir["current_witness_index"], 3);
+    assert_eq!(acir["private_parameters"], json!([0, 1]));
+    assert_eq!(acir["public_parameters"], json!([2, 3]));
+
+    let linear = &acir["opcodes"][0]["AssertZero"]["linear_combinations"];
+    assert_eq!(linear, &json!([[one, 0], [two, 1], [minus_one, 2]]));
+
+    let mul = &acir["opcodes"][1]["AssertZero"];
+    assert_eq!(mul["mul_terms"], json!([[one, 0, 1]]));
+    assert_eq!(mul["linear_combinations"], json!([[one, 3]]));
+
+    let map = export.witness_map(vec![("a".to_owned(), 3u64), ("c".to_owned(), 5)])?;
+    assert_eq!(map["0"], format!("{:064x}", 3));
+    assert_eq!(map["1"], format!("{:064x}", 0));
+    assert_eq!(map["2"], format!("{:064x}", 5));
+
+    assert!(matches!(
+        export.witness_map(vec![("e".to_owned(), 1u64)]),
+        Err(PlangError::Assignment(AssignmentError::NoSuchValue(name))) if name == "e"
+    ));
+
+    Ok(())
+}
+
+#[cfg(feature = "zkinterface")]
+#[test]
+fn zkif_messages() -> Result<()> {
+    let mut circuit = PlangCircuit::parse("a + 2*b = c\na * b = d\n")?;
+    let vals = [("a", 1u64), ("b", 2), ("c", 5), ("d", 2)];
+    circuit.set_vals(vals.iter().map(|(name, val)| (name.to_string(), *val)))?;
+
+    let export = export::zkif::to_zkif(&circuit);
+
+    let vars: Vec<_> = export
+        .variables
+        .iter()
+        .map(|v| (v.name.as_str(), v.id, v.public))
+        .collect();
+    assert_eq!(
+        vars,
+        [
+            ("c", 1, true),
+            ("d", 2, true),
+            ("a", 3, false),
+            ("b", 4, false)
+        ]
+    );
+
+    for message in [&export.header, &export.constraint_system, &export.witness] {
+        let size = u32::from_le_bytes([message[0], message[1], message[2], message[3]]);
+        assert_eq!(size as usize, message.len() - 4);
+        assert_eq!(&message[8..12], b"zkif");
+    }
+
+    let bytes = export.to_bytes();
+    assert_eq!(
+        bytes.len(),
+        export.header.len() + export.constraint_system.len() + export.witness.len()
+    );
+
+    // The messages read back with the reference implementation
+    use plang::scalar::Scalar;
+    let mut reader = zkinterface::Reader::new();
+    for message in [&export.header, &export.constraint_system, &export.witness] {
+        reader
+            .push_message(message.to_vec())
+            .expect("messages should be well formed");
+    }
+
+    let le = |n: u64| Scalar::from(n).to_bytes().to_vec();
+    let minus = |n: u64| (-Scalar::from(n)).to_bytes().to_vec();
+
+    let instance: Vec<_> = reader
+        .instance_variables()
+        .expect("the header should declare the instance")
+        .iter()
+        .map(|v| (v.id, v.value.to_vec()))
+        .collect();
+    assert_eq!(instance, [(1, le(5)), (2, le(2))]);
+
+    let witness: Vec<_> = reader
+        .iter_witness()
+        .map(|v| (v.id, v.value.to_vec()))
+        .collect();
+    assert_eq!(witness, [(3, le(1)), (4, le(2))]);
+
+    let terms = |terms: &[zkinterface::consumers::reader::Term]| -> Vec<(u64, Vec<u8>)> {
+        terms.iter().map(|t| (t.id, t.value.to_vec())).collect()
+    };
+    let constraints: Vec<_> = reader
+        .iter_constraints()
+        .map(|c| (terms(&c.a), terms(&c.b), terms(&c.c)))
+        .collect();
+    assert_eq!(
+        constraints,
+        [
+            (
+                vec![(3, le(1)), (4, le(2)), (1, minus(1))],
+                vec![(0, le(1))],
+                vec![]
+            ),
+            (vec![(3, le(1))], vec![(4, le(1))], vec![(2, le(1))]),
+        ]
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "ark")]
+#[test]
+fn ark_satisfaction_agrees() -> Result<()> {
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    let bytes = fs::read("./test.plang")?;
+
+    let text = String::from_utf8(bytes)?;
+    let mut circuit = PlangCircuit::parse(text)?;
+
+    let pp = PublicParameters::from_slice(&fs::read("./test.pp")?)?;
+    let (pk, vd) = circuit.compile(&pp)?;
+
+    // The first is a solution to `test.plang`, the second isn't
+    for (d, satisfied) in [(1, true), (2, false)] {
+        let vals = vec![
+            ("a".to_owned(), 1),
+            ("b".to_owned(), 1),
+            ("c".to_owned(), 2),
+            ("d".to_owned(), d),
+        ];
+        circuit.set_vals(vals)?;
+
+        let cs = ConstraintSystem::new_ref();
+        export::ark::ArkCircuit(&circuit)
+            .generate_constraints(cs.clone())
+            .expect("constraints should be generated");
+        assert_eq!(cs.num_constraints(), 2);
+        assert_eq!(cs.is_satisfied().ok(), Some(satisfied));
+
+        let proof = circuit.prove(&pp, &pk, b"test")?;
+        let pinputs = circuit.public_inputs_from(vec![("c".to_owned(), 2), ("d".to_owned(), d)])?;
+        let verified = PlangCircuit::verify(&pp, &vd, &proof, &pinputs, b"test").is_ok();
+        assert_eq!(verified, satisfied);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn halo2_circuit() -> Result<()> {
+    let circuit = PlangCircuit::parse("a + 2*b = c\nfn * b - a = -d\n")?;
+    let export = export::halo2::to_halo2(&circuit, "Test");
+
+    assert!(export.source.contains("use bls12_381::Scalar as Fr;"));
+    assert!(export.source.contains("pub struct Test {"));
+    assert!(export.source.contains("    pub fn_: Value<Fr>,\n"));
+    assert!(export.source.contains("impl Circuit<Fr> for Test {"));
+
+    // The second row constrains `b` and `a` to be equal to their first cells
+    assert!(export
+        .source
+        .contains("region.constrain_equal(w0_1.cell(), w1_1.cell())?;"));
+    assert!(export
+        .source
+        .contains("region.constrain_equal(w0_0.cell(), w1_2.cell())?;"));
+    assert!(export.source.contains(
+        "region.assign_fixed(|| \"q_l\", config.fixed[1], 0, || Value::known(Fr::one()))?;"
+    ));
+    assert!(export.source.contains(
+        "region.assign_fixed(|| \"q_r\", config.fixed[2], 0, || Value::known(Fr::from(2)))?;"
+    ));
+    assert!(export.source.contains(
+        "region.assign_fixed(|| \"q_o\", config.fixed[3], 1, || Value::known(-Fr::one()))?;"
+    ));
+    assert!(export
+        .source
+        .contains("layouter.constrain_instance(cell, config.instance, row)?;"));
+
+    let renamed = export
+        .fields
+        .iter()
+        .find(|f| f.name == "fn")
+        .expect("variable should be mapped");
+    assert_eq!(renamed.field, "fn_");
+
+    // Coefficients that don't fit in a u64 are written by their limbs
+    let circuit = PlangCircuit::parse("18446744073709551616*a + b = c\n")?;
+    let export = export::halo2::to_halo2(&circuit, "Wide");
+    assert!(export.source.contains(
+        "Fr::from_raw([0x0000000000000000, 0x0000000000000001, 0x0000000000000000, 0x0000000000000000])"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn markdown_tables() -> Result<()> {
+    let text =
+        "transcript \"spec\"\n# Sum of the inputs\na + 2*b = c\n\na * b = -d # product | negated\n";
+    let circuit = PlangCircuit::parse(text)?;
+
+    let markdown = export::doc::to_markdown(&circuit);
+
+    assert!(markdown.contains("transcript label `spec`"));
+    assert!(markdown.contains("| 0 | `a + 2 * b = c` | Sum of the inputs |\n"));
+    assert!(markdown.contains("| 1 | `a * b = -d` | product \\| negated |\n"));
+    assert!(markdown.contains("| 0 | `a` | `b` | | | `c` | 0 | 1 | 2 | 0 | 0 | -1 | 0 |\n"));
+    assert!(markdown.contains("| 1 | `a` | `b` | | | `d` | 1 | 0 | 0 | 0 | 0 | 1 | 0 |\n"));
+
+    Ok(())
+}
+
+#[test]
+fn latex_tables() -> Result<()> {
+    let circuit = PlangCircuit::parse("# 50% of x_1\n3*x1 - y = 0\n")?;
+
+    let latex = export::doc::to_latex(&circuit);
+
+    assert!(latex.contains("0 & $3 \\cdot \\mathit{x1} - y = 0$ & 50\\% of x\\_1 \\\\\n"));
+    assert!(latex.contains(
+        "0 & $\\mathit{x1}$ & $y$ &  &  &  & $0$ & $3$ & $-1$ & $0$ & $0$ & $0$ & $0$ \\\\\n"
+    ));
+
+    Ok(())
+}
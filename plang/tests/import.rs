@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg(feature = "prover")]
+
+use plang::import::r1cs::R1cs;
+use plang::{ParseError, PlangCircuit, PlangError};
+
+type Result<T> = std::result::Result<T, PlangError>;
+
+/// The BLS12-381 scalar field modulus, as little endian bytes.
+const MODULUS: [u8; 32] = [
+    0x01, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0x02, 0xa4, 0xbd, 0x53,
+    0x05, 0xd8, 0xa1, 0x09, 0x08, 0xd8, 0x39, 0x33, 0x48, 0x7d, 0x9d, 0x29, 0x53, 0xa7, 0xed, 0x73,
+];
+
+fn combination(bytes: &mut Vec<u8>, terms: &[(u32, u64)]) {
+    bytes.extend(&(terms.len() as u32).to_le_bytes());
+    for (wire, coeff) in terms {
+        bytes.extend(&wire.to_le_bytes());
+        let mut coeff_bytes = [0u8; 32];
+        coeff_bytes[..8].copy_from_slice(&coeff.to_le_bytes());
+        bytes.extend(&coeff_bytes);
+    }
+}
+
+/// Builds an R1CS with a single public output `w1`, constrained by
+/// `w2 * w3 = w1` and `1 * (w1 + w2 + w3 + 2*w4 + w5) = 0`.
+fn r1cs() -> Vec<u8> {
+    let mut header = vec![];
+    header.extend(&32u32.to_le_bytes());
+    header.extend(&MODULUS);
+    for n in [6u32, 1, 0, 2] {
+        header.extend(&n.to_le_bytes());
+    }
+    header.extend(&6u64.to_le_bytes());
+    header.extend(&2u32.to_le_bytes());
+
+    let mut constraints = vec![];
+    combination(&mut constraints, &[(2, 1)]);
+    combination(&mut constraints, &[(3, 1)]);
+    combination(&mut constraints, &[(1, 1)]);
+    combination(&mut constraints, &[(0, 1)]);
+    combination(&mut constraints, &[(1, 1), (2, 1), (3, 1), (4, 2), (5, 1)]);
+    combination(&mut constraints, &[]);
+
+    let mut bytes = b"r1cs".to_vec();
+    bytes.extend(&1u32.to_le_bytes());
+    bytes.extend(&2u32.to_le_bytes());
+    for (kind, section) in [(1u32, header), (2, constraints)] {
+        bytes.extend(&kind.to_le_bytes());
+        bytes.extend(&(section.len() as u64).to_le_bytes());
+        bytes.extend(section);
+    }
+    bytes
+}
+
+#[test]
+fn r1cs_to_plang() -> Result<()> {
+    let r1cs = R1cs::from_slice(&r1cs())?;
+    assert_eq!(r1cs.num_constraints(), 2);
+
+    let text = r1cs.to_plang()?;
+    let lines: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+
+    assert_eq!(
+        lines,
+        [
+            "w0 = p0",
+            "w1 = p1",
+            "w2 * w3 - w1 = 0",
+            "w1 + w2 - t0 = 0",
+            "t0 + w3 - t1 = 0",
+            "t1 + 2 * w4 + w5 = 0",
+        ]
+    );
+
+    let circuit = PlangCircuit::parse(&text)?;
+    assert_eq!(circuit.public_input_order(), ["p0", "p1"]);
+
+    r1cs.to_circuit()?;
+
+    Ok(())
+}
+
+#[test]
+fn r1cs_over_another_field() {
+    let mut bytes = r1cs();
+    // The first byte of the modulus in the header.
+    bytes[4 + 4 + 4 + 4 + 8 + 4] ^= 1;
+
+    match R1cs::from_slice(&bytes) {
+        Err(PlangError::Parse(ParseError::InvalidR1cs(_))) => {}
+        res => panic!("expected an invalid R1CS error, got {:?}", res),
+    }
+}
@@ -0,0 +1,71 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg(feature = "prover")]
+
+use plang::params;
+use plang::{PlangError, ProveError};
+
+type Result<T> = std::result::Result<T, PlangError>;
+
+#[test]
+fn degree_for_gates() -> Result<()> {
+    assert_eq!(params::degree_for_gates(0)?, 2);
+    assert_eq!(params::degree_for_gates(1)?, 2);
+    assert_eq!(params::degree_for_gates(2)?, 4);
+    assert_eq!(params::degree_for_gates(5)?, 16);
+    assert_eq!(params::degree_for_gates(1 << 20)?, 1 << 21);
+
+    // Degrees past what fits in a usize are an error, rather than wrapping
+    // around to a degree too small for the circuit.
+    let largest = 1 << (usize::BITS - 2);
+    assert_eq!(params::degree_for_gates(largest)?, 1 << (usize::BITS - 1));
+    for gates in [largest + 1, 1 << (usize::BITS - 1), usize::MAX] {
+        match params::degree_for_gates(gates) {
+            Err(PlangError::Prove(ProveError::TooManyGates(n))) => assert_eq!(n, gates),
+            res => panic!("expected too many gates for {}, got {:?}", gates, res),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn gates_for_degree() -> Result<()> {
+    assert_eq!(params::gates_for_degree(0), 0);
+    assert_eq!(params::gates_for_degree(1), 0);
+    assert_eq!(params::gates_for_degree(2), 1);
+    assert_eq!(params::gates_for_degree(16), 8);
+    // Degrees between powers of two fit as many gates as the power below
+    assert_eq!(params::gates_for_degree(31), 8);
+    assert_eq!(params::gates_for_degree(usize::MAX), 1 << (usize::BITS - 2));
+
+    // It's the largest number of gates the degree is enough for
+    for degree in [2, 3, 16, 100, 1 << 20] {
+        let gates = params::gates_for_degree(degree);
+        assert!(params::degree_for_gates(gates)? <= degree);
+        assert!(params::degree_for_gates(gates + 1)? > degree);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn seeded_setup_is_reproducible() -> Result<()> {
+    let pp = params::setup_seeded(8, [7; 32])?;
+    assert_eq!(params::max_degree(&pp), 8);
+
+    assert_eq!(
+        pp.to_var_bytes(),
+        params::setup_seeded(8, [7; 32])?.to_var_bytes()
+    );
+    assert_ne!(
+        pp.to_var_bytes(),
+        params::setup_seeded(8, [8; 32])?.to_var_bytes()
+    );
+
+    Ok(())
+}
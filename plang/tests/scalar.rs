@@ -0,0 +1,197 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use plang::scalar::Scalar;
+
+#[test]
+fn negates_in_the_field() {
+    assert_eq!(-Scalar::zero(), Scalar::zero());
+    assert_eq!(-(-Scalar::from(42)), Scalar::from(42));
+
+    let minus_one = -Scalar::one();
+    assert_eq!(minus_one.to_u64(), None);
+    assert_eq!(
+        minus_one.to_bytes(),
+        [
+            0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0x02, 0xa4,
+            0xbd, 0x53, 0x05, 0xd8, 0xa1, 0x09, 0x08, 0xd8, 0x39, 0x33, 0x48, 0x7d, 0x9d, 0x29,
+            0x53, 0xa7, 0xed, 0x73,
+        ]
+    );
+}
+
+#[test]
+fn rejects_non_canonical_bytes() {
+    let mut modulus = (-Scalar::one()).to_bytes();
+    modulus[0] += 1;
+    assert_eq!(Scalar::from_bytes(&modulus), None);
+
+    let bytes = Scalar::from(7).to_bytes();
+    assert_eq!(Scalar::from_bytes(&bytes), Some(Scalar::from(7)));
+}
+
+/// The modulus of the scalar field, in decimal.
+const MODULUS: &str =
+    "52435875175126190479447740508185965837690552500527637822603658699938581184513";
+
+#[test]
+fn parses_decimal_of_any_size() {
+    assert_eq!(Scalar::from_decimal("42"), Some((Scalar::from(42), false)));
+    assert_eq!(
+        Scalar::from_decimal("18446744073709551616").map(|(scalar, _)| scalar.to_bytes()[8]),
+        Some(1)
+    );
+
+    assert_eq!(Scalar::from_decimal(MODULUS), Some((Scalar::zero(), true)));
+    let minus_one = MODULUS.replace("513", "512");
+    assert_eq!(
+        Scalar::from_decimal(&minus_one),
+        Some((-Scalar::one(), false))
+    );
+    let plus_two = MODULUS.replace("513", "515");
+    assert_eq!(
+        Scalar::from_decimal(&plus_two),
+        Some((Scalar::from(2), true))
+    );
+
+    assert_eq!(Scalar::from_decimal(""), None);
+    assert_eq!(Scalar::from_decimal("-1"), None);
+}
+
+#[test]
+fn reduces_bytes_of_any_size() {
+    assert_eq!(Scalar::from_bytes_wide(&[]), Scalar::zero());
+    assert_eq!(Scalar::from_bytes_wide(&[1, 1]), Scalar::from(257));
+
+    let minus_one = -Scalar::one();
+    assert_eq!(Scalar::from_bytes_wide(&minus_one.to_bytes()), minus_one);
+
+    // The modulus, and 2^264 - twice reduced
+    let mut modulus = minus_one.to_bytes().to_vec();
+    modulus[0] += 1;
+    assert_eq!(Scalar::from_bytes_wide(&modulus), Scalar::zero());
+
+    let mut wide = [0; 34];
+    wide[33] = 1;
+    assert_eq!(Scalar::from_bytes_wide(&wide), Scalar::from(2).pow(264),);
+}
+
+#[test]
+fn displays_in_decimal() {
+    assert_eq!(Scalar::zero().to_string(), "0");
+    assert_eq!(Scalar::from(u64::MAX).to_string(), "18446744073709551615");
+    assert_eq!((-Scalar::one()).to_string(), MODULUS.replace("513", "512"));
+}
+
+#[test]
+fn computes_in_the_field() {
+    let minus_one = -Scalar::one();
+
+    assert_eq!(Scalar::from(2) + Scalar::from(3), Scalar::from(5));
+    assert_eq!(minus_one + Scalar::from(2), Scalar::one());
+    assert_eq!(Scalar::from(2) - Scalar::from(3), minus_one);
+    assert_eq!(Scalar::from(6) * Scalar::from(7), Scalar::from(42));
+    assert_eq!(minus_one * minus_one, Scalar::one());
+    assert_eq!(Scalar::from(2).pow(64).to_string(), "18446744073709551616");
+    assert_eq!(Scalar::from(5).pow(0), Scalar::one());
+
+    assert_eq!(Scalar::zero().invert(), None);
+    assert_eq!(minus_one.invert(), Some(minus_one));
+    let a = Scalar::from(u64::MAX).pow(3) - Scalar::from(12345);
+    assert_eq!(a * a.invert().expect("isn't zero"), Scalar::one());
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn converts_to_and_from_bls_scalars() {
+    use plang::dusk_plonk::prelude::BlsScalar;
+
+    for n in [0, 1, 42, u64::MAX] {
+        let scalar = Scalar::from(n);
+        assert_eq!(BlsScalar::from(scalar), BlsScalar::from(n));
+        assert_eq!(BlsScalar::from(-scalar), -BlsScalar::from(n));
+        assert_eq!(Scalar::from(BlsScalar::from(n)), scalar);
+    }
+
+    let (a, b) = (-Scalar::from(12345), Scalar::from(u64::MAX));
+    assert_eq!(
+        BlsScalar::from(a * b),
+        BlsScalar::from(a) * BlsScalar::from(b)
+    );
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn converts_values_to_scalars() {
+    use plang::dusk_plonk::prelude::BlsScalar;
+    use plang::scalar::ScalarValue;
+    use plang::{AssignmentError, PlangError};
+
+    let minus_one = -BlsScalar::one();
+    let values = [
+        ScalarValue::U64(255),
+        ScalarValue::Hex("0xff".into()),
+        ScalarValue::Hex("00FF".into()),
+        ScalarValue::Decimal("255".into()),
+        ScalarValue::Bytes(Scalar::from(255).to_bytes()),
+        ScalarValue::Scalar(BlsScalar::from(255)),
+    ];
+    for value in &values {
+        assert_eq!(value.to_scalar().ok(), Some(BlsScalar::from(255)));
+    }
+
+    // Negative values stand for their additive inverse
+    assert_eq!(ScalarValue::from(-1i64).to_scalar().ok(), Some(minus_one));
+    assert_eq!(
+        "-1".parse::<ScalarValue>().map(|v| v.to_scalar().ok()).ok(),
+        Some(Some(minus_one))
+    );
+
+    // The largest scalar is accepted in every form, but never reduced
+    let max = (-Scalar::one()).to_string();
+    let mut max_hex = String::from("0x");
+    for byte in (-Scalar::one()).to_bytes().iter().rev() {
+        max_hex.push_str(&format!("{:02x}", byte));
+    }
+    assert_eq!(ScalarValue::Decimal(max).to_scalar().ok(), Some(minus_one));
+    assert_eq!(ScalarValue::Hex(max_hex).to_scalar().ok(), Some(minus_one));
+
+    let invalid = [
+        ScalarValue::Decimal(MODULUS.into()),
+        ScalarValue::Decimal("12a".into()),
+        ScalarValue::Decimal("".into()),
+        ScalarValue::Hex("0x".into()),
+        ScalarValue::Hex("0xfg".into()),
+        ScalarValue::Hex(format!("0x1{}", "0".repeat(64))),
+        ScalarValue::Bytes([0xff; 32]),
+    ];
+    for value in &invalid {
+        assert!(matches!(
+            value.to_scalar(),
+            Err(PlangError::Assignment(AssignmentError::InvalidValue(_)))
+        ));
+    }
+    assert!("0xfg".parse::<ScalarValue>().is_err());
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn reads_values_from_json() {
+    use plang::dusk_plonk::prelude::BlsScalar;
+    use plang::scalar::ScalarValue;
+    use serde_json::json;
+
+    let from_json = |json| ScalarValue::from_json(&json).map(|v| v.to_scalar().ok());
+
+    assert_eq!(from_json(json!(255)), Some(Some(BlsScalar::from(255))));
+    assert_eq!(from_json(json!(-1)), Some(Some(-BlsScalar::one())));
+    assert_eq!(from_json(json!("255")), Some(Some(BlsScalar::from(255))));
+    assert_eq!(from_json(json!("0xff")), Some(Some(BlsScalar::from(255))));
+
+    assert_eq!(from_json(json!("0xfg")), None);
+    assert_eq!(from_json(json!(1.5)), None);
+    assert_eq!(from_json(json!([1])), None);
+}
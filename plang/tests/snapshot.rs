@@ -0,0 +1,46 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg(feature = "test-utils")]
+
+use std::{env, fs};
+
+use plang::{snapshot, PlangCircuit, PlangError};
+
+type Result<T> = std::result::Result<T, PlangError>;
+
+#[test]
+fn matches_stored_snapshot() -> Result<()> {
+    let text = String::from_utf8(fs::read("./test.plang")?)?;
+    let circuit = PlangCircuit::parse(text)?;
+
+    snapshot::assert_circuit_snapshot("./tests/snapshots/test.snap", &circuit);
+
+    // Reformatting the source changes nothing
+    let reformatted = PlangCircuit::parse("# comment\na  +  b = c\na*b = d\n")?;
+    assert_eq!(
+        snapshot::circuit_text(&reformatted),
+        snapshot::circuit_text(&circuit)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn diffs_changed_lines() {
+    let diff = snapshot::diff("a = b\nb = c\nc = d\n", "a = b\nb = e\nc = d\nd = e\n");
+    assert_eq!(diff, "-   2 | b = c\n+   2 | b = e\n+   4 | d = e\n");
+}
+
+#[test]
+#[should_panic(expected = "+   1 | a * b = c")]
+fn panics_on_mismatch() {
+    let path = env::temp_dir().join("plang-panics-on-mismatch.snap");
+    fs::write(&path, "a + b = c\n").expect("temp dir is writable");
+
+    let circuit = PlangCircuit::parse("a * b = c\n").expect("circuit is valid");
+    snapshot::assert_snapshot(&path, &snapshot::circuit_text(&circuit));
+}
@@ -0,0 +1,68 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg(feature = "std")]
+
+use plang::tokens::{tokenize, TokenKind};
+
+#[test]
+fn classifies_tokens() {
+    use TokenKind::*;
+
+    let text = "transcript \"label\"\n# sum\n2*a*b - c = -d # end\nab + x = 0\n";
+    let tokens = tokenize(text);
+
+    let kinds: Vec<(TokenKind, &str)> = tokens.iter().map(|t| (t.kind, t.text)).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (Keyword, "transcript"),
+            (String, "\"label\""),
+            (Comment, "# sum"),
+            (Coefficient, "2"),
+            (Operator, "*"),
+            (Variable, "a"),
+            (Operator, "*"),
+            (Variable, "b"),
+            (Operator, "-"),
+            (Variable, "c"),
+            (Operator, "="),
+            (Operator, "-"),
+            (Variable, "d"),
+            (Comment, "# end"),
+            (Variable, "ab"),
+            (Operator, "+"),
+            (Variable, "x"),
+            (Operator, "="),
+            (Coefficient, "0"),
+        ]
+    );
+
+    for token in &tokens {
+        assert_eq!(&text[token.span.clone()], token.text);
+    }
+}
+
+#[test]
+fn tokenizes_malformed_text() {
+    use TokenKind::*;
+
+    let tokens = tokenize("transcripts \"open\ta / b");
+
+    let kinds: Vec<(TokenKind, &str)> = tokens.iter().map(|t| (t.kind, t.text)).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (Variable, "transcripts"),
+            (Unknown, "\""),
+            (Variable, "open"),
+            (Unknown, "\t"),
+            (Variable, "a"),
+            (Unknown, "/"),
+            (Variable, "b"),
+        ]
+    );
+}
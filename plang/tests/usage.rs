@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg(feature = "std")]
+
+use plang::usage::{Position, Usage};
+use plang::{PlangCircuit, PlangError};
+
+#[test]
+fn finds_usages_of_variables() -> Result<(), PlangError> {
+    let circuit = PlangCircuit::parse("a * b + c = d\n\n# sum\na + c = e\n")?;
+
+    assert_eq!(
+        circuit.usages("a"),
+        [
+            Usage {
+                gate: 0,
+                line: 0,
+                position: Position::Product
+            },
+            Usage {
+                gate: 1,
+                line: 3,
+                position: Position::Linear
+            },
+        ]
+    );
+    assert_eq!(
+        circuit.usages("e"),
+        [Usage {
+            gate: 1,
+            line: 3,
+            position: Position::Public
+        }]
+    );
+    assert!(circuit.usages("x").is_empty());
+
+    assert_eq!(circuit.used_once(), ["b", "d", "e"]);
+
+    Ok(())
+}
@@ -0,0 +1,330 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! `plangc verify --batch`: verifying a directory of proofs, and printing
+//! whether each verified, and `plangc prove --witness-dir`: proving a
+//! directory of witnesses.
+
+use crate::{
+    encode_proof, proof_extension, read_envelope, read_proof, read_values, to_scalars, ProofFormat,
+    Result,
+};
+
+use plang::scalar::{Scalar, ScalarValue};
+use plang::{prover, verifier, PlangCircuit, PlangError, ProveOptions};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use tracing::info_span;
+
+use plang::dusk_plonk::circuit::VerifierData;
+use plang::dusk_plonk::commitment_scheme::PublicParameters;
+use plang::dusk_plonk::prelude::{BlsScalar, Proof, ProverKey};
+
+/// A proof of the batch, with the public inputs it's verified with.
+struct Entry {
+    path: PathBuf,
+    proof: Proof,
+    vals: Vec<(String, Scalar)>,
+}
+
+/// Where the proofs of a batch, and their public inputs, are read from.
+pub struct Batch<'a> {
+    /// The directory of the proofs and proof envelopes.
+    pub dir: &'a Path,
+    /// The directory of the public inputs of the proofs, if not next to them.
+    pub inputs: Option<&'a Path>,
+    /// The public inputs of the proofs that have no file of their own.
+    pub vals: Vec<(String, ScalarValue)>,
+    /// The options the proofs are verified with. Envelopes recording another
+    /// transcript are rejected.
+    pub options: ProveOptions,
+}
+
+impl Batch<'_> {
+    /// Verifies every proof of the batch, and prints whether each verified,
+    /// and how long it took to verify them all. It returns the error of the
+    /// first proof that failed to verify, if any.
+    ///
+    /// The proofs are verified together, with the batched verification of the
+    /// library.
+    pub fn verify(
+        &self,
+        circuit: &PlangCircuit,
+        pp: &PublicParameters,
+        vd: &VerifierData,
+    ) -> Result<()> {
+        let entries = self.entries(circuit)?;
+
+        let span = info_span!("verify", proofs = entries.len());
+        let start = Instant::now();
+
+        // Proofs missing public inputs fail without being verified.
+        let mut results: Vec<Option<Result<()>>> = entries.iter().map(|_| None).collect();
+        let mut arranged = vec![];
+        let mut verified = vec![];
+        for (i, entry) in entries.iter().enumerate() {
+            let vals = entry
+                .vals
+                .iter()
+                .map(|(name, value)| (name.clone(), BlsScalar::from(*value)));
+            match circuit.public_inputs_from(vals) {
+                Ok(pinputs) => {
+                    arranged.push((entry.proof, pinputs));
+                    verified.push(i);
+                }
+                Err(err) => results[i] = Some(Err(err)),
+            }
+        }
+
+        let transcript = self.options.transcript();
+        let outcomes = span.in_scope(|| verifier::verify_each(pp, vd, &arranged, transcript));
+        for (i, outcome) in verified.into_iter().zip(outcomes) {
+            results[i] = Some(outcome);
+        }
+        let elapsed = start.elapsed();
+
+        let width = entries
+            .iter()
+            .map(|entry| entry.path.display().to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max("PROOF".len());
+        println!("{:width$}  RESULT", "PROOF", width = width);
+
+        let mut failed = None;
+        let mut failures = 0;
+        for (entry, result) in entries.iter().zip(results) {
+            let path = entry.path.display();
+            match result.expect("every proof is verified or fails") {
+                Ok(()) => println!("{:width$}  ok", path, width = width),
+                Err(err) => {
+                    println!("{:width$}  FAILED: {}", path, err, width = width);
+                    failures += 1;
+                    failed.get_or_insert(err);
+                }
+            }
+        }
+
+        let count = entries.len();
+        println!(
+            "{} passed, {} failed, in {:.2?} ({:.2?} per proof)",
+            count - failures,
+            failures,
+            elapsed,
+            elapsed / count.max(1) as u32
+        );
+
+        match failed {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Verifies every proof of the batch with the same options, and records
+    /// it in a report, as for a single proof.
+    pub fn report(
+        &self,
+        circuit: &PlangCircuit,
+        pp: &PublicParameters,
+        vd: &VerifierData,
+        path: &Path,
+    ) -> Result<()> {
+        let entries = self.entries(circuit)?;
+
+        let proofs: Vec<(Proof, Vec<(String, Scalar)>)> = entries
+            .into_iter()
+            .map(|entry| (entry.proof, entry.vals))
+            .collect();
+        let count = proofs.len();
+
+        let report = info_span!("verify", proofs = count)
+            .in_scope(|| plang::report::verify(circuit, pp, vd, &proofs, &self.options))?;
+        fs::write(path, format!("{:#}\n", report.to_json()))?;
+        report.into_result()?;
+
+        status!("verified {} proofs", count);
+        Ok(())
+    }
+
+    /// Reads the proofs and proof envelopes of the batch, in the order of
+    /// their paths, checking the envelopes were produced for the circuit with
+    /// the options of the batch.
+    fn entries(&self, circuit: &PlangCircuit) -> Result<Vec<Entry>> {
+        let mut entries = vec![];
+
+        for path in batch_files(self.dir)? {
+            let entry = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("envelope") => {
+                    let envelope = read_envelope(&path, None)?;
+                    envelope.check(circuit)?;
+                    envelope.check_options(&self.options)?;
+                    Entry {
+                        proof: envelope.proof,
+                        vals: envelope.public_inputs,
+                        path,
+                    }
+                }
+                _ => Entry {
+                    proof: read_proof(&path, self.options.transcript(), None)?,
+                    vals: to_scalars(self.public_inputs(&path)?)?,
+                    path,
+                },
+            };
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// The public inputs of the proof in the given file: those in the file
+    /// named after it in the directory of the inputs, if any, or next to it,
+    /// as JSON or TOML, or otherwise those of the batch.
+    fn public_inputs(&self, proof: &Path) -> Result<Vec<(String, ScalarValue)>> {
+        let stem = proof.file_stem().unwrap_or_default();
+        let dir = self
+            .inputs
+            .or_else(|| proof.parent())
+            .unwrap_or_else(|| Path::new(""));
+
+        let found = ["json", "toml"]
+            .iter()
+            .map(|ext| dir.join(stem).with_extension(ext))
+            .find(|path| path.is_file());
+        match found {
+            Some(path) => read_values(path),
+            None => Ok(self.vals.clone()),
+        }
+    }
+}
+
+/// Lists the proofs and proof envelopes in a directory, sorted by path.
+fn batch_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("proof" | "envelope")
+        ) {
+            paths.push(path);
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// Where the witnesses of a batch are read from, and their proofs written to.
+pub struct Witnesses<'a> {
+    /// The directory of the witness files.
+    pub dir: &'a Path,
+    /// The directory the proofs are written to.
+    pub out_dir: &'a Path,
+    /// The values set along with those of every witness.
+    pub vals: Vec<(String, ScalarValue)>,
+    /// The options every proof is made with.
+    pub options: ProveOptions,
+    /// Whether to write proof envelopes rather than proofs.
+    pub envelope: bool,
+    /// The format to write the proofs in.
+    pub format: ProofFormat,
+}
+
+impl Witnesses<'_> {
+    /// Proves every witness of the batch with the same parameters and prover
+    /// key, in parallel, and writes each proof named after its witness file.
+    /// It prints the witnesses that failed to be proven, and returns the
+    /// error of the first, if any.
+    pub fn prove(
+        &self,
+        circuit: &PlangCircuit,
+        pp: &PublicParameters,
+        pk: &ProverKey,
+    ) -> Result<()> {
+        let paths = witness_files(self.dir)?;
+        let assignments = paths
+            .iter()
+            .map(|path| {
+                let mut vals = read_values(path)?;
+                vals.extend(self.vals.iter().cloned());
+                Ok(vals)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let start = Instant::now();
+        let results = info_span!("prove", proofs = paths.len()).in_scope(|| {
+            prover::prove_each(
+                circuit,
+                assignments.clone(),
+                pk,
+                pp,
+                self.options.transcript(),
+            )
+        });
+        let elapsed = start.elapsed();
+
+        fs::create_dir_all(self.out_dir)?;
+
+        let mut failed = None;
+        for ((path, assignment), result) in paths.iter().zip(assignments).zip(results) {
+            let proof = match result {
+                Ok(proof) => proof,
+                Err(err) => {
+                    println!("{} ... FAILED", path.display());
+                    println!("  {}", err);
+                    failed.get_or_insert_with(|| PlangError::InFile {
+                        path: path.clone(),
+                        error: Box::new(err),
+                    });
+                    continue;
+                }
+            };
+
+            // Envelopes record the public inputs, so they're made from a copy
+            // of the circuit with the values of the witness.
+            let mut assigned = circuit.clone();
+            if self.envelope {
+                assigned.set_vals(assignment)?;
+            }
+            let bytes = encode_proof(&assigned, proof, &self.options, self.envelope, self.format);
+
+            let stem = path.file_stem().unwrap_or_default();
+            let out = self
+                .out_dir
+                .join(stem)
+                .with_extension(proof_extension(self.envelope));
+            fs::write(&out, bytes)?;
+            status!("{} ... {}", path.display(), out.display());
+        }
+
+        status!("proved {} witnesses in {:.2?}", paths.len(), elapsed);
+
+        match failed {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Lists the witness files in a directory, sorted by path.
+fn witness_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("json" | "toml")
+        ) {
+            paths.push(path);
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
@@ -0,0 +1,151 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::Result;
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use plang::dusk_plonk::circuit::Circuit;
+use plang::dusk_plonk::commitment_scheme::PublicParameters;
+use plang::dusk_plonk::prelude::BlsScalar;
+use plang::{ParseOptions, PlangCircuit};
+use serde_json::{json, Map, Value};
+
+/// Transcript label used for the benchmarked proofs.
+const TRANSCRIPT: &[u8] = b"plangc-bench";
+
+/// Timings of a single phase over all iterations.
+#[derive(Debug, Default)]
+struct Timings(Vec<Duration>);
+
+impl Timings {
+    fn time<T, F: FnOnce() -> T>(&mut self, f: F) -> T {
+        let start = Instant::now();
+        let out = f();
+        self.0.push(start.elapsed());
+        out
+    }
+
+    fn min(&self) -> Duration {
+        self.0.iter().min().copied().unwrap_or_default()
+    }
+
+    fn max(&self) -> Duration {
+        self.0.iter().max().copied().unwrap_or_default()
+    }
+
+    fn mean(&self) -> Duration {
+        match self.0.len() {
+            0 => Duration::default(),
+            n => self.0.iter().sum::<Duration>() / n as u32,
+        }
+    }
+}
+
+/// Benchmarks each phase of going from a circuit file to a verified proof,
+/// printing the results as a table or as JSON.
+///
+/// Parsing includes reading the file and its imports, and lowering the
+/// equations and checking them, since they are done in one go.
+pub fn run(
+    path: &Path,
+    options: &ParseOptions,
+    pp: &PublicParameters,
+    vals: Vec<(String, BlsScalar)>,
+    iterations: usize,
+    json: bool,
+) -> Result<()> {
+    let mut parse = Timings::default();
+    let mut compile = Timings::default();
+    let mut prove = Timings::default();
+    let mut verify = Timings::default();
+
+    let mut verified = true;
+
+    for _ in 0..iterations {
+        let mut circuit = parse.time(|| PlangCircuit::from_file_with(path, options))?;
+        circuit.set_vals(vals.clone())?;
+
+        let (pk, vd) = compile.time(|| circuit.compile(pp))?;
+        let proof = prove.time(|| circuit.prove(pp, &pk, TRANSCRIPT))?;
+
+        let pinputs = circuit.public_inputs();
+        verified &= verify
+            .time(|| PlangCircuit::verify(pp, &vd, &proof, &pinputs, TRANSCRIPT))
+            .is_ok();
+    }
+
+    let phases = [
+        ("parse", parse),
+        ("compile", compile),
+        ("prove", prove),
+        ("verify", verify),
+    ];
+    let peak_memory = peak_memory();
+
+    if json {
+        let mut timings = Map::new();
+        for (name, t) in &phases {
+            timings.insert(
+                (*name).into(),
+                json!({
+                    "min_ns": t.min().as_nanos() as u64,
+                    "mean_ns": t.mean().as_nanos() as u64,
+                    "max_ns": t.max().as_nanos() as u64,
+                }),
+            );
+        }
+
+        let report = json!({
+            "iterations": iterations,
+            "phases": Value::Object(timings),
+            "verified": verified,
+            "peak_memory": peak_memory,
+        });
+        println!("{:#}", report);
+
+        return Ok(());
+    }
+
+    println!("{} iterations", iterations);
+    println!("{:<10}{:>14}{:>14}{:>14}", "phase", "min", "mean", "max");
+    for (name, t) in &phases {
+        println!(
+            "{:<10}{:>14}{:>14}{:>14}",
+            name,
+            format!("{:.3?}", t.min()),
+            format!("{:.3?}", t.mean()),
+            format!("{:.3?}", t.max())
+        );
+    }
+    if let Some(peak) = peak_memory {
+        println!("peak memory: {} KiB", peak / 1024);
+    }
+    if !verified {
+        warning!("proofs did not verify - the given values don't satisfy the circuit");
+    }
+
+    Ok(())
+}
+
+/// Returns the peak resident memory of the process in bytes, on platforms
+/// where it's available.
+fn peak_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kib: u64 = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+
+    Some(kib * 1024)
+}
@@ -0,0 +1,81 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::init::is_name;
+use crate::Result;
+
+use plang::PlangError;
+
+use std::fs::OpenOptions;
+use std::io::{self, Write as _};
+use std::path::Path;
+
+/// Writes the skeleton of a gadget to a new file: a header documenting what it
+/// constrains, its parameters, inputs and outputs, the constants it's
+/// parameterized by, a placeholder equation, and test vectors for it.
+///
+/// It returns an error if the file exists, rather than overwriting it.
+pub fn run(name: &str, params: &[(String, u64)], path: &Path) -> Result<()> {
+    let invalid = |msg: String| PlangError::Io(io::Error::new(io::ErrorKind::InvalidInput, msg));
+
+    if !is_name(name) {
+        return Err(invalid(format!(
+            "can't name a gadget `{}` - use letters, digits, `-` and `_`",
+            name
+        )));
+    }
+    for (param, _) in params {
+        if !is_constant(param) {
+            return Err(invalid(format!(
+                "`{}` can't be the name of a constant - use a letter followed by letters and \
+                 digits",
+                param
+            )));
+        }
+    }
+
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(skeleton(name, params).as_bytes())?;
+
+    status!("created gadget `{}` in {}", name, path.display());
+    Ok(())
+}
+
+/// The skeleton of a gadget, with `{name}` standing for its name, `{params}`
+/// for the lines documenting its parameters, and `{consts}` for the lines
+/// declaring them.
+const SKELETON: &str = include_str!("../templates/gadget.plang");
+
+/// The text of the skeleton of a gadget.
+fn skeleton(name: &str, params: &[(String, u64)]) -> String {
+    let (docs, consts) = match params.is_empty() {
+        true => (
+            "#   none - declare them as constants, such as `const N = 64`\n".to_owned(),
+            String::new(),
+        ),
+        false => params.iter().fold(
+            (String::new(), String::new()),
+            |(docs, consts), (param, value)| {
+                (
+                    docs + &format!("#   {} = {} - TODO: what it stands for\n", param, value),
+                    consts + &format!("const {} = {}\n", param, value),
+                )
+            },
+        ),
+    };
+
+    SKELETON
+        .replace("{name}", name)
+        .replace("{params}", &docs)
+        .replace("{consts}", &consts)
+}
+
+/// Whether the name can be given to a constant.
+fn is_constant(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric())
+}
@@ -0,0 +1,101 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::Result;
+
+use plang::PlangError;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A file of a new project, with `{name}` standing for the name of the
+/// project, both in its path and in its contents.
+struct Template {
+    path: &'static str,
+    text: &'static str,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    executable: bool,
+}
+
+const TEMPLATES: &[Template] = &[
+    Template {
+        path: "{name}.plang",
+        text: include_str!("../templates/init/circuit.plang"),
+        executable: false,
+    },
+    Template {
+        path: "witness.toml",
+        text: include_str!("../templates/init/witness.toml"),
+        executable: false,
+    },
+    Template {
+        path: "plang.toml",
+        text: include_str!("../templates/init/plang.toml"),
+        executable: false,
+    },
+    Template {
+        path: "run.sh",
+        text: include_str!("../templates/init/run.sh"),
+        executable: true,
+    },
+];
+
+/// Creates a project in the given directory, named after it: an example
+/// circuit, values to prove it with, the settings of the project, and a script
+/// compiling, proving and verifying the circuit.
+///
+/// The directory is created if it doesn't exist. It returns an error if it
+/// does and isn't empty, rather than overwriting anything in it.
+pub fn run(dir: &Path) -> Result<()> {
+    let invalid = |msg: String| PlangError::Io(io::Error::new(io::ErrorKind::InvalidInput, msg));
+
+    let name = dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| is_name(name))
+        .ok_or_else(|| {
+            invalid(format!(
+                "can't name a project after `{}` - use letters, digits, `-` and `_`",
+                dir.display()
+            ))
+        })?;
+
+    if dir.exists() && fs::read_dir(dir)?.next().is_some() {
+        return Err(PlangError::Io(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("`{}` already exists and isn't empty", dir.display()),
+        )));
+    }
+    fs::create_dir_all(dir)?;
+
+    for template in TEMPLATES {
+        let path = dir.join(template.path.replace("{name}", name));
+        fs::write(&path, template.text.replace("{name}", name))?;
+
+        #[cfg(unix)]
+        if template.executable {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+    }
+
+    status!(
+        "created project `{}` - run {} to compile, prove and verify it",
+        name,
+        dir.join("run.sh").display()
+    );
+    Ok(())
+}
+
+/// Whether the name can be given to a project, and so to its files and the
+/// transcript label of its circuit.
+pub fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
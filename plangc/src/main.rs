@@ -4,101 +4,700 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use plang::{PlangCircuit, PlangError};
+/// Prints a warning to stderr, unless running with `--quiet`.
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        if !crate::QUIET.load(std::sync::atomic::Ordering::Relaxed) {
+            eprintln!("warning: {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Prints a status message to stdout, unless running with `--quiet`.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !crate::QUIET.load(std::sync::atomic::Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+mod batch;
+mod bench;
+mod gadget;
+mod init;
+mod playground;
+mod vectors;
+mod workspace;
+
+use plang::artifact::{self, ArtifactKind};
+use plang::cache::KeyCache;
+use plang::envelope::ProofEnvelope;
+use plang::grammar::Edition;
+use plang::repro::{self, Manifest};
+use plang::scalar::{Scalar, ScalarValue};
+use plang::transcript::DEFAULT_LABEL;
+use plang::warning::{CheckConfig, Level};
+use plang::{explain, export, import, verifier};
+use plang::{
+    params, AssignmentError, ParseError, ParseOptions, PlangCircuit, PlangError, PlangKeys,
+    PlangProof, PlangVerifier, ProveError, ProveOptions, SemanticError,
+};
+use workspace::Workspace;
 
+use std::convert::TryInto;
+use std::env;
 use std::error::Error;
 use std::fs;
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use dusk_bytes::{DeserializableSlice, Serializable};
 use rand_core::OsRng;
+use serde_json::{json, Value};
 use structopt::StructOpt;
+use tracing::info_span;
+use tracing_subscriber::fmt::format::FmtSpan;
 
 use plang::dusk_plonk::circuit::{Circuit, VerifierData};
 use plang::dusk_plonk::commitment_scheme::PublicParameters;
-use plang::dusk_plonk::prelude::{BlsScalar, ProverKey};
-use plang::dusk_plonk::proof_system::Proof;
+use plang::dusk_plonk::error::Error as PlonkError;
+use plang::dusk_plonk::prelude::{BlsScalar, Proof, ProverKey};
 
 type Result<T> = std::result::Result<T, PlangError>;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "plangc", about = "A language for plonk circuits")]
+struct Opt {
+    /// Log what the compiler is doing, with timings, to stderr. Repeat for more detail.
+    #[structopt(short, long, parse(from_occurrences))]
+    verbose: u8,
+    /// Print nothing but errors and the output of commands that are run for it, such as `hash`.
+    #[structopt(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Treat every warning about a circuit as an error.
+    #[structopt(long)]
+    strict: bool,
+    /// Treat the warning with the given name, such as `reduced_coefficient`, as an error.
+    #[structopt(long, number_of_values = 1)]
+    deny: Vec<String>,
+    /// Ignore the warning with the given name, even with `--strict`.
+    #[structopt(long, number_of_values = 1)]
+    allow: Vec<String>,
+    /// Look imports up in the directory, after the directory of the circuit importing them.
+    /// Repeat to look in several, in order. A directory with the `.plangpkg` extension is opened
+    /// as a package, whose exports are imported as `<package>/<path>`. The directories in the
+    /// PLANG_INCLUDE_PATH environment variable, separated as in PATH, are looked in after these.
+    #[structopt(short = "I", long, number_of_values = 1)]
+    include_path: Vec<PathBuf>,
+    /// Parse circuits by the rules of the edition, 2021 or 2024, unless they declare their own
+    /// with `edition 2024`.
+    #[structopt(long, default_value = "2021")]
+    edition: Edition,
+    #[structopt(subcommand)]
+    cmd: Plangc,
+}
+
+#[derive(Debug, StructOpt)]
 enum Plangc {
+    /// Create a project in a new directory, named after it, with an example circuit, values to
+    /// prove it with, a plang.toml and a script compiling, proving and verifying the circuit.
+    Init {
+        /// The directory to create the project in. It may exist, as long as it's empty.
+        #[structopt(parse(from_os_str))]
+        dir: PathBuf,
+    },
+    /// Compile every circuit listed in a plang.toml with the settings given for each, and write a
+    /// build manifest listing their keys, which `repro` checks.
+    Build {
+        /// The plang.toml of the workspace. Paths in it are relative to its directory, where the
+        /// manifest is written as "build.json".
+        #[structopt(long, parse(from_os_str), default_value = "plang.toml")]
+        config: PathBuf,
+        /// Public parameters to compile the circuits with, instead of the `params` of the
+        /// plang.toml. Circuits naming their own parameters keep them. If not specified the
+        /// parameters pointed to by the PLANG_PARAMS environment variable are used, if set.
+        #[structopt(long, short, parse(from_os_str), env = "PLANG_PARAMS")]
+        params: Option<PathBuf>,
+        /// Directory to cache compiled keys in, instead of the `cache_dir` of the plang.toml. If
+        /// not specified the directory in the PLANG_CACHE_DIR environment variable is used, if
+        /// set.
+        #[structopt(long, parse(from_os_str), env = "PLANG_CACHE_DIR")]
+        cache_dir: Option<PathBuf>,
+    },
+    /// Write the skeleton of a gadget to a new file: a header documenting it, the constants it's
+    /// parameterized by, a placeholder equation, and test vectors for it.
+    NewGadget {
+        /// The name of the gadget, such as "range64".
+        name: String,
+        /// A constant to parameterize the gadget by, such as "N=64". Can be repeated.
+        #[structopt(long = "param", parse(try_from_str = parse_key_val))]
+        params: Vec<(String, u64)>,
+        /// Where to write the gadget to. If not specified it will be written to a file with the
+        /// name of the gadget plus the extension ".plang".
+        #[structopt(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
     /// Compile the given circuit into its keys.
+    #[structopt(alias = "keygen")]
     Compile {
         /// The circuit to compile.
         #[structopt(parse(from_os_str))]
         circuit: PathBuf,
-        /// Public parameters for compilation. If not specified random parameters will be used.
-        #[structopt(long, short, parse(from_os_str))]
+        /// Public parameters for compilation. If not specified the parameters pointed to by the
+        /// PLANG_PARAMS environment variable will be used, or random ones if it is unset.
+        #[structopt(long, short, parse(from_os_str), env = "PLANG_PARAMS")]
         params: Option<PathBuf>,
         /// The file name of the generated keys, excluding the extensions ".vd" and "pk".
         #[structopt(long, short, parse(from_os_str))]
         output: Option<PathBuf>,
+        /// Where to write the prover key, overriding the path derived from `--output`.
+        #[structopt(long, parse(from_os_str))]
+        pk_output: Option<PathBuf>,
+        /// Where to write the verifier data, overriding the path derived from `--output`.
+        #[structopt(long, parse(from_os_str))]
+        vd_output: Option<PathBuf>,
+        /// Only write the prover key.
+        #[structopt(long, conflicts_with = "vd-only")]
+        pk_only: bool,
+        /// Only write the verifier data. Useful for verifier deployments, which shouldn't ship the
+        /// much larger prover key.
+        #[structopt(long)]
+        vd_only: bool,
+        /// Write the keys into a single archive with the extension ".plangkeys", together with
+        /// the circuit hash, the transcript label and a digest of the public parameters.
+        #[structopt(long, conflicts_with_all = &["pk-output", "vd-output"])]
+        archive: bool,
+        /// The transcript label to record in the key archive, given either as text or as hex
+        /// prefixed by "0x". If not specified the label declared in the circuit, or otherwise
+        /// "dusk_plang", will be used.
+        #[structopt(long, short, alias = "transcript", requires = "archive", parse(try_from_str = parse_label))]
+        label: Option<Label>,
+        /// Also write a verifier archive with the extension ".plangvk", bundling the verifier data
+        /// with the order of the public inputs, for verifiers that can't parse the circuit.
+        #[structopt(long)]
+        verifier: bool,
+        /// Directory to cache compiled keys in, keyed by the circuit hash and the digest of the
+        /// public parameters. Compiling a circuit that didn't change with the same parameters
+        /// reads the keys back instead. If not specified the directory in the PLANG_CACHE_DIR
+        /// environment variable is used, if set. Unused with random parameters.
+        #[structopt(long, parse(from_os_str), env = "PLANG_CACHE_DIR")]
+        cache_dir: Option<PathBuf>,
+        #[structopt(flatten)]
+        identity: Identity,
     },
-    /// Generate random public parameters to use with compilation of a circuit.
+    /// Generate random public parameters to use with compilation of a circuit, or of any circuit
+    /// up to a given size.
     GenerateParams {
         /// Circuit to generate public parameters for.
+        #[structopt(
+            parse(from_os_str),
+            required_unless_one = &["degree", "max-gates"],
+            conflicts_with_all = &["degree", "max-gates"]
+        )]
+        circuit: Option<PathBuf>,
+        /// Degree of the parameters to generate, either as a number or in the form "2^k". The
+        /// resulting parameters can be reused for any circuit they are large enough for.
+        #[structopt(long, parse(try_from_str = parse_degree), conflicts_with = "max-gates")]
+        degree: Option<usize>,
+        /// Generate parameters large enough for any circuit with up to this number of gates.
+        #[structopt(long)]
+        max_gates: Option<usize>,
+        /// Where to write the public parameters. If not specified the public parameters will be
+        /// written to a file with the name of circuit plus the extension ".pp", or to "plang.pp"
+        /// when no circuit is given.
+        #[structopt(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Hex encoded seed to deterministically derive the parameters from, padded with zeroes
+        /// up to 32 bytes. Seeded parameters are INSECURE and only meant for testing.
+        #[structopt(long, parse(try_from_str = parse_seed))]
+        seed: Option<[u8; 32]>,
+    },
+    /// Print information about prover keys and verifier data, or key archives.
+    InspectKeys {
+        /// Prover key to inspect.
+        #[structopt(long, parse(from_os_str), required_unless_one = &["vd", "keys"])]
+        pk: Option<PathBuf>,
+        /// Verifier data to inspect.
+        #[structopt(long, parse(from_os_str))]
+        vd: Option<PathBuf>,
+        /// Key archive to inspect, as compiled with `--archive`, along with the circuit hash,
+        /// transcript label and parameters recorded in it.
+        #[structopt(long, parse(from_os_str))]
+        keys: Option<PathBuf>,
+        /// Print the information as JSON.
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Check that prover keys and verifier data match the ones compiled from a circuit.
+    VerifyKeys {
+        /// The circuit the keys were supposedly compiled from.
         #[structopt(parse(from_os_str))]
         circuit: PathBuf,
-        /// Where to write the public parameters. If not specified the public parameters will be
-        /// written to a file with the name of circuit plus the extension ".pp".
+        /// The public parameters the keys were compiled with. If not specified the parameters
+        /// pointed to by the PLANG_PARAMS environment variable are used.
+        #[structopt(long, short, parse(from_os_str), env = "PLANG_PARAMS")]
+        params: PathBuf,
+        /// Prover key to check.
+        #[structopt(long, parse(from_os_str), required_unless = "vd")]
+        pk: Option<PathBuf>,
+        /// Verifier data to check.
+        #[structopt(long, parse(from_os_str))]
+        vd: Option<PathBuf>,
+    },
+    /// Recompile every circuit listed in a build manifest, and check that the keys released for
+    /// each are byte for byte the ones compiled.
+    Repro {
+        /// The build manifest, listing the source, public parameters, prover key and verifier
+        /// data of each circuit, relative to its directory.
+        #[structopt(parse(from_os_str))]
+        manifest: PathBuf,
+    },
+    /// Print the hash of a circuit, as recorded in its key archives.
+    Hash {
+        /// The circuit to hash.
+        #[structopt(parse(from_os_str))]
+        circuit: PathBuf,
+        /// Write the raw bytes of the hash instead of its hex encoding.
+        #[structopt(long, conflicts_with = "hex")]
+        raw: bool,
+        /// Write the hash hex encoded. This is the default.
+        #[structopt(long)]
+        hex: bool,
+    },
+    /// Measure the time taken by each step from parsing a circuit to verifying its proof.
+    Bench {
+        /// The circuit to benchmark.
+        #[structopt(parse(from_os_str))]
+        circuit: PathBuf,
+        /// Public parameters to use. If not specified the parameters pointed to by the
+        /// PLANG_PARAMS environment variable will be used, or random ones if it is unset.
+        #[structopt(long, short, parse(from_os_str), env = "PLANG_PARAMS")]
+        params: Option<PathBuf>,
+        /// Values to use for witnesses and public inputs.
+        #[structopt(long, short, parse(try_from_str = parse_key_val))]
+        vals: Vec<(String, ScalarValue)>,
+        /// Number of times to run each step.
+        #[structopt(long, short = "n", default_value = "10")]
+        iterations: usize,
+        /// Print the results as JSON.
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Check the circuit against the test vectors declared in it with `test` blocks.
+    Test {
+        /// The circuit to test.
+        #[structopt(parse(from_os_str))]
+        circuit: PathBuf,
+        /// Also prove each test vector and verify the proof, which has to verify only for test
+        /// vectors expected to satisfy the circuit.
+        #[structopt(long)]
+        prove: bool,
+        /// Public parameters to prove with. If not specified the parameters pointed to by the
+        /// PLANG_PARAMS environment variable will be used, or insecure toy ones derived from a
+        /// fixed seed if it is unset. Unused without `--prove`.
+        #[structopt(long, short, parse(from_os_str), env = "PLANG_PARAMS")]
+        params: Option<PathBuf>,
+        /// Directory to cache the keys proofs are made with in, keyed by the circuit hash and
+        /// the digest of the public parameters. If not specified the directory in the
+        /// PLANG_CACHE_DIR environment variable is used, if set. Unused without `--prove`.
+        #[structopt(long, parse(from_os_str), env = "PLANG_CACHE_DIR")]
+        cache_dir: Option<PathBuf>,
+    },
+    /// Mutate each equation of the circuit - dropping it, flipping the sign of a coefficient or
+    /// zeroing one - and print the mutants the test vectors expected not to satisfy the circuit
+    /// don't notice.
+    Probe {
+        /// The circuit to probe.
+        #[structopt(parse(from_os_str))]
+        circuit: PathBuf,
+        /// Values expected not to satisfy the circuit, checked along with its test vectors.
+        #[structopt(long, short, parse(try_from_str = parse_key_val))]
+        vals: Vec<(String, ScalarValue)>,
+    },
+    /// Report which selectors of each gate the test vectors of the circuit exercise, and which
+    /// are never, as code coverage does for lines.
+    Coverage {
+        /// The circuit to report the coverage of.
+        #[structopt(parse(from_os_str))]
+        circuit: PathBuf,
+        /// Values to cover the circuit with, along with its test vectors.
+        #[structopt(long, short, parse(try_from_str = parse_key_val))]
+        vals: Vec<(String, ScalarValue)>,
+        /// Print the report as JSON.
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Render the circuit as a graph in the DOT language.
+    Graph {
+        /// The circuit to render.
+        #[structopt(parse(from_os_str))]
+        circuit: PathBuf,
+        /// Where to write the graph. If not specified a file with the name of the circuit plus
+        /// the extension ".dot" will be written.
         #[structopt(long, short, parse(from_os_str))]
         output: Option<PathBuf>,
+        /// Draw public inputs highlighted.
+        #[structopt(long)]
+        highlight_public: bool,
+    },
+    /// Operations on public parameters.
+    Params {
+        #[structopt(subcommand)]
+        cmd: ParamsCommand,
     },
     /// Attempt to generate a proof given the parameter set.
     Prove {
         /// Circuit to solve for.
         #[structopt(parse(from_os_str))]
         circuit: PathBuf,
-        /// Public parameters for verification. If not specified a file with the name of the circuit
-        /// plus the extension ".pp" will be tried. If this fails random parameters will be used.
-        #[structopt(long, short, parse(from_os_str))]
+        /// Public parameters for proving. If not specified the parameters pointed to by the
+        /// PLANG_PARAMS environment variable are used. If it is unset a file with the name of the
+        /// circuit plus the extension ".pp" will be tried. If this fails random parameters will be
+        /// used.
+        #[structopt(long, short, parse(from_os_str), env = "PLANG_PARAMS")]
         params: Option<PathBuf>,
         /// Prover key generated by compiling the circuit. If not specified a file with the name of
-        /// the circuit plus the extension ".pk" will be tried. If this fails the circuit will be
+        /// the circuit plus the extension ".pk" will be tried. If it doesn't exist the circuit will be
         /// compiled.
         #[structopt(long, short, parse(from_os_str))]
         key: Option<PathBuf>,
+        /// Key archive to take the prover key and transcript label from.
+        #[structopt(long, parse(from_os_str), conflicts_with = "key")]
+        keys: Option<PathBuf>,
         /// Values to use for witnesses and public inputs.
         #[structopt(long, short, parse(try_from_str = parse_key_val))]
-        vals: Vec<(String, i64)>,
-        /// Where to write the proof to. If not specified the proof will be writen to a file with
-        /// the name of the circuit plus the extension ".proof".
+        vals: Vec<(String, ScalarValue)>,
+        /// TOML or JSON file mapping the names of witnesses and public inputs to their values, to
+        /// use along with `--vals`. It's read as TOML if its extension is ".toml".
+        #[structopt(long, parse(from_os_str))]
+        witness: Option<PathBuf>,
+        /// Where to write the proof to, or "-" to write it to stdout. If not specified the proof
+        /// will be writen to a file with the name of the circuit plus the extension ".proof", or
+        /// ".envelope" with `--envelope`.
         #[structopt(long, short, parse(from_os_str))]
         output: Option<PathBuf>,
-        /// The transcript to use to generate a proof with. If not specified the transcript
-        /// "dusk_plang" will be used.
-        #[structopt(long, short)]
-        transcript: Option<String>,
+        /// Directory of TOML or JSON witness files to prove each of, instead of a single one,
+        /// along with `--vals`. The proofs are made in parallel, with the same parameters and
+        /// prover key.
+        #[structopt(long, parse(from_os_str), conflicts_with_all = &["witness", "output"])]
+        witness_dir: Option<PathBuf>,
+        /// Directory to write the proofs of `--witness-dir` to, each named after its witness file.
+        /// If not specified they're written next to the witness files.
+        #[structopt(long, parse(from_os_str), requires = "witness_dir")]
+        out_dir: Option<PathBuf>,
+        /// Write a proof envelope, bundling the proof with the values of the public inputs, the
+        /// circuit hash, the transcript label and the compiler version.
+        #[structopt(long)]
+        envelope: bool,
+        /// Write the proof envelope as JSON. The same as `--format json`.
+        #[structopt(long, requires = "envelope")]
+        json: bool,
+        /// How to encode the proof: "binary", "json" for proof envelopes, or "hex", the binary
+        /// encoding as a line of hex. A binary proof is not written to stdout if it's a terminal.
+        #[structopt(long, possible_values = &["binary", "json", "hex"], conflicts_with = "json")]
+        format: Option<ProofFormat>,
+        /// The transcript label to generate a proof with, given either as text or as hex prefixed
+        /// by "0x". If not specified the label recorded in the key archive, or declared in the
+        /// circuit, or otherwise "dusk_plang", will be used. It is recorded in the proof.
+        #[structopt(long, short, alias = "transcript", parse(try_from_str = parse_label))]
+        label: Option<Label>,
+        /// Domain separation prefix to start the transcript with, before the label, given either
+        /// as text or as hex prefixed by "0x". Can be repeated, and the prefixes are recorded in
+        /// the proof together with the label.
+        #[structopt(long = "domain", parse(try_from_str = parse_label))]
+        domains: Vec<Label>,
     },
     /// Verify the given proof for the circuit.
     Verify {
         /// Circuit to verify proof for.
         #[structopt(parse(from_os_str))]
         circuit: PathBuf,
-        /// Public parameters for verification. If not specified a file with the name of the circuit
-        /// plus the extension ".pp" will be tried. If this fails random parameters will be used.
-        #[structopt(long, parse(from_os_str))]
+        /// Public parameters for verification. If not specified the parameters pointed to by the
+        /// PLANG_PARAMS environment variable are used. If it is unset a file with the name of the
+        /// circuit plus the extension ".pp" will be tried. If this fails random parameters will be
+        /// used.
+        #[structopt(long, parse(from_os_str), env = "PLANG_PARAMS")]
         params: Option<PathBuf>,
         /// Verifier data generated by compiling the circuit. If not specified a file with the name
-        /// of the circuit plus the extension ".vd" will be tried. If this fails the circuit will be
+        /// of the circuit plus the extension ".vd" will be tried. If it doesn't exist the circuit will be
         /// compiled.
         #[structopt(long, parse(from_os_str))]
         vdata: Option<PathBuf>,
-        /// Values to use for public inputs.
+        /// Key archive to take the verifier data and transcript label from.
+        #[structopt(long, parse(from_os_str), conflicts_with = "vdata")]
+        keys: Option<PathBuf>,
+        /// Values to use for public inputs. They are arranged in the order verification expects
+        /// them in, regardless of the order they're given in.
         #[structopt(long, parse(try_from_str = parse_key_val))]
-        vals: Vec<(String, i64)>,
-        /// The proof to check.
+        vals: Vec<(String, ScalarValue)>,
+        /// TOML or JSON file mapping the names of the public inputs to their values, to use instead
+        /// of `--vals`. It's read as TOML if its extension is ".toml". With `--batch` it can be a
+        /// directory, holding the public inputs of each proof in a file named after it.
+        #[structopt(long, parse(from_os_str), conflicts_with = "vals")]
+        public_inputs: Option<PathBuf>,
+        /// The proof to check, or "-" to read it from stdin.
+        #[structopt(
+            long,
+            parse(from_os_str),
+            required_unless_one = &["batch", "envelope"]
+        )]
+        proof: Option<PathBuf>,
+        /// Proof envelope to check, written as an artifact or as JSON, or "-" to read it from
+        /// stdin. The proof is verified with the public inputs in the envelope, after checking it
+        /// was produced for the circuit, and with the transcript `--label` and `--domain` start -
+        /// envelopes recording another transcript are rejected.
+        #[structopt(
+            long,
+            parse(from_os_str),
+            conflicts_with_all = &["proof", "batch", "vals", "public_inputs"]
+        )]
+        envelope: Option<PathBuf>,
+        /// Directory of proofs (".proof") and proof envelopes (".envelope") to check in a batch,
+        /// instead of a single one, printing whether each verified and how long it took. The public
+        /// inputs of each proof are read from the JSON or TOML file named after it, in the
+        /// directory given with `--public-inputs` or next to it, or taken from `--vals` or
+        /// `--public-inputs` if there is none.
+        #[structopt(long, parse(from_os_str), conflicts_with = "proof")]
+        batch: Option<PathBuf>,
+        /// The transcript label to verify the proof with, given either as text or as hex prefixed
+        /// by "0x". If not specified the label recorded in the key archive, or declared in the
+        /// circuit, or otherwise "dusk_plang", will be used.
+        #[structopt(long, short, alias = "transcript", parse(try_from_str = parse_label))]
+        label: Option<Label>,
+        /// Domain separation prefix the transcript was started with, before the label. Can be
+        /// repeated, in the same order the proof was generated with.
+        #[structopt(long = "domain", parse(try_from_str = parse_label))]
+        domains: Vec<Label>,
+        /// Write a JSON report of the verification to the given file - its outcome, how long it
+        /// took, the public inputs of each proof, the circuit hash and digests of the parameters
+        /// and verifier data - whether the proofs verify or not.
+        #[structopt(long, parse(from_os_str))]
+        report: Option<PathBuf>,
+        /// How the proof or proof envelope is encoded: "binary", "json" or "hex". If not specified
+        /// it's told from its first bytes.
+        #[structopt(long, possible_values = &["binary", "json", "hex"], conflicts_with = "batch")]
+        format: Option<ProofFormat>,
+    },
+    /// Export the verifier data of a circuit, for verifiers not written in Rust.
+    ExportVk {
+        /// The circuit the verifier data was compiled from.
+        #[structopt(parse(from_os_str))]
+        circuit: PathBuf,
+        /// Verifier data to export. If not specified a file with the name of the circuit plus the
+        /// extension ".vd" will be used.
         #[structopt(long, parse(from_os_str))]
-        proof: PathBuf,
-        #[structopt(long, short)]
-        /// The transcript to use to generate a proof with. If not specified the transcript
-        /// "dusk_plang" will be used.
-        #[structopt(long, short)]
-        transcript: Option<String>,
+        vdata: Option<PathBuf>,
+        /// Key archive to take the verifier data and transcript label from.
+        #[structopt(long, parse(from_os_str), conflicts_with = "vdata")]
+        keys: Option<PathBuf>,
+        /// The transcript label to include in the export, given either as text or as hex
+        /// prefixed by "0x". If not specified the label recorded in the key archive, or declared
+        /// in the circuit, or otherwise "dusk_plang", will be used.
+        #[structopt(long, short, parse(try_from_str = parse_label))]
+        label: Option<Label>,
+        /// The format to export in. With "plang" the verifier data is decomposed into scalars, and
+        /// written as equations binding each to a public input of an outer circuit verifying
+        /// proofs of this one.
+        #[structopt(long, default_value = "json", possible_values = &["json", "plang"])]
+        format: VkFormat,
+        /// Where to write the export to. If not specified it is written to stdout.
+        #[structopt(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+        #[structopt(flatten)]
+        identity: Identity,
+    },
+    /// Export the circuit to the language of another proof system.
+    Export {
+        /// The circuit to export.
+        #[structopt(parse(from_os_str))]
+        circuit: PathBuf,
+        /// The format to export in.
+        #[structopt(long, possible_values = ExportFormat::NAMES)]
+        format: ExportFormat,
+        /// Name of the exported template or struct. If not specified it is derived from the name of the
+        /// circuit file.
+        #[structopt(long)]
+        name: Option<String>,
+        /// Where to write the export to. If not specified it will be written to a file with the
+        /// name of the circuit plus the extension of the format.
+        #[structopt(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Values for witnesses and public inputs. With the "acir" format they're translated into
+        /// a witness map, written to a file with the name of the circuit plus the extension
+        /// ".witness.json". With the "zkif" format they're included in the exported messages.
+        #[structopt(long, short, parse(try_from_str = parse_key_val))]
+        vals: Vec<(String, ScalarValue)>,
+    },
+    /// Import a circuit written for another proof system as plang.
+    Import {
+        /// The file to import.
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+        /// The format of the file to import.
+        #[structopt(long, possible_values = &["r1cs"])]
+        format: ImportFormat,
+        /// Where to write the circuit to. If not specified it will be written to a file with the
+        /// name of the input plus the extension ".plang".
+        #[structopt(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Explain an error code in detail, or list all error codes if none is given.
+    Explain {
+        /// The error code to explain, such as P0007.
+        code: Option<String>,
     },
+    /// Serve a web playground for writing circuits, with example circuits, live diagnostics and
+    /// proving on insecure toy parameters.
+    Playground {
+        /// The address to serve the playground on.
+        #[structopt(long, short, default_value = "127.0.0.1:8000")]
+        listen: String,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum ParamsCommand {
+    /// Check that public parameters deserialize correctly and report their degree.
+    Validate {
+        /// The public parameters to validate. If not specified the parameters pointed to by the
+        /// PLANG_PARAMS environment variable are validated.
+        #[structopt(parse(from_os_str), env = "PLANG_PARAMS")]
+        params: PathBuf,
+        /// Also check that the parameters are large enough to compile this circuit.
+        #[structopt(long, short, parse(from_os_str))]
+        circuit: Option<PathBuf>,
+    },
+}
+
+/// Formats verifier data can be exported in.
+#[derive(Debug)]
+enum VkFormat {
+    Json,
+    Plang,
+}
+
+impl FromStr for VkFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "plang" => Ok(Self::Plang),
+            _ => Err(format!("unknown format `{}`", s)),
+        }
+    }
+}
+
+/// Encodings proofs and proof envelopes are written and read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProofFormat {
+    Binary,
+    Json,
+    Hex,
+}
+
+impl FromStr for ProofFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "binary" => Ok(Self::Binary),
+            "json" => Ok(Self::Json),
+            "hex" => Ok(Self::Hex),
+            _ => Err(format!("unknown format `{}`", s)),
+        }
+    }
+}
+
+/// Formats a circuit can be exported in.
+#[derive(Debug)]
+enum ExportFormat {
+    Circom,
+    Acir,
+    Halo2,
+    Markdown,
+    Latex,
+    #[cfg(feature = "zkinterface")]
+    Zkif,
+}
+
+impl ExportFormat {
+    #[cfg(not(feature = "zkinterface"))]
+    const NAMES: &'static [&'static str] = &["circom", "acir", "halo2", "markdown", "latex"];
+    #[cfg(feature = "zkinterface")]
+    const NAMES: &'static [&'static str] =
+        &["circom", "acir", "halo2", "markdown", "latex", "zkif"];
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "circom" => Ok(Self::Circom),
+            "acir" => Ok(Self::Acir),
+            "halo2" => Ok(Self::Halo2),
+            "markdown" => Ok(Self::Markdown),
+            "latex" => Ok(Self::Latex),
+            #[cfg(feature = "zkinterface")]
+            "zkif" => Ok(Self::Zkif),
+            _ => Err(format!("unknown format `{}`", s)),
+        }
+    }
+}
+
+/// The ID and metadata to give a circuit, recorded in the artifacts compiled from it.
+#[derive(Debug, StructOpt)]
+struct Identity {
+    /// Hex encoded 32 byte ID to give the circuit, for protocols that mandate one. If not
+    /// specified the ID recorded in the key archive, or otherwise the circuit hash, is used.
+    #[structopt(long, parse(try_from_str = parse_circuit_id))]
+    circuit_id: Option<[u8; 32]>,
+    /// Name of the circuit to record in the artifacts.
+    #[structopt(long)]
+    circuit_name: Option<String>,
+    /// Version of the circuit to record in the artifacts.
+    #[structopt(long)]
+    circuit_version: Option<String>,
+}
+
+impl Identity {
+    /// Gives the circuit the ID and metadata that were specified, keeping what it already has
+    /// otherwise.
+    fn apply(self, mut circuit: PlangCircuit) -> PlangCircuit {
+        if let Some(id) = self.circuit_id {
+            circuit = circuit.with_id(id);
+        }
+
+        let mut metadata = circuit.metadata().clone();
+        if let Some(name) = self.circuit_name {
+            metadata.name = Some(name);
+        }
+        if let Some(version) = self.circuit_version {
+            metadata.version = Some(version);
+        }
+        circuit.with_metadata(metadata)
+    }
+}
+
+/// Formats a circuit can be imported from.
+#[derive(Debug)]
+enum ImportFormat {
+    R1cs,
+}
+
+impl FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "r1cs" => Ok(Self::R1cs),
+            _ => Err(format!("unknown format `{}`", s)),
+        }
+    }
 }
 
 fn parse_key_val<T, U>(s: &str) -> std::result::Result<(T, U), Box<dyn Error>>
@@ -114,164 +713,1230 @@ where
     Ok((s[..pos].trim().parse()?, s[pos + 1..].trim().parse()?))
 }
 
-fn main() -> Result<()> {
-    let opt = Plangc::from_args();
+fn parse_seed(s: &str) -> std::result::Result<[u8; 32], Box<dyn Error>> {
+    let bytes = hex::decode(s)?;
+    if bytes.len() > 32 {
+        return Err(format!("seed is {} bytes long - maximum is 32", bytes.len()).into());
+    }
+
+    let mut seed = [0u8; 32];
+    seed[..bytes.len()].copy_from_slice(&bytes);
+    Ok(seed)
+}
+
+fn parse_circuit_id(s: &str) -> std::result::Result<[u8; 32], Box<dyn Error>> {
+    let bytes = hex::decode(s)?;
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| format!("circuit ID is {} bytes long - it must be 32", len).into())
+}
+
+fn parse_degree(s: &str) -> std::result::Result<usize, Box<dyn Error>> {
+    match s.strip_prefix("2^") {
+        Some(exp) => {
+            let exp: u32 = exp.parse()?;
+            1usize
+                .checked_shl(exp)
+                .ok_or_else(|| format!("degree `{}` is too large", s).into())
+        }
+        None => Ok(s.parse()?),
+    }
+}
+
+/// Installs a subscriber logging spans, and how long they took, to stderr. Nothing is logged
+/// unless `-v` is given at least once.
+fn init_tracing(verbose: u8) {
+    let level = match verbose {
+        0 => return,
+        1 => tracing::Level::INFO,
+        2 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(io::stderr)
+        .init();
+}
+
+/// Set by `--quiet`, suppressing warnings and status messages.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Returns the code to exit with on the given error:
+///
+/// | code | meaning                                              |
+/// |------|------------------------------------------------------|
+/// | 0    | success                                              |
+/// | 1    | usage error, such as a missing or invalid argument   |
+/// | 2    | the circuit could not be parsed                      |
+/// | 3    | the circuit is not valid                             |
+/// | 4    | compiling the circuit or proving failed              |
+/// | 5    | the proof did not verify                             |
+/// | 6    | invalid values were given for the circuit            |
+/// | 7    | keys or parameters don't match the circuit           |
+/// | 8    | a file could not be read, written or deserialized    |
+/// | 9    | values don't satisfy the circuit as expected         |
+fn exit_code(err: &PlangError) -> i32 {
+    match err {
+        PlangError::InFile { error, .. } | PlangError::Imported { error, .. } => exit_code(error),
+        PlangError::Parse(ParseError::InvalidR1cs(_) | ParseError::InvalidPackage(_)) => 8,
+        PlangError::Parse(_) => 2,
+        PlangError::Semantic(SemanticError::UnknownWarning(_)) => 1,
+        PlangError::Semantic(SemanticError::UnrepresentableCoefficient) => 8,
+        PlangError::Semantic(_) => 3,
+        PlangError::Prove(ProveError::Plonk(PlonkError::ProofVerificationError)) => 5,
+        PlangError::Prove(ProveError::Plonk(_) | ProveError::TooManyGates(_)) => 4,
+        PlangError::Prove(
+            ProveError::ParamsTooSmall { .. }
+            | ProveError::KeysMismatch
+            | ProveError::CircuitMismatch
+            | ProveError::TranscriptMismatch
+            | ProveError::ParamsMismatch
+            | ProveError::NoProverKey,
+        ) => 7,
+        PlangError::Assignment(
+            AssignmentError::Unsatisfied { .. }
+            | AssignmentError::UnexpectedlySatisfied(_)
+            | AssignmentError::FailedTests { .. },
+        ) => 9,
+        PlangError::Assignment(_) => 6,
+        PlangError::UnknownErrorCode(_) => 1,
+        _ => 8,
+    }
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    QUIET.store(opt.quiet, Ordering::Relaxed);
+    init_tracing(opt.verbose);
+
+    let result = check_config(opt.strict, &opt.deny, &opt.allow).and_then(|checks| {
+        let mut parse = ParseOptions {
+            edition: opt.edition,
+            ..ParseOptions::default()
+        };
+        // The directories of the environment are looked in after those of the command line, and
+        // before those of a plang.toml.
+        let from_env = env::var_os("PLANG_INCLUDE_PATH");
+        let from_env = from_env.iter().flat_map(env::split_paths);
+        for dir in opt.include_path.into_iter().chain(from_env) {
+            if !dir.as_os_str().is_empty() {
+                parse.search.include(dir)?;
+            }
+        }
+        run(opt.cmd, &Config { checks, parse })
+    });
+    if let Err(err) = result {
+        match err.code() {
+            Some(code) => {
+                eprintln!("error[{}]: {}", code, err);
+                eprintln!(
+                    "help: for more information about this error, run `plangc explain {}`",
+                    code
+                );
+            }
+            None => eprintln!("error: {}", err),
+        }
+        process::exit(exit_code(&err));
+    }
+}
+
+/// How circuits are read, as set by the options common to every command.
+#[derive(Debug, Clone)]
+struct Config {
+    /// The levels of the warnings about circuits.
+    checks: CheckConfig,
+    /// Where imports are looked up, as set by `--include-path`, and the edition circuits are
+    /// parsed by.
+    parse: ParseOptions,
+}
+
+/// The levels of the warnings about circuits, as set by `--strict`, `--deny` and `--allow`. A
+/// warning both denied and allowed is allowed.
+fn check_config(strict: bool, deny: &[String], allow: &[String]) -> Result<CheckConfig> {
+    let mut checks = match strict {
+        true => CheckConfig::strict(),
+        false => CheckConfig::default(),
+    };
+
+    for name in deny {
+        checks.set(name, Level::Deny)?;
+    }
+    for name in allow {
+        checks.set(name, Level::Allow)?;
+    }
+
+    Ok(checks)
+}
+
+fn run(cmd: Plangc, config: &Config) -> Result<()> {
+    match cmd {
+        Plangc::Init { dir } => init::run(&dir)?,
+        Plangc::Build {
+            config: manifest,
+            params,
+            cache_dir,
+        } => {
+            let mut workspace = Workspace::from_file(&manifest)?;
 
-    match opt {
+            // Paths given on the command line or in the environment are relative to the current
+            // directory rather than to the workspace.
+            let cwd = env::current_dir()?;
+            let absolute = |path: PathBuf| cwd.join(path).to_string_lossy().into_owned();
+            if let Some(params) = params {
+                workspace.params = Some(absolute(params));
+            }
+            if let Some(dir) = cache_dir {
+                workspace.cache_dir = Some(absolute(dir));
+            }
+
+            workspace.build(config)?;
+        }
+        Plangc::NewGadget {
+            name,
+            params,
+            output,
+        } => {
+            let out = output.unwrap_or_else(|| PathBuf::from(format!("{}.plang", name)));
+            gadget::run(&name, &params, &out)?;
+        }
         Plangc::Compile {
             circuit: circuit_file,
             params,
             output,
+            pk_output,
+            vd_output,
+            pk_only,
+            vd_only,
+            archive,
+            label,
+            verifier,
+            cache_dir,
+            identity,
         } => {
-            let bytes = fs::read(&circuit_file)?;
+            let mut circuit = identity.apply(read_circuit(&circuit_file, config)?);
 
-            let text = String::from_utf8(bytes)?;
-            let mut circuit = PlangCircuit::parse(text)?;
+            // Keys compiled with random parameters are never compiled again,
+            // so there's no point in caching them.
+            let cache = cache_dir.filter(|_| params.is_some()).map(KeyCache::new);
 
             let pp = match params {
-                Some(params) => PublicParameters::from_slice(&fs::read(params)?)?,
-                None => PublicParameters::setup(circuit.padded_gates() << 1, &mut OsRng)?,
+                Some(params) => read_pp_for_circuit(&circuit, params)?,
+                None => setup_random_pp(&circuit)?,
+            };
+            let (pk, vd) = match cache {
+                Some(cache) => cache.compile(&mut circuit, &pp)?,
+                None => info_span!("compile").in_scope(|| circuit.compile(&pp))?,
             };
-            let (pk, vd) = circuit.compile(&pp)?;
 
             let out = output.map_or(circuit_file, |out| out);
-            fs::write(out.with_extension("pk"), &pk.to_var_bytes())?;
-            fs::write(out.with_extension("vd"), &vd.to_var_bytes())?;
+            if verifier {
+                let verifier = PlangVerifier::new(&circuit, vd.clone());
+                fs::write(out.with_extension("plangvk"), verifier.to_bytes())?;
+            }
+            if archive {
+                let pk = if vd_only { None } else { Some(pk) };
+                let label = transcript_label(label, None, &circuit);
+
+                let keys = PlangKeys::new(&circuit, &pp, pk, vd, label);
+                fs::write(out.with_extension("plangkeys"), keys.to_bytes())?;
+                return Ok(());
+            }
+
+            if !vd_only {
+                let pk_out = pk_output.unwrap_or_else(|| out.with_extension("pk"));
+                write_artifact(pk_out, ArtifactKind::ProverKey, 0, &pk.to_var_bytes())?;
+            }
+            if !pk_only {
+                let vd_out = vd_output.unwrap_or_else(|| out.with_extension("vd"));
+                write_artifact(vd_out, ArtifactKind::VerifierData, 0, &vd.to_var_bytes())?;
+            }
         }
         Plangc::GenerateParams {
+            circuit: circuit_file,
+            degree,
+            max_gates,
+            output,
+            seed,
+        } => {
+            let degree = match (&circuit_file, degree, max_gates) {
+                (Some(circuit_file), _, _) => {
+                    let circuit = read_circuit(circuit_file, config)?;
+
+                    params::degree_for_gates(circuit.padded_gates())?
+                }
+                (None, Some(degree), _) => degree,
+                (None, None, Some(max_gates)) => params::degree_for_gates(max_gates)?,
+                (None, None, None) => unreachable!("enforced by the argument parser"),
+            };
+
+            let pp = match seed {
+                Some(seed) => params::setup_seeded(degree, seed)?,
+                None => PublicParameters::setup(degree, &mut OsRng)?,
+            };
+
+            let out = output.unwrap_or_else(|| {
+                circuit_file.map_or(PathBuf::from("plang.pp"), |c| c.with_extension("pp"))
+            });
+
+            // Seeded parameters are flagged as such in their header, so they can't be mistaken
+            // for the output of a proper setup.
+            let flags = match seed {
+                Some(_) => {
+                    warning!("seeded parameters are insecure - use them for testing only");
+                    artifact::FLAG_INSECURE
+                }
+                None => 0,
+            };
+            write_artifact(out, ArtifactKind::Params, flags, &pp.to_var_bytes())?;
+        }
+        Plangc::InspectKeys { pk, vd, keys, json } => {
+            let mut info = serde_json::Map::new();
+
+            if let Some(pk_file) = pk {
+                let (bytes, _) = read_artifact(&pk_file, ArtifactKind::ProverKey)?;
+                ProverKey::from_slice(&bytes)?;
+
+                if !json {
+                    println!("prover key {}: {} bytes", pk_file.display(), bytes.len());
+                }
+                info.insert(
+                    "prover_key".into(),
+                    json!({
+                        "path": pk_file,
+                        "size": bytes.len(),
+                    }),
+                );
+            }
+
+            if let Some(vd_file) = vd {
+                let (bytes, _) = read_artifact(&vd_file, ArtifactKind::VerifierData)?;
+                let vd = VerifierData::from_slice(&bytes)?;
+
+                let padded_gates = vd.key().padded_gates();
+                let public_inputs = vd.pi_pos().len();
+
+                if !json {
+                    println!(
+                        "verifier data {}: {} bytes, {} padded gates, {} public inputs",
+                        vd_file.display(),
+                        bytes.len(),
+                        padded_gates,
+                        public_inputs
+                    );
+                }
+                info.insert(
+                    "verifier_data".into(),
+                    json!({
+                        "path": vd_file,
+                        "size": bytes.len(),
+                        "padded_gates": padded_gates,
+                        "public_inputs": public_inputs,
+                    }),
+                );
+            }
+
+            if let Some(keys_file) = keys {
+                let bytes = fs::read(&keys_file)?;
+                let keys = PlangKeys::from_slice(&bytes)?;
+
+                let label = String::from_utf8_lossy(&keys.label);
+                let padded_gates = keys.verifier_data.key().padded_gates();
+                let public_inputs = keys.verifier_data.pi_pos().len();
+                let prover_key = keys.prover_key.is_some();
+
+                if !json {
+                    println!(
+                        "key archive {}: {} bytes, {}, {} padded gates, {} public inputs",
+                        keys_file.display(),
+                        bytes.len(),
+                        match prover_key {
+                            true => "with prover key",
+                            false => "verifier data only",
+                        },
+                        padded_gates,
+                        public_inputs
+                    );
+                    println!("  circuit hash: {}", hex::encode(keys.circuit_hash));
+                    println!("  circuit id: {}", hex::encode(keys.circuit_id));
+                    println!("  transcript label: {}", label);
+                    println!("  params digest: {}", hex::encode(keys.params_digest));
+                    println!("  compiler version: {}", keys.compiler_version);
+                }
+                info.insert(
+                    "keys".into(),
+                    json!({
+                        "path": keys_file,
+                        "size": bytes.len(),
+                        "prover_key": prover_key,
+                        "padded_gates": padded_gates,
+                        "public_inputs": public_inputs,
+                        "circuit_hash": hex::encode(keys.circuit_hash),
+                        "circuit_id": hex::encode(keys.circuit_id),
+                        "label": label,
+                        "params_digest": hex::encode(keys.params_digest),
+                        "compiler_version": keys.compiler_version,
+                    }),
+                );
+            }
+
+            if json {
+                println!("{:#}", Value::Object(info));
+            }
+        }
+        Plangc::VerifyKeys {
+            circuit: circuit_file,
+            params,
+            pk,
+            vd,
+        } => {
+            let mut circuit = read_circuit(&circuit_file, config)?;
+
+            let pp = read_pp_for_circuit(&circuit, params)?;
+            let (cpk, cvd) = info_span!("compile").in_scope(|| circuit.compile(&pp))?;
+
+            let mut drifted = false;
+            for (name, kind, path, compiled) in [
+                (
+                    "prover key",
+                    ArtifactKind::ProverKey,
+                    pk,
+                    cpk.to_var_bytes(),
+                ),
+                (
+                    "verifier data",
+                    ArtifactKind::VerifierData,
+                    vd,
+                    cvd.to_var_bytes(),
+                ),
+            ] {
+                if let Some(path) = path {
+                    if read_artifact(&path, kind)?.0 == compiled {
+                        status!("{} {}: up to date", name, path.display());
+                    } else {
+                        eprintln!(
+                            "{} {}: does not match the circuit {}",
+                            name,
+                            path.display(),
+                            circuit_file.display()
+                        );
+                        drifted = true;
+                    }
+                }
+            }
+
+            if drifted {
+                return Err(PlangError::Prove(ProveError::KeysMismatch));
+            }
+        }
+        Plangc::Repro { manifest } => {
+            let manifest = Manifest::from_file(&manifest)?;
+
+            let results = info_span!("repro").in_scope(|| repro::check_manifest(&manifest));
+            let mut failed = 0;
+            for (entry, result) in manifest.entries.iter().zip(results) {
+                match result {
+                    Ok(()) => status!("{}: reproduced", entry.source.display()),
+                    Err(err) => {
+                        eprintln!("{}: {}", entry.source.display(), err);
+                        failed += 1;
+                    }
+                }
+            }
+
+            if failed > 0 {
+                return Err(PlangError::Prove(ProveError::KeysMismatch));
+            }
+        }
+        Plangc::Hash {
+            circuit: circuit_file,
+            raw,
+            hex,
+        } => {
+            let circuit = read_circuit(&circuit_file, config)?;
+
+            let hash = circuit.hash();
+            match (raw, hex) {
+                (true, false) => io::stdout().write_all(&hash)?,
+                _ => println!("{}", hex::encode(hash)),
+            }
+        }
+        Plangc::Bench {
+            circuit: circuit_file,
+            params,
+            vals,
+            iterations,
+            json,
+        } => {
+            let circuit = read_circuit(&circuit_file, config)?;
+
+            let pp = match params {
+                Some(params) => read_pp_for_circuit(&circuit, params)?,
+                None => setup_random_pp(&circuit)?,
+            };
+
+            let vals = scalars(vals)?;
+
+            bench::run(&circuit_file, &config.parse, &pp, vals, iterations, json)?;
+        }
+        Plangc::Test {
+            circuit: circuit_file,
+            prove,
+            params,
+            cache_dir,
+        } => {
+            let mut circuit = read_circuit(&circuit_file, config)?;
+
+            let pp = match params.filter(|_| prove) {
+                Some(params) => Some(read_pp_for_circuit(&circuit, params)?),
+                None => None,
+            };
+            vectors::run(&mut circuit, prove, pp, cache_dir.map(KeyCache::new))?;
+        }
+        Plangc::Probe {
+            circuit: circuit_file,
+            vals,
+        } => {
+            let circuit = read_circuit(&circuit_file, config)?;
+
+            let vals = scalars(vals)?;
+            vectors::probe(&circuit, vals)?;
+        }
+        Plangc::Coverage {
+            circuit: circuit_file,
+            vals,
+            json,
+        } => {
+            let circuit = read_circuit(&circuit_file, config)?;
+
+            let vals = scalars(vals)?;
+            vectors::coverage(&circuit, vals, json)?;
+        }
+        Plangc::Graph {
             circuit: circuit_file,
             output,
+            highlight_public,
+        } => {
+            let circuit = read_circuit(&circuit_file, config)?;
+
+            let out = output.map_or(circuit_file.with_extension("dot"), |out| out);
+            fs::write(out, circuit.to_dot(highlight_public))?;
+        }
+        Plangc::Params {
+            cmd:
+                ParamsCommand::Validate {
+                    params: params_file,
+                    circuit,
+                },
         } => {
-            let bytes = fs::read(&circuit_file)?;
+            let (pp, flags) = params::read_file(&params_file)?;
 
-            let text = String::from_utf8(bytes)?;
-            let circuit = PlangCircuit::parse(text)?;
+            let degree = params::max_degree(&pp);
+            status!(
+                "{}: maximum degree {}, supporting circuits of up to {} gates",
+                params_file.display(),
+                degree,
+                params::gates_for_degree(degree)
+            );
+            if flags & artifact::FLAG_INSECURE != 0 {
+                warning!(
+                    "{}: parameters are marked INSECURE and must not be used in production",
+                    params_file.display()
+                );
+            }
 
-            let pp = PublicParameters::setup(circuit.padded_gates() << 1, &mut OsRng)?;
+            if let Some(circuit_file) = circuit {
+                let circuit = read_circuit(&circuit_file, config)?;
 
-            let out = output.map_or(circuit_file.with_extension("pp"), |out| out);
-            fs::write(out, &pp.to_var_bytes())?;
+                params::check_degree(&pp, &circuit)?;
+                status!(
+                    "{}: {} padded gates, parameters are large enough",
+                    circuit_file.display(),
+                    circuit.padded_gates()
+                );
+            }
         }
         Plangc::Prove {
             circuit: circuit_file,
             params,
             key,
+            keys,
             vals,
+            witness,
             output,
-            transcript,
+            witness_dir,
+            out_dir,
+            envelope,
+            json,
+            format,
+            label,
+            domains,
         } => {
-            let bytes = fs::read(&circuit_file)?;
-
-            let text = String::from_utf8(bytes)?;
-            let mut circuit = PlangCircuit::parse(text)?;
-
-            let vals: Vec<(String, BlsScalar)> = vals
-                .into_iter()
-                .map(|(name, val)| {
-                    (
-                        name,
-                        match val.is_negative() {
-                            true => -BlsScalar::from((-val) as u64),
-                            false => BlsScalar::from(val as u64),
-                        },
-                    )
-                })
-                .collect();
-            circuit.set_vals(vals)?;
+            let format = match format {
+                Some(ProofFormat::Json) if !envelope => {
+                    return Err(invalid_input("only proof envelopes are written as JSON"));
+                }
+                Some(format) => format,
+                None if json => ProofFormat::Json,
+                None => ProofFormat::Binary,
+            };
+            // Check before proving that the proof can be written where it's asked to be.
+            let stdout = output.as_deref() == Some(Path::new("-"));
+            if stdout && format == ProofFormat::Binary && atty::is(atty::Stream::Stdout) {
+                return Err(invalid_input(
+                    "not writing a binary proof to a terminal, use `--format hex` or redirect stdout",
+                ));
+            }
 
-            let transcript: &'static [u8] =
-                transcript.map_or(b"dusk_plang", |t| Box::leak(t.into_boxed_str()).as_bytes());
+            let mut circuit = read_circuit(&circuit_file, config)?;
+
+            // The values of a batch are set on a copy of the circuit for each witness.
+            if witness_dir.is_none() {
+                if let Some(path) = witness {
+                    circuit.set_vals(read_values(path)?)?;
+                }
+                circuit.set_vals(vals.clone())?;
+            }
 
             let pp = get_pp_or_generate_and_write(&circuit, circuit_file.clone(), params)?;
 
-            let pk = {
-                match key {
-                    Some(key_path) => ProverKey::from_slice(&fs::read(key_path)?)?,
-                    None => match fs::read(circuit_file.with_extension("pp")) {
-                        Ok(bytes) => ProverKey::from_slice(&bytes)?,
-                        Err(_) => {
-                            let (pk, _) = circuit.compile(&pp)?;
-                            fs::write(circuit_file.with_extension("pk"), pk.to_var_bytes())?;
-                            pk
+            let (pk, archived_label) = match keys {
+                Some(keys_path) => {
+                    let keys = read_keys_for_circuit(&circuit, &pp, keys_path)?;
+                    let pk = keys
+                        .prover_key
+                        .ok_or(PlangError::Prove(ProveError::NoProverKey))?;
+                    (pk, Some(keys.label))
+                }
+                None => match key {
+                    Some(key_path) => {
+                        let (bytes, _) = read_artifact(key_path, ArtifactKind::ProverKey)?;
+                        (ProverKey::from_slice(&bytes)?, None)
+                    }
+                    None => match read_artifact(
+                        circuit_file.with_extension("pk"),
+                        ArtifactKind::ProverKey,
+                    ) {
+                        Ok((bytes, _)) => (ProverKey::from_slice(&bytes)?, None),
+                        Err(PlangError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+                            let (pk, _) =
+                                info_span!("compile").in_scope(|| circuit.compile(&pp))?;
+                            write_artifact(
+                                circuit_file.with_extension("pk"),
+                                ArtifactKind::ProverKey,
+                                0,
+                                &pk.to_var_bytes(),
+                            )?;
+                            (pk, None)
                         }
+                        Err(err) => return Err(err),
                     },
-                }
+                },
             };
 
-            let proof = circuit.prove(&pp, &pk, transcript)?;
+            let label = transcript_label(label, archived_label, &circuit);
+            let options = ProveOptions::with_domains(label, domains);
+
+            if let Some(dir) = witness_dir {
+                let batch = batch::Witnesses {
+                    out_dir: out_dir.as_deref().unwrap_or(&dir),
+                    dir: &dir,
+                    vals,
+                    options,
+                    envelope,
+                    format,
+                };
+                return batch.prove(&circuit, &pp, &pk);
+            }
+
+            let proof = info_span!("prove").in_scope(|| circuit.prove_with(&pp, &pk, &options))?;
 
-            let out = output.map_or(circuit_file.with_extension("proof"), |out| out);
-            fs::write(out, &proof.to_bytes())?;
+            let bytes = encode_proof(&circuit, proof, &options, envelope, format);
+            match output {
+                Some(_) if stdout => {
+                    let mut stdout = io::stdout();
+                    stdout.write_all(&bytes)?;
+                    stdout.flush()?;
+                }
+                Some(out) => fs::write(out, bytes)?,
+                None => fs::write(
+                    circuit_file.with_extension(proof_extension(envelope)),
+                    bytes,
+                )?,
+            }
         }
         Plangc::Verify {
             circuit: circuit_file,
             params,
             vdata,
-            mut vals,
+            keys,
+            vals,
+            public_inputs,
             proof,
-            transcript,
+            envelope,
+            batch,
+            label,
+            domains,
+            report,
+            format,
         } => {
-            let bytes = fs::read(&circuit_file)?;
-
-            let text = String::from_utf8(bytes)?;
-            let mut circuit = PlangCircuit::parse(text)?;
-
-            let proof = Proof::from_slice(&fs::read(proof)?)
-                .map_err(|_| PlangError::Io(io::Error::from(io::ErrorKind::InvalidInput)))?;
-
-            let transcript: &'static [u8] =
-                transcript.map_or(b"dusk_plang", |t| Box::leak(t.into_boxed_str()).as_bytes());
+            let mut circuit = read_circuit(&circuit_file, config)?;
 
             let pp = get_pp_or_generate_and_write(&circuit, circuit_file.clone(), params)?;
 
-            let vd = {
-                match vdata {
-                    Some(key_path) => VerifierData::from_slice(&fs::read(key_path)?)?,
-                    None => match fs::read(circuit_file.with_extension("vd")) {
-                        Ok(bytes) => VerifierData::from_slice(&bytes)?,
-                        Err(_) => {
-                            let (_, vd) = circuit.compile(&pp)?;
-                            fs::write(circuit_file.with_extension("vd"), vd.to_var_bytes())?;
-                            vd
+            let (vd, archived_label) = match keys {
+                Some(keys_path) => {
+                    let keys = read_keys_for_circuit(&circuit, &pp, keys_path)?;
+                    (keys.verifier_data, Some(keys.label))
+                }
+                None => match vdata {
+                    Some(key_path) => {
+                        let (bytes, _) = read_artifact(key_path, ArtifactKind::VerifierData)?;
+                        (VerifierData::from_slice(&bytes)?, None)
+                    }
+                    None => match read_artifact(
+                        circuit_file.with_extension("vd"),
+                        ArtifactKind::VerifierData,
+                    ) {
+                        Ok((bytes, _)) => (VerifierData::from_slice(&bytes)?, None),
+                        Err(PlangError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+                            let (_, vd) =
+                                info_span!("compile").in_scope(|| circuit.compile(&pp))?;
+                            write_artifact(
+                                circuit_file.with_extension("vd"),
+                                ArtifactKind::VerifierData,
+                                0,
+                                &vd.to_var_bytes(),
+                            )?;
+                            (vd, None)
                         }
+                        Err(err) => return Err(err),
                     },
+                },
+            };
+
+            // Proofs, envelopes included, are verified with the transcript the
+            // verifier expects, never with one the prover picked.
+            let label = transcript_label(label, archived_label, &circuit);
+            let options = ProveOptions::with_domains(label, domains);
+
+            if let Some(dir) = batch {
+                let inputs = public_inputs.as_deref().filter(|path| path.is_dir());
+                let vals = match public_inputs.as_deref() {
+                    Some(path) if inputs.is_none() => read_values(path)?,
+                    _ => vals,
+                };
+                let batch = batch::Batch {
+                    dir: &dir,
+                    inputs,
+                    vals,
+                    options,
+                };
+                return match report {
+                    Some(path) => batch.report(&circuit, &pp, &vd, &path),
+                    None => batch.verify(&circuit, &pp, &vd),
+                };
+            }
+
+            let proofs = match envelope {
+                Some(path) => {
+                    let envelope = read_envelope(path, format)?;
+                    envelope.check(&circuit)?;
+                    envelope.check_options(&options)?;
+                    vec![(envelope.proof, envelope.public_inputs)]
+                }
+                None => {
+                    let vals = match public_inputs {
+                        Some(path) => read_values(path)?,
+                        None => vals,
+                    };
+                    let path = proof.expect("enforced by the argument parser");
+                    let proof = read_proof(&path, options.transcript(), format)?;
+                    vec![(proof, to_scalars(vals)?)]
+                }
+            };
+
+            let count = proofs.len();
+            let span = info_span!("verify", proofs = count);
+            match report {
+                Some(path) => {
+                    let report = span.in_scope(|| {
+                        plang::report::verify(&circuit, &pp, &vd, &proofs, &options)
+                    })?;
+                    fs::write(path, format!("{:#}\n", report.to_json()))?;
+                    report.into_result()?;
+                }
+                None => {
+                    let proofs = proofs
+                        .into_iter()
+                        .map(|(proof, vals)| {
+                            let vals = vals
+                                .into_iter()
+                                .map(|(name, value)| (name, BlsScalar::from(value)));
+                            Ok((proof, circuit.public_inputs_from(vals)?))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    span.in_scope(|| {
+                        verifier::verify_batch(&pp, &vd, &proofs, options.transcript())
+                    })?;
+                }
+            }
+        }
+        Plangc::ExportVk {
+            circuit: circuit_file,
+            vdata,
+            keys,
+            label,
+            format,
+            output,
+            identity,
+        } => {
+            let mut circuit = read_circuit(&circuit_file, config)?;
+
+            let (vd, archived_label) = match keys {
+                Some(keys_path) => {
+                    let keys = PlangKeys::from_slice(&fs::read(keys_path)?)?;
+                    if keys.circuit_hash != circuit.hash() {
+                        return Err(PlangError::Prove(ProveError::CircuitMismatch));
+                    }
+                    circuit = circuit
+                        .with_id(keys.circuit_id)
+                        .with_metadata(keys.metadata);
+                    (keys.verifier_data, Some(keys.label))
+                }
+                None => {
+                    let vd_path = vdata.unwrap_or_else(|| circuit_file.with_extension("vd"));
+                    let (bytes, _) = read_artifact(vd_path, ArtifactKind::VerifierData)?;
+                    (VerifierData::from_slice(&bytes)?, None)
+                }
+            };
+
+            let circuit = identity.apply(circuit);
+            let label = transcript_label(label, archived_label, &circuit);
+            let exported = match format {
+                VkFormat::Json => format!("{:#}\n", export::vk::to_json(&circuit, &vd, &label)),
+                VkFormat::Plang => export::recursion::to_plang(&export::recursion::decompose(&vd)),
+            };
+
+            match output {
+                Some(out) => fs::write(out, exported)?,
+                None => print!("{}", exported),
+            }
+        }
+        Plangc::Export {
+            circuit: circuit_file,
+            format,
+            name,
+            output,
+            vals,
+        } => {
+            let circuit = read_circuit(&circuit_file, config)?;
+
+            let name = name.unwrap_or_else(|| template_name(&circuit_file));
+
+            match format {
+                ExportFormat::Circom => {
+                    let export = export::circom::to_circom(&circuit, &name);
+
+                    let out = output.unwrap_or_else(|| circuit_file.with_extension("circom"));
+                    fs::write(out, export.source)?;
+
+                    for signal in export.signals {
+                        let role = if signal.public { "public" } else { "witness" };
+                        status!("{} -> {} ({})", signal.name, signal.signal, role);
+                    }
+                }
+                ExportFormat::Acir => {
+                    let export = export::acir::to_acir(&circuit);
+
+                    let out = output.unwrap_or_else(|| circuit_file.with_extension("acir.json"));
+                    fs::write(out, format!("{:#}\n", export.circuit))?;
+
+                    if !vals.is_empty() {
+                        let map = export.witness_map(vals)?;
+                        let out = circuit_file.with_extension("witness.json");
+                        fs::write(out, format!("{:#}\n", map))?;
+                    }
+
+                    for witness in export.witnesses {
+                        let role = if witness.public { "public" } else { "witness" };
+                        status!("{} -> _{} ({})", witness.name, witness.index, role);
+                    }
+                }
+                ExportFormat::Halo2 => {
+                    let export = export::halo2::to_halo2(&circuit, &name);
+
+                    let out = output.unwrap_or_else(|| circuit_file.with_extension("rs"));
+                    fs::write(out, export.source)?;
+
+                    for field in export.fields {
+                        let role = if field.public { "public" } else { "witness" };
+                        status!("{} -> {} ({})", field.name, field.field, role);
+                    }
+                }
+                ExportFormat::Markdown => {
+                    let out = output.unwrap_or_else(|| circuit_file.with_extension("md"));
+                    fs::write(out, export::doc::to_markdown(&circuit))?;
+                }
+                ExportFormat::Latex => {
+                    let out = output.unwrap_or_else(|| circuit_file.with_extension("tex"));
+                    fs::write(out, export::doc::to_latex(&circuit))?;
                 }
+                #[cfg(feature = "zkinterface")]
+                ExportFormat::Zkif => {
+                    let mut circuit = circuit;
+                    circuit.set_vals(vals)?;
+                    let export = export::zkif::to_zkif(&circuit);
+
+                    let out = output.unwrap_or_else(|| circuit_file.with_extension("zkif"));
+                    fs::write(out, export.to_bytes())?;
+
+                    for var in export.variables {
+                        let role = if var.public { "instance" } else { "witness" };
+                        status!("{} -> {} ({})", var.name, var.id, role);
+                    }
+                }
+            }
+        }
+        Plangc::Import {
+            input,
+            format,
+            output,
+        } => {
+            let bytes = fs::read(&input)?;
+
+            let text = match format {
+                ImportFormat::R1cs => import::r1cs::R1cs::from_slice(&bytes)?.to_plang()?,
             };
 
-            vals.sort_by(|(name1, _), (name2, _)| Ord::cmp(name1, name2));
-            let mut pinputs = Vec::with_capacity(vals.len());
-            pinputs.append(
-                &mut vals
+            let out = output.unwrap_or_else(|| input.with_extension("plang"));
+            fs::write(&out, text)?;
+
+            status!("Imported {} as {}", input.display(), out.display());
+        }
+        Plangc::Explain { code } => match code {
+            Some(code) => {
+                let explanation =
+                    explain::explain(&code).ok_or(PlangError::UnknownErrorCode(code))?;
+                print!("{}", explanation);
+            }
+            None => {
+                for (code, summary) in explain::codes() {
+                    println!("{}: {}", code, summary);
+                }
+            }
+        },
+        Plangc::Playground { listen } => playground::serve(&listen)?,
+    }
+
+    Ok(())
+}
+
+/// Converts values given on the command line, or read from a file, into
+/// scalars.
+fn scalars(vals: Vec<(String, ScalarValue)>) -> Result<Vec<(String, BlsScalar)>> {
+    vals.into_iter()
+        .map(|(name, val)| Ok((name, val.to_scalar()?)))
+        .collect()
+}
+
+/// Derives the name of an exported template from the name of the circuit file,
+/// capitalizing it and replacing characters that can't be in an identifier.
+fn template_name(circuit_file: &Path) -> String {
+    let stem = circuit_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+
+    let mut name: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if let Some(first) = name.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => name,
+        _ => format!("Plang{}", name),
+    }
+}
+
+/// Reads and parses a circuit from a file, printing the warnings about it that aren't allowed. It
+/// returns an error if any is denied.
+fn read_circuit<P: AsRef<Path>>(path: P, config: &Config) -> Result<PlangCircuit> {
+    let path = path.as_ref();
+
+    let circuit = PlangCircuit::from_file_with(path, &config.parse)?;
+
+    for warning in circuit.check_warnings(&config.checks)? {
+        warning!(
+            "{}:{}: {}",
+            path.display(),
+            warning.line + 1,
+            warning.warning
+        );
+    }
+    Ok(circuit)
+}
+
+/// Reads a proof envelope, written as an artifact, as JSON or as hex, telling which from its
+/// first bytes if the format isn't given.
+fn read_envelope<P: AsRef<Path>>(path: P, format: Option<ProofFormat>) -> Result<ProofEnvelope> {
+    let bytes = read_input(path.as_ref())?;
+    match format.unwrap_or_else(|| sniff_format(&bytes)) {
+        ProofFormat::Binary => ProofEnvelope::from_slice(&bytes),
+        ProofFormat::Json => {
+            let json: Value = serde_json::from_slice(&bytes).map_err(io::Error::from)?;
+            ProofEnvelope::from_json(&json)
+        }
+        ProofFormat::Hex => ProofEnvelope::from_slice(&decode_hex(&bytes)?),
+    }
+}
+
+/// Reads the contents of a file, or of stdin if the path is "-". Stdin is read as bytes, and
+/// isn't read from if it's a terminal.
+fn read_input(path: &Path) -> Result<Vec<u8>> {
+    if path != Path::new("-") {
+        return Ok(fs::read(path)?);
+    }
+
+    if atty::is(atty::Stream::Stdin) {
+        return Err(invalid_input("expected a proof on stdin, not a terminal"));
+    }
+    let mut bytes = vec![];
+    io::stdin().lock().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Tells the format of a proof or proof envelope from its first bytes.
+fn sniff_format(bytes: &[u8]) -> ProofFormat {
+    if bytes.starts_with(artifact::MAGIC) {
+        ProofFormat::Binary
+    } else if bytes.starts_with(hex::encode(artifact::MAGIC).as_bytes()) {
+        ProofFormat::Hex
+    } else {
+        ProofFormat::Json
+    }
+}
+
+/// Decodes a proof or proof envelope written as hex, ignoring surrounding whitespace.
+fn decode_hex(bytes: &[u8]) -> Result<Vec<u8>> {
+    let text = String::from_utf8_lossy(bytes);
+    hex::decode(text.trim())
+        .map_err(|err| PlangError::Io(io::Error::new(io::ErrorKind::InvalidData, err.to_string())))
+}
+
+/// An error for arguments that can't be acted on together.
+fn invalid_input(msg: &str) -> PlangError {
+    PlangError::Io(io::Error::new(io::ErrorKind::InvalidInput, msg))
+}
+
+/// Reads values from a file holding a JSON object, or a TOML table if its
+/// extension is ".toml", that maps the names of variables to integers, or to
+/// numbers written as strings.
+fn read_values<P: AsRef<Path>>(path: P) -> Result<Vec<(String, ScalarValue)>> {
+    let path = path.as_ref();
+    let invalid = |msg: String| PlangError::Io(io::Error::new(io::ErrorKind::InvalidData, msg));
+
+    let values: Vec<(String, Option<ScalarValue>)> =
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => {
+                let toml: toml::Value =
+                    toml::from_slice(&fs::read(path)?).map_err(|err| invalid(err.to_string()))?;
+                let table = match toml {
+                    toml::Value::Table(table) => table,
+                    _ => return Err(invalid("values must be a TOML table".into())),
+                };
+                table
                     .into_iter()
-                    .map(|(_, v)| match v.is_negative() {
-                        true => -BlsScalar::from((-v) as u64),
-                        false => BlsScalar::from(v as u64),
+                    .map(|(name, val)| {
+                        let val = match val {
+                            toml::Value::Integer(n) => Some(ScalarValue::from(n)),
+                            toml::Value::String(text) => text.parse().ok(),
+                            _ => None,
+                        };
+                        (name, val)
                     })
-                    .map(Into::into)
-                    .collect(),
-            );
+                    .collect()
+            }
+            _ => {
+                let json: Value =
+                    serde_json::from_slice(&fs::read(path)?).map_err(io::Error::from)?;
+                let object = match json {
+                    Value::Object(object) => object,
+                    _ => return Err(invalid("values must be a JSON object".into())),
+                };
+                object
+                    .into_iter()
+                    .map(|(name, val)| (name, ScalarValue::from_json(&val)))
+                    .collect()
+            }
+        };
+
+    values
+        .into_iter()
+        .map(|(name, val)| match val {
+            Some(val) => Ok((name, val)),
+            None => Err(invalid(format!("value of `{}` is not a number", name))),
+        })
+        .collect()
+}
 
-            PlangCircuit::verify(&pp, &vd, &proof, &pinputs, transcript)?;
+/// Encodes a proof as a proof file, or as a proof envelope, in the given format. Proofs that
+/// aren't in envelopes have no JSON encoding, and are encoded as binary instead.
+fn encode_proof(
+    circuit: &PlangCircuit,
+    proof: Proof,
+    options: &ProveOptions,
+    envelope: bool,
+    format: ProofFormat,
+) -> Vec<u8> {
+    match (envelope, format) {
+        (_, ProofFormat::Hex) => {
+            let bytes = encode_proof(circuit, proof, options, envelope, ProofFormat::Binary);
+            format!("{}\n", hex::encode(bytes)).into_bytes()
+        }
+        (true, ProofFormat::Json) => {
+            let envelope = ProofEnvelope::new(circuit, proof, options);
+            format!("{:#}\n", envelope.to_json()).into_bytes()
         }
+        (true, _) => ProofEnvelope::new(circuit, proof, options).to_bytes(),
+        (false, _) => PlangProof::new(proof, options.transcript().to_vec()).to_bytes(),
     }
+}
+
+/// The extension of proof files, or of proof envelopes.
+fn proof_extension(envelope: bool) -> &'static str {
+    match envelope {
+        true => "envelope",
+        false => "proof",
+    }
+}
+
+/// Reads a proof, written as binary or as hex, telling which from its first bytes if the format
+/// isn't given, and warning if it was produced with another transcript label than the one given.
+fn read_proof(path: &Path, transcript: &[u8], format: Option<ProofFormat>) -> Result<Proof> {
+    let bytes = read_input(path)?;
+    let proof = match format.unwrap_or_else(|| sniff_format(&bytes)) {
+        ProofFormat::Hex => PlangProof::from_slice(&decode_hex(&bytes)?)?,
+        _ => PlangProof::from_slice(&bytes)?,
+    };
+    if let Some(recorded) = proof
+        .label
+        .as_ref()
+        .filter(|recorded| **recorded != transcript)
+    {
+        warning!(
+            "{} was produced with the transcript label \"{}\", not \"{}\"",
+            path.display(),
+            String::from_utf8_lossy(recorded),
+            String::from_utf8_lossy(transcript)
+        );
+    }
+    Ok(proof.proof)
+}
 
+/// Converts values to scalars, keeping their names.
+fn to_scalars(vals: Vec<(String, ScalarValue)>) -> Result<Vec<(String, Scalar)>> {
+    vals.into_iter()
+        .map(|(name, v)| Ok((name, Scalar::from(v.to_scalar()?))))
+        .collect()
+}
+
+/// Reads an artifact of the given kind from a file, returning its payload and
+/// flags.
+fn read_artifact<P: AsRef<Path>>(path: P, kind: ArtifactKind) -> Result<(Vec<u8>, u8)> {
+    let mut bytes = fs::read(path)?;
+
+    let (payload, flags) = artifact::unwrap(kind, &bytes)?;
+    let header_len = bytes.len() - payload.len();
+
+    bytes.drain(..header_len);
+    Ok((bytes, flags))
+}
+
+/// Writes an artifact of the given kind to a file, prefixed by its header.
+fn write_artifact<P: AsRef<Path>>(
+    path: P,
+    kind: ArtifactKind,
+    flags: u8,
+    payload: &[u8],
+) -> Result<()> {
+    fs::write(path, artifact::wrap(kind, flags, payload))?;
     Ok(())
 }
 
+/// A transcript label. Named so that structopt takes it as a single value rather than a list of
+/// bytes.
+type Label = Vec<u8>;
+
+/// Picks the transcript label to use, preferring the one explicitly given, then
+/// the one recorded in a key archive, and then the one declared in the circuit.
+fn transcript_label(
+    given: Option<Vec<u8>>,
+    archived: Option<Vec<u8>>,
+    circuit: &PlangCircuit,
+) -> Vec<u8> {
+    given
+        .or(archived)
+        .or_else(|| circuit.transcript().map(<[u8]>::to_vec))
+        .unwrap_or_else(|| DEFAULT_LABEL.to_vec())
+}
+
+/// Parses a transcript label, given either as text or as hex prefixed by "0x".
+fn parse_label(s: &str) -> std::result::Result<Label, hex::FromHexError> {
+    match s.strip_prefix("0x") {
+        Some(h) => hex::decode(h),
+        None => Ok(s.as_bytes().to_vec()),
+    }
+}
+
+/// Reads a key archive, checking it was compiled from the given circuit and
+/// public parameters.
+fn read_keys_for_circuit(
+    circuit: &PlangCircuit,
+    pp: &PublicParameters,
+    path: PathBuf,
+) -> Result<PlangKeys> {
+    PlangKeys::read_for_circuit(&fs::read(path)?, circuit, pp)
+}
+
 fn get_pp_or_generate_and_write(
     circuit: &PlangCircuit,
     circuit_file: PathBuf,
     params: Option<PathBuf>,
 ) -> Result<PublicParameters> {
+    let default_params = circuit_file.with_extension("pp");
+
     Ok(match params {
-        Some(params) => PublicParameters::from_slice(&fs::read(params)?)?,
-        None => match fs::read(circuit_file.with_extension("pp")) {
-            Ok(bytes) => PublicParameters::from_slice(&bytes)?,
-            Err(_) => {
-                let pp = PublicParameters::setup(circuit.padded_gates() << 1, &mut OsRng)?;
-                fs::write(circuit_file.with_extension("pp"), &pp.to_var_bytes())?;
-                pp
-            }
-        },
+        Some(params) => read_pp_for_circuit(circuit, params)?,
+        None if default_params.exists() => read_pp_for_circuit(circuit, default_params)?,
+        None => {
+            let pp = setup_random_pp(circuit)?;
+            write_artifact(default_params, ArtifactKind::Params, 0, &pp.to_var_bytes())?;
+            pp
+        }
     })
 }
+
+/// Reads public parameters from a file, checking they're large enough for the
+/// circuit. Parameters larger than needed are fine, since they're trimmed down
+/// to the circuit's size on compilation.
+fn read_pp_for_circuit(circuit: &PlangCircuit, path: PathBuf) -> Result<PublicParameters> {
+    let (pp, _) = params::read_file(path)?;
+    params::check_degree(&pp, circuit)?;
+    Ok(pp)
+}
+
+fn setup_random_pp(circuit: &PlangCircuit) -> Result<PublicParameters> {
+    warning!(
+        "no public parameters given - using insecure random parameters. Pass them with \
+         `--params` or set PLANG_PARAMS to use a trusted setup."
+    );
+
+    let degree = params::degree_for_gates(circuit.padded_gates())?;
+    Ok(PublicParameters::setup(degree, &mut OsRng)?)
+}
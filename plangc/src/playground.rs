@@ -0,0 +1,256 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::Result;
+
+use plang::document::Document;
+use plang::scalar::ScalarValue;
+use plang::tokens::{self, TokenKind};
+use plang::{params, PlangCircuit, ProveOptions};
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::io;
+use std::time::Instant;
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::info_span;
+
+use plang::dusk_plonk::circuit::Circuit;
+use plang::dusk_plonk::commitment_scheme::PublicParameters;
+use plang::dusk_plonk::error::Error as PlonkError;
+use plang::dusk_plonk::prelude::BlsScalar;
+
+/// The page of the playground, calling back into the routes below.
+const INDEX: &str = include_str!("../playground/index.html");
+
+/// Seed the toy parameters are derived from. They're insecure, which is fine
+/// for circuits that are only ever proven in the playground.
+const SEED: [u8; 32] = *b"plang playground toy parameters!";
+
+/// A built-in example circuit, with values that satisfy it.
+struct Example {
+    name: &'static str,
+    text: &'static str,
+    vals: &'static [(&'static str, i64)],
+}
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        name: "multiply",
+        text: include_str!("../playground/examples/multiply.plang"),
+        vals: &[("a", 3), ("b", 5), ("c", 15)],
+    },
+    Example {
+        name: "sum-and-product",
+        text: include_str!("../playground/examples/sum-and-product.plang"),
+        vals: &[("a", 1), ("b", 1), ("c", 2), ("d", 1)],
+    },
+    Example {
+        name: "coefficients",
+        text: include_str!("../playground/examples/coefficients.plang"),
+        vals: &[("x", 2), ("y", 3), ("z", 12), ("w", -54)],
+    },
+    Example {
+        name: "transcript",
+        text: include_str!("../playground/examples/transcript.plang"),
+        vals: &[("x", 4), ("y", 9), ("n", 36), ("s", 13)],
+    },
+];
+
+/// Serves the playground on the given address until interrupted.
+///
+/// Requests are served one at a time, since the playground is meant to be run
+/// locally by a single user.
+pub fn serve(listen: &str) -> Result<()> {
+    let server = Server::http(listen).map_err(|err| match err.downcast::<io::Error>() {
+        Ok(err) => *err,
+        Err(err) => io::Error::new(io::ErrorKind::InvalidInput, err),
+    })?;
+    status!("serving the playground on http://{}", listen);
+
+    // Toy parameters are generated once for each degree circuits require.
+    let mut pps = HashMap::new();
+    // The circuit being edited, checked again on every change to it.
+    let mut document = Document::default();
+
+    for mut request in server.incoming_requests() {
+        let method = request.method().clone();
+        let path = request.url().to_owned();
+        let _span = info_span!("request", %path).entered();
+
+        let (status, content_type, body) = match (method, path.as_str()) {
+            (Method::Get, "/") => (200, "text/html; charset=utf-8", INDEX.to_owned()),
+            (Method::Get, "/examples") => (200, "application/json", examples().to_string()),
+            (Method::Post, "/check") => {
+                reply(request_json(&mut request).map(|req| check(&req, &mut document)))
+            }
+            (Method::Post, "/run") => {
+                reply(request_json(&mut request).and_then(|req| run(&req, &mut pps)))
+            }
+            _ => (404, "text/plain", format!("no such page {}", path)),
+        };
+
+        respond(request, status, content_type, body);
+    }
+
+    Ok(())
+}
+
+fn examples() -> Value {
+    let examples: Vec<Value> = EXAMPLES
+        .iter()
+        .map(|example| {
+            let vals: serde_json::Map<String, Value> = example
+                .vals
+                .iter()
+                .map(|(name, val)| (name.to_string(), json!(val)))
+                .collect();
+            json!({ "name": example.name, "circuit": example.text, "vals": vals })
+        })
+        .collect();
+
+    Value::Array(examples)
+}
+
+/// `{ circuit }` → `{ diagnostics, tokens, stats }`, with `stats` null if the
+/// circuit has errors.
+///
+/// Only the lines changed since the last check are checked again.
+fn check(req: &Value, document: &mut Document) -> Value {
+    let text = req["circuit"].as_str().unwrap_or_default();
+    document.update(text);
+
+    let diagnostics: Vec<Value> = document
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.to_json())
+        .collect();
+
+    let tokens: Vec<Value> = tokens::tokenize(text)
+        .into_iter()
+        .map(|token| {
+            let kind = match token.kind {
+                TokenKind::Keyword => "keyword",
+                TokenKind::Variable => "variable",
+                TokenKind::Coefficient => "coefficient",
+                TokenKind::Operator => "operator",
+                TokenKind::String => "string",
+                TokenKind::Comment => "comment",
+                TokenKind::Unknown => "unknown",
+            };
+            json!({ "kind": kind, "start": token.span.start, "end": token.span.end })
+        })
+        .collect();
+
+    let stats = document.to_circuit().ok().map(|circuit| {
+        json!({
+            "hash": hex::encode(circuit.hash()),
+            "paddedGates": circuit.padded_gates(),
+            "publicInputs": circuit.public_input_order(),
+        })
+    });
+
+    json!({ "diagnostics": diagnostics, "tokens": tokens, "stats": stats })
+}
+
+/// `{ circuit, vals }` → `{ valid, timings }`, with timings in seconds.
+///
+/// Compiles the circuit with toy parameters, then proves it with the given
+/// values and verifies the proof with the values of its public inputs.
+fn run(req: &Value, pps: &mut HashMap<usize, PublicParameters>) -> Result<Value> {
+    let text = req["circuit"].as_str().unwrap_or_default();
+    let mut circuit = PlangCircuit::parse(text)?;
+
+    let vals = match req["vals"].as_object() {
+        Some(vals) => vals
+            .iter()
+            .map(|(name, val)| match ScalarValue::from_json(val) {
+                Some(val) => Ok((name.clone(), val)),
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("value of `{}` is not a number", name),
+                )),
+            })
+            .collect::<io::Result<Vec<_>>>()?,
+        None => vec![],
+    };
+    circuit.set_vals(vals)?;
+
+    let degree = params::degree_for_gates(circuit.padded_gates())?;
+    let pp = match pps.entry(degree) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(params::setup_seeded(degree, SEED)?),
+    };
+
+    // Each label is only leaked the first time it's run with, however many
+    // times the circuit is edited and run again.
+    let label = ProveOptions::for_circuit(&circuit).transcript();
+
+    let start = Instant::now();
+    let (pk, vd) = circuit.compile(pp)?;
+    let compile = start.elapsed();
+
+    let start = Instant::now();
+    let proof = circuit.prove(pp, &pk, label)?;
+    let prove = start.elapsed();
+
+    let pinputs: Vec<(String, BlsScalar)> = circuit
+        .public_inputs_named()
+        .map(|(name, value)| (name.to_owned(), value))
+        .collect();
+    let pinputs = circuit.public_inputs_from(pinputs)?;
+
+    let start = Instant::now();
+    let valid = match PlangCircuit::verify(pp, &vd, &proof, &pinputs, label) {
+        Ok(()) => true,
+        Err(PlonkError::ProofVerificationError) => false,
+        Err(err) => return Err(err.into()),
+    };
+    let verify = start.elapsed();
+
+    Ok(json!({
+        "valid": valid,
+        "timings": {
+            "compile": compile.as_secs_f64(),
+            "prove": prove.as_secs_f64(),
+            "verify": verify.as_secs_f64(),
+        },
+    }))
+}
+
+fn request_json(request: &mut Request) -> Result<Value> {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body)?;
+
+    let req = serde_json::from_str(&body)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok(req)
+}
+
+/// Turns the result of a route into a response, with errors as
+/// `{ error, code }`.
+fn reply(result: Result<Value>) -> (u16, &'static str, String) {
+    match result {
+        Ok(body) => (200, "application/json", body.to_string()),
+        Err(err) => {
+            let body = json!({ "error": err.to_string(), "code": err.code() });
+            (422, "application/json", body.to_string())
+        }
+    }
+}
+
+fn respond(request: Request, status: u16, content_type: &str, body: String) {
+    let content_type = Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes())
+        .expect("content type header is valid");
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type);
+
+    if let Err(err) = request.respond(response) {
+        warning!("failed to respond: {}", err);
+    }
+}
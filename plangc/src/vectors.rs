@@ -0,0 +1,262 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! `plangc test`: checking a circuit against the test vectors in its source,
+//! `plangc probe`: checking they notice mutations of the circuit, and
+//! `plangc coverage`: reporting the gates they exercise.
+
+use crate::Result;
+
+use plang::backend::SELECTORS;
+use plang::cache::KeyCache;
+use plang::scalar::Scalar;
+use plang::vector::TestVector;
+use plang::{params, AssignmentError, PlangCircuit, PlangError, ProveError, ProveOptions};
+
+use serde_json::json;
+use tracing::info_span;
+
+use plang::dusk_plonk::circuit::{Circuit, VerifierData};
+use plang::dusk_plonk::commitment_scheme::PublicParameters;
+use plang::dusk_plonk::error::Error as PlonkError;
+use plang::dusk_plonk::prelude::{BlsScalar, ProverKey};
+
+/// Seed the toy parameters are derived from. They're insecure, which is fine
+/// for proofs that are only ever made to be checked right away.
+const SEED: [u8; 32] = *b"plang test vectors toy params!!!";
+
+/// The keys test vectors are proven with, and the parameters they were
+/// compiled with.
+struct Keys {
+    pp: PublicParameters,
+    pk: ProverKey,
+    vd: VerifierData,
+}
+
+/// Runs every test vector of the circuit through the satisfaction checker,
+/// and, with `prove`, proves and verifies it as well, printing whether each
+/// passed. It returns an error if any failed.
+///
+/// Proofs are made with the given parameters, or with toy ones, and the keys
+/// compiled for them are kept in the cache, if any. Toy parameters are
+/// derived from a fixed seed, so their keys are cached as well.
+pub fn run(
+    circuit: &mut PlangCircuit,
+    prove: bool,
+    pp: Option<PublicParameters>,
+    cache: Option<KeyCache>,
+) -> Result<()> {
+    let tests = circuit.tests().to_vec();
+    if tests.is_empty() {
+        warning!("the circuit declares no test vectors");
+        return Ok(());
+    }
+
+    let keys = match prove {
+        true => Some(compile(circuit, pp, cache)?),
+        false => None,
+    };
+
+    let mut failed = 0;
+    for test in &tests {
+        let _span = info_span!("test", name = %test.name).entered();
+
+        let result = circuit.run_test(test).and_then(|_| match &keys {
+            Some(keys) => check_proof(circuit, keys, test),
+            None => Ok(()),
+        });
+        match result {
+            Ok(()) => status!("test {} ... ok", test.name),
+            Err(err) => {
+                println!("test {} ... FAILED", test.name);
+                println!("  {}", err);
+                failed += 1;
+            }
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        failed => Err(PlangError::Assignment(AssignmentError::FailedTests {
+            failed,
+            total: tests.len(),
+        })),
+    }
+}
+
+fn compile(
+    circuit: &mut PlangCircuit,
+    pp: Option<PublicParameters>,
+    cache: Option<KeyCache>,
+) -> Result<Keys> {
+    let pp = match pp {
+        Some(pp) => pp,
+        None => {
+            let degree = params::degree_for_gates(circuit.padded_gates())?;
+            params::setup_seeded(degree, SEED)?
+        }
+    };
+
+    let (pk, vd) = match cache {
+        Some(cache) => cache.compile(circuit, &pp)?,
+        None => info_span!("compile").in_scope(|| circuit.compile(&pp))?,
+    };
+
+    Ok(Keys { pp, pk, vd })
+}
+
+/// Proves the circuit with the values of the test vector, and verifies the
+/// proof, which has to verify only if the values are expected to satisfy the
+/// circuit.
+fn check_proof(circuit: &mut PlangCircuit, keys: &Keys, test: &TestVector) -> Result<()> {
+    let options = ProveOptions::for_circuit(circuit);
+
+    circuit.clear_secrets();
+    let values = test
+        .values
+        .iter()
+        .map(|(name, value)| (name.clone(), BlsScalar::from(*value)));
+    let verified = circuit.set_vals(values).and_then(|_| {
+        let proof = circuit.prove_with(&keys.pp, &keys.pk, &options)?;
+
+        let pinputs: Vec<(String, BlsScalar)> = circuit
+            .public_inputs_named()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect();
+        let pinputs = circuit.public_inputs_from(pinputs)?;
+
+        match PlangCircuit::verify_with(&keys.pp, &keys.vd, &proof, &pinputs, &options) {
+            Ok(()) => Ok(true),
+            Err(PlangError::Prove(ProveError::Plonk(PlonkError::ProofVerificationError))) => {
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    });
+    circuit.clear_secrets();
+
+    match (verified?, test.satisfied) {
+        (true, false) => Err(PlangError::Assignment(
+            AssignmentError::UnexpectedlySatisfied(test.name.clone()),
+        )),
+        (false, true) => Err(PlangError::Prove(ProveError::Plonk(
+            PlonkError::ProofVerificationError,
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Probes the circuit with mutants, checked against its test vectors and the
+/// given values, if any, which are expected not to satisfy it. It prints the
+/// mutants that survive.
+pub fn probe(circuit: &PlangCircuit, vals: Vec<(String, BlsScalar)>) -> Result<()> {
+    let mut tests = circuit.tests().to_vec();
+    if !vals.is_empty() {
+        tests.push(TestVector {
+            name: "--vals".to_owned(),
+            satisfied: false,
+            values: vals
+                .into_iter()
+                .map(|(name, value)| (name, Scalar::from(value)))
+                .collect(),
+            line: 0,
+        });
+    }
+
+    let report = info_span!("probe").in_scope(|| circuit.probe(&tests))?;
+    if report.vectors == 0 {
+        warning!("no test vectors expected not to satisfy the circuit, every mutant survives");
+    }
+
+    let mut survived = 0;
+    for mutant in report.survivors() {
+        println!("{}", mutant);
+        survived += 1;
+    }
+    status!(
+        "{} of {} mutants survived {} test vectors",
+        survived,
+        report.mutants.len(),
+        report.vectors
+    );
+
+    Ok(())
+}
+
+/// Reports the coverage of the circuit by its test vectors and the given
+/// values, if any, printing the gates and selectors never exercised, or the
+/// whole report as JSON.
+pub fn coverage(circuit: &PlangCircuit, vals: Vec<(String, BlsScalar)>, json: bool) -> Result<()> {
+    let mut assignments: Vec<Vec<(String, Scalar)>> = circuit
+        .tests()
+        .iter()
+        .map(|test| test.values.clone())
+        .collect();
+    if !vals.is_empty() {
+        assignments.push(
+            vals.into_iter()
+                .map(|(name, value)| (name, Scalar::from(value)))
+                .collect(),
+        );
+    }
+
+    let report = info_span!("coverage").in_scope(|| circuit.coverage(assignments))?;
+    let (total, exercised) = report.exercised();
+
+    if json {
+        let gates: Vec<_> = report
+            .gates
+            .iter()
+            .map(|gate| {
+                json!({
+                    "line": gate.line + 1,
+                    "equation": gate.text,
+                    "selectors": SELECTORS
+                        .iter()
+                        .zip(&gate.selectors)
+                        .filter_map(|(name, count)| count.map(|count| (name.to_string(), json!(count))))
+                        .collect::<serde_json::Map<_, _>>(),
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            json!({
+                "assignments": report.assignments,
+                "selectors": total,
+                "exercised": exercised,
+                "gates": gates,
+            })
+        );
+        return Ok(());
+    }
+
+    if report.assignments == 0 {
+        warning!("no test vectors nor values to cover the circuit with");
+    }
+    for gate in &report.gates {
+        if !gate.is_active() {
+            println!("line {}: `{}` is never active", gate.line + 1, gate.text);
+            continue;
+        }
+        for selector in gate.unexercised() {
+            println!(
+                "line {}: `{}`: {} is never exercised",
+                gate.line + 1,
+                gate.text,
+                selector
+            );
+        }
+    }
+    status!(
+        "{} of {} selectors exercised by {} assignments",
+        exercised,
+        total,
+        report.assignments
+    );
+
+    Ok(())
+}
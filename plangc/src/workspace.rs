@@ -0,0 +1,237 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::{parse_label, read_circuit, transcript_label, write_artifact, Config, Result};
+
+use plang::artifact::ArtifactKind;
+use plang::cache::KeyCache;
+use plang::{params, PlangError, PlangKeys};
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use tracing::info_span;
+
+use plang::dusk_plonk::circuit::Circuit;
+use plang::dusk_plonk::commitment_scheme::PublicParameters;
+
+/// The name of the manifest a build writes, next to the `plang.toml`.
+const MANIFEST: &str = "build.json";
+
+/// A circuit listed in a `plang.toml`, with its settings. Paths are kept as
+/// written, relative to the directory of the workspace.
+#[derive(Debug)]
+pub struct CircuitConfig {
+    /// The name of the circuit, recorded in its key archive.
+    pub name: String,
+    /// The source of the circuit.
+    pub source: String,
+    /// The public parameters to compile the circuit with, if not those of the
+    /// workspace.
+    pub params: Option<String>,
+    /// The transcript label to record in the key archive, if not the one
+    /// declared in the circuit.
+    pub label: Option<Vec<u8>>,
+    /// The file name of the keys, excluding their extensions.
+    pub output: String,
+}
+
+/// The circuits of a protocol, built together from a `plang.toml` of the
+/// form:
+///
+/// ```toml
+/// params = "params.pp"
+/// include = ["gadgets"]
+/// cache_dir = "target/keys"
+///
+/// [[circuit]]
+/// name = "transfer"
+/// source = "circuits/transfer.plang"
+/// label = "transfer"
+/// output = "keys/transfer"
+/// ```
+///
+/// Circuits look their imports up in the `include` directories, after those
+/// given with `--include-path` or in `PLANG_INCLUDE_PATH`. Each circuit can
+/// name its own `params`. Its `label` defaults to the one declared in it, and
+/// its `output` to its source without the extension. Keys are compiled through
+/// the cache in `cache_dir`, if there is one.
+#[derive(Debug)]
+pub struct Workspace {
+    /// The directory of the `plang.toml`, paths in it are relative to.
+    pub dir: PathBuf,
+    /// The public parameters shared by the circuits.
+    pub params: Option<String>,
+    /// The directories the circuits look their imports up in.
+    pub include: Vec<String>,
+    /// The directory compiled keys are cached in.
+    pub cache_dir: Option<String>,
+    /// The circuits, in the order they're listed in.
+    pub circuits: Vec<CircuitConfig>,
+}
+
+impl Workspace {
+    /// Reads a workspace from its `plang.toml`.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let invalid = |msg: String| {
+            PlangError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid {}: {}", path.display(), msg),
+            ))
+        };
+
+        let toml: toml::Value =
+            toml::from_slice(&fs::read(path)?).map_err(|err| invalid(err.to_string()))?;
+        let string = |value: &toml::Value, field: &str| -> Result<Option<String>> {
+            match value.get(field) {
+                None => Ok(None),
+                Some(toml::Value::String(text)) => Ok(Some(text.clone())),
+                Some(_) => Err(invalid(format!("`{}` is not a string", field))),
+            }
+        };
+
+        let params = string(&toml, "params")?;
+        let cache_dir = string(&toml, "cache_dir")?;
+        let include = match toml.get("include") {
+            None => vec![],
+            Some(toml::Value::Array(dirs)) => dirs
+                .iter()
+                .map(|dir| match dir {
+                    toml::Value::String(dir) => Ok(dir.clone()),
+                    _ => Err(invalid("`include` is not a list of strings".into())),
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => return Err(invalid("`include` is not a list of strings".into())),
+        };
+        let circuits = match toml.get("circuit") {
+            None => vec![],
+            Some(toml::Value::Array(circuits)) => circuits
+                .iter()
+                .map(|circuit| {
+                    let source = string(circuit, "source")?
+                        .ok_or_else(|| invalid("a circuit has no `source`".into()))?;
+                    let stem = Path::new(&source).with_extension("");
+                    let label = string(circuit, "label")?
+                        .map(|label| parse_label(&label))
+                        .transpose()
+                        .map_err(|err| invalid(format!("invalid `label`: {}", err)))?;
+
+                    Ok(CircuitConfig {
+                        name: string(circuit, "name")?.unwrap_or_else(|| {
+                            stem.file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or_default()
+                        }),
+                        params: string(circuit, "params")?,
+                        label,
+                        output: string(circuit, "output")?
+                            .unwrap_or_else(|| stem.to_string_lossy().into_owned()),
+                        source,
+                    })
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => return Err(invalid("`circuit` is not a list of tables".into())),
+        };
+
+        Ok(Self {
+            dir: path.parent().unwrap_or_else(|| Path::new("")).to_owned(),
+            params,
+            include,
+            cache_dir,
+            circuits,
+        })
+    }
+
+    /// Compiles every circuit of the workspace, reading parameters shared
+    /// between circuits only once, and writes the prover key and verifier
+    /// data of each, along with a key archive of the verifier data recording
+    /// the label of the circuit. It then writes a build manifest listing them,
+    /// which `plangc repro` checks the keys against.
+    pub fn build(&self, opts: &Config) -> Result<()> {
+        let mut opts = opts.clone();
+        for dir in &self.include {
+            opts.parse.search.include(self.dir.join(dir))?;
+        }
+
+        let cache = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| KeyCache::new(self.dir.join(dir)));
+
+        let mut pps: HashMap<&str, PublicParameters> = HashMap::new();
+        let mut entries = vec![];
+
+        for config in &self.circuits {
+            let _span = info_span!("build", circuit = %config.name).entered();
+
+            let params = config
+                .params
+                .as_deref()
+                .or(self.params.as_deref())
+                .ok_or_else(|| {
+                    PlangError::Io(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("no `params` to compile `{}` with", config.name),
+                    ))
+                })?;
+            if !pps.contains_key(params) {
+                let (pp, _) = params::read_file(self.dir.join(params))?;
+                pps.insert(params, pp);
+            }
+            let pp = &pps[params];
+
+            let mut circuit = read_circuit(self.dir.join(&config.source), &opts)?;
+            let mut metadata = circuit.metadata().clone();
+            metadata.name = Some(config.name.clone());
+            circuit = circuit.with_metadata(metadata);
+
+            params::check_degree(pp, &circuit)?;
+            let (pk, vd) = match &cache {
+                Some(cache) => cache.compile(&mut circuit, pp)?,
+                None => info_span!("compile").in_scope(|| circuit.compile(pp))?,
+            };
+
+            let out = self.dir.join(&config.output);
+            write_artifact(
+                out.with_extension("pk"),
+                ArtifactKind::ProverKey,
+                0,
+                &pk.to_var_bytes(),
+            )?;
+            write_artifact(
+                out.with_extension("vd"),
+                ArtifactKind::VerifierData,
+                0,
+                &vd.to_var_bytes(),
+            )?;
+
+            let label = transcript_label(config.label.clone(), None, &circuit);
+            let keys = PlangKeys::new(&circuit, pp, None, vd, label);
+            fs::write(out.with_extension("plangkeys"), keys.to_bytes())?;
+
+            status!("built {} into {}", config.name, out.display());
+
+            let output = Path::new(&config.output);
+            entries.push(json!({
+                "source": config.source,
+                "params": params,
+                "pk": output.with_extension("pk"),
+                "vd": output.with_extension("vd"),
+            }));
+        }
+
+        let mut manifest = json!({ "circuits": entries });
+        if let Some(params) = &self.params {
+            manifest["params"] = Value::from(params.as_str());
+        }
+        fs::write(self.dir.join(MANIFEST), format!("{:#}\n", manifest))?;
+
+        Ok(())
+    }
+}
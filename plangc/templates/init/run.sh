@@ -0,0 +1,18 @@
+#!/bin/sh
+# Compiles {name}.plang, proves it with the values in witness.toml, and
+# verifies the proof.
+#
+# The public parameters generated here are large enough for circuits of up to
+# 1024 gates, and derived from a fixed seed, and so INSECURE. Replace params.pp
+# with the output of a trusted setup before proving anything that matters.
+set -e
+cd "$(dirname "$0")"
+
+if [ ! -f params.pp ]; then
+    plangc generate-params --seed 00 --max-gates 1024 --output params.pp
+fi
+
+plangc build
+plangc prove --params params.pp --key {name}.pk --witness witness.toml --envelope {name}.plang
+plangc verify --params params.pp --vdata {name}.vd --envelope {name}.envelope {name}.plang
+echo "{name}: proof verified"
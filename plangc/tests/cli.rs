@@ -0,0 +1,1516 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+use std::{env, fs, process, thread};
+
+use plang::{artifact, params};
+
+// The configuration of the environment the tests are run in is left out
+fn command(args: &[&str], dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_plangc"));
+    command.args(args).current_dir(dir);
+    for var in ["PLANG_PARAMS", "PLANG_CACHE_DIR", "PLANG_INCLUDE_PATH"] {
+        command.env_remove(var);
+    }
+    command
+}
+
+fn plangc(args: &[&str], dir: &Path) -> Output {
+    command(args, dir).output().expect("plangc can be run")
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("plangc-{}-{}", name, process::id()));
+    fs::create_dir_all(&dir).expect("temporary directory can be created");
+    dir
+}
+
+#[test]
+fn generates_params_of_a_degree() {
+    let dir = temp_dir("degree");
+
+    for (args, degree) in [
+        (&["--degree", "16"][..], 16),
+        (&["--degree", "2^5"][..], 32),
+        (&["--max-gates", "5"][..], 16),
+    ] {
+        let mut command = vec!["generate-params"];
+        command.extend(args);
+        command.extend(["--seed", "01", "-o", "out.pp"]);
+        let output = plangc(&command, &dir);
+        assert!(output.status.success(), "{:?}", output);
+
+        let (pp, _) = params::read_file(dir.join("out.pp")).expect("parameters can be read");
+        assert_eq!(params::max_degree(&pp), degree);
+    }
+
+    // Without a circuit or an output, they're written to `plang.pp`
+    let output = plangc(
+        &["generate-params", "--degree", "2^3", "--seed", "01"],
+        &dir,
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(dir.join("plang.pp").exists());
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn rejects_invalid_degrees() {
+    let dir = temp_dir("invalid-degree");
+    fs::write(dir.join("circuit.plang"), "a + b = c\n").expect("circuit can be written");
+
+    for args in [
+        &["--degree", "2^64"][..],
+        &["--degree", "2^x"][..],
+        &["--degree", "-1"][..],
+        &["--degree", "16", "--max-gates", "5"][..],
+        &["circuit.plang", "--degree", "16"][..],
+        &["circuit.plang", "--max-gates", "5"][..],
+        &[][..],
+    ] {
+        let mut command = vec!["generate-params"];
+        command.extend(args);
+        let output = plangc(&command, &dir);
+        assert_eq!(output.status.code(), Some(1), "{:?}: {:?}", args, output);
+    }
+    assert!(!dir.join("plang.pp").exists());
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn verifies_with_public_inputs_from_json() {
+    let dir = temp_dir("public-inputs");
+    fs::write(dir.join("circuit.plang"), "a + b = c\na * b = d\n").expect("circuit can be written");
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    fs::write(dir.join("witness.json"), r#"{ "a": 3, "b": "0x4" }"#)
+        .expect("witness can be written");
+    let prove = [
+        "prove",
+        "circuit.plang",
+        "--params",
+        "circuit.pp",
+        "--witness",
+        "witness.json",
+        "--vals",
+        "c=7",
+        "--vals",
+        "d=12",
+    ];
+    let output = plangc(&prove, &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    // Public inputs are integers, or numbers in decimal or hex as strings
+    let verify = |inputs: &str| {
+        fs::write(dir.join("pi.json"), inputs).expect("public inputs can be written");
+        let args = [
+            "verify",
+            "circuit.plang",
+            "--params",
+            "circuit.pp",
+            "--proof",
+            "circuit.proof",
+            "--public-inputs",
+            "pi.json",
+        ];
+        plangc(&args, &dir).status.code()
+    };
+    assert_eq!(verify(r#"{ "c": 7, "d": 12 }"#), Some(0));
+    assert_eq!(verify(r#"{ "c": "7", "d": "0xc" }"#), Some(0));
+    assert_eq!(verify(r#"{ "d": "0x0c", "c": "0x7" }"#), Some(0));
+    assert_eq!(verify(r#"{ "c": "0x7", "d": "13" }"#), Some(5));
+    assert_eq!(verify(r#"{ "c": "seven", "d": 12 }"#), Some(8));
+    assert_eq!(verify(r#"{ "c": 7.5, "d": 12 }"#), Some(8));
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn verifies_envelopes_with_the_expected_transcript() {
+    let dir = temp_dir("envelope-transcript");
+    fs::write(dir.join("circuit.plang"), "a + b = c\n").expect("circuit can be written");
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let prove = [
+        "prove",
+        "circuit.plang",
+        "--params",
+        "circuit.pp",
+        "--envelope",
+        "--label",
+        "mine",
+        "--vals",
+        "a=1",
+        "--vals",
+        "b=2",
+        "--vals",
+        "c=3",
+    ];
+    let output = plangc(&prove, &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    // The envelope doesn't pick the transcript it's verified with
+    let verify = |args: &[&str]| {
+        let mut command = vec![
+            "verify",
+            "circuit.plang",
+            "--params",
+            "circuit.pp",
+            "--envelope",
+            "circuit.envelope",
+        ];
+        command.extend(args);
+        plangc(&command, &dir).status.code()
+    };
+    assert_eq!(verify(&["--label", "mine"]), Some(0));
+    assert_eq!(verify(&[]), Some(7));
+    assert_eq!(verify(&["--label", "other"]), Some(7));
+    assert_eq!(verify(&["--label", "mine", "--domain", "app"]), Some(7));
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn inspects_key_archives() {
+    let dir = temp_dir("inspect-keys");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let compile = ["compile", "circuit.plang", "--params", "circuit.pp"];
+    let output = plangc(&compile, &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(
+        &[&compile[..], &["--archive", "--label", "mine"]].concat(),
+        &dir,
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = plangc(&["hash", "circuit.plang"], &dir);
+    let hash = String::from_utf8(output.stdout).expect("the hash is printed");
+
+    let args = ["inspect-keys", "--keys", "circuit.plangkeys", "--json"];
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let info: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("the information is printed as JSON");
+    let keys = &info["keys"];
+    assert_eq!(keys["circuit_hash"], hash.trim());
+    assert_eq!(keys["circuit_id"], hash.trim());
+    assert_eq!(keys["label"], "mine");
+    assert_eq!(keys["prover_key"], true);
+    assert_eq!(keys["public_inputs"], 1);
+
+    // Bare keys are inspected alongside, and the text output names the archive's
+    let args = [
+        "inspect-keys",
+        "--pk",
+        "circuit.pk",
+        "--vd",
+        "circuit.vd",
+        "--keys",
+        "circuit.plangkeys",
+    ];
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let text = String::from_utf8(output.stdout).expect("the information is printed");
+    assert!(text.contains("prover key circuit.pk"), "{}", text);
+    assert!(text.contains("verifier data circuit.vd"), "{}", text);
+    assert!(
+        text.contains(&format!("circuit hash: {}", hash.trim())),
+        "{}",
+        text
+    );
+    assert!(text.contains("transcript label: mine"), "{}", text);
+
+    // Something else than an archive is rejected as one
+    let output = plangc(&["inspect-keys", "--keys", "circuit.vd"], &dir);
+    assert!(!output.status.success(), "{:?}", output);
+    let output = plangc(&["inspect-keys"], &dir);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn benches_circuits_with_imports() {
+    let dir = temp_dir("bench");
+    fs::write(dir.join("product.plang"), "a * b = c\n").expect("circuit can be written");
+    fs::write(
+        dir.join("circuit.plang"),
+        "import \"product.plang\" as p\np::c + d = e\n",
+    )
+    .expect("circuit can be written");
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let mut args = vec!["bench", "circuit.plang", "--params", "circuit.pp"];
+    args.extend(["-n", "2", "--json"]);
+    for vals in ["p::a=3", "p::b=5", "p::c=15", "d=1", "e=16"] {
+        args.extend(["--vals", vals]);
+    }
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("the report is printed as JSON");
+    assert_eq!(report["iterations"], 2);
+    assert_eq!(report["verified"], true);
+    for phase in ["parse", "compile", "prove", "verify"] {
+        assert!(report["phases"][phase]["mean_ns"].is_u64(), "{}", report);
+    }
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn validates_params() {
+    let dir = temp_dir("validate-params");
+    fs::write(dir.join("small.plang"), "a + b = c\n").expect("circuit can be written");
+    let large: String = (0..20)
+        .map(|i| format!("a{0} * b{0} = c{0}\n", i))
+        .collect();
+    fs::write(dir.join("large.plang"), large).expect("circuit can be written");
+
+    let args = [
+        "generate-params",
+        "--degree",
+        "16",
+        "--seed",
+        "01",
+        "-o",
+        "p.pp",
+    ];
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = plangc(&["params", "validate", "p.pp"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("maximum degree 16, supporting circuits of up to 8 gates"),
+        "{}",
+        stdout
+    );
+    // Seeded parameters are flagged as such
+    assert!(String::from_utf8_lossy(&output.stderr).contains("INSECURE"));
+
+    let output = plangc(&["params", "validate", "p.pp", "-c", "small.plang"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("parameters are large enough"));
+
+    // Parameters too small for the circuit are a mismatch, and invalid ones an artifact error
+    let output = plangc(&["params", "validate", "p.pp", "-c", "large.plang"], &dir);
+    assert_eq!(output.status.code(), Some(7), "{:?}", output);
+    let bytes = fs::read(dir.join("p.pp")).expect("parameters can be read");
+    fs::write(dir.join("truncated.pp"), &bytes[..bytes.len() / 2]).expect("file can be written");
+    let output = plangc(&["params", "validate", "truncated.pp"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+    let output = plangc(&["params", "validate", "missing.pp"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn discovers_shared_params() {
+    let dir = temp_dir("shared-params");
+    fs::write(dir.join("circuit.plang"), "a * b = c\na + b = d\n").expect("circuit can be written");
+
+    let args = [
+        "generate-params",
+        "--degree",
+        "2^6",
+        "--seed",
+        "01",
+        "-o",
+        "shared.pp",
+    ];
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let args = [
+        "generate-params",
+        "--degree",
+        "2",
+        "--seed",
+        "01",
+        "-o",
+        "tiny.pp",
+    ];
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let with_params = |args: &[&str], params: &str| {
+        command(args, &dir)
+            .env("PLANG_PARAMS", params)
+            .output()
+            .expect("plangc can be run")
+    };
+
+    // The shared parameters are used by every command, without random ones
+    let output = with_params(&["compile", "circuit.plang"], "shared.pp");
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("random"));
+    assert!(dir.join("circuit.pk").exists() && dir.join("circuit.vd").exists());
+
+    let prove = [
+        "prove",
+        "circuit.plang",
+        "--vals",
+        "a=3",
+        "--vals",
+        "b=5",
+        "--vals",
+        "c=15",
+        "--vals",
+        "d=8",
+    ];
+    let output = with_params(&prove, "shared.pp");
+    assert!(output.status.success(), "{:?}", output);
+    let verify = [
+        "verify",
+        "circuit.plang",
+        "--proof",
+        "circuit.proof",
+        "--vals",
+        "c=15",
+        "--vals",
+        "d=8",
+    ];
+    let output = with_params(&verify, "shared.pp");
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!dir.join("circuit.pp").exists());
+
+    // Parameters given on the command line take precedence, and too small ones are rejected
+    let output = with_params(
+        &["compile", "circuit.plang", "-p", "missing.pp"],
+        "shared.pp",
+    );
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+    let output = with_params(&["compile", "circuit.plang"], "tiny.pp");
+    assert_eq!(output.status.code(), Some(7), "{:?}", output);
+
+    // Without any, compiling falls back to random parameters with a warning
+    let output = plangc(&["compile", "circuit.plang"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("insecure random parameters"));
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn compiles_keys_separately() {
+    let dir = temp_dir("separate-keys");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let compile = |args: &[&str]| {
+        for file in ["circuit.pk", "circuit.vd", "keys.pk", "keys.vd"] {
+            let _ = fs::remove_file(dir.join(file));
+        }
+        let command = [&["compile", "circuit.plang", "-p", "circuit.pp"][..], args].concat();
+        plangc(&command, &dir)
+    };
+    let written = |file: &str| dir.join(file).exists();
+
+    let output = compile(&["--pk-only"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(written("circuit.pk") && !written("circuit.vd"));
+
+    let output = compile(&["--vd-only"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!written("circuit.pk") && written("circuit.vd"));
+
+    let output = compile(&["--pk-output", "keys.pk", "--vd-output", "keys.vd"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(written("keys.pk") && written("keys.vd"));
+    assert!(!written("circuit.pk") && !written("circuit.vd"));
+
+    // The keys written separately are the same as those written together
+    let output = compile(&["--vd-output", "keys.vd"]);
+    assert!(output.status.success(), "{:?}", output);
+    let together = fs::read(dir.join("keys.vd")).expect("verifier data can be read");
+    let output = compile(&["--vd-only", "--vd-output", "keys.vd"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(fs::read(dir.join("keys.vd")).ok(), Some(together));
+
+    // Verifier archives leave the prover key out
+    let output = compile(&["--archive", "--vd-only"]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(
+        &["inspect-keys", "--keys", "circuit.plangkeys", "--json"],
+        &dir,
+    );
+    let info: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("the information is printed as JSON");
+    assert_eq!(info["keys"]["prover_key"], false);
+
+    assert_eq!(compile(&["--pk-only", "--vd-only"]).status.code(), Some(1));
+    assert_eq!(
+        compile(&["--archive", "--pk-output", "keys.pk"])
+            .status
+            .code(),
+        Some(1)
+    );
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn verifies_keys_against_circuits() {
+    let dir = temp_dir("verify-keys");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+
+    let output = plangc(&["generate-params", "--degree", "16", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(&["compile", "circuit.plang", "-p", "plang.pp"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let verify_keys = |args: &[&str]| {
+        let command = [
+            &["verify-keys", "circuit.plang", "-p", "plang.pp"][..],
+            args,
+        ]
+        .concat();
+        plangc(&command, &dir)
+    };
+    let both = ["--pk", "circuit.pk", "--vd", "circuit.vd"];
+
+    let output = verify_keys(&both);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("prover key circuit.pk: up to date"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("verifier data circuit.vd: up to date"),
+        "{}",
+        stdout
+    );
+
+    // Keys compiled from an older version of the circuit drifted from it
+    fs::write(dir.join("circuit.plang"), "a * b = c\na + b = d\n").expect("circuit can be written");
+    let output = verify_keys(&both);
+    assert_eq!(output.status.code(), Some(7), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("verifier data circuit.vd: does not match"),
+        "{}",
+        stderr
+    );
+    assert_eq!(verify_keys(&["--vd", "circuit.vd"]).status.code(), Some(7));
+
+    assert_eq!(verify_keys(&[]).status.code(), Some(1));
+    assert_eq!(verify_keys(&["--vd", "missing.vd"]).status.code(), Some(8));
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn hashes_circuits() {
+    let dir = temp_dir("hash");
+    fs::write(dir.join("circuit.plang"), "a * b = c\na + b = d\n").expect("circuit can be written");
+    fs::write(
+        dir.join("formatted.plang"),
+        "# The same equations\na*b   = c\n\na +  b = d # formatted otherwise\n",
+    )
+    .expect("circuit can be written");
+    fs::write(dir.join("other.plang"), "a * b = c\na + 2*b = d\n").expect("circuit can be written");
+
+    let hash = |args: &[&str]| {
+        let output = plangc(&[&["hash"][..], args].concat(), &dir);
+        assert!(output.status.success(), "{:?}", output);
+        output.stdout
+    };
+
+    let hex = String::from_utf8(hash(&["circuit.plang"])).expect("the hash is hex encoded");
+    assert_eq!(hex.trim().len(), 64);
+    assert_eq!(hash(&["circuit.plang", "--hex"]), hex.as_bytes());
+    assert_eq!(
+        hex::encode(hash(&["circuit.plang", "--raw"])),
+        hex.trim(),
+        "the raw hash is the bytes of the hex encoded one"
+    );
+
+    // Formatting and comments make no difference, but the equations do
+    assert_eq!(hash(&["formatted.plang"]), hex.as_bytes());
+    assert_ne!(hash(&["other.plang"]), hex.as_bytes());
+
+    let output = plangc(&["hash", "circuit.plang", "--raw", "--hex"], &dir);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn logs_spans_when_verbose() {
+    let dir = temp_dir("verbose");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+
+    let stderr = |args: &[&str]| {
+        let output = plangc(args, &dir);
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stderr).expect("the log is text")
+    };
+
+    assert_eq!(stderr(&["hash", "circuit.plang"]), "");
+
+    // Spans are logged when they close, with how long they took
+    let info = stderr(&["-v", "hash", "circuit.plang"]);
+    assert!(info.contains("parse") && info.contains("close"), "{}", info);
+    assert!(!info.contains("grammar"), "{}", info);
+    let debug = stderr(&["-vv", "hash", "circuit.plang"]);
+    assert!(debug.contains("grammar"), "{}", debug);
+
+    let output = plangc(&["-v", "-q", "hash", "circuit.plang"], &dir);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn exits_with_the_code_of_the_error() {
+    let dir = temp_dir("exit-codes");
+    for (file, circuit) in [
+        ("circuit.plang", "a * b = c\n"),
+        ("syntax.plang", "a + = b\n"),
+        ("invalid.plang", "a*a = c\n"),
+        (
+            "failing.plang",
+            "a * b = c\ntest satisfied \"t\" { a = 1; b = 2; c = 3; }\n",
+        ),
+        ("warning.plang", "0*a + b = c\n"),
+    ] {
+        fs::write(dir.join(file), circuit).expect("circuit can be written");
+    }
+
+    let code = |args: &[&str]| plangc(args, &dir).status.code();
+    assert_eq!(code(&["hash", "circuit.plang"]), Some(0));
+    assert_eq!(code(&["nonsense"]), Some(1));
+    assert_eq!(
+        code(&["--deny", "nonsense", "hash", "circuit.plang"]),
+        Some(1)
+    );
+    assert_eq!(code(&["explain", "P9999"]), Some(1));
+    assert_eq!(code(&["hash", "syntax.plang"]), Some(2));
+    assert_eq!(code(&["hash", "invalid.plang"]), Some(3));
+    assert_eq!(code(&["--strict", "hash", "warning.plang"]), Some(3));
+    assert_eq!(code(&["probe", "circuit.plang", "--vals", "z=1"]), Some(6));
+    assert_eq!(code(&["hash", "missing.plang"]), Some(8));
+    assert_eq!(code(&["test", "failing.plang"]), Some(9));
+
+    // Errors name their code, for `explain`
+    let output = plangc(&["hash", "invalid.plang"], &dir);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error[P0004]"), "{}", stderr);
+    assert!(stderr.contains("plangc explain P0004"), "{}", stderr);
+
+    // Quiet mode leaves the output of the command, and errors, but not warnings
+    let output = plangc(&["hash", "warning.plang"], &dir);
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("warning: "));
+    let quiet = plangc(&["-q", "hash", "warning.plang"], &dir);
+    assert!(quiet.status.success(), "{:?}", quiet);
+    assert_eq!(quiet.stdout, output.stdout);
+    assert!(quiet.stderr.is_empty(), "{:?}", quiet);
+    let quiet = plangc(&["-q", "hash", "syntax.plang"], &dir);
+    assert!(String::from_utf8_lossy(&quiet.stderr).contains("error[P0001]"));
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+// Sends a request to a server on the port, retrying until it's listening
+fn request(port: u16, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+    let start = Instant::now();
+    let mut stream = loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => break stream,
+            Err(_) if start.elapsed() < Duration::from_secs(10) => {
+                thread::sleep(Duration::from_millis(50))
+            }
+            Err(err) => panic!("the server isn't listening: {}", err),
+        }
+    };
+    write!(
+        stream,
+        "{} {} HTTP/1.0\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    )
+    .expect("the request can be sent");
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .expect("the response can be read");
+    let status = response[9..12].parse().expect("the response has a status");
+    let (_, body) = response
+        .split_once("\r\n\r\n")
+        .expect("the response has a body");
+    (status, serde_json::from_str(body).unwrap_or_default())
+}
+
+#[test]
+fn serves_the_playground() {
+    let dir = temp_dir("playground");
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("a port is free")
+        .port();
+    let listen = format!("127.0.0.1:{}", port);
+    let mut server = command(&["playground", "--listen", &listen], &dir)
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("plangc can be run");
+
+    let (status, examples) = request(port, "GET", "/examples", "");
+    assert_eq!(status, 200);
+    let examples = examples
+        .as_array()
+        .expect("the examples are listed")
+        .clone();
+    assert!(examples.iter().any(|example| example["name"] == "multiply"));
+
+    let (status, checked) = request(port, "POST", "/check", r#"{ "circuit": "a * b = c\n" }"#);
+    assert_eq!(status, 200);
+    assert_eq!(checked["diagnostics"], serde_json::json!([]));
+    assert_eq!(checked["stats"]["publicInputs"], serde_json::json!(["c"]));
+    let (_, checked) = request(port, "POST", "/check", r#"{ "circuit": "a + = b\n" }"#);
+    assert_eq!(checked["stats"], serde_json::Value::Null);
+    assert_eq!(checked["diagnostics"][0]["line"], 0);
+
+    // Every example is satisfied by the values it comes with
+    for example in &examples {
+        let (status, ran) = request(port, "POST", "/run", &example.to_string());
+        assert_eq!(status, 200, "{}: {}", example["name"], ran);
+        assert_eq!(ran["valid"], true, "{}", example["name"]);
+    }
+    let run = r#"{ "circuit": "a * b = c\n", "vals": { "a": 2, "b": "two" } }"#;
+    let (status, ran) = request(port, "POST", "/run", run);
+    assert_eq!(status, 422);
+    assert!(ran["error"].is_string());
+
+    server.kill().expect("the server can be stopped");
+    let _ = server.wait();
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn reads_params_through_a_mapping() {
+    let dir = temp_dir("mapped-params");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+
+    let args = [
+        "generate-params",
+        "--degree",
+        "16",
+        "--seed",
+        "01",
+        "-o",
+        "p.pp",
+    ];
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let bytes = fs::read(dir.join("p.pp")).expect("parameters can be read");
+
+    // Parameters written before artifacts had a header are read as well
+    fs::write(dir.join("raw.pp"), &bytes[artifact::HEADER_SIZE..]).expect("file can be written");
+    let output = plangc(&["params", "validate", "raw.pp"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("maximum degree 16"));
+    let output = plangc(&["compile", "circuit.plang", "-p", "raw.pp"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    // The mapped file is checked against its checksum before it's deserialized
+    let mut corrupt = bytes.clone();
+    *corrupt.last_mut().expect("parameters aren't empty") ^= 1;
+    fs::write(dir.join("corrupt.pp"), corrupt).expect("file can be written");
+    let output = plangc(&["params", "validate", "corrupt.pp"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+    let output = plangc(&["compile", "circuit.plang", "-p", "corrupt.pp"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn scaffolds_projects() {
+    let dir = temp_dir("init");
+    let _ = fs::remove_dir_all(dir.join("demo"));
+
+    let output = plangc(&["init", "demo"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let project = dir.join("demo");
+    for file in ["demo.plang", "witness.toml", "plang.toml", "run.sh"] {
+        assert!(project.join(file).exists(), "{} is created", file);
+    }
+    let circuit = fs::read_to_string(project.join("demo.plang")).expect("circuit can be read");
+    assert!(circuit.contains("transcript \"demo\""), "{}", circuit);
+
+    // The example circuit passes its own test vectors
+    let output = plangc(&["test", "demo/demo.plang"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    // The script runs the whole workflow with the plangc being tested
+    #[cfg(unix)]
+    {
+        let bin = Path::new(env!("CARGO_BIN_EXE_plangc"))
+            .parent()
+            .expect("plangc is in a directory");
+        let path = env::join_paths(
+            Some(bin.to_owned())
+                .into_iter()
+                .chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+        )
+        .expect("the path can be joined");
+        let output = Command::new(project.join("run.sh"))
+            .env("PATH", path)
+            .env_remove("PLANG_PARAMS")
+            .env_remove("PLANG_CACHE_DIR")
+            .output()
+            .expect("the script can be run");
+        assert!(output.status.success(), "{:?}", output);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("demo: proof verified"));
+    }
+
+    // Nothing is overwritten, and projects are named after their directory
+    let output = plangc(&["init", "demo"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+    let output = plangc(&["init", "bad name"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+    assert!(!dir.join("bad name").exists());
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn generates_gadget_skeletons() {
+    let dir = temp_dir("new-gadget");
+    let _ = fs::remove_file(dir.join("range64.plang"));
+
+    let args = ["new-gadget", "range64", "--param", "N=64", "--param", "M=8"];
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let gadget = fs::read_to_string(dir.join("range64.plang")).expect("gadget can be read");
+    assert!(gadget.starts_with("# range64\n"), "{}", gadget);
+    assert!(gadget.contains("#   N = 64 - "), "{}", gadget);
+    assert!(gadget.contains("const N = 64\nconst M = 8\n"), "{}", gadget);
+
+    // The skeleton parses, and its test vectors pass, before it's filled in
+    let output = plangc(&["--strict", "test", "range64.plang"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = plangc(&["new-gadget", "plain", "-o", "gadgets.plang"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let gadget = fs::read_to_string(dir.join("gadgets.plang")).expect("gadget can be read");
+    assert!(gadget.contains("#   none - "), "{}", gadget);
+    assert!(!gadget.contains("\nconst "), "{}", gadget);
+
+    // Nothing is overwritten, and names are checked
+    for args in [
+        &["new-gadget", "range64"][..],
+        &["new-gadget", "bad name"][..],
+        &["new-gadget", "range32", "--param", "1N=64"][..],
+    ] {
+        let output = plangc(args, &dir);
+        assert_eq!(output.status.code(), Some(8), "{:?}: {:?}", args, output);
+    }
+    assert!(!dir.join("range32.plang").exists());
+    assert_eq!(
+        plangc(&["new-gadget", "range32", "--param", "N"], &dir)
+            .status
+            .code(),
+        Some(1)
+    );
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn builds_workspaces() {
+    let dir = temp_dir("build");
+    fs::create_dir_all(dir.join("gadgets")).expect("directory can be created");
+    fs::write(dir.join("gadgets/product.plang"), "a * b = c\n").expect("gadget can be written");
+    fs::write(
+        dir.join("transfer.plang"),
+        "import \"product.plang\" as p\np::c + d = e\n",
+    )
+    .expect("circuit can be written");
+    fs::write(dir.join("mint.plang"), "transcript \"mint\"\na + b = c\n")
+        .expect("circuit can be written");
+    fs::write(
+        dir.join("plang.toml"),
+        r#"
+params = "shared.pp"
+include = ["gadgets"]
+
+[[circuit]]
+name = "transfer"
+source = "transfer.plang"
+label = "transfer-v1"
+output = "transfer-keys"
+
+[[circuit]]
+name = "mint"
+source = "mint.plang"
+params = "mint.pp"
+"#,
+    )
+    .expect("workspace can be written");
+
+    for (degree, out) in [("2^6", "shared.pp"), ("16", "mint.pp")] {
+        let args = [
+            "generate-params",
+            "--degree",
+            degree,
+            "--seed",
+            "01",
+            "-o",
+            out,
+        ];
+        let output = plangc(&args, &dir);
+        assert!(output.status.success(), "{:?}", output);
+    }
+
+    let output = plangc(&["build"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    for file in ["transfer-keys.pk", "transfer-keys.vd", "mint.pk", "mint.vd"] {
+        assert!(dir.join(file).exists(), "{} is built", file);
+    }
+
+    let manifest = fs::read(dir.join("build.json")).expect("the manifest is written");
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&manifest).expect("the manifest is JSON");
+    assert_eq!(manifest["params"], "shared.pp");
+    assert_eq!(manifest["circuits"][0]["source"], "transfer.plang");
+    assert_eq!(manifest["circuits"][0]["vd"], "transfer-keys.vd");
+    assert_eq!(manifest["circuits"][1]["params"], "mint.pp");
+
+    // Each archive records the label of its circuit
+    for (keys, label) in [
+        ("transfer-keys.plangkeys", "transfer-v1"),
+        ("mint.plangkeys", "mint"),
+    ] {
+        let output = plangc(&["inspect-keys", "--keys", keys, "--json"], &dir);
+        let info: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("the information is printed as JSON");
+        assert_eq!(info["keys"]["label"], label);
+    }
+
+    // The manifest reproduces, until a circuit changes
+    let output = plangc(&["repro", "build.json"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    fs::write(dir.join("mint.plang"), "transcript \"mint\"\na + 2*b = c\n")
+        .expect("circuit can be written");
+    let output = plangc(&["repro", "build.json"], &dir);
+    assert_eq!(output.status.code(), Some(7), "{:?}", output);
+
+    // Circuits without parameters aren't built
+    fs::write(
+        dir.join("plang.toml"),
+        "[[circuit]]\nname = \"mint\"\nsource = \"mint.plang\"\n",
+    )
+    .expect("workspace can be written");
+    let output = plangc(&["build"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn verifies_batches_with_reports() {
+    let dir = temp_dir("batch");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+    for sub in ["proofs", "inputs"] {
+        fs::create_dir_all(dir.join(sub)).expect("directory can be created");
+    }
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(&["compile", "circuit.plang", "-p", "circuit.pp"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let prove = |vals: [&str; 3], args: &[&str]| {
+        let mut command = vec!["prove", "circuit.plang", "-p", "circuit.pp"];
+        for val in &vals {
+            command.extend(["--vals", val]);
+        }
+        command.extend(args);
+        let output = plangc(&command, &dir);
+        assert!(output.status.success(), "{:?}", output);
+    };
+    prove(["a=3", "b=5", "c=15"], &["-o", "proofs/one.proof"]);
+    prove(
+        ["a=2", "b=2", "c=4"],
+        &["--envelope", "-o", "proofs/two.envelope"],
+    );
+    fs::write(dir.join("inputs/one.json"), r#"{ "c": 15 }"#).expect("inputs can be written");
+
+    let verify = |args: &[&str]| {
+        let mut command = vec!["verify", "circuit.plang", "--params", "circuit.pp"];
+        command.extend(["--batch", "proofs", "--public-inputs", "inputs"]);
+        command.extend(args);
+        plangc(&command, &dir)
+    };
+    // The result in the table of proofs, after the padded path of the proof
+    let result = |table: &str, proof: &str| {
+        let line = table.lines().find(|line| line.starts_with(proof));
+        line.map(|line| line[proof.len()..].trim_start().to_owned())
+            .unwrap_or_default()
+    };
+    let read_report = || {
+        let report = fs::read(dir.join("report.json")).expect("the report is written");
+        serde_json::from_slice::<serde_json::Value>(&report).expect("the report is JSON")
+    };
+
+    let output = verify(&[]);
+    assert!(output.status.success(), "{:?}", output);
+    let table = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(result(&table, "proofs/one.proof"), "ok", "{}", table);
+    assert_eq!(result(&table, "proofs/two.envelope"), "ok", "{}", table);
+    assert!(table.contains("2 passed, 0 failed"), "{}", table);
+
+    let output = verify(&["--report", "report.json"]);
+    assert!(output.status.success(), "{:?}", output);
+    let hash = plangc(&["hash", "circuit.plang"], &dir).stdout;
+    let report = read_report();
+    assert_eq!(report["verified"], true);
+    assert_eq!(
+        report["circuit_hash"],
+        String::from_utf8_lossy(&hash).trim()
+    );
+    assert_eq!(report["proofs"].as_array().map(Vec::len), Some(2));
+    assert_eq!(
+        report["proofs"][1]["public_inputs"],
+        serde_json::json!([{ "name": "c", "value": "4" }])
+    );
+
+    // A proof with the wrong public inputs fails the batch, and the report records it
+    fs::copy(dir.join("proofs/one.proof"), dir.join("proofs/three.proof"))
+        .expect("proof can be copied");
+    fs::write(dir.join("inputs/three.json"), r#"{ "c": 16 }"#).expect("inputs can be written");
+
+    let output = verify(&[]);
+    assert_eq!(output.status.code(), Some(5), "{:?}", output);
+    let table = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        result(&table, "proofs/three.proof").starts_with("FAILED"),
+        "{}",
+        table
+    );
+    assert!(table.contains("2 passed, 1 failed"), "{}", table);
+
+    let output = verify(&["--report", "report.json"]);
+    assert_eq!(output.status.code(), Some(5), "{:?}", output);
+    let report = read_report();
+    assert_eq!(report["verified"], false);
+    assert!(report["error"].is_string());
+    assert_eq!(report["proofs"].as_array().map(Vec::len), Some(3));
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn proves_directories_of_witnesses() {
+    let dir = temp_dir("witness-dir");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+    fs::create_dir_all(dir.join("witnesses")).expect("directory can be created");
+    fs::write(
+        dir.join("witnesses/one.json"),
+        r#"{ "a": 3, "b": 5, "c": 15 }"#,
+    )
+    .expect("witness can be written");
+    fs::write(dir.join("witnesses/two.toml"), "a = 2\nb = 2\nc = 4\n")
+        .expect("witness can be written");
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(&["compile", "circuit.plang", "-p", "circuit.pp"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let prove = |args: &[&str]| {
+        let mut command = vec!["prove", "circuit.plang", "-p", "circuit.pp"];
+        command.extend(["--witness-dir", "witnesses"]);
+        command.extend(args);
+        plangc(&command, &dir)
+    };
+    let verify = |args: &[&str]| {
+        let command = [
+            &["verify", "circuit.plang", "--params", "circuit.pp"][..],
+            args,
+        ]
+        .concat();
+        plangc(&command, &dir).status.code()
+    };
+
+    let output = prove(&["--out-dir", "proofs"]);
+    assert!(output.status.success(), "{:?}", output);
+    let verify_proof = |proof: &str, vals: &str| verify(&["--proof", proof, "--vals", vals]);
+    assert_eq!(verify_proof("proofs/one.proof", "c=15"), Some(0));
+    assert_eq!(verify_proof("proofs/two.proof", "c=4"), Some(0));
+    assert_eq!(verify_proof("proofs/two.proof", "c=15"), Some(5));
+
+    // Envelopes are written next to the witnesses without an output directory
+    let output = prove(&["--envelope"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(dir.join("witnesses/one.envelope").exists());
+    assert_eq!(verify(&["--batch", "witnesses"]), Some(0));
+
+    // A witness failing to be proven fails the run, but not the other proofs
+    fs::write(dir.join("witnesses/three.json"), r#"{ "z": 1 }"#).expect("witness can be written");
+    fs::remove_dir_all(dir.join("proofs")).expect("proofs can be removed");
+    let output = prove(&["--out-dir", "proofs"]);
+    assert_eq!(output.status.code(), Some(6), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("three.json ... FAILED"), "{}", stdout);
+    assert!(dir.join("proofs/one.proof").exists() && dir.join("proofs/two.proof").exists());
+    assert!(!dir.join("proofs/three.proof").exists());
+
+    assert_eq!(
+        prove(&["--witness", "witnesses/one.json"]).status.code(),
+        Some(1)
+    );
+    let output = plangc(&["prove", "circuit.plang", "--out-dir", "proofs"], &dir);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+fn piped(args: &[&str], dir: &Path, input: &[u8]) -> Output {
+    let mut child = command(args, dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("plangc can be run");
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input)
+        .expect("the input can be written");
+    child.wait_with_output().expect("plangc can be waited for")
+}
+
+#[test]
+fn streams_proofs_through_pipes() {
+    let dir = temp_dir("streams");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(&["compile", "circuit.plang", "-p", "circuit.pp"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let prove = |args: &[&str]| {
+        let mut command = vec!["prove", "circuit.plang", "-p", "circuit.pp", "-o", "-"];
+        command.extend(["--vals", "a=3", "--vals", "b=5", "--vals", "c=15"]);
+        command.extend(args);
+        let output = plangc(&command, &dir);
+        assert!(output.status.success(), "{:?}", output);
+        output.stdout
+    };
+    let verify = |args: &[&str], input: &[u8]| {
+        let command = [
+            &["verify", "circuit.plang", "--params", "circuit.pp"][..],
+            args,
+        ]
+        .concat();
+        piped(&command, &dir, input).status.code()
+    };
+
+    // Nothing but the proof is written to stdout, in any format
+    let binary = prove(&[]);
+    assert_eq!(
+        verify(&["--proof", "-", "--vals", "c=15"], &binary),
+        Some(0)
+    );
+    assert_eq!(
+        verify(&["--proof", "-", "--vals", "c=16"], &binary),
+        Some(5)
+    );
+
+    let hex = prove(&["--format", "hex"]);
+    assert!(hex.ends_with(b"\n") && hex[..hex.len() - 1].iter().all(u8::is_ascii_hexdigit));
+    assert_eq!(verify(&["--proof", "-", "--vals", "c=15"], &hex), Some(0));
+
+    let json = prove(&["--envelope", "--format", "json"]);
+    let envelope: serde_json::Value =
+        serde_json::from_slice(&json).expect("the envelope is written as JSON");
+    assert!(envelope.is_object());
+    assert_eq!(verify(&["--envelope", "-"], &json), Some(0));
+    assert_eq!(
+        verify(&["--envelope", "-", "--format", "json"], &json),
+        Some(0)
+    );
+    let binary = prove(&["--envelope"]);
+    assert_eq!(verify(&["--envelope", "-"], &binary), Some(0));
+
+    assert!(!dir.join("circuit.proof").exists() && !dir.join("circuit.envelope").exists());
+
+    // Binary proofs aren't written to a terminal, and proofs aren't read from one. `script`
+    // runs plangc with a terminal for its stdin and stdout.
+    #[cfg(target_os = "linux")]
+    {
+        let on_terminal = |args: &str| {
+            let line = format!("'{}' {}", env!("CARGO_BIN_EXE_plangc"), args);
+            Command::new("script")
+                .args(["-qec", &line, "/dev/null"])
+                .current_dir(&dir)
+                .env_remove("PLANG_PARAMS")
+                .output()
+                .ok()
+        };
+        let prove = "prove circuit.plang -p circuit.pp -o - --vals a=3 --vals b=5 --vals c=15";
+        if let Some(output) = on_terminal(prove) {
+            assert_eq!(output.status.code(), Some(8), "{:?}", output);
+            let output = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                output.contains("not writing a binary proof to a terminal"),
+                "{}",
+                output
+            );
+
+            let output = on_terminal(&format!("{} --format hex", prove)).expect("script runs");
+            assert!(output.status.success(), "{:?}", output);
+
+            let verify = "verify circuit.plang --params circuit.pp --proof - --vals c=15";
+            let output = on_terminal(verify).expect("script runs");
+            assert_eq!(output.status.code(), Some(8), "{:?}", output);
+            let output = String::from_utf8_lossy(&output.stdout);
+            assert!(output.contains("expected a proof on stdin"), "{}", output);
+        }
+    }
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn configures_commands_from_the_environment() {
+    let dir = temp_dir("environment");
+    for (gadgets, product) in [
+        ("flag", "a * b = c\n"),
+        ("env", "a + b = c\n"),
+        ("toml", "2*a*b = c\n"),
+    ] {
+        fs::create_dir_all(dir.join(gadgets)).expect("directory can be created");
+        fs::write(dir.join(gadgets).join("product.plang"), product).expect("gadget can be written");
+    }
+    fs::write(
+        dir.join("circuit.plang"),
+        "import \"product.plang\" as p\np::c + e = d\n",
+    )
+    .expect("circuit can be written");
+    fs::write(
+        dir.join("plang.toml"),
+        "params = \"missing.pp\"\ninclude = [\"toml\"]\n\n[[circuit]]\nname = \"circuit\"\nsource = \"circuit.plang\"\n",
+    )
+    .expect("workspace can be written");
+
+    let with_env = |args: &[&str], vars: &[(&str, &str)]| {
+        let mut command = command(args, &dir);
+        command.envs(vars.iter().copied());
+        command.output().expect("plangc can be run")
+    };
+    let hash = |args: &[&str], vars: &[(&str, &str)]| {
+        let output = with_env(&[args, &["hash", "circuit.plang"]].concat(), vars);
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).expect("the hash is printed")
+    };
+
+    // Imports are looked up with `-I` first, then in PLANG_INCLUDE_PATH, then in the plang.toml
+    let output = plangc(&["hash", "circuit.plang"], &dir);
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    let flag = hash(&["-I", "flag"], &[]);
+    let env = hash(&[], &[("PLANG_INCLUDE_PATH", "env")]);
+    let toml = hash(&["-I", "toml"], &[]);
+    assert_ne!(flag, env);
+    assert_eq!(
+        hash(&["-I", "flag"], &[("PLANG_INCLUDE_PATH", "env")]),
+        flag
+    );
+    let path = env::join_paths(["missing", "env"]).expect("the path can be joined");
+    let path = path.to_str().expect("the path is UTF-8");
+    assert_eq!(hash(&[], &[("PLANG_INCLUDE_PATH", path)]), env);
+
+    // Parameters are taken from `--params`, then PLANG_PARAMS, then the plang.toml
+    let args = [
+        "generate-params",
+        "--degree",
+        "2^6",
+        "--seed",
+        "01",
+        "-o",
+        "shared.pp",
+    ];
+    let output = plangc(&args, &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = plangc(&["build"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+    let output = with_env(&["build"], &[("PLANG_PARAMS", "shared.pp")]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = with_env(
+        &["build", "--params", "missing.pp"],
+        &[("PLANG_PARAMS", "shared.pp")],
+    );
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+
+    let built_hash = || {
+        let output = plangc(
+            &["inspect-keys", "--keys", "circuit.plangkeys", "--json"],
+            &dir,
+        );
+        let info: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("the information is printed as JSON");
+        info["keys"]["circuit_hash"].clone()
+    };
+    let output = with_env(&["build", "--params", "shared.pp"], &[]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(built_hash(), toml.trim());
+    let vars = [("PLANG_PARAMS", "shared.pp"), ("PLANG_INCLUDE_PATH", "env")];
+    let output = with_env(&["build"], &vars);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(built_hash(), env.trim());
+
+    // Keys are cached in `--cache-dir`, or otherwise PLANG_CACHE_DIR
+    let cached = |cache: &str| fs::read_dir(dir.join(cache)).map_or(0, Iterator::count);
+    let compile = ["-I", "flag", "compile", "circuit.plang", "-p", "shared.pp"];
+    let output = with_env(&compile, &[("PLANG_CACHE_DIR", "env-cache")]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(cached("env-cache"), 1);
+    let compile = [&compile[..], &["--cache-dir", "flag-cache"]].concat();
+    let output = with_env(&compile, &[("PLANG_CACHE_DIR", "env-cache")]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(cached("flag-cache"), 1);
+    assert_eq!(cached("env-cache"), 1);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+/// The BLS12-381 scalar field modulus, as little endian bytes.
+const MODULUS: [u8; 32] = [
+    0x01, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0x02, 0xa4, 0xbd, 0x53,
+    0x05, 0xd8, 0xa1, 0x09, 0x08, 0xd8, 0x39, 0x33, 0x48, 0x7d, 0x9d, 0x29, 0x53, 0xa7, 0xed, 0x73,
+];
+
+fn combination(bytes: &mut Vec<u8>, terms: &[(u32, u64)]) {
+    bytes.extend(&(terms.len() as u32).to_le_bytes());
+    for (wire, coeff) in terms {
+        bytes.extend(&wire.to_le_bytes());
+        let mut coeff_bytes = [0u8; 32];
+        coeff_bytes[..8].copy_from_slice(&coeff.to_le_bytes());
+        bytes.extend(&coeff_bytes);
+    }
+}
+
+/// Builds an R1CS with a single public output `w1`, constrained by
+/// `w2 * w3 = w1` and `1 * (w1 + w2 + w3 + 2*w4 + w5) = 0`.
+fn r1cs() -> Vec<u8> {
+    let mut header = vec![];
+    header.extend(&32u32.to_le_bytes());
+    header.extend(&MODULUS);
+    for n in [6u32, 1, 0, 2] {
+        header.extend(&n.to_le_bytes());
+    }
+    header.extend(&6u64.to_le_bytes());
+    header.extend(&2u32.to_le_bytes());
+
+    let mut constraints = vec![];
+    combination(&mut constraints, &[(2, 1)]);
+    combination(&mut constraints, &[(3, 1)]);
+    combination(&mut constraints, &[(1, 1)]);
+    combination(&mut constraints, &[(0, 1)]);
+    combination(&mut constraints, &[(1, 1), (2, 1), (3, 1), (4, 2), (5, 1)]);
+    combination(&mut constraints, &[]);
+
+    let mut bytes = b"r1cs".to_vec();
+    bytes.extend(&1u32.to_le_bytes());
+    bytes.extend(&2u32.to_le_bytes());
+    for (kind, section) in [(1u32, header), (2, constraints)] {
+        bytes.extend(&kind.to_le_bytes());
+        bytes.extend(&(section.len() as u64).to_le_bytes());
+        bytes.extend(section);
+    }
+    bytes
+}
+
+#[test]
+fn imports_r1cs() {
+    let dir = temp_dir("import");
+    fs::write(dir.join("model.r1cs"), r1cs()).expect("R1CS can be written");
+
+    let output = plangc(&["import", "model.r1cs", "--format", "r1cs"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(
+        &[
+            "import",
+            "model.r1cs",
+            "--format",
+            "r1cs",
+            "-o",
+            "other.plang",
+        ],
+        &dir,
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let imported = fs::read_to_string(dir.join("model.plang")).expect("the circuit is written");
+    assert_eq!(
+        fs::read_to_string(dir.join("other.plang")).expect("the circuit is written"),
+        imported
+    );
+
+    // The imported circuit is checked, hashed and proven as any other
+    let satisfying =
+        "p0 = 1; p1 = 6; w0 = 1; w1 = 6; w2 = 2; w3 = 3; w4 = 0; w5 = -11; t0 = 8; t1 = 11;";
+    let tests = format!(
+        "{}test satisfied \"imported\" {{ {} }}\ntest unsatisfied \"wrong product\" {{ {} }}\n",
+        imported,
+        satisfying,
+        satisfying.replace("w3 = 3", "w3 = 4"),
+    );
+    fs::write(dir.join("model.plang"), tests).expect("test vectors can be written");
+    let output = plangc(&["test", "model.plang"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(&["hash", "model.plang"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = plangc(&["generate-params", "model.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let vals = satisfying.replace(' ', "").replace(';', " ");
+    let mut prove = vec![
+        "prove",
+        "model.plang",
+        "-p",
+        "model.pp",
+        "-o",
+        "model.proof",
+    ];
+    for val in vals.split_whitespace() {
+        prove.extend(["--vals", val]);
+    }
+    let output = plangc(&prove, &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let verify = |vals: &[&str]| {
+        let mut command = vec!["verify", "model.plang", "-p", "model.pp"];
+        command.extend(["--proof", "model.proof"]);
+        for val in vals {
+            command.extend(["--vals", val]);
+        }
+        plangc(&command, &dir).status.code()
+    };
+    assert_eq!(verify(&["p0=1", "p1=6"]), Some(0));
+    assert_eq!(verify(&["p0=1", "p1=7"]), Some(5));
+
+    // Files that aren't R1CS over the scalar field aren't imported
+    let mut bytes = r1cs();
+    bytes[4 + 4 + 4 + 4 + 8 + 4] ^= 1;
+    fs::write(dir.join("other.r1cs"), bytes).expect("R1CS can be written");
+    let output = plangc(&["import", "other.r1cs", "--format", "r1cs"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+    assert_eq!(
+        fs::read_to_string(dir.join("other.plang")).expect("the circuit is left alone"),
+        imported
+    );
+    let output = plangc(&["import", "missing.r1cs", "--format", "r1cs"], &dir);
+    assert_eq!(output.status.code(), Some(8), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
+
+#[test]
+fn exports_verifier_data() {
+    let dir = temp_dir("export-vk");
+    fs::write(dir.join("circuit.plang"), "a * b = c\n").expect("circuit can be written");
+    fs::write(dir.join("other.plang"), "a + b = c\n").expect("circuit can be written");
+
+    let output = plangc(&["generate-params", "circuit.plang", "--seed", "01"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let compile = ["compile", "circuit.plang", "--params", "circuit.pp"];
+    let output = plangc(&compile, &dir);
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(
+        &[&compile[..], &["--archive", "--label", "mine"]].concat(),
+        &dir,
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let export = |args: &[&str]| {
+        let output = plangc(&[&["export-vk", "circuit.plang"][..], args].concat(), &dir);
+        assert!(output.status.success(), "{:?}", output);
+        serde_json::from_slice::<serde_json::Value>(&output.stdout)
+            .expect("the verifier data is exported as JSON")
+    };
+
+    // The label recorded in the archive is exported, and otherwise the default
+    let from_archive = export(&["--keys", "circuit.plangkeys"]);
+    assert_eq!(from_archive["transcript_label"], hex::encode("mine"));
+    let from_vd = export(&[]);
+    assert_eq!(from_vd["transcript_label"], hex::encode("dusk_plang"));
+    assert_eq!(from_vd["commitments"], from_archive["commitments"]);
+    assert_eq!(
+        export(&["--vdata", "circuit.vd", "--label", "mine"]),
+        from_archive
+    );
+    assert_eq!(
+        export(&["--keys", "circuit.plangkeys", "--label", "0x00ff"])["transcript_label"],
+        "00ff"
+    );
+
+    let output = plangc(&["hash", "circuit.plang"], &dir);
+    let hash = String::from_utf8(output.stdout).expect("the hash is printed");
+    assert_eq!(from_archive["circuit_hash"], hash.trim());
+    assert_eq!(from_archive["public_input_order"], serde_json::json!(["c"]));
+
+    // It's written to a file with `-o`, and decomposed into equations with `--format plang`
+    let args = ["export-vk", "circuit.plang", "--keys", "circuit.plangkeys"];
+    let output = plangc(&[&args[..], &["-o", "vk.json"]].concat(), &dir);
+    assert!(
+        output.status.success() && output.stdout.is_empty(),
+        "{:?}",
+        output
+    );
+    let written = fs::read(dir.join("vk.json")).expect("the export is written");
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&written).expect("the export is JSON"),
+        from_archive
+    );
+    let output = plangc(
+        &[&args[..], &["--format", "plang", "-o", "vk.plang"]].concat(),
+        &dir,
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let output = plangc(&["hash", "vk.plang"], &dir);
+    assert!(output.status.success(), "{:?}", output);
+
+    // Keys of another circuit aren't exported
+    let output = plangc(
+        &["export-vk", "other.plang", "--keys", "circuit.plangkeys"],
+        &dir,
+    );
+    assert_eq!(output.status.code(), Some(7), "{:?}", output);
+    let output = plangc(&[&args[..], &["--vdata", "circuit.vd"]].concat(), &dir);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    fs::remove_dir_all(dir).expect("temporary directory can be removed");
+}
@@ -0,0 +1,202 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! A service exposing `plangc`'s operations over HTTP, so they can be run on
+//! demand without reading the public parameters, or compiling a circuit, on
+//! every request.
+//!
+//! Every operation is a `POST` to its path, taking and returning a JSON object:
+//!
+//! | path       | request                             | response                       |
+//! |------------|-------------------------------------|--------------------------------|
+//! | `/check`   | `{ circuit }`                       | `{ diagnostics }`              |
+//! | `/compile` | `{ circuit }`                       | `{ hash, public_inputs }`      |
+//! | `/solve`   | `{ circuit \| hash, vals }`         | `{ witnesses, public_inputs }` |
+//! | `/prove`   | `{ circuit \| hash, vals }`         | `{ proof }`                    |
+//! | `/verify`  | `{ circuit \| hash, vals, proof }`  | `{ valid }`                    |
+//!
+//! Errors are responded with as `{ error, code }`.
+
+use plangd::service::{Error, Service};
+
+use plang::artifact;
+use plang::params;
+
+use std::io::{self, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::thread;
+
+use serde_json::Value;
+use structopt::StructOpt;
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{error, info, info_span};
+use tracing_subscriber::fmt::format::FmtSpan;
+
+use plang::dusk_plonk::commitment_scheme::PublicParameters;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "plangd", about = "Serve plang circuits over HTTP")]
+struct Opt {
+    /// Log requests, and how long they took, to stderr. Repeat for more detail.
+    #[structopt(short, long, parse(from_occurrences))]
+    verbose: u8,
+    /// The address to listen on.
+    #[structopt(long, short, default_value = "127.0.0.1:8080")]
+    listen: String,
+    /// Public parameters to compile and prove with, read once on startup. If not specified the
+    /// parameters pointed to by the PLANG_PARAMS environment variable are used.
+    #[structopt(long, short, parse(from_os_str), env = "PLANG_PARAMS")]
+    params: PathBuf,
+    /// Number of requests served at once.
+    #[structopt(long, short, default_value = "4")]
+    workers: usize,
+    /// Number of compiled circuits kept in memory, with their keys. The circuit compiled first
+    /// is evicted first.
+    #[structopt(long, default_value = "64")]
+    max_circuits: usize,
+    /// Largest request body read, in bytes. Larger requests are refused.
+    #[structopt(long, default_value = "16777216")]
+    max_body: u64,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    init_tracing(opt.verbose);
+
+    let pp = match read_params(&opt.params) {
+        Ok(pp) => pp,
+        Err(err) => {
+            eprintln!("error: {}: {}", opt.params.display(), err);
+            process::exit(8);
+        }
+    };
+    let service = Arc::new(Service::new(pp, opt.max_circuits));
+
+    let server = match Server::http(&opt.listen) {
+        Ok(server) => Arc::new(server),
+        Err(err) => {
+            eprintln!("error: failed to listen on {}: {}", opt.listen, err);
+            process::exit(1);
+        }
+    };
+    eprintln!("listening on {}", opt.listen);
+
+    let workers: Vec<_> = (0..opt.workers.max(1))
+        .map(|_| {
+            let server = server.clone();
+            let service = service.clone();
+            let max_body = opt.max_body;
+
+            thread::spawn(move || loop {
+                match server.recv() {
+                    Ok(request) => serve(&service, request, max_body),
+                    Err(err) => error!("failed to receive request: {}", err),
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
+
+fn read_params(path: &Path) -> Result<PublicParameters, plang::PlangError> {
+    let (pp, flags) = params::read_file(path)?;
+
+    if flags & artifact::FLAG_INSECURE != 0 {
+        eprintln!("warning: parameters are marked INSECURE and must not be used in production");
+    }
+
+    info!(
+        "read parameters supporting circuits of up to {} gates",
+        params::gates_for_degree(params::max_degree(&pp))
+    );
+    Ok(pp)
+}
+
+/// Responds to a request, routing it to the operation for its path.
+///
+/// A panic serving the request is responded to as an internal error, rather
+/// than taking the worker down with it.
+fn serve(service: &Service, mut request: Request, max_body: u64) {
+    let path = request.url().to_owned();
+    let span = info_span!("request", %path);
+    let _enter = span.enter();
+
+    let operation = match path.as_str() {
+        "/check" => Service::check,
+        "/compile" => Service::compile,
+        "/solve" => Service::solve,
+        "/prove" => Service::prove,
+        "/verify" => Service::verify,
+        _ => return respond(request, 404, &error_json(&format!("no such path {}", path))),
+    };
+    if request.method() != &Method::Post {
+        return respond(request, 405, &error_json("operations must be POSTed"));
+    }
+
+    let mut body = String::new();
+    let mut reader = request.as_reader().take(max_body.saturating_add(1));
+    if let Err(err) = reader.read_to_string(&mut body) {
+        return respond(request, 400, &error_json(&err.to_string()));
+    }
+    if body.len() as u64 > max_body {
+        let err = Error::TooLarge(max_body);
+        return respond(request, err.status(), &err.to_json());
+    }
+
+    let result = serde_json::from_str::<Value>(&body)
+        .map_err(|err| Error::BadRequest(format!("request is not valid JSON: {}", err)))
+        .and_then(|req| {
+            panic::catch_unwind(AssertUnwindSafe(|| operation(service, &req)))
+                .unwrap_or(Err(Error::Internal))
+        });
+
+    match result {
+        Ok(response) => respond(request, 200, &response),
+        Err(err) => {
+            info!("failed: {}", err);
+            respond(request, err.status(), &err.to_json())
+        }
+    }
+}
+
+fn respond(request: Request, status: u16, body: &Value) {
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("content type header is valid");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+
+    if let Err(err) = request.respond(response) {
+        error!("failed to respond: {}", err);
+    }
+}
+
+fn error_json(msg: &str) -> Value {
+    serde_json::json!({ "error": msg, "code": null })
+}
+
+/// Installs a subscriber logging spans, and how long they took, to stderr. Nothing is logged
+/// unless `-v` is given at least once.
+fn init_tracing(verbose: u8) {
+    let level = match verbose {
+        0 => return,
+        1 => tracing::Level::INFO,
+        2 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(io::stderr)
+        .init();
+}
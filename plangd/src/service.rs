@@ -0,0 +1,336 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! The operations served, on JSON requests.
+//!
+//! A circuit is given in a request either by its text, as `"circuit"`, or by
+//! the hex encoded hash of a circuit compiled before, as `"hash"`, with the
+//! transcript label it declares, if any, as `"transcript"`. Compiled circuits
+//! are kept in memory parsed and with their keys, so proving and verifying
+//! with a circuit only parses and compiles it the first time.
+
+use plang::document::Document;
+use plang::scalar::ScalarValue;
+use plang::{PlangCircuit, PlangError, PlangProof, ProveError, ProveOptions};
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use serde_json::{json, Value};
+use tracing::info_span;
+
+use plang::dusk_plonk::circuit::{Circuit, VerifierData};
+use plang::dusk_plonk::commitment_scheme::PublicParameters;
+use plang::dusk_plonk::error::Error as PlonkError;
+use plang::dusk_plonk::prelude::{BlsScalar, ProverKey};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error serving a request.
+#[derive(Debug)]
+pub enum Error {
+    /// The request is not valid JSON, or is missing a field.
+    BadRequest(String),
+    /// No circuit with the given hash and transcript label has been compiled.
+    UnknownCircuit(String),
+    /// The request body is larger than the given number of bytes.
+    TooLarge(u64),
+    /// Serving the request panicked, which is a bug.
+    Internal,
+    Plang(PlangError),
+}
+
+impl Error {
+    /// Returns the HTTP status to respond with.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::BadRequest(_) => 400,
+            Self::UnknownCircuit(_) => 404,
+            Self::TooLarge(_) => 413,
+            Self::Internal => 500,
+            Self::Plang(PlangError::Prove(ProveError::Plonk(_))) => 500,
+            Self::Plang(_) => 422,
+        }
+    }
+
+    /// Returns the error as the body of a response.
+    pub fn to_json(&self) -> Value {
+        let code = match self {
+            Self::Plang(err) => err.code(),
+            _ => None,
+        };
+        json!({ "error": self.to_string(), "code": code })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadRequest(msg) => f.write_str(msg),
+            Self::UnknownCircuit(hash) => write!(f, "no circuit with hash {} was compiled", hash),
+            Self::TooLarge(limit) => write!(f, "request is larger than {} bytes", limit),
+            Self::Internal => f.write_str("internal error"),
+            Self::Plang(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<PlangError> for Error {
+    fn from(err: PlangError) -> Self {
+        Self::Plang(err)
+    }
+}
+
+impl From<PlonkError> for Error {
+    fn from(err: PlonkError) -> Self {
+        Self::Plang(err.into())
+    }
+}
+
+/// A circuit compiled with the parameters of the service.
+struct Compiled {
+    /// The circuit as parsed, without values, cloned for each request.
+    circuit: PlangCircuit,
+    pk: ProverKey,
+    vd: VerifierData,
+    /// The options proofs of the circuit are produced and verified with,
+    /// with the transcript label it declares.
+    options: ProveOptions,
+}
+
+/// A compiled circuit is looked up by its hash and the transcript label it
+/// declares, since circuits differing only in their label have the same hash
+/// but produce proofs of different transcripts.
+type Key = ([u8; 32], Option<Vec<u8>>);
+
+/// The circuits compiled so far, evicted in the order they were compiled in.
+#[derive(Default)]
+struct Cache {
+    circuits: HashMap<Key, Arc<Compiled>>,
+    order: VecDeque<Key>,
+}
+
+/// The operations, proving with the parameters read on startup.
+pub struct Service {
+    pp: PublicParameters,
+    max_circuits: usize,
+    cache: Mutex<Cache>,
+}
+
+impl Service {
+    /// Creates a service proving with the given parameters, and keeping at
+    /// most the given number of compiled circuits in memory.
+    pub fn new(pp: PublicParameters, max_circuits: usize) -> Self {
+        Self {
+            pp,
+            max_circuits: max_circuits.max(1),
+            cache: Mutex::default(),
+        }
+    }
+
+    /// `{ circuit }` → `{ diagnostics: [{ line, code, message }] }`
+    pub fn check(&self, req: &Value) -> Result<Value> {
+        let text = field_str(req, "circuit")?;
+
+        let diagnostics: Vec<Value> = Document::new(text)
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.to_json())
+            .collect();
+
+        Ok(json!({ "diagnostics": diagnostics }))
+    }
+
+    /// `{ circuit }` → `{ hash, transcript, public_inputs }`, with the
+    /// transcript label the circuit declares, or `null`.
+    pub fn compile(&self, req: &Value) -> Result<Value> {
+        let (hash, compiled) = self.compiled(req)?;
+        let circuit = &compiled.circuit;
+
+        Ok(json!({
+            "hash": hex::encode(hash),
+            "transcript": circuit.transcript().map(String::from_utf8_lossy),
+            "public_inputs": circuit.public_input_order(),
+        }))
+    }
+
+    /// `{ circuit | hash, vals }` →
+    /// `{ witnesses: { name: value }, public_inputs: [{ name, value }] }`
+    ///
+    /// Solves the circuit for the values of the witnesses introduced while
+    /// lowering it, such as bits and partial sums, from the values given, and
+    /// checks they satisfy every equation. Values are in decimal, and the
+    /// public inputs are in the order verification expects them in. Values not
+    /// given are 0. The circuit isn't compiled.
+    pub fn solve(&self, req: &Value) -> Result<Value> {
+        let vals = field_vals(req)?;
+
+        let mut circuit = match req.get("hash") {
+            Some(_) => self.compiled(req)?.1.circuit.clone(),
+            None => PlangCircuit::parse(field_str(req, "circuit")?)?,
+        };
+        circuit.set_vals(vals)?;
+        circuit.check_satisfied()?;
+
+        let decimal = |value: BlsScalar| plang::scalar::Scalar::from(value).to_string();
+        let assignment = circuit.assignment();
+        let witnesses: serde_json::Map<String, Value> = assignment
+            .witnesses
+            .into_iter()
+            .map(|(name, value)| (name, decimal(value).into()))
+            .collect();
+        let public_inputs: Vec<Value> = circuit
+            .public_inputs_named()
+            .map(|(name, value)| json!({ "name": name, "value": decimal(value) }))
+            .collect();
+
+        Ok(json!({ "witnesses": witnesses, "public_inputs": public_inputs }))
+    }
+
+    /// `{ circuit | hash, vals }` → `{ proof }`, with the proof hex encoded.
+    pub fn prove(&self, req: &Value) -> Result<Value> {
+        let (_, compiled) = self.compiled(req)?;
+        let vals = field_vals(req)?;
+
+        let mut circuit = compiled.circuit.clone();
+        circuit.set_vals(vals)?;
+
+        let proof = info_span!("prove")
+            .in_scope(|| circuit.prove_with(&self.pp, &compiled.pk, &compiled.options))?;
+        let proof = PlangProof::new(proof, compiled.options.label().to_vec());
+
+        Ok(json!({ "proof": hex::encode(proof.to_bytes()) }))
+    }
+
+    /// `{ circuit | hash, vals, proof }` → `{ valid }`, with the values of the
+    /// public inputs.
+    pub fn verify(&self, req: &Value) -> Result<Value> {
+        let (_, compiled) = self.compiled(req)?;
+        let vals = field_vals(req)?;
+
+        let proof = hex::decode(field_str(req, "proof")?)
+            .map_err(|_| Error::BadRequest("`proof` is not hex encoded".into()))?;
+        let proof = PlangProof::from_slice(&proof)?;
+
+        let pinputs = compiled.circuit.public_inputs_from(vals)?;
+
+        let result = info_span!("verify").in_scope(|| {
+            PlangCircuit::verify_with(
+                &self.pp,
+                &compiled.vd,
+                &proof.proof,
+                &pinputs,
+                &compiled.options,
+            )
+        });
+
+        match result {
+            Ok(()) => Ok(json!({ "valid": true })),
+            Err(PlangError::Prove(ProveError::Plonk(PlonkError::ProofVerificationError))) => {
+                Ok(json!({ "valid": false }))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Returns the circuit the request refers to, compiling it if it hasn't
+    /// been yet.
+    fn compiled(&self, req: &Value) -> Result<([u8; 32], Arc<Compiled>)> {
+        if let Some(hash) = req.get("hash") {
+            let hex_hash = hash
+                .as_str()
+                .ok_or_else(|| Error::BadRequest("`hash` must be a string".into()))?;
+            let transcript = match req.get("transcript") {
+                None | Some(Value::Null) => None,
+                Some(Value::String(label)) => Some(label.as_bytes().to_vec()),
+                Some(_) => return Err(Error::BadRequest("`transcript` must be a string".into())),
+            };
+            let cache = self.cache();
+
+            return hex::decode(hex_hash)
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .and_then(|hash| {
+                    let compiled = cache.circuits.get(&(hash, transcript))?;
+                    Some((hash, compiled.clone()))
+                })
+                .ok_or_else(|| Error::UnknownCircuit(hex_hash.to_owned()));
+        }
+
+        let text = field_str(req, "circuit")?;
+        let mut circuit = PlangCircuit::parse(text)?;
+        let hash = circuit.hash();
+        let key = (hash, circuit.transcript().map(<[u8]>::to_vec));
+
+        if let Some(compiled) = self.cache().circuits.get(&key) {
+            return Ok((hash, compiled.clone()));
+        }
+
+        // Compiling is slow, so it's done without holding the lock. A circuit
+        // compiled by two requests at once is cached by both, to the same
+        // effect.
+        plang::params::check_degree(&self.pp, &circuit)?;
+        let parsed = circuit.clone();
+        let (pk, vd) = info_span!("compile").in_scope(|| circuit.compile(&self.pp))?;
+
+        let compiled = Arc::new(Compiled {
+            options: ProveOptions::for_circuit(&parsed),
+            circuit: parsed,
+            pk,
+            vd,
+        });
+
+        let mut cache = self.cache();
+        if cache
+            .circuits
+            .insert(key.clone(), compiled.clone())
+            .is_none()
+        {
+            cache.order.push_back(key);
+        }
+        while cache.order.len() > self.max_circuits {
+            if let Some(evicted) = cache.order.pop_front() {
+                cache.circuits.remove(&evicted);
+            }
+        }
+
+        Ok((hash, compiled))
+    }
+
+    /// Locks the cache. A request panicking while holding the lock leaves the
+    /// cache as it was before or after an insertion, so it's used regardless.
+    fn cache(&self) -> MutexGuard<'_, Cache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn field_str<'a>(req: &'a Value, field: &str) -> Result<&'a str> {
+    req.get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::BadRequest(format!("`{}` must be a string", field)))
+}
+
+/// Reads the values in the request, an object mapping the names of variables
+/// to integers, or to numbers written as strings, if any.
+fn field_vals(req: &Value) -> Result<Vec<(String, ScalarValue)>> {
+    let vals = match req.get("vals") {
+        None => return Ok(vec![]),
+        Some(Value::Object(vals)) => vals,
+        Some(_) => return Err(Error::BadRequest("`vals` must be an object".into())),
+    };
+
+    vals.iter()
+        .map(|(name, val)| match ScalarValue::from_json(val) {
+            Some(val) => Ok((name.clone(), val)),
+            None => Err(Error::BadRequest(format!(
+                "value of `{}` is not a number",
+                name
+            ))),
+        })
+        .collect::<Result<_>>()
+}
@@ -0,0 +1,125 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::fs;
+
+use plang::dusk_plonk::commitment_scheme::PublicParameters;
+use plangd::service::{Error, Service};
+use serde_json::json;
+
+fn service() -> Service {
+    let bytes = fs::read("../plang/test.pp").expect("test parameters can be read");
+    let pp = PublicParameters::from_slice(&bytes).expect("test parameters are valid");
+    Service::new(pp, 2)
+}
+
+#[test]
+fn checks_circuits() -> Result<(), Error> {
+    let service = service();
+
+    let response = service.check(&json!({ "circuit": "a + b = c\na*a = d\n" }))?;
+    let diagnostics = response["diagnostics"].as_array().expect("diagnostics");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["line"], 1);
+    assert!(diagnostics[0]["code"].is_string());
+
+    Ok(())
+}
+
+#[test]
+fn solves_for_witnesses() -> Result<(), Error> {
+    let service = service();
+    let circuit = "bits(x, 4) -> b[]\nb[0] + b[3] = y\n";
+
+    let response = service.solve(&json!({ "circuit": circuit, "vals": { "x": 9, "y": 2 } }))?;
+    assert_eq!(response["witnesses"]["b[0]"], "1");
+    assert_eq!(response["witnesses"]["b[1]"], "0");
+    assert_eq!(response["witnesses"]["b[3]"], "1");
+    assert_eq!(
+        response["public_inputs"],
+        json!([{ "name": "y", "value": "2" }])
+    );
+
+    // Values not satisfying the circuit aren't solved for
+    let err = service
+        .solve(&json!({ "circuit": circuit, "vals": { "x": 9, "y": 1 } }))
+        .expect_err("the values don't satisfy the circuit");
+    assert_eq!(err.status(), 422);
+
+    Ok(())
+}
+
+#[test]
+fn proves_and_verifies_compiled_circuits() -> Result<(), Error> {
+    let service = service();
+
+    let response = service.compile(&json!({ "circuit": "a * b = c\n" }))?;
+    assert_eq!(response["public_inputs"], json!(["c"]));
+    let hash = response["hash"].as_str().expect("hash");
+
+    let vals = json!({ "a": 3, "b": 5, "c": 15 });
+    let response = service.prove(&json!({ "hash": hash, "vals": vals }))?;
+    let proof = response["proof"].as_str().expect("proof");
+
+    let response = service.verify(&json!({ "hash": hash, "vals": { "c": 15 }, "proof": proof }))?;
+    assert_eq!(response["valid"], true);
+    let response = service.verify(&json!({ "hash": hash, "vals": { "c": 16 }, "proof": proof }))?;
+    assert_eq!(response["valid"], false);
+
+    Ok(())
+}
+
+#[test]
+fn keeps_circuits_declaring_other_transcripts_apart() -> Result<(), Error> {
+    let service = service();
+    let labeled = "transcript \"mine\"\na * b = c\n";
+
+    let response = service.compile(&json!({ "circuit": labeled }))?;
+    assert_eq!(response["transcript"], "mine");
+    let hash = response["hash"].as_str().expect("hash").to_owned();
+
+    // The hash is the same without the directive, but the circuit isn't
+    let err = service
+        .prove(&json!({ "hash": hash, "vals": { "a": 3, "b": 5, "c": 15 } }))
+        .expect_err("only the labeled circuit was compiled");
+    assert_eq!(err.status(), 404);
+
+    let response = service.compile(&json!({ "circuit": "a * b = c\n" }))?;
+    assert_eq!(response["hash"], hash);
+    assert_eq!(response["transcript"], json!(null));
+
+    let vals = json!({ "a": 3, "b": 5, "c": 15 });
+    let response = service.prove(&json!({ "hash": hash, "transcript": "mine", "vals": vals }))?;
+    let proof = response["proof"].as_str().expect("proof");
+
+    let verify = |circuit: &str| {
+        let req = json!({ "circuit": circuit, "vals": { "c": 15 }, "proof": proof });
+        service
+            .verify(&req)
+            .map(|response| response["valid"].clone())
+    };
+    assert_eq!(verify(labeled)?, true);
+    assert_eq!(verify("a * b = c\n")?, false);
+
+    Ok(())
+}
+
+#[test]
+fn rejects_invalid_requests() {
+    let service = service();
+
+    let status = |result: Result<_, Error>| result.expect_err("the request is invalid").status();
+    assert_eq!(status(service.compile(&json!({}))), 400);
+    assert_eq!(status(service.prove(&json!({ "hash": "00" }))), 404);
+    assert_eq!(
+        status(service.solve(&json!({ "circuit": "a * b = c\n", "vals": [] }))),
+        400
+    );
+    assert_eq!(
+        status(service.compile(&json!({ "circuit": "a*a = c\n" }))),
+        422
+    );
+}